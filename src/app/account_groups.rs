@@ -0,0 +1,106 @@
+//! Named account groups ("Work", "Personal") in the sidebar.
+//!
+//! The account list belongs to core's multi-account config, which this crate
+//! can't extend, so each account's group is kept in settings.json
+//! (`account_groups`) and set from the account dialog. Groups show in the
+//! order of their first account; ungrouped accounts keep a headerless
+//! section. A group header carries the unread count of its accounts'
+//! inboxes and opens an "All Work inboxes" smart view.
+
+use cosmic::app::Task;
+
+use super::{AccountState, AppModel, Message};
+
+/// One sidebar section: a named group, or the ungrouped accounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountSection {
+    pub name: Option<String>,
+    /// Indices into `accounts`, in account order.
+    pub accounts: Vec<usize>,
+    /// Unread messages across the accounts' inboxes.
+    pub unread: u32,
+    pub collapsed: bool,
+}
+
+/// Accounts bucketed by group, sections ordered by their first account.
+fn group_order(groups: &[Option<&str>]) -> Vec<(Option<String>, Vec<usize>)> {
+    let mut sections: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+    for (idx, group) in groups.iter().enumerate() {
+        let name = group.map(str::to_string);
+        match sections.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, members)) => members.push(idx),
+            None => sections.push((name, vec![idx])),
+        }
+    }
+    sections
+}
+
+fn inbox_unread(acct: &AccountState) -> u32 {
+    acct.folders
+        .iter()
+        .filter(|f| f.role.as_deref() == Some("inbox"))
+        .map(|f| f.unread_count)
+        .sum()
+}
+
+impl AppModel {
+    /// Sidebar sections; empty when no account is in a group, so the
+    /// sidebar keeps its flat list.
+    pub(super) fn account_sections(&self) -> Vec<AccountSection> {
+        let groups: Vec<Option<&str>> = self
+            .accounts
+            .iter()
+            .map(|a| self.settings.account_group(&a.config.id))
+            .collect();
+        if groups.iter().all(Option::is_none) {
+            return Vec::new();
+        }
+        group_order(&groups)
+            .into_iter()
+            .map(|(name, accounts)| AccountSection {
+                unread: accounts.iter().map(|&i| inbox_unread(&self.accounts[i])).sum(),
+                collapsed: name
+                    .as_ref()
+                    .is_some_and(|n| self.collapsed_groups.contains(n)),
+                name,
+                accounts,
+            })
+            .collect()
+    }
+
+    /// Account ids in `group`.
+    pub(super) fn group_members(&self, group: &str) -> Vec<String> {
+        self.accounts
+            .iter()
+            .filter(|a| self.settings.account_group(&a.config.id) == Some(group))
+            .map(|a| a.config.id.clone())
+            .collect()
+    }
+
+    pub(super) fn handle_account_groups(&mut self, message: Message) -> Task<Message> {
+        if let Message::ToggleGroupCollapse(name) = message {
+            if !self.collapsed_groups.remove(&name) {
+                self.collapsed_groups.insert(name);
+            }
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_follow_their_first_account() {
+        let sections = group_order(&[None, Some("Work"), Some("Personal"), Some("Work"), None]);
+        assert_eq!(
+            sections,
+            vec![
+                (None, vec![0, 4]),
+                (Some("Work".to_string()), vec![1, 3]),
+                (Some("Personal".to_string()), vec![2]),
+            ]
+        );
+    }
+}
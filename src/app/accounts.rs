@@ -5,6 +5,9 @@ use neverlight_mail_core::config::ConfigNeedsInput;
 use neverlight_mail_core::models::Folder;
 use neverlight_mail_core::setup::SetupModel;
 
+use super::recipients;
+use super::settings::{AccountColor, SyncInterval};
+use super::signatures::SignatureEditor;
 use super::{AppModel, Message, Phase};
 
 fn revalidated_selected_folder_index(
@@ -101,6 +104,33 @@ impl AppModel {
             log::warn!("Failed to delete token from keyring: {}", e);
         }
 
+        self.folder_sync_times.remove_account(&removed_id);
+        self.folder_activity.remove(&removed_id);
+        self.pause_overrides.remove(&removed_id);
+        self.recipient_index.remove(&removed_id);
+        self.unsynced_sends.remove(&removed_id);
+        if let Err(e) = self.folder_sync_times.save() {
+            log::warn!("Failed to save folder sync times: {}", e);
+        }
+        self.folder_usage.remove_account(&removed_id);
+        if let Err(e) = self.folder_usage.save() {
+            log::warn!("Failed to save folder usage: {}", e);
+        }
+        self.warmed_up_accounts.remove(&removed_id);
+        self.thread_overrides.remove_account(&removed_id);
+        if let Err(e) = self.thread_overrides.save() {
+            log::warn!("Failed to save thread overrides: {}", e);
+        }
+        self.saved_collapsed_threads.remove_account(&removed_id);
+        if let Err(e) = self.saved_collapsed_threads.save() {
+            log::warn!("Failed to save collapsed threads: {}", e);
+        }
+        if self.settings.remove_account(&removed_id) {
+            if let Err(e) = self.settings.save() {
+                log::warn!("Failed to save settings: {}", e);
+            }
+        }
+
         self.status_message = "Account removed".into();
 
         // Clean up cached data for removed account
@@ -123,7 +153,7 @@ impl AppModel {
             .is_some_and(|a| a.folders.iter().any(|f| f.mailbox_id == mailbox_id))
     }
 
-    fn clear_selected_folder_projection(&mut self) {
+    pub(super) fn clear_selected_folder_projection(&mut self) {
         self.messages.clear();
         self.selected_message = None;
         self.messages_offset = 0;
@@ -133,8 +163,10 @@ impl AppModel {
         self.preview_markdown.clear();
         self.preview_attachments.clear();
         self.preview_image_handles.clear();
+        self.preview_attachment_hashes.clear();
         self.conversation.clear();
         self.active_conversation_id = None;
+        self.conversation_sender_filter = None;
         self.collapsed_threads.clear();
         self.recompute_visible();
     }
@@ -196,6 +228,36 @@ impl AppModel {
             .unwrap_or_default()
     }
 
+    /// Last successful sync (Unix seconds) of the selected folder.
+    pub(super) fn selected_mailbox_sync_time(&self) -> Option<u64> {
+        let acct = self.active_account.and_then(|i| self.accounts.get(i))?;
+        let folder = self.selected_folder.and_then(|fi| acct.folders.get(fi))?;
+        self.folder_sync_times.get(&acct.config.id, &folder.mailbox_id)
+    }
+
+    /// Note a successful sync; the flush timer writes it out later.
+    pub(super) fn record_folder_synced(&mut self, account_id: &str, mailbox_id: &str) {
+        self.folder_sync_times
+            .record(account_id, mailbox_id, super::drafts::unix_now());
+        self.folder_sync_times_dirty = true;
+    }
+
+    /// Write recorded sync times in the background.
+    pub(super) fn flush_folder_sync_times(&mut self) -> Task<Message> {
+        if !std::mem::take(&mut self.folder_sync_times_dirty) {
+            return Task::none();
+        }
+        let times = self.folder_sync_times.clone();
+        cosmic::task::future(async move {
+            match tokio::task::spawn_blocking(move || times.save()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => log::warn!("Failed to save folder sync times: {}", e),
+                Err(e) => log::warn!("Folder sync times save task failed: {}", e),
+            }
+            Message::Noop
+        })
+    }
+
     /// Get the active account's client.
     pub(super) fn active_client(&self) -> Option<JmapClient> {
         self.active_account
@@ -220,6 +282,7 @@ impl AppModel {
         let label = acct.config.label.clone();
         log::warn!("Dropping client for '{}' (reason: {})", label, reason);
         acct.client = None;
+        acct.reset_sync();
         acct.conn_state = super::ConnectionState::Error(format!("Session lost: {}", reason));
         acct.last_error = Some(format!("Session lost: {}", reason));
         let delay = acct.reconnect_backoff();
@@ -272,6 +335,20 @@ impl AppModel {
             .get(self.compose_account)
             .map(|a| a.config.email_addresses.clone())
             .unwrap_or_default();
+        self.refresh_external_recipients();
+        self.refresh_compose_suggestions();
+    }
+
+    /// Recompute the compose "external recipient" banner for the current
+    /// account and To field.
+    pub(super) fn refresh_external_recipients(&mut self) {
+        let internal = self
+            .accounts
+            .get(self.compose_account)
+            .map(|a| self.settings.internal_domains(&a.config.id))
+            .unwrap_or_default();
+        self.compose_external_recipients =
+            recipients::external_recipients(&self.compose_to, internal);
     }
 
     /// Handle account management messages (add/edit/remove/collapse).
@@ -280,6 +357,13 @@ impl AppModel {
             Message::AccountAdd => {
                 self.setup_model = Some(SetupModel::from_config_needs(&ConfigNeedsInput::FullSetup));
                 self.setup_password_visible = false;
+                self.setup_sync_interval = SyncInterval::default().choice_index();
+                self.setup_internal_domains.clear();
+                self.setup_account_group.clear();
+                self.setup_account_color = 0;
+                self.setup_signatures = SignatureEditor::default();
+                self.setup_saved_passwords.clear();
+                self.setup_saved_passwords_note = None;
             }
             Message::RequestDeleteAccount(ref id) => {
                 self.confirm_delete_account_id = Some(id.clone());
@@ -307,6 +391,18 @@ impl AppModel {
                         },
                     ));
                     self.setup_password_visible = false;
+                    self.setup_sync_interval = self.settings.sync_interval(id).choice_index();
+                    self.setup_internal_domains = self.settings.internal_domains(id).join(", ");
+                    self.setup_account_group =
+                        self.settings.account_group(id).unwrap_or_default().to_string();
+                    self.setup_account_color =
+                        AccountColor::choice_index(self.settings.account_color(id));
+                    self.setup_signatures = SignatureEditor::new(
+                        &acct.config.email_addresses.join(", "),
+                        &self.settings.quoting,
+                    );
+                    self.setup_saved_passwords.clear();
+                    self.setup_saved_passwords_note = None;
                 }
             }
             Message::ToggleAccountCollapse(idx) => {
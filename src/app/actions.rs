@@ -1,12 +1,22 @@
+use std::time::Duration;
+
 use cosmic::app::Task;
+use cosmic::widget;
+use neverlight_mail_core::models::Folder;
 use neverlight_mail_core::store;
 use neverlight_mail_core::FlagOp;
 
+use super::toasts::Severity;
 use super::{
     ActionKind, AppModel, FlagIntentKind, MailboxIdentity, Message, MessageIdentity,
     PendingFlagIntent, PendingMoveIntent, Phase, RecoverableActionError, RetryAction,
+    UndoableMove,
 };
 
+/// How many completed moves keep an undo entry (older toasts go inert).
+const MAX_UNDOABLE_MOVES: usize = 10;
+const UNDO_TOAST_DURATION: Duration = Duration::from_secs(6);
+
 fn error_indicates_dead_session(e: &str) -> bool {
     let lower = e.to_lowercase();
     lower.contains("broken pipe")
@@ -16,6 +26,16 @@ fn error_indicates_dead_session(e: &str) -> bool {
         || lower.contains("timeout")
 }
 
+/// Mailbox ID of the folder at `path`; an exact match wins over a
+/// case-insensitive one, so "Done" and "done" can coexist.
+fn folder_by_path(folders: &[Folder], path: &str) -> Option<String> {
+    folders
+        .iter()
+        .find(|f| f.path == path)
+        .or_else(|| folders.iter().find(|f| f.path.eq_ignore_ascii_case(path)))
+        .map(|f| f.mailbox_id.clone())
+}
+
 impl AppModel {
     pub(super) fn handle_actions(&mut self, message: Message) -> Task<Message> {
         match message {
@@ -34,11 +54,7 @@ impl AppModel {
             Message::ToggleRead(index) => {
                 if let Some(msg) = self.messages.get(index) {
                     return self.queue_or_start_flag(PendingFlagIntent {
-                        message: MessageIdentity {
-                            account_id: msg.account_id.clone(),
-                            mailbox_id: msg.context_mailbox_id.clone(),
-                            email_id: msg.email_id.clone(),
-                        },
+                        message: MessageIdentity::of(msg),
                         kind: FlagIntentKind::ToggleRead,
                     });
                 }
@@ -46,11 +62,7 @@ impl AppModel {
             Message::ToggleStar(index) => {
                 if let Some(msg) = self.messages.get(index) {
                     return self.queue_or_start_flag(PendingFlagIntent {
-                        message: MessageIdentity {
-                            account_id: msg.account_id.clone(),
-                            mailbox_id: msg.context_mailbox_id.clone(),
-                            email_id: msg.email_id.clone(),
-                        },
+                        message: MessageIdentity::of(msg),
                         kind: FlagIntentKind::ToggleStar,
                     });
                 }
@@ -62,17 +74,11 @@ impl AppModel {
                 return self.run_flag_intent(intent);
             }
             Message::Trash(index) => {
-                if let Some(intent) = self.trash_intent_for_index(index) {
-                    log::debug!(
-                        "Trash: index={} email_id={} source={} dest={}",
-                        index,
-                        intent.message.email_id,
-                        intent.source.mailbox_id,
-                        intent.dest.mailbox_id,
-                    );
-                    return self.queue_or_start_move(intent);
+                if let Some(confirm) = self.trash_confirm_for_index(index) {
+                    self.trash_confirm = Some(confirm);
+                    return Task::none();
                 }
-                log::debug!("Trash: trash_intent_for_index({}) returned None", index);
+                return self.trash_index(index);
             }
             Message::Archive(index) => {
                 if let Some(intent) = self.archive_intent_for_index(index) {
@@ -82,6 +88,49 @@ impl AppModel {
             Message::RunMoveIntent(intent) => {
                 return self.run_move_intent(intent);
             }
+            Message::BulkArchive => {
+                return self.bulk_move_selection("archive");
+            }
+            Message::BulkTrash => {
+                return self.bulk_move_selection("trash");
+            }
+            Message::BulkMarkRead => {
+                let unread: Vec<MessageIdentity> = self
+                    .message_selection
+                    .identities()
+                    .filter(|id| {
+                        self.messages.iter().any(|m| {
+                            m.email_id == id.email_id
+                                && m.context_mailbox_id == id.mailbox_id
+                                && !m.is_read
+                        })
+                    })
+                    .cloned()
+                    .collect();
+                return self.toggle_read_all(unread);
+            }
+            Message::MarkThreadRead { index, read } => {
+                let Some(root) = self.messages.get(index) else {
+                    return Task::none();
+                };
+                let Some(thread_id) = root.thread_id.clone() else {
+                    return Task::none();
+                };
+                let account_id = root.account_id.clone();
+                // Only the thread's messages loaded in this list; copies in
+                // other folders (e.g. Sent) keep their own state.
+                let to_flip: Vec<MessageIdentity> = self
+                    .messages
+                    .iter()
+                    .filter(|m| {
+                        m.account_id == account_id
+                            && m.thread_id.as_deref() == Some(thread_id.as_str())
+                            && m.is_read != read
+                    })
+                    .map(MessageIdentity::of)
+                    .collect();
+                return self.toggle_read_all(to_flip);
+            }
             Message::DragMessageToFolder {
                 message,
                 source,
@@ -96,19 +145,20 @@ impl AppModel {
 
                 // Prevent cross-account moves
                 if source.account_id != dest.account_id {
-                    self.status_message = "Cannot move messages between accounts".into();
+                    self.notify(Severity::Warning, "Cannot move messages between accounts");
                     return Task::none();
                 }
                 if message.account_id != source.account_id
                     || message.mailbox_id != source.mailbox_id
                 {
-                    self.status_message = "Cannot move message: source identity mismatch".into();
+                    self.notify(Severity::Warning, "Cannot move message: source identity mismatch");
                     return Task::none();
                 }
                 if !self.mailbox_belongs_to_account(&source.account_id, &source.mailbox_id)
                     || !self.mailbox_belongs_to_account(&dest.account_id, &dest.mailbox_id)
                 {
-                    self.status_message = "Cannot move message: mailbox no longer available".into();
+                    let text = "Cannot move message: mailbox no longer available";
+                    self.notify(Severity::Warning, text);
                     return Task::none();
                 }
 
@@ -135,13 +185,18 @@ impl AppModel {
                     return Task::none();
                 }
                 self.pending_flag_epochs.remove(&message);
+                self.pending_local_flags.remove(&message);
                 self.flag_in_flight_accounts.remove(&message.account_id);
+                let server_won = self.server_won_flags.remove(&message);
 
                 let mut tasks: Vec<Task<Message>> = Vec::new();
                 match result {
                     Ok(new_flags) => {
                         self.clear_error_surface();
-                        if let Some(cache) = &self.cache {
+                        self.prune_smart_view();
+                        if let Some(server_flags) = server_won {
+                            tasks.push(self.restore_server_flags(&message, new_flags, server_flags));
+                        } else if let Some(cache) = &self.cache {
                             let cache = cache.clone();
                             let account_id = message.account_id.clone();
                             let email_id = message.email_id.clone();
@@ -210,17 +265,13 @@ impl AppModel {
                         }
                     }
                 }
-                if let Some(next) = self.pending_flag_intents.remove(&message.account_id) {
-                    tasks.push(self.dispatch(Message::RunFlagIntent(next)));
-                }
-                if tasks.is_empty() {
-                    return Task::none();
-                }
+                tasks.push(self.try_run_next_flag_intent_for(&message.account_id));
                 return cosmic::task::batch(tasks);
             }
             Message::MoveOpComplete {
                 message,
                 source,
+                dest,
                 epoch,
                 result,
             } => {
@@ -246,8 +297,15 @@ impl AppModel {
                         self.clear_error_surface();
                         let account_id = message.account_id.clone();
                         let email_id = message.email_id.clone();
-                        self.pending_move_restore.remove(&message);
                         let mut tasks: Vec<Task<Message>> = Vec::new();
+                        if let Some((summary, index)) = self.pending_move_restore.remove(&message) {
+                            tasks.push(self.offer_undo(UndoableMove {
+                                message: message.clone(),
+                                summary,
+                                index,
+                                dest,
+                            }));
+                        }
                         if let Some(cache) = &self.cache {
                             let cache = cache.clone();
                             let account_id_for_cache = account_id.clone();
@@ -317,29 +375,158 @@ impl AppModel {
                     }
                 }
             }
+            Message::UndoMove(message) => {
+                return self.start_undo_move(&message);
+            }
+            Message::UndoMoveComplete { undo, result } => {
+                self.mutation_in_flight_accounts.remove(&undo.message.account_id);
+                let account_id = undo.message.account_id.clone();
+                let mut tasks: Vec<Task<Message>> = Vec::new();
+                match result {
+                    Ok(()) => {
+                        self.status_message = "Move undone".into();
+                        // The first move dropped the row from the cache; re-sync
+                        // the account so the source folder's cache has it again.
+                        tasks.push(self.handle_refresh_accounts(Some(&account_id)));
+                    }
+                    Err(e) => {
+                        log::error!("Undo move failed: {}", e);
+                        if let Some(pos) = self.messages.iter().position(|m| {
+                            m.email_id == undo.message.email_id
+                                && m.context_mailbox_id == undo.message.mailbox_id
+                        }) {
+                            self.remove_message_optimistic(pos);
+                        }
+                        self.set_recoverable_action_error(RecoverableActionError {
+                            action: ActionKind::Move,
+                            message: format!("Undo failed: {}", e),
+                            retry: RetryAction::Refresh,
+                            email_id: Some(undo.message.email_id.clone()),
+                            mailbox_id: Some(undo.dest.mailbox_id.clone()),
+                        });
+                    }
+                }
+                tasks.push(self.try_run_next_move_intent_for(&account_id));
+                return cosmic::task::batch(tasks);
+            }
             _ => {}
         }
         Task::none()
     }
 
-    fn try_run_next_flag_intent_for(&mut self, account_id: &str) -> Task<Message> {
-        if let Some(next) = self.pending_flag_intents.remove(account_id) {
+    /// Remember a completed move and show its "Undo" toast.
+    fn offer_undo(&mut self, undo: UndoableMove) -> Task<Message> {
+        let dest_name = self
+            .account_index(&undo.dest.account_id)
+            .and_then(|idx| self.accounts.get(idx))
+            .and_then(|a| a.folders.iter().find(|f| f.mailbox_id == undo.dest.mailbox_id))
+            .map(|f| f.name.clone())
+            .unwrap_or_else(|| "folder".into());
+        let key = undo.message.clone();
+        self.undoable_moves.push_back(undo);
+        while self.undoable_moves.len() > MAX_UNDOABLE_MOVES {
+            self.undoable_moves.pop_front();
+        }
+        let toast = widget::toaster::Toast::new(format!("Moved to {dest_name}"))
+            .action("Undo".into(), move |_id| Message::UndoMove(key.clone()))
+            .duration(UNDO_TOAST_DURATION);
+        self.toasts.push(toast).map(cosmic::Action::App)
+    }
+
+    /// Move a message back to its source mailbox and restore it in the list.
+    fn start_undo_move(&mut self, message: &MessageIdentity) -> Task<Message> {
+        let Some(pos) = self.undoable_moves.iter().position(|u| &u.message == message) else {
+            return Task::none();
+        };
+        let account_id = message.account_id.clone();
+        if self.mutation_in_flight_accounts.contains(&account_id) {
+            self.status_message = "Undo unavailable while another move is running".into();
+            return Task::none();
+        }
+        let Some(client) = self.client_for_account(&account_id) else {
+            self.notify(Severity::Error, "Undo failed: account is offline");
+            return Task::none();
+        };
+        let Some(undo) = self.undoable_moves.remove(pos) else {
+            return Task::none();
+        };
+
+        let viewing_source = self
+            .active_account
+            .and_then(|i| self.accounts.get(i))
+            .is_some_and(|a| a.config.id == undo.message.account_id)
+            && self.selected_mailbox_id.as_deref() == Some(undo.message.mailbox_id.as_str());
+        if viewing_source {
+            let at = undo.index.min(self.messages.len());
+            self.messages.insert(at, undo.summary.clone());
+            if let Some(sel) = self.selected_message.filter(|sel| *sel >= at) {
+                self.selected_message = Some(sel + 1);
+            }
+            self.recompute_visible();
+        }
+
+        self.mutation_in_flight_accounts.insert(account_id);
+        cosmic::task::future(async move {
+            let result = neverlight_mail_core::email::move_to(
+                &client,
+                &undo.message.email_id,
+                &undo.dest.mailbox_id,
+                &undo.message.mailbox_id,
+            )
+            .await
+            .map_err(|e| e.to_string());
+            Message::UndoMoveComplete { undo, result }
+        })
+    }
+
+    pub(super) fn try_run_next_flag_intent_for(&mut self, account_id: &str) -> Task<Message> {
+        let next = self
+            .pending_flag_intents
+            .get_mut(account_id)
+            .and_then(|queue| queue.pop_front());
+        if let Some(next) = next {
             return self.dispatch(Message::RunFlagIntent(next));
         }
         Task::none()
     }
 
-    fn try_run_next_move_intent_for(&mut self, account_id: &str) -> Task<Message> {
-        if let Some(next) = self.pending_move_intents.remove(account_id) {
+    pub(super) fn try_run_next_move_intent_for(&mut self, account_id: &str) -> Task<Message> {
+        let next = self
+            .pending_move_intents
+            .get_mut(account_id)
+            .and_then(|queue| queue.pop_front());
+        if let Some(next) = next {
             return self.dispatch(Message::RunMoveIntent(next));
         }
+        // A cleanup run on this account waits for queued moves to drain.
+        if self.cleanup.as_ref().is_some_and(|w| w.account_id == account_id) {
+            return self.cleanup_next_move();
+        }
         Task::none()
     }
 
+    /// Queue a read toggle for each message; flag ops run one at a time per
+    /// account.
+    fn toggle_read_all(&mut self, messages: Vec<MessageIdentity>) -> Task<Message> {
+        let tasks: Vec<Task<Message>> = messages
+            .into_iter()
+            .map(|message| {
+                self.queue_or_start_flag(PendingFlagIntent {
+                    message,
+                    kind: FlagIntentKind::ToggleRead,
+                })
+            })
+            .collect();
+        cosmic::task::batch(tasks)
+    }
+
     fn queue_or_start_flag(&mut self, intent: PendingFlagIntent) -> Task<Message> {
         let account_id = intent.message.account_id.clone();
         if self.flag_in_flight_accounts.contains(&account_id) {
-            self.pending_flag_intents.insert(account_id, intent);
+            self.pending_flag_intents
+                .entry(account_id)
+                .or_default()
+                .push_back(intent);
             self.status_message = "Flag update queued...".into();
             return Task::none();
         }
@@ -427,6 +614,8 @@ impl AppModel {
             self.flag_epoch = self.flag_epoch.saturating_add(1);
             let epoch = self.flag_epoch;
             self.pending_flag_epochs.insert(message_id.clone(), epoch);
+            self.pending_local_flags
+                .insert(message_id.clone(), (prev_flags, new_flags));
             self.flag_in_flight_accounts
                 .insert(message_id.account_id.clone());
             op_epoch = Some(epoch);
@@ -450,6 +639,7 @@ impl AppModel {
 
         if op_epoch.is_none() {
             self.pending_flag_epochs.remove(&message_id);
+            self.pending_local_flags.remove(&message_id);
             self.flag_in_flight_accounts.remove(&message_id.account_id);
         }
         if tasks.is_empty() {
@@ -459,7 +649,23 @@ impl AppModel {
         }
     }
 
-    fn queue_or_start_move(&mut self, intent: PendingMoveIntent) -> Task<Message> {
+    /// Move the message at `index` to Trash, without the reply warning.
+    pub(super) fn trash_index(&mut self, index: usize) -> Task<Message> {
+        let Some(intent) = self.trash_intent_for_index(index) else {
+            log::debug!("Trash: trash_intent_for_index({}) returned None", index);
+            return Task::none();
+        };
+        log::debug!(
+            "Trash: index={} email_id={} source={} dest={}",
+            index,
+            intent.message.email_id,
+            intent.source.mailbox_id,
+            intent.dest.mailbox_id,
+        );
+        self.queue_or_start_move(intent)
+    }
+
+    pub(super) fn queue_or_start_move(&mut self, intent: PendingMoveIntent) -> Task<Message> {
         let account_id = intent.source.account_id.clone();
         if self.mutation_in_flight_accounts.contains(&account_id) {
             log::debug!(
@@ -467,7 +673,10 @@ impl AppModel {
                 intent.message.email_id,
                 account_id,
             );
-            self.pending_move_intents.insert(account_id, intent);
+            self.pending_move_intents
+                .entry(account_id)
+                .or_default()
+                .push_back(intent);
             self.status_message = "Move queued...".into();
             return Task::none();
         }
@@ -493,7 +702,7 @@ impl AppModel {
                 intent.message.email_id,
                 intent.source.account_id,
             );
-            self.status_message = "Move failed: account is offline".into();
+            self.notify(Severity::Error, "Move failed: account is offline");
             return self.try_run_next_move_intent_for(&source_account_id);
         }
         let Some(index) = self.messages.iter().position(|m| {
@@ -517,6 +726,7 @@ impl AppModel {
                 intent.source.mailbox_id,
                 intent.dest.mailbox_id,
             );
+            self.message_selection.remove(&intent.message);
             self.pending_move_restore
                 .insert(intent.message.clone(), (removed, index));
             return self.dispatch_move(intent.message, intent.source, intent.dest);
@@ -529,14 +739,13 @@ impl AppModel {
         self.try_run_next_move_intent_for(&source_account_id)
     }
 
-    fn trash_intent_for_index(&mut self, index: usize) -> Option<PendingMoveIntent> {
+    pub(super) fn trash_intent_for_index(&mut self, index: usize) -> Option<PendingMoveIntent> {
         let Some(msg) = self.messages.get(index) else {
             log::debug!("trash_intent: index {} out of range (len={})", index, self.messages.len());
             return None;
         };
-        let account_id = msg.account_id.clone();
-        let mailbox_id = msg.context_mailbox_id.clone();
-        let email_id = msg.email_id.clone();
+        let message = MessageIdentity::of(msg);
+        let account_id = message.account_id.clone();
         let Some(acct) = self
             .account_index(&account_id)
             .and_then(|idx| self.accounts.get(idx))
@@ -548,19 +757,16 @@ impl AppModel {
             neverlight_mail_core::mailbox::find_by_role(&acct.folders, "trash")
         else {
             log::debug!("trash_intent: no trash folder for account {}", account_id);
-            self.status_message = "Trash folder not found".into();
+            self.notify(Severity::Warning, "Trash folder not found");
             return None;
         };
+        let source = MailboxIdentity {
+            account_id: account_id.clone(),
+            mailbox_id: message.mailbox_id.clone(),
+        };
         Some(PendingMoveIntent {
-            message: MessageIdentity {
-                account_id: account_id.clone(),
-                mailbox_id: mailbox_id.clone(),
-                email_id,
-            },
-            source: MailboxIdentity {
-                account_id: account_id.clone(),
-                mailbox_id,
-            },
+            message,
+            source,
             dest: MailboxIdentity {
                 account_id,
                 mailbox_id: trash_id,
@@ -568,30 +774,67 @@ impl AppModel {
         })
     }
 
-    fn archive_intent_for_index(&mut self, index: usize) -> Option<PendingMoveIntent> {
-        let msg = self.messages.get(index)?;
-        let account_id = msg.account_id.clone();
-        let mailbox_id = msg.context_mailbox_id.clone();
-        let email_id = msg.email_id.clone();
+    /// Queue a move of every selected message to its account's `role` mailbox.
+    pub(super) fn bulk_move_selection(&mut self, role: &str) -> Task<Message> {
+        let selected: Vec<MessageIdentity> =
+            self.message_selection.identities().cloned().collect();
+        let mut tasks: Vec<Task<Message>> = Vec::new();
+        let mut missing_dest = 0usize;
+        for message in selected {
+            let Some(dest_id) = self.role_mailbox(&message.account_id, role) else {
+                missing_dest += 1;
+                continue;
+            };
+            let intent = PendingMoveIntent {
+                source: MailboxIdentity {
+                    account_id: message.account_id.clone(),
+                    mailbox_id: message.mailbox_id.clone(),
+                },
+                dest: MailboxIdentity {
+                    account_id: message.account_id.clone(),
+                    mailbox_id: dest_id,
+                },
+                message,
+            };
+            tasks.push(self.queue_or_start_move(intent));
+        }
+        self.message_selection.clear();
+        if missing_dest > 0 {
+            self.status_message = format!("{missing_dest} skipped: no {role} folder");
+        }
+        cosmic::task::batch(tasks)
+    }
+
+    /// Destination for a move to `role`. Archive honours the account's
+    /// custom archive folder from settings when one is set.
+    pub(super) fn role_mailbox(&self, account_id: &str, role: &str) -> Option<String> {
         let acct = self
-            .account_index(&account_id)
+            .account_index(account_id)
             .and_then(|idx| self.accounts.get(idx))?;
-        let Some(archive_id) =
-            neverlight_mail_core::mailbox::find_by_role(&acct.folders, "archive")
-        else {
-            self.status_message = "Archive folder not found".into();
+        match self.settings.archive_folder(account_id) {
+            Some(path) if role == "archive" => folder_by_path(&acct.folders, path),
+            _ => neverlight_mail_core::mailbox::find_by_role(&acct.folders, role),
+        }
+    }
+
+    fn archive_intent_for_index(&mut self, index: usize) -> Option<PendingMoveIntent> {
+        let msg = self.messages.get(index)?;
+        let message = MessageIdentity::of(msg);
+        let account_id = message.account_id.clone();
+        let Some(archive_id) = self.role_mailbox(&account_id, "archive") else {
+            self.status_message = match self.settings.archive_folder(&account_id) {
+                Some(path) => format!("Archive folder \"{path}\" not found"),
+                None => "Archive folder not found".into(),
+            };
             return None;
         };
+        let source = MailboxIdentity {
+            account_id: account_id.clone(),
+            mailbox_id: message.mailbox_id.clone(),
+        };
         Some(PendingMoveIntent {
-            message: MessageIdentity {
-                account_id: account_id.clone(),
-                mailbox_id: mailbox_id.clone(),
-                email_id,
-            },
-            source: MailboxIdentity {
-                account_id: account_id.clone(),
-                mailbox_id,
-            },
+            message,
+            source,
             dest: MailboxIdentity {
                 account_id,
                 mailbox_id: archive_id,
@@ -620,6 +863,7 @@ impl AppModel {
                 self.preview_markdown.clear();
                 self.preview_attachments.clear();
                 self.preview_image_handles.clear();
+                self.preview_attachment_hashes.clear();
             }
             Some(sel) if sel > index => {
                 self.selected_message = Some(sel - 1);
@@ -675,6 +919,7 @@ impl AppModel {
             self.pending_move_epochs.insert(message.clone(), epoch);
             let message_for_completion = message.clone();
             let source_for_completion = source.clone();
+            let dest_for_completion = dest.clone();
             let email_id = message.email_id.clone();
             let source_mailbox_id = source.mailbox_id.clone();
             let dest_mailbox_id = dest.mailbox_id.clone();
@@ -690,6 +935,7 @@ impl AppModel {
                 Message::MoveOpComplete {
                     message: message_for_completion,
                     source: source_for_completion,
+                    dest: dest_for_completion,
                     epoch,
                     result,
                 }
@@ -702,6 +948,35 @@ impl AppModel {
             cosmic::task::batch(tasks)
         }
     }
-
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_folder(mailbox_id: &str, path: &str) -> Folder {
+        Folder {
+            mailbox_id: mailbox_id.to_string(),
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            role: None,
+            sort_order: 0,
+            unread_count: 0,
+            total_count: 0,
+        }
+    }
+
+    #[test]
+    fn custom_archive_folder_matches_path() {
+        let folders = vec![sample_folder("M1", "INBOX"), sample_folder("M2", "Work/Done")];
+        assert_eq!(folder_by_path(&folders, "Work/Done").as_deref(), Some("M2"));
+        assert_eq!(folder_by_path(&folders, "work/done").as_deref(), Some("M2"));
+        assert_eq!(folder_by_path(&folders, "Done"), None);
+    }
+
+    #[test]
+    fn exact_path_beats_case_insensitive_match() {
+        let folders = vec![sample_folder("M1", "done"), sample_folder("M2", "Done")];
+        assert_eq!(folder_by_path(&folders, "Done").as_deref(), Some("M2"));
+    }
+}
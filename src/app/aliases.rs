@@ -0,0 +1,74 @@
+//! Plus-address aliases: mail sent to `me+shop@example.com` when the
+//! account's address is `me@example.com`.
+//!
+//! The alias is read from the cached `To` header against the account's
+//! configured addresses; the cache keeps no `Delivered-To`, so an alias that
+//! only reached us through Bcc or a list goes unnoticed. The message header
+//! names the alias, and clicking it narrows the message list to mail for
+//! that alias until the filter is cleared.
+
+use cosmic::app::Task;
+use neverlight_mail_core::models::MessageSummary;
+
+use super::recipients::bare_address;
+use super::{AppModel, Message};
+
+/// The tag of the first `To` address that is one of `own` plus a `+tag`,
+/// lowercased.
+pub fn plus_alias(to: &str, own: &[String]) -> Option<String> {
+    to.split(',').find_map(|recipient| {
+        let (local, domain) = bare_address(recipient).rsplit_once('@')?;
+        let (base, tag) = local.split_once('+')?;
+        let base_address = format!("{base}@{domain}");
+        let ours = own
+            .iter()
+            .any(|addr| bare_address(addr).eq_ignore_ascii_case(&base_address));
+        (ours && !tag.is_empty()).then(|| tag.to_lowercase())
+    })
+}
+
+impl AppModel {
+    /// The plus alias `msg` was addressed to, if its account has one.
+    pub(super) fn message_alias(&self, msg: &MessageSummary) -> Option<String> {
+        let account = self.accounts.get(self.account_index(&msg.account_id)?)?;
+        plus_alias(&msg.to, &account.config.email_addresses)
+    }
+
+    pub(super) fn handle_alias_filter(&mut self, message: Message) -> Task<Message> {
+        if let Message::FilterAlias(alias) = message {
+            self.alias_filter = alias;
+            self.recompute_visible();
+            self.status_message = match &self.alias_filter {
+                Some(alias) => format!("Showing mail sent to +{alias}"),
+                None => "Showing all mail".into(),
+            };
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn own() -> Vec<String> {
+        vec!["me@example.com".into(), "Work <me@work.example>".into()]
+    }
+
+    #[test]
+    fn tag_on_an_own_address_is_the_alias() {
+        assert_eq!(plus_alias("me+Shop@example.com", &own()), Some("shop".into()));
+        assert_eq!(
+            plus_alias("Ann <ann@example.com>, Me <ME+news@work.example>", &own()),
+            Some("news".into())
+        );
+    }
+
+    #[test]
+    fn other_peoples_tags_are_not_aliases() {
+        assert_eq!(plus_alias("ann+shop@example.com", &own()), None);
+        assert_eq!(plus_alias("me@example.com", &own()), None);
+        assert_eq!(plus_alias("me+@example.com", &own()), None);
+        assert_eq!(plus_alias("me+shop@example.com", &[]), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Saving attachments: a save dialog per file, "Save all" into a picked
+//! directory.
+//!
+//! Both start in the last directory something was saved to (settings.json
+//! `last_save_dir`, else Downloads). Names already taken there get a
+//! " (1)", " (2)" suffix instead of being overwritten; the save dialog is
+//! offered the free name so its own overwrite prompt stays for deliberate
+//! choices. Save all creates each file new, moving on to the next suffix
+//! when the name is taken, so it never writes through a file or symlink
+//! that was already there.
+
+use std::path::{Path, PathBuf};
+
+use cosmic::app::Task;
+use cosmic::dialog::file_chooser;
+use neverlight_mail_core::models::AttachmentData;
+
+use super::attachments::{AttachmentAction, BrowserRead};
+use super::thumbnails::ThumbnailTarget;
+use super::{AppModel, Message};
+
+/// The attachment's name with any directory part dropped, so a crafted
+/// "../../.bashrc" can't land outside the chosen directory.
+pub(super) fn safe_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    match base {
+        "" | "." | ".." => "attachment".to_string(),
+        base => base.to_string(),
+    }
+}
+
+/// `name`, then "name (1).ext", "name (2).ext"...
+fn candidate_names(name: &str) -> impl Iterator<Item = String> + '_ {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    std::iter::once(name.to_string()).chain((1..).map(move |n| format!("{stem} ({n}){ext}")))
+}
+
+/// The first of `candidate_names` that `taken` doesn't reject.
+fn unique_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    candidate_names(name)
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// A name not yet used in `dir`.
+fn free_name(dir: &Path, name: &str) -> String {
+    unique_name(&safe_file_name(name), |n| dir.join(n).exists())
+}
+
+/// Write `data` into `dir` under the first free form of `name`. Each try
+/// creates the file, so one that appears meanwhile is skipped, not
+/// overwritten.
+async fn save_new(dir: &Path, name: &str, data: &[u8]) -> std::io::Result<PathBuf> {
+    use tokio::io::AsyncWriteExt;
+    for candidate in candidate_names(&safe_file_name(name)) {
+        let path = dir.join(candidate);
+        let opened = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await;
+        let mut file = match opened {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        file.write_all(data).await?;
+        file.flush().await?;
+        return Ok(path);
+    }
+    Err(std::io::ErrorKind::AlreadyExists.into())
+}
+
+pub(super) async fn save_with_dialog(att: AttachmentData, dir: PathBuf) -> Message {
+    let dialog = file_chooser::save::Dialog::new()
+        .title("Save attachment")
+        .current_folder(&dir)
+        .current_name(free_name(&dir, &att.filename));
+    let url = match dialog.save_file().await {
+        Ok(response) => match response.url() {
+            Some(url) => url.clone(),
+            None => return Message::Noop,
+        },
+        Err(file_chooser::Error::Cancelled) => return Message::Noop,
+        Err(e) => return Message::SaveAttachmentComplete(Err(format!("File picker error: {e}"))),
+    };
+    let Ok(path) = url.to_file_path() else {
+        return Message::SaveAttachmentComplete(Err(format!("Not a local path: {url}")));
+    };
+    match tokio::fs::write(&path, &att.data).await {
+        Ok(()) => Message::SaveAttachmentComplete(Ok(path)),
+        Err(e) => Message::SaveAttachmentComplete(Err(format!("Save failed: {e}"))),
+    }
+}
+
+pub(super) async fn save_all_to_directory(attachments: Vec<AttachmentData>, dir: PathBuf) -> Message {
+    let dialog = file_chooser::open::Dialog::new()
+        .title("Save all attachments to")
+        .current_folder(&dir);
+    let url = match dialog.open_folder().await {
+        Ok(response) => response.url().clone(),
+        Err(file_chooser::Error::Cancelled) => return Message::Noop,
+        Err(e) => {
+            return Message::SaveAllAttachmentsComplete(Err(format!("File picker error: {e}")))
+        }
+    };
+    let Ok(dir) = url.to_file_path() else {
+        return Message::SaveAllAttachmentsComplete(Err(format!("Not a local path: {url}")));
+    };
+    for att in &attachments {
+        // One at a time, so each name sees the files saved before it.
+        if let Err(e) = save_new(&dir, &att.filename, &att.data).await {
+            return Message::SaveAllAttachmentsComplete(Err(format!(
+                "Save failed for {}: {e}",
+                att.filename
+            )));
+        }
+    }
+    Message::SaveAllAttachmentsComplete(Ok((dir, attachments.len())))
+}
+
+impl AppModel {
+    /// Where the next save dialog opens.
+    pub(super) fn save_dir(&self) -> PathBuf {
+        self.settings
+            .last_save_dir
+            .clone()
+            .filter(|d| d.is_dir())
+            .or_else(dirs::download_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    fn remember_save_dir(&mut self, dir: Option<&Path>) {
+        let Some(dir) = dir else {
+            return;
+        };
+        if self.settings.last_save_dir.as_deref() == Some(dir) {
+            return;
+        }
+        self.settings.last_save_dir = Some(dir.to_path_buf());
+        if let Err(e) = self.settings.save() {
+            log::warn!("Failed to save settings: {}", e);
+        }
+    }
+
+    pub(super) fn handle_attachment_save(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SaveAllAttachments(target) => {
+                let attachments: Vec<AttachmentData> = match &target {
+                    ThumbnailTarget::Preview { .. } => self.preview_attachments.clone(),
+                    ThumbnailTarget::Conversation { email_id } => self
+                        .conversation
+                        .iter()
+                        .find(|e| e.email_id == *email_id)
+                        .map(|e| e.attachments.clone())
+                        .unwrap_or_default(),
+                    ThumbnailTarget::Browser { .. } => {
+                        return self.read_browser_entries(BrowserRead::SaveAll);
+                    }
+                };
+                if attachments.is_empty() {
+                    return Task::none();
+                }
+                return self.attachment_action(AttachmentAction::SaveAll(attachments));
+            }
+            Message::SaveAttachmentComplete(Ok(path)) => {
+                self.remember_save_dir(path.parent());
+                self.status_message = format!("Saved to {}", path.display());
+            }
+            Message::SaveAllAttachmentsComplete(Ok((dir, count))) => {
+                self.remember_save_dir(Some(&dir));
+                self.status_message = format!("Saved {count} attachments to {}", dir.display());
+            }
+            Message::SaveAttachmentComplete(Err(e))
+            | Message::SaveAllAttachmentsComplete(Err(e)) => {
+                log::error!("Attachment save failed: {}", e);
+                self.status_message = e;
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taken_names_get_a_counter_before_the_extension() {
+        let taken = ["report.pdf", "report (1).pdf", "notes"];
+        let is_taken = |n: &str| taken.contains(&n);
+        assert_eq!(unique_name("invoice.pdf", is_taken), "invoice.pdf");
+        assert_eq!(unique_name("report.pdf", is_taken), "report (2).pdf");
+        assert_eq!(unique_name("notes", is_taken), "notes (1)");
+    }
+
+    #[test]
+    fn dotfiles_keep_their_name_whole() {
+        assert_eq!(unique_name(".profile", |n| n == ".profile"), ".profile (1)");
+    }
+
+    #[test]
+    fn directory_parts_are_dropped() {
+        assert_eq!(safe_file_name("../../.bashrc"), ".bashrc");
+        assert_eq!(safe_file_name("C:\\Users\\x\\evil.exe"), "evil.exe");
+        assert_eq!(safe_file_name(".."), "attachment");
+        assert_eq!(safe_file_name(""), "attachment");
+    }
+
+    #[tokio::test]
+    async fn save_all_skips_a_planted_link() {
+        let dir = std::env::temp_dir().join(format!("neverlight-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::os::unix::fs::symlink(dir.join("elsewhere"), dir.join("report.pdf")).unwrap();
+        let path = save_new(&dir, "report.pdf", b"%PDF").await.unwrap();
+        assert_eq!(path, dir.join("report (1).pdf"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"%PDF");
+        assert!(!dir.join("elsewhere").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
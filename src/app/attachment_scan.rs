@@ -0,0 +1,258 @@
+//! Virus scanning of attachments through an external command.
+//!
+//! With `"attachment_scanner"` set in settings.json (e.g. `"clamscan
+//! --no-summary -"`), every save or open first pipes each attachment into
+//! the command's stdin. Exit status 0 means clean and 1 a detection, as
+//! with clamscan and clamdscan; anything else is a failed scan. Detected
+//! files are refused. A failed scan is held like a risky file name: asked
+//! about, refused or allowed per `dangerous_attachments`. Verdicts are kept
+//! for the session by content hash, so a file is only scanned again after
+//! a failure, and shown under the attachment in the preview and browser.
+//! Those hash their attachments as they load, and only with a scanner set.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+
+use cosmic::app::Task;
+use neverlight_mail_core::models::AttachmentData;
+use tokio::io::AsyncWriteExt;
+
+use super::attachments::AttachmentAction;
+use super::thumbnails::content_hash;
+use super::{AppModel, Message};
+
+/// A scanner still running after this is stopped and the scan fails.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// What the scanner reported.
+    Infected(String),
+    /// Why there is no verdict.
+    Failed(String),
+}
+
+impl ScanVerdict {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Clean => "Virus scan: no threat found".into(),
+            Self::Infected(found) => format!("Virus scan: {found}"),
+            Self::Failed(e) => format!("Virus scan failed: {e}"),
+        }
+    }
+}
+
+/// One attachment's scan, in the order of the action's files.
+#[derive(Debug, Clone)]
+pub struct ScannedFile {
+    hash: String,
+    filename: String,
+    verdict: ScanVerdict,
+}
+
+/// Verdicts so far this session, by content hash.
+#[derive(Debug, Default)]
+pub struct AttachmentScans {
+    verdicts: HashMap<String, ScanVerdict>,
+}
+
+impl AttachmentScans {
+    /// Verdict for the attachment whose `content_hash` is `hash`.
+    pub fn verdict(&self, hash: &str) -> Option<&ScanVerdict> {
+        self.verdicts.get(hash)
+    }
+
+    fn record(&mut self, file: ScannedFile) {
+        self.verdicts.insert(file.hash, file.verdict);
+    }
+}
+
+/// Verdict from the scanner's exit code and output.
+fn verdict_from(code: Option<i32>, stdout: &str, stderr: &str) -> ScanVerdict {
+    let first_line = |text: &str| {
+        text.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+    };
+    match code {
+        Some(0) => ScanVerdict::Clean,
+        Some(1) => {
+            // clamscan: "stdin: Eicar-Signature FOUND".
+            let found = stdout
+                .lines()
+                .find(|line| line.contains("FOUND"))
+                .map(|line| line.trim().trim_start_matches("stdin:").trim().to_string())
+                .or_else(|| first_line(stdout))
+                .unwrap_or_else(|| "threat found".into());
+            ScanVerdict::Infected(found)
+        }
+        Some(code) => ScanVerdict::Failed(
+            first_line(stderr)
+                .or_else(|| first_line(stdout))
+                .unwrap_or_else(|| format!("the scanner exited with {code}")),
+        ),
+        None => ScanVerdict::Failed("the scanner was stopped".into()),
+    }
+}
+
+/// Pipe `data` through `command` and read its verdict.
+async fn scan(command: &[String], data: &[u8]) -> ScanVerdict {
+    let Some((program, args)) = command.split_first() else {
+        return ScanVerdict::Failed("no scanner command".into());
+    };
+    let child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => return ScanVerdict::Failed(format!("couldn't start {program}: {e}")),
+    };
+    let stdin = child.stdin.take();
+    let write = async move {
+        // A scanner may stop reading once it has a verdict.
+        if let Some(mut stdin) = stdin {
+            if let Err(e) = stdin.write_all(data).await {
+                log::debug!("Scanner closed its input early: {}", e);
+            }
+        }
+    };
+    let run = async { tokio::join!(write, child.wait_with_output()).1 };
+    match tokio::time::timeout(SCAN_TIMEOUT, run).await {
+        Ok(Ok(output)) => verdict_from(
+            output.status.code(),
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+        ),
+        Ok(Err(e)) => ScanVerdict::Failed(format!("{program}: {e}")),
+        Err(_) => ScanVerdict::Failed(format!("{program} took too long")),
+    }
+}
+
+impl AppModel {
+    /// Program and arguments of the configured scanner.
+    pub(super) fn scanner_command(&self) -> Option<Vec<String>> {
+        let command = self.settings.attachment_scanner.as_deref()?;
+        let words: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+        (!words.is_empty()).then_some(words)
+    }
+
+    /// Content hashes to look `attachments`' verdicts up by; none without a
+    /// scanner, so the hashing is only paid for when scans can happen.
+    pub(super) fn attachment_hashes(&self, attachments: &[AttachmentData]) -> Vec<String> {
+        if self.scanner_command().is_none() {
+            return Vec::new();
+        }
+        attachments.iter().map(|att| content_hash(&att.data)).collect()
+    }
+
+    /// Scan the action's attachments with `command`, then carry on with it.
+    pub(super) fn scan_attachments(
+        &mut self,
+        command: Vec<String>,
+        action: AttachmentAction,
+    ) -> Task<Message> {
+        let known = self.attachment_scans.verdicts.clone();
+        self.status_message = "Scanning attachments…".into();
+        cosmic::task::future(async move {
+            let mut files = Vec::new();
+            for att in action.files() {
+                let hash = content_hash(&att.data);
+                let verdict = match known.get(&hash) {
+                    Some(verdict) if !matches!(verdict, ScanVerdict::Failed(_)) => verdict.clone(),
+                    _ => scan(&command, &att.data).await,
+                };
+                files.push(ScannedFile {
+                    hash,
+                    filename: att.filename.clone(),
+                    verdict,
+                });
+            }
+            Message::AttachmentsScanned { action, files }
+        })
+    }
+
+    /// Refuse detected files and hold failed scans for the risk check.
+    pub(super) fn handle_attachments_scanned(
+        &mut self,
+        action: AttachmentAction,
+        files: Vec<ScannedFile>,
+    ) -> Task<Message> {
+        let infected: Vec<bool> = files
+            .iter()
+            .map(|f| matches!(f.verdict, ScanVerdict::Infected(_)))
+            .collect();
+        let mut blocked = None;
+        let mut failed = Vec::new();
+        for file in files {
+            match &file.verdict {
+                ScanVerdict::Clean => {}
+                ScanVerdict::Infected(found) => {
+                    blocked.get_or_insert_with(|| format!("Blocked {}: {found}", file.filename));
+                }
+                ScanVerdict::Failed(e) => {
+                    failed.push((file.filename.clone(), format!("it couldn't be scanned ({e})")));
+                }
+            }
+            self.attachment_scans.record(file);
+        }
+        let Some(blocked) = blocked else {
+            self.status_message = if failed.is_empty() {
+                "Virus scan: no threat found".into()
+            } else {
+                "Virus scan failed".into()
+            };
+            return self.check_attachment_risk(action, failed);
+        };
+        self.set_status_error(blocked);
+        let AttachmentAction::SaveAll(atts) = action else {
+            return Task::none();
+        };
+        let rest: Vec<AttachmentData> = atts
+            .into_iter()
+            .zip(infected)
+            .filter(|(_, infected)| !infected)
+            .map(|(att, _)| att)
+            .collect();
+        if rest.is_empty() {
+            return Task::none();
+        }
+        self.check_attachment_risk(AttachmentAction::SaveAll(rest), failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_zero_is_clean() {
+        assert_eq!(verdict_from(Some(0), "stdin: OK\n", ""), ScanVerdict::Clean);
+    }
+
+    #[test]
+    fn exit_one_reports_what_was_found() {
+        assert_eq!(
+            verdict_from(Some(1), "stdin: Eicar-Signature FOUND\n", ""),
+            ScanVerdict::Infected("Eicar-Signature FOUND".into())
+        );
+    }
+
+    #[test]
+    fn other_exits_fail_the_scan() {
+        assert_eq!(
+            verdict_from(Some(2), "", "ERROR: Can't open database\n"),
+            ScanVerdict::Failed("ERROR: Can't open database".into())
+        );
+        assert_eq!(
+            verdict_from(None, "", ""),
+            ScanVerdict::Failed("the scanner was stopped".into())
+        );
+    }
+}
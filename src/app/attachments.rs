@@ -0,0 +1,581 @@
+//! Attachments browser: every cached attachment in the current folder or
+//! account, with sender/date context and open/save actions.
+//!
+//! Built from the local cache only — bodies that haven't been downloaded yet
+//! (neither opened nor backfilled) are counted but not fetched, so opening the
+//! browser never fans out into hundreds of JMAP body requests. The scan keeps
+//! only names and sizes; an attachment's bytes are read back from the cache
+//! when it is opened, saved, viewed or thumbnailed.
+//!
+//! Every save or open also passes `attachment_risk` here: programs, scripts,
+//! macro-enabled Office files and names dressed up as documents
+//! ("invoice.pdf.exe", right-to-left overrides) are held for a confirmation
+//! or refused, per `dangerous_attachments` in settings.json.
+
+use std::collections::HashSet;
+
+use cosmic::app::Task;
+use cosmic::widget::image;
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::models::AttachmentData;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::attachment_save::{safe_file_name, save_all_to_directory, save_with_dialog};
+use super::settings::DangerousAttachmentPolicy;
+use super::thumbnails::{content_hash, load_or_make_thumbnail, ThumbnailTarget};
+use super::{AppModel, Message};
+
+/// Upper bound on messages scanned per browse, across all folders.
+const MAX_SCANNED_MESSAGES: u32 = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentScope {
+    Folder,
+    Account,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttachmentEntry {
+    pub account_id: AccountId,
+    pub email_id: String,
+    pub subject: String,
+    pub from: String,
+    pub date: String,
+    /// Position among the message's attachments.
+    pub index: usize,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: usize,
+    pub is_image: bool,
+    /// Content hash for the scan verdict; only taken with a scanner set.
+    pub hash: Option<String>,
+}
+
+/// Result of a cache scan: entries plus how many messages had no cached body.
+#[derive(Debug, Clone, Default)]
+pub struct AttachmentScan {
+    pub entries: Vec<AttachmentEntry>,
+    pub uncached: usize,
+}
+
+pub enum BrowserState {
+    Loading,
+    Loaded(AttachmentScan),
+    Failed(String),
+}
+
+pub struct AttachmentBrowser {
+    pub scope: AttachmentScope,
+    /// Folder name or account label shown in the header.
+    pub title: String,
+    pub epoch: u64,
+    pub state: BrowserState,
+    /// Parallel to `entries()`; `Some` for image attachments.
+    pub thumbnails: Vec<Option<image::Handle>>,
+}
+
+impl AttachmentBrowser {
+    /// Entries of the finished scan; empty while scanning or after a failure.
+    pub fn entries(&self) -> &[AttachmentEntry] {
+        match &self.state {
+            BrowserState::Loaded(scan) => &scan.entries,
+            BrowserState::Loading | BrowserState::Failed(_) => &[],
+        }
+    }
+}
+
+/// Browser entries whose bytes are being read back, and what for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserRead {
+    Save(usize),
+    Open(usize),
+    SaveAll,
+}
+
+/// "14 attachments · 3 messages not downloaded yet".
+pub fn scan_summary(count: usize, uncached: usize) -> String {
+    let noun = if count == 1 { "attachment" } else { "attachments" };
+    match uncached {
+        0 => format!("{count} {noun}"),
+        1 => format!("{count} {noun} · 1 message not downloaded yet"),
+        n => format!("{count} {noun} · {n} messages not downloaded yet"),
+    }
+}
+
+/// Run as programs by Windows, macOS or a Linux desktop.
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "scr", "pif", "msi", "msp", "dll", "cpl", "jar", "lnk", "reg", "hta", "app",
+    "apk", "deb", "rpm", "appimage", "run", "desktop",
+];
+
+const SCRIPT_EXTENSIONS: &[&str] = &[
+    "js", "jse", "vbs", "vbe", "wsf", "wsh", "ps1", "psm1", "bat", "cmd", "sh", "bash", "zsh",
+    "csh", "command",
+];
+
+const MACRO_EXTENSIONS: &[&str] = &[
+    "docm", "dotm", "xlsm", "xltm", "xlam", "pptm", "potm", "ppam", "ppsm", "sldm",
+];
+
+/// Extensions a disguised file pretends to have.
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "txt", "rtf", "csv", "jpg",
+    "jpeg", "png", "gif", "zip",
+];
+
+/// Characters that reorder or hide text, used to make "invoice\u{202e}fdp.exe"
+/// read as "invoiceexe.pdf".
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Why opening `filename` could run code, or `None` for ordinary files.
+pub fn attachment_risk(filename: &str) -> Option<String> {
+    if filename.chars().any(is_bidi_control) {
+        return Some("its name hides its real extension".into());
+    }
+    let lower = filename.trim().to_lowercase();
+    let mut parts = lower.rsplit('.');
+    let ext = parts.next().filter(|_| lower.contains('.'))?;
+    let kind = if EXECUTABLE_EXTENSIONS.contains(&ext) {
+        "a program"
+    } else if SCRIPT_EXTENSIONS.contains(&ext) {
+        "a script"
+    } else if MACRO_EXTENSIONS.contains(&ext) {
+        return Some(format!("it is an Office file with macros (.{ext})"));
+    } else {
+        return None;
+    };
+    match parts.next().map(str::trim) {
+        Some(fake) if DOCUMENT_EXTENSIONS.contains(&fake) => Some(format!(
+            "it looks like a .{fake} but is {kind} (.{ext})"
+        )),
+        _ => Some(format!("it is {kind} (.{ext})")),
+    }
+}
+
+/// What to do with attachment data once it has passed the risk check.
+#[derive(Debug, Clone)]
+pub enum AttachmentAction {
+    Save(AttachmentData),
+    SaveAll(Vec<AttachmentData>),
+    Open(AttachmentData),
+}
+
+impl AttachmentAction {
+    pub(super) fn files(&self) -> &[AttachmentData] {
+        match self {
+            Self::Save(att) | Self::Open(att) => std::slice::from_ref(att),
+            Self::SaveAll(atts) => atts,
+        }
+    }
+}
+
+/// An action waiting on the "dangerous attachment" prompt.
+#[derive(Debug, Clone)]
+pub struct RiskyAttachment {
+    pub action: AttachmentAction,
+    /// (file name, reason) for each risky file in the action.
+    pub risks: Vec<(String, String)>,
+}
+
+impl RiskyAttachment {
+    pub fn verb(&self) -> &'static str {
+        match self.action {
+            AttachmentAction::Open(_) => "Open",
+            AttachmentAction::Save(_) | AttachmentAction::SaveAll(_) => "Save",
+        }
+    }
+}
+
+fn risks_of<'a>(files: impl IntoIterator<Item = &'a AttachmentData>) -> Vec<(String, String)> {
+    files
+        .into_iter()
+        .filter_map(|att| attachment_risk(&att.filename).map(|r| (att.filename.clone(), r)))
+        .collect()
+}
+
+/// Keep the first occurrence of each key (a message can sit in several
+/// mailboxes when scanning a whole account).
+fn first_seen<T, K, F>(items: Vec<T>, seen: &mut HashSet<K>, key: F) -> Vec<T>
+where
+    K: std::hash::Hash + Eq,
+    F: Fn(&T) -> K,
+{
+    items
+        .into_iter()
+        .filter(|item| seen.insert(key(item)))
+        .collect()
+}
+
+async fn scan_cache(
+    cache: CacheHandle,
+    account_id: AccountId,
+    mailbox_ids: Vec<String>,
+    hash: bool,
+) -> Result<AttachmentScan, String> {
+    let mut scan = AttachmentScan::default();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut budget = MAX_SCANNED_MESSAGES;
+
+    for mailbox_id in mailbox_ids {
+        if budget == 0 {
+            break;
+        }
+        let messages = cache
+            .load_messages(account_id.clone(), mailbox_id, budget, 0)
+            .await?;
+        for msg in first_seen(messages, &mut seen, |m| m.email_id.clone()) {
+            budget = budget.saturating_sub(1);
+            match cache
+                .load_body(account_id.clone(), msg.email_id.clone())
+                .await
+            {
+                Ok(Some((_, _, attachments))) => {
+                    scan.entries.extend(attachments.iter().enumerate().map(|(index, att)| {
+                        AttachmentEntry {
+                            account_id: account_id.clone(),
+                            email_id: msg.email_id.clone(),
+                            subject: msg.subject.clone(),
+                            from: msg.from.clone(),
+                            date: msg.date.clone(),
+                            index,
+                            filename: att.filename.clone(),
+                            mime_type: att.mime_type.clone(),
+                            size: att.data.len(),
+                            is_image: att.is_image(),
+                            hash: hash.then(|| content_hash(&att.data)),
+                        }
+                    }));
+                }
+                Ok(None) => scan.uncached += 1,
+                Err(e) => log::warn!("Attachments: body load failed for {}: {}", msg.email_id, e),
+            }
+        }
+    }
+    Ok(scan)
+}
+
+/// `entry`'s bytes, read back from the cache.
+pub(super) async fn read_entry(
+    cache: &CacheHandle,
+    entry: &AttachmentEntry,
+) -> Result<AttachmentData, String> {
+    cache
+        .load_body(entry.account_id.clone(), entry.email_id.clone())
+        .await?
+        .and_then(|(_, _, attachments)| attachments.into_iter().nth(entry.index))
+        .ok_or_else(|| format!("{} is no longer in the cache", entry.filename))
+}
+
+/// Thumbnails for the browser's image entries, reading one body at a time so
+/// a single attachment's bytes are held at once.
+fn browser_thumbnails(
+    cache: CacheHandle,
+    epoch: u64,
+    images: Vec<(usize, AttachmentEntry)>,
+) -> Task<Message> {
+    if images.is_empty() {
+        return Task::none();
+    }
+    cosmic::task::future(async move {
+        let mut thumbnails = Vec::new();
+        for (index, entry) in images {
+            let data = match read_entry(&cache, &entry).await {
+                Ok(att) => att.data,
+                Err(e) => {
+                    log::warn!("Attachments: {}", e);
+                    continue;
+                }
+            };
+            match tokio::task::spawn_blocking(move || load_or_make_thumbnail(&data)).await {
+                Ok(Ok(handle)) => thumbnails.push((index, handle)),
+                Ok(Err(e)) => log::warn!("Thumbnail decode failed for {}: {}", entry.filename, e),
+                Err(e) => log::error!("Thumbnail task failed: {}", e),
+            }
+        }
+        Message::ThumbnailsReady {
+            target: ThumbnailTarget::Browser { epoch },
+            thumbnails,
+        }
+    })
+}
+
+impl AppModel {
+    pub(super) fn handle_attachments(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ShowAttachments(scope) => {
+                return self.open_attachment_browser(scope);
+            }
+            Message::CloseAttachments => {
+                self.attachment_browser = None;
+            }
+            Message::AttachmentsLoaded { epoch, result } => {
+                let Some(browser) = self.attachment_browser.as_mut() else {
+                    return Task::none();
+                };
+                if browser.epoch != epoch {
+                    return Task::none();
+                }
+                match result {
+                    Ok(scan) => {
+                        browser.thumbnails = vec![None; scan.entries.len()];
+                        let images = scan
+                            .entries
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, e)| e.is_image)
+                            .map(|(i, e)| (i, e.clone()))
+                            .collect();
+                        browser.state = BrowserState::Loaded(scan);
+                        if let Some(cache) = self.cache.clone() {
+                            return browser_thumbnails(cache, epoch, images);
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Attachments scan failed: {}", e);
+                        browser.state = BrowserState::Failed(e);
+                    }
+                }
+            }
+            Message::SaveBrowserAttachment(index) => {
+                return self.read_browser_entries(BrowserRead::Save(index));
+            }
+            Message::OpenBrowserAttachment(index) => {
+                return self.read_browser_entries(BrowserRead::Open(index));
+            }
+            Message::BrowserAttachmentsRead { read, result } => {
+                let mut files = match result {
+                    Ok(files) => files,
+                    Err(e) => {
+                        log::error!("Attachment read failed: {}", e);
+                        self.set_status_error(e);
+                        return Task::none();
+                    }
+                };
+                let action = match read {
+                    BrowserRead::SaveAll => AttachmentAction::SaveAll(files),
+                    BrowserRead::Save(_) | BrowserRead::Open(_) => {
+                        let Some(att) = files.pop() else {
+                            return Task::none();
+                        };
+                        if matches!(read, BrowserRead::Open(_)) {
+                            AttachmentAction::Open(att)
+                        } else {
+                            AttachmentAction::Save(att)
+                        }
+                    }
+                };
+                return self.attachment_action(action);
+            }
+            Message::RiskyAttachmentConfirm => {
+                if let Some(risky) = self.risky_attachment.take() {
+                    return self.run_attachment_action(risky.action);
+                }
+            }
+            Message::RiskyAttachmentCancel => {
+                self.risky_attachment = None;
+            }
+            Message::AttachmentsScanned { action, files } => {
+                return self.handle_attachments_scanned(action, files);
+            }
+            Message::OpenAttachmentReady(Ok(path)) => {
+                neverlight_mail_core::mime::open_link(&format!("file://{path}"));
+            }
+            Message::OpenAttachmentReady(Err(e)) => {
+                log::error!("Attachment open failed: {}", e);
+                self.status_message = e;
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// Save or open attachments, virus-scanning them first when a scanner
+    /// is configured.
+    pub(super) fn attachment_action(&mut self, action: AttachmentAction) -> Task<Message> {
+        match self.scanner_command() {
+            Some(command) => self.scan_attachments(command, action),
+            None => self.check_attachment_risk(action, Vec::new()),
+        }
+    }
+
+    /// Go ahead with `action`, asking first (or refusing) when one of its
+    /// files could run code or, per `unscanned`, couldn't be scanned.
+    pub(super) fn check_attachment_risk(
+        &mut self,
+        action: AttachmentAction,
+        unscanned: Vec<(String, String)>,
+    ) -> Task<Message> {
+        let mut risks = risks_of(action.files());
+        risks.extend(unscanned);
+        if risks.is_empty() {
+            return self.run_attachment_action(action);
+        }
+        match self.settings.dangerous_attachments {
+            DangerousAttachmentPolicy::Allow => self.run_attachment_action(action),
+            DangerousAttachmentPolicy::Warn => {
+                self.risky_attachment = Some(RiskyAttachment { action, risks });
+                Task::none()
+            }
+            DangerousAttachmentPolicy::Refuse => {
+                let (name, reason) = &risks[0];
+                self.set_status_error(format!("Blocked {name}: {reason}"));
+                let AttachmentAction::SaveAll(atts) = action else {
+                    return Task::none();
+                };
+                let safe: Vec<AttachmentData> = atts
+                    .into_iter()
+                    .filter(|a| !risks.iter().any(|(name, _)| *name == a.filename))
+                    .collect();
+                if safe.is_empty() {
+                    return Task::none();
+                }
+                self.run_attachment_action(AttachmentAction::SaveAll(safe))
+            }
+        }
+    }
+
+    fn run_attachment_action(&mut self, action: AttachmentAction) -> Task<Message> {
+        let att = match action {
+            AttachmentAction::Save(att) => {
+                return cosmic::task::future(save_with_dialog(att, self.save_dir()));
+            }
+            AttachmentAction::SaveAll(atts) => {
+                return cosmic::task::future(save_all_to_directory(atts, self.save_dir()));
+            }
+            AttachmentAction::Open(att) => att,
+        };
+        cosmic::task::future(async move {
+            let dir = std::env::temp_dir().join("neverlight-mail");
+            let path = dir.join(safe_file_name(&att.filename));
+            let result = match tokio::fs::create_dir_all(&dir).await {
+                Ok(()) => tokio::fs::write(&path, &att.data)
+                    .await
+                    .map(|()| path.display().to_string())
+                    .map_err(|e| format!("Open failed: {e}")),
+                Err(e) => Err(format!("Open failed: {e}")),
+            };
+            Message::OpenAttachmentReady(result)
+        })
+    }
+
+    /// Read the bytes `read` needs back from the cache, then save or open
+    /// them. For Save all, entries that have left the cache are skipped.
+    pub(super) fn read_browser_entries(&mut self, read: BrowserRead) -> Task<Message> {
+        let Some(browser) = &self.attachment_browser else {
+            return Task::none();
+        };
+        let entries: Vec<AttachmentEntry> = match read {
+            BrowserRead::Save(i) | BrowserRead::Open(i) => {
+                browser.entries().get(i).cloned().into_iter().collect()
+            }
+            BrowserRead::SaveAll => browser.entries().to_vec(),
+        };
+        let Some(cache) = self.cache.clone().filter(|_| !entries.is_empty()) else {
+            return Task::none();
+        };
+        cosmic::task::future(async move {
+            let mut files = Vec::with_capacity(entries.len());
+            let mut error = None;
+            for entry in &entries {
+                match read_entry(&cache, entry).await {
+                    Ok(att) => files.push(att),
+                    Err(e) => {
+                        log::warn!("Attachments: {}", e);
+                        error = Some(e);
+                    }
+                }
+            }
+            let result = match error {
+                Some(e) if files.is_empty() => Err(e),
+                _ => Ok(files),
+            };
+            Message::BrowserAttachmentsRead { read, result }
+        })
+    }
+
+    fn open_attachment_browser(&mut self, scope: AttachmentScope) -> Task<Message> {
+        let Some(cache) = self.cache.clone() else {
+            self.status_message = "Attachments need the local cache".into();
+            return Task::none();
+        };
+        let Some(acct) = self.active_account.and_then(|i| self.accounts.get(i)) else {
+            self.status_message = "Select a folder first".into();
+            return Task::none();
+        };
+        let account_id = acct.config.id.clone();
+        let (title, mailbox_ids) = match scope {
+            AttachmentScope::Folder => {
+                let Some(folder) = self.selected_folder.and_then(|i| acct.folders.get(i)) else {
+                    self.status_message = "Select a folder first".into();
+                    return Task::none();
+                };
+                (folder.name.clone(), vec![folder.mailbox_id.clone()])
+            }
+            AttachmentScope::Account => (
+                acct.config.label.clone(),
+                acct.folders.iter().map(|f| f.mailbox_id.clone()).collect(),
+            ),
+        };
+
+        let hash = self.scanner_command().is_some();
+        self.attachment_epoch = self.attachment_epoch.wrapping_add(1);
+        let epoch = self.attachment_epoch;
+        self.attachment_browser = Some(AttachmentBrowser {
+            scope,
+            title,
+            epoch,
+            state: BrowserState::Loading,
+            thumbnails: Vec::new(),
+        });
+
+        cosmic::task::future(async move {
+            let result = scan_cache(cache, account_id, mailbox_ids, hash).await;
+            Message::AttachmentsLoaded { epoch, result }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_mentions_uncached_only_when_present() {
+        assert_eq!(scan_summary(1, 0), "1 attachment");
+        assert_eq!(scan_summary(14, 1), "14 attachments · 1 message not downloaded yet");
+        assert_eq!(scan_summary(0, 3), "0 attachments · 3 messages not downloaded yet");
+    }
+
+    #[test]
+    fn programs_scripts_and_macros_are_risky() {
+        assert!(attachment_risk("setup.EXE").unwrap().contains("a program"));
+        assert!(attachment_risk("install.sh").unwrap().contains("a script"));
+        assert!(attachment_risk("budget.xlsm").unwrap().contains("macros"));
+        assert_eq!(attachment_risk("report.pdf"), None);
+        assert_eq!(attachment_risk("Makefile"), None);
+        assert_eq!(attachment_risk("exe"), None);
+    }
+
+    #[test]
+    fn disguised_names_are_called_out() {
+        assert_eq!(
+            attachment_risk("invoice.pdf   .exe").as_deref(),
+            Some("it looks like a .pdf but is a program (.exe)")
+        );
+        assert_eq!(
+            attachment_risk("invoice\u{202e}fdp.exe").as_deref(),
+            Some("its name hides its real extension")
+        );
+    }
+
+    #[test]
+    fn first_seen_drops_repeats_across_batches() {
+        let mut seen = HashSet::new();
+        let inbox = first_seen(vec!["e1", "e2"], &mut seen, |s| s.to_string());
+        let archive = first_seen(vec!["e2", "e3"], &mut seen, |s| s.to_string());
+        assert_eq!(inbox, vec!["e1", "e2"]);
+        assert_eq!(archive, vec!["e3"]);
+    }
+}
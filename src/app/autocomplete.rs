@@ -0,0 +1,390 @@
+//! To-field suggestions: an address book built from the account's mail.
+//!
+//! Rebuilt from cached Sent recipients and Inbox senders whenever either
+//! folder syncs, and bumped locally on each successful send; those sends are
+//! carried across rebuilds until the cached Sent folder has them. The contact
+//! store lives in neverlight-mail-core's cache, which has no contacts table,
+//! so the book is built from summaries in memory. Ranking mixes how often an
+//! address was written to (a received message counts a quarter) with how
+//! recently (a 30-day half-life), so last week's colleague beats someone
+//! mailed fifty times two years ago. Prefix matches on the address, domain
+//! or a name word come first; looser matches, where the typed letters
+//! appear in order ("jsmth" for John Smith), follow.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use cosmic::app::Task;
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::folder_activity::parse_summary_date;
+use super::recipients::{bare_address, display_name};
+use super::{AppModel, Message};
+
+/// Sent and Inbox summaries read per rebuild, each.
+const MAX_SCANNED_PER_FOLDER: u32 = 2000;
+/// Weight of a message received from an address, against one sent to it.
+const RECEIVED_WEIGHT: f64 = 0.25;
+/// Suggestions shown under the To field.
+const MAX_SUGGESTIONS: usize = 5;
+/// Characters typed before suggesting.
+const MIN_QUERY_LEN: usize = 2;
+/// Characters typed before letters in order count as a match; shorter
+/// queries would match nearly everyone.
+const MIN_SCATTERED_LEN: usize = 3;
+const HALF_LIFE_DAYS: f64 = 30.0;
+/// Age assumed for entries whose date couldn't be parsed.
+const UNDATED_AGE_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, PartialEq)]
+struct RecipientEntry {
+    /// `Name <addr>` when a name was ever seen, else the bare address.
+    display: String,
+    count: u32,
+    /// Messages received from the address.
+    received: u32,
+    last_used: Option<NaiveDate>,
+    /// Last day a message to the address was sent.
+    last_sent: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecipientIndex {
+    /// Keyed by lowercase bare address.
+    entries: HashMap<String, RecipientEntry>,
+}
+
+impl RecipientIndex {
+    /// Build from `(to, date)` pairs of Sent messages.
+    pub fn from_sent<'a>(sent: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut index = Self::default();
+        for (to, date) in sent {
+            index.record(to, parse_summary_date(date));
+        }
+        index
+    }
+
+    /// Add the senders of `(from, date)` pairs of received messages.
+    pub fn with_received<'a>(
+        mut self,
+        received: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        for (from, date) in received {
+            if let Some(entry) = self.entry(from, parse_summary_date(date)) {
+                entry.received += 1;
+            }
+        }
+        self
+    }
+
+    /// Count every address in a comma-separated To field.
+    pub fn record(&mut self, to: &str, day: Option<NaiveDate>) {
+        for recipient in to.split(',') {
+            if let Some(entry) = self.entry(recipient, day) {
+                entry.count += 1;
+                entry.last_sent = entry.last_sent.max(day);
+            }
+        }
+    }
+
+    /// The name last seen with `address`, if it was ever seen with one.
+    pub fn name_for(&self, address: &str) -> Option<String> {
+        let entry = self.entries.get(&address.to_ascii_lowercase())?;
+        entry.display.contains('<').then(|| display_name(&entry.display))
+    }
+
+    /// Count the sends in `pending` (To field, day sent) that this index's
+    /// Sent data doesn't include yet, and drop the ones it now does.
+    pub fn carry_unsynced(&mut self, pending: &mut Vec<(String, NaiveDate)>) {
+        pending.retain(|(to, day)| !self.sent_covers(to, *day));
+        for (to, day) in pending.iter() {
+            self.record(to, Some(*day));
+        }
+    }
+
+    /// Whether every address in `to` was sent to on `day` or later.
+    fn sent_covers(&self, to: &str, day: NaiveDate) -> bool {
+        to.split(',')
+            .map(|r| bare_address(r.trim()).to_ascii_lowercase())
+            .filter(|address| address.contains('@'))
+            .all(|address| self.entries.get(&address).is_some_and(|e| e.last_sent >= Some(day)))
+    }
+
+    /// The entry for one `Name <addr>` or `addr`, seen on `day`; `None` for
+    /// text that isn't an address.
+    fn entry(&mut self, recipient: &str, day: Option<NaiveDate>) -> Option<&mut RecipientEntry> {
+        let recipient = recipient.trim();
+        let address = bare_address(recipient);
+        if !address.contains('@') {
+            return None;
+        }
+        let entry = self
+            .entries
+            .entry(address.to_ascii_lowercase())
+            .or_insert_with(|| RecipientEntry {
+                display: address.to_string(),
+                count: 0,
+                received: 0,
+                last_used: None,
+                last_sent: None,
+            });
+        entry.last_used = entry.last_used.max(day);
+        if recipient != address {
+            entry.display = recipient.to_string();
+        }
+        Some(entry)
+    }
+
+    /// Best matches for the segment being typed, skipping addresses already
+    /// in the field.
+    pub fn suggest(&self, to: &str, today: NaiveDate) -> Vec<String> {
+        let query = current_segment(to).to_ascii_lowercase();
+        if query.chars().count() < MIN_QUERY_LEN {
+            return Vec::new();
+        }
+        let already: Vec<String> = to
+            .split(',')
+            .map(|r| bare_address(r).to_ascii_lowercase())
+            .collect();
+        let mut matches: Vec<(MatchKind, &String, &RecipientEntry)> = self
+            .entries
+            .iter()
+            .filter(|(address, _)| !already.contains(address))
+            .filter_map(|(address, entry)| {
+                Some((match_query(address, &entry.display, &query)?, address, entry))
+            })
+            .collect();
+        matches.sort_by(|(a_kind, a_addr, a), (b_kind, b_addr, b)| {
+            a_kind
+                .cmp(b_kind)
+                .then_with(|| frecency(b, today).total_cmp(&frecency(a, today)))
+                .then_with(|| a_addr.cmp(b_addr))
+        });
+        matches
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, _, entry)| entry.display.clone())
+            .collect()
+    }
+}
+
+/// Text after the last comma: what the user is typing now.
+fn current_segment(to: &str) -> &str {
+    to.rsplit(',').next().unwrap_or(to).trim()
+}
+
+/// Replace the segment being typed with `pick`, ready for the next address.
+pub fn complete_recipient(to: &str, pick: &str) -> String {
+    match to.rsplit_once(',') {
+        Some((done, _)) => format!("{done}, {pick}, "),
+        None => format!("{pick}, "),
+    }
+}
+
+/// How a contact matched what was typed; better matches sort first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    /// Address prefix, domain prefix, or the start of a display name word.
+    Prefix,
+    /// The typed letters appear in order in the address or name.
+    Scattered,
+}
+
+fn match_query(address: &str, display: &str, query: &str) -> Option<MatchKind> {
+    let domain = address.rsplit_once('@').map(|(_, d)| d).unwrap_or("");
+    let display = display.to_ascii_lowercase();
+    let prefix = address.starts_with(query)
+        || domain.starts_with(query)
+        || display
+            .split(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\''))
+            .any(|word| word.starts_with(query));
+    if prefix {
+        Some(MatchKind::Prefix)
+    } else if query.chars().count() >= MIN_SCATTERED_LEN
+        && (in_order(address, query) || in_order(&display, query))
+    {
+        Some(MatchKind::Scattered)
+    } else {
+        None
+    }
+}
+
+/// Whether the characters of `query` appear in `text` in order.
+fn in_order(text: &str, query: &str) -> bool {
+    let mut text = text.chars();
+    query.chars().all(|q| text.any(|c| c == q))
+}
+
+fn frecency(entry: &RecipientEntry, today: NaiveDate) -> f64 {
+    let age = entry
+        .last_used
+        .map(|d| (today - d).num_days().max(0))
+        .unwrap_or(UNDATED_AGE_DAYS);
+    let weight = f64::from(entry.count) + f64::from(entry.received) * RECEIVED_WEIGHT;
+    weight * 0.5_f64.powf(age as f64 / HALF_LIFE_DAYS)
+}
+
+/// Read the cached Sent and Inbox folders into a fresh index.
+pub(super) async fn index_contacts(
+    cache: CacheHandle,
+    account_id: AccountId,
+    sent_id: Option<String>,
+    inbox_id: Option<String>,
+) -> Message {
+    let load = |mailbox_id: Option<String>| {
+        let cache = cache.clone();
+        let account_id = account_id.clone();
+        async move {
+            let Some(mailbox_id) = mailbox_id else {
+                return Ok(Vec::new());
+            };
+            cache
+                .load_messages(account_id, mailbox_id, MAX_SCANNED_PER_FOLDER, 0)
+                .await
+        }
+    };
+    let (sent, inbox) = match (load(sent_id).await, load(inbox_id).await) {
+        (Ok(sent), Ok(inbox)) => (sent, inbox),
+        (Err(e), _) | (_, Err(e)) => {
+            log::warn!("Recipient index: folder load failed: {}", e);
+            return Message::Noop;
+        }
+    };
+    let index = RecipientIndex::from_sent(sent.iter().map(|m| (m.to.as_str(), m.date.as_str())))
+        .with_received(inbox.iter().map(|m| (m.from.as_str(), m.date.as_str())));
+    Message::RecipientIndexLoaded { account_id, index }
+}
+
+impl AppModel {
+    /// Rebuild an account's index from its cached Sent and Inbox folders.
+    pub(super) fn load_recipient_index(&self, account_id: &str) -> Task<Message> {
+        let Some(cache) = self.cache.clone() else {
+            return Task::none();
+        };
+        let sent_id = self.role_mailbox(account_id, "sent");
+        let inbox_id = self.role_mailbox(account_id, "inbox");
+        if sent_id.is_none() && inbox_id.is_none() {
+            return Task::none();
+        }
+        let account_id = account_id.to_string();
+        cosmic::task::future(index_contacts(cache, account_id, sent_id, inbox_id))
+    }
+
+    /// Whether syncing `mailbox_id` changes the account's address book.
+    pub(super) fn is_contact_source(&self, account_id: &str, mailbox_id: &str) -> bool {
+        ["sent", "inbox"]
+            .into_iter()
+            .any(|role| self.role_mailbox(account_id, role).as_deref() == Some(mailbox_id))
+    }
+
+    /// Recompute To-field suggestions for the compose account.
+    pub(super) fn refresh_compose_suggestions(&mut self) {
+        self.compose_suggestions = self
+            .accounts
+            .get(self.compose_account)
+            .and_then(|a| self.recipient_index.get(&a.config.id))
+            .map(|index| index.suggest(&self.compose_to, chrono::Local::now().date_naive()))
+            .unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn sample_index() -> RecipientIndex {
+        RecipientIndex::from_sent([
+            ("alice@corp.example", "2026-03-01"),
+            ("Alice Smith <alice@corp.example>", "2026-03-02"),
+            ("alan@old.example", "2024-01-01"),
+            ("alan@old.example, bob@corp.example", "2024-01-02"),
+            ("alan@old.example", "2024-01-03"),
+            ("not an address", "2026-03-02"),
+        ])
+    }
+
+    #[test]
+    fn recent_beats_frequent_but_stale() {
+        let suggestions = sample_index().suggest("al", day("2026-03-10"));
+        assert_eq!(suggestions, vec!["Alice Smith <alice@corp.example>", "alan@old.example"]);
+    }
+
+    #[test]
+    fn matches_name_words_and_domains() {
+        let index = sample_index();
+        assert_eq!(index.suggest("smi", day("2026-03-10")).len(), 1);
+        assert_eq!(index.suggest("corp", day("2026-03-10")).len(), 2);
+        assert_eq!(index.name_for("ALICE@corp.example").as_deref(), Some("Alice Smith"));
+        assert_eq!(index.name_for("bob@corp.example"), None);
+    }
+
+    #[test]
+    fn scattered_letters_match_after_prefixes() {
+        let index = sample_index();
+        let today = day("2026-03-10");
+        assert_eq!(index.suggest("alsmth", today), vec!["Alice Smith <alice@corp.example>"]);
+        assert_eq!(index.suggest("bcorp", today), vec!["bob@corp.example"]);
+        // A prefix match outranks a more recent contact that only has the
+        // letters ("a", "l", "a" in alice@corp.example).
+        assert_eq!(
+            index.suggest("ala", today),
+            vec!["alan@old.example", "Alice Smith <alice@corp.example>"]
+        );
+    }
+
+    #[test]
+    fn senders_of_received_mail_join_the_book() {
+        let today = day("2026-03-10");
+        let index = sample_index()
+            .with_received([("Carol Jones <carol@partner.example>", "2026-03-09")]);
+        assert_eq!(index.suggest("jon", today), vec!["Carol Jones <carol@partner.example>"]);
+
+        // Three received messages weigh less than one sent.
+        let index = RecipientIndex::from_sent([("dan@x.example", "2026-03-09")])
+            .with_received([("dave@x.example", "2026-03-09"); 3]);
+        assert_eq!(index.suggest("da", today), vec!["dan@x.example", "dave@x.example"]);
+    }
+
+    #[test]
+    fn skips_addresses_already_entered_and_short_queries() {
+        let index = sample_index();
+        let today = day("2026-03-10");
+        assert_eq!(
+            index.suggest("alice@corp.example, al", today),
+            vec!["alan@old.example"]
+        );
+        assert!(index.suggest("a", today).is_empty());
+    }
+
+    #[test]
+    fn sends_survive_rebuilds_until_sent_has_them() {
+        let today = day("2026-03-10");
+        let mut pending = vec![("erin@new.example".to_string(), today)];
+
+        // Rebuilt before the Sent folder synced: the send is counted again.
+        let mut index = sample_index();
+        index.carry_unsynced(&mut pending);
+        assert_eq!(index.suggest("eri", today), vec!["erin@new.example"]);
+        assert_eq!(pending.len(), 1);
+
+        // Sent has it now; it isn't counted twice and is let go.
+        let mut index = RecipientIndex::from_sent([("erin@new.example", "2026-03-10")]);
+        index.carry_unsynced(&mut pending);
+        assert!(pending.is_empty());
+        assert_eq!(index, RecipientIndex::from_sent([("erin@new.example", "2026-03-10")]));
+    }
+
+    #[test]
+    fn completing_replaces_only_the_last_segment() {
+        assert_eq!(complete_recipient("al", "alan@old.example"), "alan@old.example, ");
+        assert_eq!(
+            complete_recipient("bob@corp.example, al", "alan@old.example"),
+            "bob@corp.example, alan@old.example, "
+        );
+    }
+}
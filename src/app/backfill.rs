@@ -14,11 +14,14 @@ use super::{AppModel, Message};
 
 /// Returns a stream that round-robins over incomplete mailboxes,
 /// fetching one batch per mailbox, sleeping between batches.
+///
+/// `folders` pairs each mailbox id with the most messages to keep there
+/// (`None` walks all of history); mailboxes not listed use `max_messages`.
 pub(super) fn backfill_stream(
     client: JmapClient,
     cache: CacheHandle,
     account_id: AccountId,
-    folder_mailbox_ids: Vec<String>,
+    folders: Vec<(String, Option<u32>)>,
     max_messages: Option<u32>,
     pause: Arc<AtomicBool>,
 ) -> impl futures::Stream<Item = Message> {
@@ -49,7 +52,7 @@ pub(super) fn backfill_stream(
             let mut work: Vec<String> = incomplete.into_iter().map(|p| p.mailbox_id).collect();
 
             // Add mailboxes that have never started backfill
-            for mid in &folder_mailbox_ids {
+            for (mid, _) in &folders {
                 if !has_progress.contains(mid) {
                     // Check if already completed
                     let progress = cache
@@ -77,6 +80,10 @@ pub(super) fn backfill_stream(
                 if pause.load(Ordering::Relaxed) {
                     break;
                 }
+                let limit = folders
+                    .iter()
+                    .find(|(mid, _)| mid == mailbox_id)
+                    .map_or(max_messages, |(_, limit)| *limit);
 
                 match backfill::backfill_batch(
                     &client,
@@ -84,7 +91,7 @@ pub(super) fn backfill_stream(
                     &aid,
                     mailbox_id,
                     page_size,
-                    max_messages,
+                    limit,
                 )
                 .await
                 {
@@ -1,11 +1,19 @@
-use std::path::PathBuf;
+use std::sync::Arc;
 
 use cosmic::app::Task;
-use cosmic::widget::{image, markdown};
+use cosmic::widget::markdown;
 use futures::future::{AbortHandle, Abortable};
 
-use super::{AppModel, ConversationEntry, Message};
-use neverlight_mail_core::models::AttachmentData;
+use super::attachments::AttachmentAction;
+use super::metrics::{HealthMetrics, MetricKind};
+use super::thumbnails::{self, ThumbnailTarget};
+use super::charset::{self, ReceivedBody};
+use super::paged_markdown::PagedMarkdown;
+use super::toasts::Severity;
+use super::participants;
+use super::smart_views::date_key;
+use super::snippets;
+use super::{detected_event, flowed, AppModel, ConversationEntry, Message};
 use neverlight_mail_core::store::CacheHandle;
 
 fn body_error_indicates_stale_message(e: &str) -> bool {
@@ -25,32 +33,28 @@ fn should_apply_body_result(
         && current_selected_email_id == Some(incoming_email_id)
 }
 
-const MAX_MD_ITEMS: usize = 200;
-
-fn parse_markdown_capped(markdown_body: &str, plain_body: &str) -> Vec<markdown::Item> {
-    let items: Vec<markdown::Item> = markdown::parse(markdown_body).collect();
-    if items.len() <= MAX_MD_ITEMS {
-        items
-    } else {
-        log::warn!(
-            "Markdown items ({}) exceed cap ({}), falling back to plain text",
-            items.len(),
-            MAX_MD_ITEMS
-        );
-        markdown::parse(plain_body).collect()
+/// Relative scroll position of a conversation card. Cards vary in height, so
+/// this lands near the target rather than exactly on it; the accent border
+/// marks the one that was picked.
+fn conversation_scroll_offset(index: usize, len: usize) -> f32 {
+    if len <= 1 {
+        return 0.0;
     }
+    index.min(len - 1) as f32 / (len - 1) as f32
 }
 
-fn build_image_handles(attachments: &[AttachmentData]) -> Vec<Option<image::Handle>> {
-    attachments
+/// Which conversation cards open unfolded, given each message's (email_id,
+/// date): the newest one and the one that was clicked.
+fn unfolded_on_open(dates: &[(&str, &str)], clicked: &str) -> Vec<bool> {
+    let newest = dates
         .iter()
-        .map(|a| {
-            if a.is_image() {
-                Some(image::Handle::from_bytes(a.data.clone()))
-            } else {
-                None
-            }
-        })
+        .enumerate()
+        .max_by_key(|(_, (_, date))| date_key(date))
+        .map(|(i, _)| i);
+    dates
+        .iter()
+        .enumerate()
+        .map(|(i, (email_id, _))| Some(i) == newest || *email_id == clicked)
         .collect()
 }
 
@@ -59,6 +63,7 @@ fn build_image_handles(attachments: &[AttachmentData]) -> Vec<Option<image::Hand
 fn body_fetch_task(
     cache: Option<CacheHandle>,
     client: Option<neverlight_mail_core::client::JmapClient>,
+    metrics: Arc<HealthMetrics>,
     account_id: String,
     email_id: String,
     epoch: u64,
@@ -68,10 +73,12 @@ fn body_fetch_task(
         let email_id_for_fetch = email_id.clone();
         cosmic::task::future(async move {
             // Cache-first
-            if let Ok(Some((md_body, plain_body, attachments))) =
-                cache
-                    .load_body(account_id.clone(), email_id.clone())
-                    .await
+            if let Ok(Some((md_body, plain_body, attachments))) = metrics
+                .timed(
+                    MetricKind::CacheQuery,
+                    cache.load_body(account_id.clone(), email_id.clone()),
+                )
+                .await
             {
                 let result = Ok((md_body, plain_body, attachments));
                 return if let Some(tid) = thread_id {
@@ -107,7 +114,11 @@ fn body_fetch_task(
                     }
                 };
             };
-            let result = neverlight_mail_core::email::get_body(&client, &email_id_for_fetch)
+            let result = metrics
+                .timed(
+                    MetricKind::BodyFetch,
+                    neverlight_mail_core::email::get_body(&client, &email_id_for_fetch),
+                )
                 .await
                 .map_err(|e| e.to_string());
             if let Ok((ref md_body, ref plain_body, ref attachments)) = result {
@@ -143,7 +154,11 @@ fn body_fetch_task(
         // No-cache fallback
         cosmic::task::future(async move {
             let result = if let Some(client) = client {
-                neverlight_mail_core::email::get_body(&client, &email_id)
+                metrics
+                    .timed(
+                        MetricKind::BodyFetch,
+                        neverlight_mail_core::email::get_body(&client, &email_id),
+                    )
                     .await
                     .map_err(|e| e.to_string())
             } else {
@@ -184,6 +199,7 @@ impl AppModel {
     fn clear_conversation(&mut self) {
         self.conversation.clear();
         self.active_conversation_id = None;
+        self.conversation_sender_filter = None;
     }
 
     /// Get inbox + sent mailbox IDs for the given account (for cross-mailbox thread queries).
@@ -208,6 +224,8 @@ impl AppModel {
         match message {
             Message::ViewBody(index) => {
                 self.selected_message = Some(index);
+                self.attachment_browser = None;
+                self.detected_event = None;
                 self.pending_body = None;
                 self.body_defer_retries = 0;
                 self.auto_read_suppressed = false;
@@ -249,16 +267,25 @@ impl AppModel {
 
                     if !mailbox_ids.is_empty() {
                         let eid = email_id.clone();
-                        let tid = thread_id.clone();
                         let aid = account_id.clone();
+                        // Plus the server threads of messages merged into it.
+                        let threads = self.thread_overrides.threads_to_load(&aid, &thread_id);
                         self.status_message = "Loading conversation...".into();
 
                         let thread_task = cosmic::task::future(async move {
-                            let result = cache
-                                .load_thread(aid, tid.clone(), mailbox_ids)
-                                .await;
+                            let result = async {
+                                let mut msgs = Vec::new();
+                                for tid in threads {
+                                    let thread = cache
+                                        .load_thread(aid.clone(), tid, mailbox_ids.clone())
+                                        .await?;
+                                    msgs.extend(thread);
+                                }
+                                Ok::<_, String>(msgs)
+                            }
+                            .await;
                             Message::ThreadLoaded {
-                                thread_id: tid,
+                                thread_id,
                                 email_id: eid,
                                 epoch: body_epoch,
                                 result,
@@ -275,16 +302,19 @@ impl AppModel {
 
                 let body_task = if let Some(cache) = &self.cache {
                     let cache = cache.clone();
+                    let metrics = self.metrics.clone();
                     let client = self.client_for_account(&account_id);
                     let email_id_for_fetch = email_id.clone();
                     self.status_message = "Loading message...".into();
                     cosmic::task::future(async move {
                         let load = async move {
                             // Cache-first: try cache (includes attachments)
-                            if let Ok(Some((md_body, plain_body, attachments))) =
-                                cache
-                                    .load_body(account_id.clone(), email_id.clone())
-                                    .await
+                            if let Ok(Some((md_body, plain_body, attachments))) = metrics
+                                .timed(
+                                    MetricKind::CacheQuery,
+                                    cache.load_body(account_id.clone(), email_id.clone()),
+                                )
+                                .await
                             {
                                 return Message::BodyLoaded {
                                     email_id,
@@ -300,12 +330,16 @@ impl AppModel {
                                     epoch: body_epoch,
                                 };
                             };
-                            let result = neverlight_mail_core::email::get_body(
-                                &client,
-                                &email_id_for_fetch,
-                            )
-                            .await
-                            .map_err(|e| e.to_string());
+                            let result = metrics
+                                .timed(
+                                    MetricKind::BodyFetch,
+                                    neverlight_mail_core::email::get_body(
+                                        &client,
+                                        &email_id_for_fetch,
+                                    ),
+                                )
+                                .await
+                                .map_err(|e| e.to_string());
                             if let Ok((ref md_body, ref plain_body, ref attachments)) = result {
                                 if let Err(e) = cache
                                     .save_body(
@@ -340,16 +374,19 @@ impl AppModel {
                         return auto_read_task;
                     };
                     self.status_message = "Loading message...".into();
+                    let metrics = self.metrics.clone();
                     cosmic::task::future(async move {
                         let load = async move {
                             Message::BodyLoaded {
                                 email_id: email_id.clone(),
                                 epoch: body_epoch,
-                                result: neverlight_mail_core::email::get_body(
-                                    &client, &email_id,
-                                )
-                                .await
-                                .map_err(|e| e.to_string()),
+                                result: metrics
+                                    .timed(
+                                        MetricKind::BodyFetch,
+                                        neverlight_mail_core::email::get_body(&client, &email_id),
+                                    )
+                                    .await
+                                    .map_err(|e| e.to_string()),
                             }
                         };
                         match Abortable::new(load, abort_reg).await {
@@ -416,11 +453,35 @@ impl AppModel {
                 }
                 self.body_abort = None;
 
-                self.preview_markdown = parse_markdown_capped(&markdown_body, &plain_body);
+                let (markdown_body, plain_body) = flowed::reflow_body(markdown_body, plain_body);
+                let (markdown_body, plain_body) = charset::repair_body(markdown_body, plain_body);
+                self.preview_markdown = markdown::parse(&markdown_body).collect();
+                self.preview_received = Some(ReceivedBody {
+                    email_id: email_id.clone(),
+                    markdown: markdown_body,
+                    plain: plain_body.clone(),
+                });
+                self.preview_encoding = 0;
+                self.detected_event = if self.settings.detect_events {
+                    detected_event::detect(&email_id, &plain_body, chrono::Local::now().date_naive())
+                } else {
+                    None
+                };
                 self.preview_body = plain_body;
-                self.preview_image_handles = build_image_handles(&attachments);
+                self.preview_image_handles = vec![None; attachments.len()];
+                self.preview_attachment_hashes = self.attachment_hashes(&attachments);
+                self.attachment_counts.insert(email_id.clone(), attachments.len());
+                self.remember_snippet(&email_id, &plain_body);
+                let thumbnails = thumbnails::load_thumbnails(
+                    ThumbnailTarget::Preview {
+                        email_id: email_id.clone(),
+                    },
+                    &attachments,
+                );
                 self.preview_attachments = attachments;
                 self.status_message = "Ready".into();
+                let compose = self.run_pending_row_compose(&email_id);
+                return cosmic::task::batch(vec![thumbnails, compose]);
             }
             Message::BodyLoaded {
                 email_id,
@@ -486,6 +547,7 @@ impl AppModel {
                     self.preview_markdown.clear();
                     self.preview_attachments.clear();
                     self.preview_image_handles.clear();
+                    self.preview_attachment_hashes.clear();
                     self.status_message = "Message no longer exists on server".into();
                     return self.dispatch(Message::Refresh);
                 }
@@ -494,6 +556,7 @@ impl AppModel {
                 self.preview_markdown = markdown::parse(&msg).collect();
                 self.preview_body = msg;
                 self.status_message = "Error loading message".into();
+                self.notify(Severity::Error, format!("Couldn't load message: {e}"));
                 log::error!("Body fetch failed: {}", e);
             }
 
@@ -508,7 +571,7 @@ impl AppModel {
                     return Task::none();
                 }
                 let thread_msgs = match result {
-                    Ok(msgs) => msgs,
+                    Ok(msgs) => self.thread_overrides.regroup(msgs, &thread_id),
                     Err(e) => {
                         log::warn!("Thread load failed: {}", e);
                         // Fall through to single-message view
@@ -537,6 +600,7 @@ impl AppModel {
                     return body_fetch_task(
                         self.cache.clone(),
                         self.client_for_account(&account_id),
+                        self.metrics.clone(),
                         account_id,
                         email_id,
                         epoch,
@@ -546,18 +610,27 @@ impl AppModel {
 
                 // --- Multi-message conversation ---
                 self.active_conversation_id = Some(email_id.clone());
+                self.conversation_sender_filter = None;
 
+                let dates: Vec<(&str, &str)> = thread_msgs
+                    .iter()
+                    .map(|m| (m.email_id.as_str(), m.date.as_str()))
+                    .collect();
+                let unfolded = unfolded_on_open(&dates, &email_id);
                 self.conversation = thread_msgs
                     .iter()
-                    .map(|m| ConversationEntry {
+                    .zip(unfolded)
+                    .map(|(m, expanded)| ConversationEntry {
                         email_id: m.email_id.clone(),
                         summary: m.clone(),
-                        markdown_items: Vec::new(),
+                        markdown_items: PagedMarkdown::default(),
                         plain_body: String::new(),
                         attachments: Vec::new(),
                         image_handles: Vec::new(),
+                        attachment_hashes: Vec::new(),
                         is_sent: self.is_sent_message(&m.from, &m.account_id),
                         loaded: false,
+                        expanded,
                     })
                     .collect();
 
@@ -565,6 +638,7 @@ impl AppModel {
                 self.preview_body.clear();
                 self.preview_attachments.clear();
                 self.preview_image_handles.clear();
+                self.preview_attachment_hashes.clear();
 
                 let cache = self.cache.clone();
                 let account_id = thread_msgs
@@ -578,6 +652,7 @@ impl AppModel {
                         body_fetch_task(
                             cache.clone(),
                             client.clone(),
+                            self.metrics.clone(),
                             m.account_id.clone(),
                             m.email_id.clone(),
                             epoch,
@@ -613,6 +688,10 @@ impl AppModel {
                     return Task::none();
                 }
 
+                let hashes = match &result {
+                    Ok((_, _, attachments)) => self.attachment_hashes(attachments),
+                    Err(_) => Vec::new(),
+                };
                 let Some(entry) = self
                     .conversation
                     .iter_mut()
@@ -621,13 +700,29 @@ impl AppModel {
                     return Task::none();
                 };
 
+                let mut thumbnail_task = Task::none();
                 match result {
                     Ok((markdown_body, plain_body, attachments)) => {
-                        entry.markdown_items =
-                            parse_markdown_capped(&markdown_body, &plain_body);
-                        entry.image_handles = build_image_handles(&attachments);
+                        let (markdown_body, plain_body) =
+                            flowed::reflow_body(markdown_body, plain_body);
+                        let (markdown_body, plain_body) =
+                            charset::repair_body(markdown_body, plain_body);
+                        entry.markdown_items = markdown::parse(&markdown_body).collect();
+                        entry.image_handles = vec![None; attachments.len()];
+                        self.attachment_counts.insert(email_id.clone(), attachments.len());
+                        let snippet = snippets::excerpt(&plain_body, &entry.summary.subject);
+                        if let Some(snippet) = snippet {
+                            self.insert_snippet(email_id.clone(), snippet);
+                        }
+                        thumbnail_task = thumbnails::load_thumbnails(
+                            ThumbnailTarget::Conversation {
+                                email_id: email_id.clone(),
+                            },
+                            &attachments,
+                        );
                         entry.plain_body = plain_body;
                         entry.attachments = attachments;
+                        entry.attachment_hashes = hashes;
                         entry.loaded = true;
                     }
                     Err(e) => {
@@ -642,16 +737,25 @@ impl AppModel {
                 let total = self.conversation.len();
                 if loaded_count == total {
                     self.status_message = "Ready".into();
+                    if let Some(active) = self.active_conversation_id.clone() {
+                        let compose = self.run_pending_row_compose(&active);
+                        return cosmic::task::batch(vec![thumbnail_task, compose]);
+                    }
                 } else {
                     self.status_message = format!(
                         "Loading conversation ({}/{})...",
                         loaded_count, total
                     );
                 }
+                return thumbnail_task;
             }
 
             Message::SetActiveConversation(ref email_id) => {
                 self.active_conversation_id = Some(email_id.clone());
+                let entry = self.conversation.iter_mut().find(|e| e.email_id == *email_id);
+                if let Some(entry) = entry {
+                    entry.expanded = true;
+                }
                 // Update selected_message to match so toolbar actions target the right message
                 if let Some(idx) = self
                     .messages
@@ -662,6 +766,52 @@ impl AppModel {
                 }
             }
 
+            Message::ContinueReading(ref email_id) => {
+                if let Some(entry) = self.conversation.iter_mut().find(|e| e.email_id == *email_id) {
+                    entry.markdown_items.show_more();
+                } else {
+                    self.preview_markdown.show_more();
+                }
+            }
+
+            Message::ViewWithEncoding(index) => return self.view_with_encoding(index),
+
+            Message::JumpToConversationEntry(email_id) => {
+                let filter = self.conversation_sender_filter.as_deref();
+                let shown: Vec<&str> = self
+                    .conversation
+                    .iter()
+                    .filter(|e| participants::shows_entry(filter, e))
+                    .map(|e| e.email_id.as_str())
+                    .collect();
+                let Some(pos) = shown.iter().position(|id| *id == email_id) else {
+                    return Task::none();
+                };
+                let y = conversation_scroll_offset(pos, shown.len());
+                let activate = self.dispatch(Message::SetActiveConversation(email_id));
+                let scroll = cosmic::iced::widget::scrollable::snap_to(
+                    crate::ui::message_view::conversation_scroll_id(),
+                    cosmic::iced::widget::scrollable::RelativeOffset { x: 0.0, y },
+                );
+                return cosmic::task::batch(vec![activate, scroll]);
+            }
+
+            Message::ToggleConversationEntry(ref email_id) => {
+                let entry = self.conversation.iter_mut().find(|e| e.email_id == *email_id);
+                if let Some(entry) = entry {
+                    entry.expanded = !entry.expanded;
+                }
+            }
+
+            Message::ConversationCompose { email_id, action } => {
+                let Some(compose) = action.compose_message() else {
+                    return Task::none();
+                };
+                // Answer this card, not whichever one was active before.
+                let activate = self.dispatch(Message::SetActiveConversation(email_id));
+                return cosmic::task::batch(vec![activate, self.dispatch(compose)]);
+            }
+
             Message::SaveConversationAttachment {
                 ref email_id,
                 index,
@@ -670,23 +820,10 @@ impl AppModel {
                     .conversation
                     .iter()
                     .find(|e| e.email_id == *email_id)
-                    .and_then(|e| e.attachments.get(index));
+                    .and_then(|e| e.attachments.get(index))
+                    .cloned();
                 if let Some(att) = att {
-                    let filename = att.filename.clone();
-                    let data = att.data.clone();
-                    return cosmic::task::future(async move {
-                        let dir =
-                            dirs::download_dir().unwrap_or_else(|| PathBuf::from("."));
-                        let path = dir.join(&filename);
-                        match tokio::fs::write(&path, &data).await {
-                            Ok(()) => Message::SaveAttachmentComplete(Ok(
-                                path.display().to_string(),
-                            )),
-                            Err(e) => Message::SaveAttachmentComplete(Err(format!(
-                                "Save failed: {e}"
-                            ))),
-                        }
-                    });
+                    return self.attachment_action(AttachmentAction::Save(att));
                 }
             }
 
@@ -718,33 +855,11 @@ impl AppModel {
             }
 
             Message::SaveAttachment(index) => {
-                if let Some(att) = self.preview_attachments.get(index) {
-                    let filename = att.filename.clone();
-                    let data = att.data.clone();
-                    return cosmic::task::future(async move {
-                        let dir = dirs::download_dir()
-                            .unwrap_or_else(|| PathBuf::from("."));
-                        let path = dir.join(&filename);
-                        match tokio::fs::write(&path, &data).await {
-                            Ok(()) => Message::SaveAttachmentComplete(
-                                Ok(path.display().to_string()),
-                            ),
-                            Err(e) => Message::SaveAttachmentComplete(
-                                Err(format!("Save failed: {e}")),
-                            ),
-                        }
-                    });
+                if let Some(att) = self.preview_attachments.get(index).cloned() {
+                    return self.attachment_action(AttachmentAction::Save(att));
                 }
             }
 
-            Message::SaveAttachmentComplete(Ok(path)) => {
-                self.status_message = format!("Saved to {path}");
-            }
-            Message::SaveAttachmentComplete(Err(e)) => {
-                self.status_message = e;
-                log::error!("Attachment save failed: {}", self.status_message);
-            }
-
             _ => {}
         }
         Task::none()
@@ -753,7 +868,16 @@ impl AppModel {
 
 #[cfg(test)]
 mod tests {
-    use super::should_apply_body_result;
+    use super::{conversation_scroll_offset, should_apply_body_result, unfolded_on_open};
+
+    #[test]
+    fn conversation_scroll_offset_spans_first_to_last() {
+        assert_eq!(conversation_scroll_offset(0, 1), 0.0);
+        assert_eq!(conversation_scroll_offset(0, 5), 0.0);
+        assert_eq!(conversation_scroll_offset(2, 5), 0.5);
+        assert_eq!(conversation_scroll_offset(4, 5), 1.0);
+        assert_eq!(conversation_scroll_offset(9, 5), 1.0);
+    }
 
     #[test]
     fn body_result_applies_when_epoch_and_selected_message_match() {
@@ -770,4 +894,16 @@ mod tests {
         assert!(!should_apply_body_result(7, 7, Some("M99"), "M42"));
         assert!(!should_apply_body_result(7, 7, None, "M42"));
     }
+
+    #[test]
+    fn newest_and_clicked_cards_open_unfolded() {
+        let dates = [
+            ("M1", "2026-03-01T10:00:00Z"),
+            ("M2", "2026-03-03T10:00:00Z"),
+            ("M3", "Mon, 2 Mar 2026 10:00:00 +0000"),
+            ("M4", "garbled"),
+        ];
+        assert_eq!(unfolded_on_open(&dates, "M1"), [true, true, false, false]);
+        assert_eq!(unfolded_on_open(&dates, "M2"), [false, true, false, false]);
+    }
 }
@@ -0,0 +1,173 @@
+//! Charset repair for message bodies, and the "View with encoding" override.
+//!
+//! Core decodes MIME and caches the result as text; the original bytes are
+//! not kept. What can still be fixed is text decoded with the wrong
+//! single-byte charset: every windows-1252/latin-1 character maps back to
+//! one byte, so those bytes can be decoded again. UTF-8 read as windows-1252
+//! ("Ã©tÃ©") is repaired automatically when a body loads; anything else
+//! (KOI8-R, Shift_JIS shown as Western text) is picked by hand. Bodies that
+//! were decoded as UTF-8 with replacement characters can't be recovered.
+
+use cosmic::app::Task;
+use cosmic::widget::markdown;
+use encoding_rs::Encoding;
+
+use super::{AppModel, Message};
+
+/// Choices in the "View with encoding" dropdown; index 0 shows the body as
+/// received (after automatic repair).
+pub const ENCODING_LABELS: &[&str] = &[
+    "As received",
+    "Unicode (UTF-8)",
+    "Western (windows-1252)",
+    "Central European (ISO-8859-2)",
+    "Cyrillic (KOI8-R)",
+    "Cyrillic (windows-1251)",
+    "Japanese (Shift_JIS)",
+    "Japanese (EUC-JP)",
+    "Chinese (GBK)",
+    "Chinese (Big5)",
+    "Korean (EUC-KR)",
+];
+
+fn encoding_at(index: usize) -> Option<&'static Encoding> {
+    let encoding = match index {
+        1 => encoding_rs::UTF_8,
+        2 => encoding_rs::WINDOWS_1252,
+        3 => encoding_rs::ISO_8859_2,
+        4 => encoding_rs::KOI8_R,
+        5 => encoding_rs::WINDOWS_1251,
+        6 => encoding_rs::SHIFT_JIS,
+        7 => encoding_rs::EUC_JP,
+        8 => encoding_rs::GBK,
+        9 => encoding_rs::BIG5,
+        10 => encoding_rs::EUC_KR,
+        _ => return None,
+    };
+    Some(encoding)
+}
+
+/// The bytes `text` was decoded from, assuming a windows-1252 or latin-1
+/// decoder. `None` when it holds characters neither could have produced.
+fn single_byte_source(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut buf = [0u8; 4];
+    for c in text.chars() {
+        if let Ok(b) = u8::try_from(u32::from(c)) {
+            bytes.push(b);
+            continue;
+        }
+        let (encoded, _, unmappable) = encoding_rs::WINDOWS_1252.encode(c.encode_utf8(&mut buf));
+        match *encoded {
+            [b] if !unmappable => bytes.push(b),
+            _ => return None,
+        }
+    }
+    Some(bytes)
+}
+
+/// UTF-8 text that went through a windows-1252 decoder, decoded properly.
+/// `None` when the text doesn't look like that.
+pub fn repair_mojibake(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+    let bytes = single_byte_source(text)?;
+    // Real Western text almost never forms valid multi-byte UTF-8.
+    let repaired = String::from_utf8(bytes).ok()?;
+    (repaired != text).then_some(repaired)
+}
+
+/// `text` read again as `encoding`. `None` when its source bytes can't be
+/// recovered.
+pub fn reinterpret(text: &str, encoding: &'static Encoding) -> Option<String> {
+    let bytes = single_byte_source(text)?;
+    let (decoded, _) = encoding.decode_without_bom_handling(&bytes);
+    Some(decoded.into_owned())
+}
+
+/// Automatic repair for a freshly loaded (markdown, plain) body pair.
+pub fn repair_body(markdown_body: String, plain_body: String) -> (String, String) {
+    match (repair_mojibake(&markdown_body), repair_mojibake(&plain_body)) {
+        (None, None) => (markdown_body, plain_body),
+        (md, plain) => {
+            log::info!("Repaired UTF-8 body decoded as windows-1252");
+            (md.unwrap_or(markdown_body), plain.unwrap_or(plain_body))
+        }
+    }
+}
+
+/// The previewed body as loaded, kept so an encoding override can start
+/// from it rather than from an earlier override.
+#[derive(Debug, Clone)]
+pub struct ReceivedBody {
+    pub email_id: String,
+    pub markdown: String,
+    pub plain: String,
+}
+
+impl AppModel {
+    pub(super) fn view_with_encoding(&mut self, index: usize) -> Task<Message> {
+        let current_id = self
+            .selected_message
+            .and_then(|i| self.messages.get(i))
+            .map(|m| m.email_id.as_str());
+        let Some(received) = self
+            .preview_received
+            .as_ref()
+            .filter(|r| Some(r.email_id.as_str()) == current_id)
+        else {
+            return Task::none();
+        };
+
+        let (md, plain) = match encoding_at(index) {
+            None => (received.markdown.clone(), received.plain.clone()),
+            Some(encoding) => {
+                match (
+                    reinterpret(&received.markdown, encoding),
+                    reinterpret(&received.plain, encoding),
+                ) {
+                    (Some(md), Some(plain)) => (md, plain),
+                    _ => {
+                        self.status_message = format!(
+                            "Can't re-read as {}: the original bytes weren't kept",
+                            ENCODING_LABELS[index]
+                        );
+                        return Task::none();
+                    }
+                }
+            }
+        };
+        self.preview_encoding = index;
+        self.preview_markdown = markdown::parse(&md).collect();
+        self.preview_body = plain;
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_read_as_windows_1252_is_repaired() {
+        assert_eq!(repair_mojibake("CafÃ© â‚¬5, naÃ¯ve").as_deref(), Some("Café €5, naïve"));
+    }
+
+    #[test]
+    fn correct_text_is_left_alone() {
+        assert_eq!(repair_mojibake("plain ascii"), None);
+        assert_eq!(repair_mojibake("Café déjà vu"), None);
+        assert_eq!(repair_mojibake("Привет"), None);
+    }
+
+    #[test]
+    fn koi8_r_shown_as_western_reads_back() {
+        // "Привет" in KOI8-R, decoded as windows-1252.
+        let (western, _) =
+            encoding_rs::WINDOWS_1252.decode_without_bom_handling(b"\xf0\xd2\xc9\xd7\xc5\xd4");
+        let koi8 = encoding_at(4).unwrap();
+        assert_eq!(reinterpret(&western, koi8).as_deref(), Some("Привет"));
+        assert_eq!(reinterpret("Привет", koi8), None);
+    }
+}
@@ -0,0 +1,503 @@
+//! Sender cleanup wizard: find every cached message from an address or
+//! domain across the active account's folders, then archive or trash them
+//! in batches with progress.
+//!
+//! Trash and Junk aren't scanned, so archiving doesn't pull deleted mail or
+//! spam back out; Sent and Drafts aren't either, as they hold your own mail
+//! rather than the sender's. Core moves one message per `Email/set`, so a
+//! batch sends up to `MOVE_BATCH` of them at once.
+//!
+//! Each batch holds the account's mutation lock like an ordinary move does:
+//! moves made while a batch runs queue behind it, and the wizard waits for
+//! queued moves to drain before its next batch. Rows in the open folder are
+//! removed from the list as they go and put back if their move fails.
+//!
+//! Matching runs against the local cache (core has no server-side sender
+//! query yet), so results are only as complete as sync + backfill. Totals
+//! are message counts only: cached summaries don't carry a size.
+
+use std::collections::HashSet;
+
+use cosmic::app::Task;
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::models::MessageSummary;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::recipients::{bare_address, domain_in};
+use super::{AppModel, Message, MessageIdentity};
+
+/// Per-folder cap on cached messages scanned.
+const MAX_SCAN_PER_FOLDER: u32 = 10_000;
+
+/// Folder roles left out of the scan.
+const SKIPPED_ROLES: &[&str] = &["trash", "junk", "sent", "drafts"];
+
+/// Moves sent together in one step.
+const MOVE_BATCH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupAction {
+    Archive,
+    Trash,
+}
+
+impl CleanupAction {
+    fn role(self) -> &'static str {
+        match self {
+            Self::Archive => "archive",
+            Self::Trash => "trash",
+        }
+    }
+
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Archive => "Archive",
+            Self::Trash => "Trash",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanupMatch {
+    pub email_id: String,
+    pub mailbox_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CleanupStage {
+    Input,
+    Scanning,
+    Review,
+    Running {
+        action: CleanupAction,
+        next: usize,
+        moved: usize,
+        failed: usize,
+    },
+    Finished {
+        action: CleanupAction,
+        moved: usize,
+        failed: usize,
+    },
+}
+
+pub struct CleanupWizard {
+    pub account_id: AccountId,
+    pub pattern: String,
+    pub stage: CleanupStage,
+    pub matches: Vec<CleanupMatch>,
+    /// (folder name, match count) for the review screen, largest first.
+    pub folder_counts: Vec<(String, usize)>,
+    pub error: Option<String>,
+    epoch: u64,
+    /// List rows taken out for the batch in flight, and where they were.
+    restore: Vec<(MessageSummary, usize)>,
+}
+
+/// Whether a From header matches an address (`a@b.com`) or a domain
+/// (`b.com` / `@b.com`, subdomains included).
+pub fn sender_matches(from: &str, pattern: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    if pattern.is_empty() {
+        return false;
+    }
+    let addr = bare_address(from).to_ascii_lowercase();
+    match pattern.strip_prefix('@') {
+        Some(domain) => addr
+            .rsplit_once('@')
+            .is_some_and(|(_, d)| domain_in(d, &[domain.to_string()])),
+        None if pattern.contains('@') => addr == pattern,
+        None => addr
+            .rsplit_once('@')
+            .is_some_and(|(_, d)| domain_in(d, &[pattern])),
+    }
+}
+
+/// Count matches per folder, largest first (ties keep folder order).
+fn count_by_folder(matches: &[CleanupMatch], folders: &[(String, String)]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = folders
+        .iter()
+        .map(|(mailbox_id, name)| {
+            let n = matches.iter().filter(|m| &m.mailbox_id == mailbox_id).count();
+            (name.clone(), n)
+        })
+        .filter(|(_, n)| *n > 0)
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+    counts
+}
+
+async fn scan_folders(
+    cache: CacheHandle,
+    account_id: AccountId,
+    mailbox_ids: Vec<String>,
+    pattern: String,
+) -> Result<Vec<CleanupMatch>, String> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut matches = Vec::new();
+    for mailbox_id in mailbox_ids {
+        let messages = cache
+            .load_messages(account_id.clone(), mailbox_id.clone(), MAX_SCAN_PER_FOLDER, 0)
+            .await?;
+        for msg in messages {
+            if sender_matches(&msg.from, &pattern) && seen.insert(msg.email_id.clone()) {
+                matches.push(CleanupMatch {
+                    email_id: msg.email_id,
+                    mailbox_id: mailbox_id.clone(),
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+impl AppModel {
+    pub(super) fn handle_cleanup(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::CleanupOpen => {
+                let Some(acct) = self.active_account.and_then(|i| self.accounts.get(i)) else {
+                    self.status_message = "Select an account first".into();
+                    return Task::none();
+                };
+                // Prefill with the selected message's sender.
+                let pattern = self
+                    .selected_message
+                    .and_then(|i| self.messages.get(i))
+                    .map(|m| bare_address(&m.from).to_string())
+                    .unwrap_or_default();
+                self.cleanup_epoch = self.cleanup_epoch.wrapping_add(1);
+                self.cleanup = Some(CleanupWizard {
+                    account_id: acct.config.id.clone(),
+                    pattern,
+                    stage: CleanupStage::Input,
+                    matches: Vec::new(),
+                    folder_counts: Vec::new(),
+                    error: None,
+                    epoch: self.cleanup_epoch,
+                    restore: Vec::new(),
+                });
+            }
+            Message::CleanupPatternChanged(pattern) => {
+                if let Some(wizard) = self.cleanup.as_mut() {
+                    wizard.pattern = pattern;
+                    wizard.stage = CleanupStage::Input;
+                    wizard.error = None;
+                }
+            }
+            Message::CleanupScan => return self.start_cleanup_scan(),
+            Message::CleanupScanned { epoch, result } => {
+                let folders = self.cleanup_folder_names();
+                let Some(wizard) = self.cleanup.as_mut().filter(|w| w.epoch == epoch) else {
+                    return Task::none();
+                };
+                match result {
+                    Ok(matches) => {
+                        wizard.folder_counts = count_by_folder(&matches, &folders);
+                        wizard.matches = matches;
+                        wizard.stage = CleanupStage::Review;
+                    }
+                    Err(e) => {
+                        log::error!("Cleanup scan failed: {}", e);
+                        wizard.error = Some(e);
+                        wizard.stage = CleanupStage::Input;
+                    }
+                }
+            }
+            Message::CleanupRun(action) => {
+                if let Some(wizard) = self.cleanup.as_mut() {
+                    if wizard.stage != CleanupStage::Review {
+                        return Task::none();
+                    }
+                    wizard.stage = CleanupStage::Running {
+                        action,
+                        next: 0,
+                        moved: 0,
+                        failed: 0,
+                    };
+                }
+                return self.cleanup_next_move();
+            }
+            Message::CleanupStepDone {
+                epoch,
+                account_id,
+                results,
+            } => {
+                self.mutation_in_flight_accounts.remove(&account_id);
+                let mut restore = Vec::new();
+                let mut failed_ids = HashSet::new();
+                for (email_id, result) in &results {
+                    if let Err(e) = result {
+                        log::warn!("Cleanup move of {} failed: {}", email_id, e);
+                        failed_ids.insert(email_id.as_str());
+                    }
+                }
+                if let Some(wizard) = self.cleanup.as_mut().filter(|w| w.epoch == epoch) {
+                    restore = std::mem::take(&mut wizard.restore);
+                    if let CleanupStage::Running { moved, failed, .. } = &mut wizard.stage {
+                        *moved += results.len() - failed_ids.len();
+                        *failed += failed_ids.len();
+                    }
+                }
+                // Back in reverse order of removal, so each index still fits.
+                let mut restored = false;
+                for (summary, index) in restore.into_iter().rev() {
+                    let mailbox_id = summary.context_mailbox_id.as_str();
+                    if failed_ids.contains(summary.email_id.as_str())
+                        && self.selected_mailbox_id.as_deref() == Some(mailbox_id)
+                    {
+                        self.messages.insert(index.min(self.messages.len()), summary);
+                        restored = true;
+                    }
+                }
+                if restored {
+                    self.recompute_visible();
+                }
+                // Moves queued during the step run first; the wizard picks up
+                // again once the queue is empty.
+                return self.try_run_next_move_intent_for(&account_id);
+            }
+            Message::CleanupClose => {
+                let finished_account = self
+                    .cleanup
+                    .take()
+                    .filter(|w| {
+                        matches!(w.stage, CleanupStage::Running { .. } | CleanupStage::Finished { .. })
+                    })
+                    .map(|w| w.account_id);
+                // Pick up the moves in folder lists and counts.
+                if let Some(account_id) = finished_account {
+                    return self.handle_refresh_accounts(Some(&account_id));
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// (mailbox_id, name) of the folders the wizard scans.
+    fn cleanup_folder_names(&self) -> Vec<(String, String)> {
+        self.cleanup
+            .as_ref()
+            .and_then(|w| self.account_index(&w.account_id))
+            .and_then(|idx| self.accounts.get(idx))
+            .map(|a| {
+                a.folders
+                    .iter()
+                    .filter(|f| !f.role.as_deref().is_some_and(|r| SKIPPED_ROLES.contains(&r)))
+                    .map(|f| (f.mailbox_id.clone(), f.name.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn start_cleanup_scan(&mut self) -> Task<Message> {
+        let Some(cache) = self.cache.clone() else {
+            if let Some(wizard) = self.cleanup.as_mut() {
+                wizard.error = Some("Cleanup needs the local cache".into());
+            }
+            return Task::none();
+        };
+        let mailbox_ids: Vec<String> = self
+            .cleanup_folder_names()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let Some(wizard) = self.cleanup.as_mut() else {
+            return Task::none();
+        };
+        let pattern = wizard.pattern.trim().to_string();
+        if pattern.is_empty() {
+            wizard.error = Some("Enter an address or domain".into());
+            return Task::none();
+        }
+        wizard.stage = CleanupStage::Scanning;
+        wizard.error = None;
+        let epoch = wizard.epoch;
+        let account_id = wizard.account_id.clone();
+        cosmic::task::future(async move {
+            let result = scan_folders(cache, account_id, mailbox_ids, pattern).await;
+            Message::CleanupScanned { epoch, result }
+        })
+    }
+
+    /// Dispatch the next batch of moves, or finish when the list is
+    /// exhausted. Waits while another move holds the account's mutation lock.
+    pub(super) fn cleanup_next_move(&mut self) -> Task<Message> {
+        let Some(wizard) = self.cleanup.as_ref() else {
+            return Task::none();
+        };
+        let CleanupStage::Running {
+            action,
+            next,
+            moved,
+            failed,
+        } = wizard.stage
+        else {
+            return Task::none();
+        };
+        if self.mutation_in_flight_accounts.contains(&wizard.account_id) {
+            return Task::none();
+        }
+        let client = self.client_for_account(&wizard.account_id);
+        let dest = self.role_mailbox(&wizard.account_id, action.role());
+
+        let (Some(client), Some(dest)) = (client, dest) else {
+            let reason = format!("{} folder or connection unavailable", action.verb());
+            let remaining = wizard.matches.len().saturating_sub(next);
+            if let Some(wizard) = self.cleanup.as_mut() {
+                wizard.error = Some(reason);
+                wizard.stage = CleanupStage::Finished {
+                    action,
+                    moved,
+                    failed: failed + remaining,
+                };
+            }
+            return Task::none();
+        };
+
+        let (targets, next) = next_batch(&wizard.matches, next, &dest);
+        if targets.is_empty() {
+            if let Some(wizard) = self.cleanup.as_mut() {
+                wizard.stage = CleanupStage::Finished { action, moved, failed };
+            }
+            self.status_message = format!("Cleanup: {} messages → {}", moved, action.verb());
+            return Task::none();
+        }
+        let epoch = wizard.epoch;
+        let account_id = wizard.account_id.clone();
+        let mut restore = Vec::new();
+        for target in &targets {
+            let index = self.messages.iter().position(|m| {
+                m.account_id == account_id
+                    && m.email_id == target.email_id
+                    && m.context_mailbox_id == target.mailbox_id
+            });
+            if let Some(index) = index {
+                restore.extend(self.remove_message_optimistic(index).map(|m| (m, index)));
+            }
+            self.message_selection.remove(&MessageIdentity {
+                account_id: account_id.clone(),
+                mailbox_id: target.mailbox_id.clone(),
+                email_id: target.email_id.clone(),
+            });
+        }
+        if let Some(wizard) = self.cleanup.as_mut() {
+            wizard.stage = CleanupStage::Running {
+                action,
+                next,
+                moved,
+                failed,
+            };
+            wizard.restore = restore;
+        }
+        self.mutation_in_flight_accounts.insert(account_id.clone());
+
+        let cache = self.cache.clone();
+        cosmic::task::future(async move {
+            let moves = targets.iter().map(|target| {
+                neverlight_mail_core::email::move_to(
+                    &client,
+                    &target.email_id,
+                    &target.mailbox_id,
+                    &dest,
+                )
+            });
+            let outcomes = futures::future::join_all(moves).await;
+            let mut results = Vec::with_capacity(targets.len());
+            for (target, outcome) in targets.into_iter().zip(outcomes) {
+                let result = outcome.map_err(|e| e.to_string());
+                if let (Ok(()), Some(cache)) = (&result, &cache) {
+                    let email_id = target.email_id.clone();
+                    if let Err(e) = cache.remove_message(account_id.clone(), email_id).await {
+                        log::warn!("Failed to remove message from cache: {}", e);
+                    }
+                }
+                results.push((target.email_id, result));
+            }
+            Message::CleanupStepDone {
+                epoch,
+                account_id,
+                results,
+            }
+        })
+    }
+}
+
+/// Up to `MOVE_BATCH` matches from `next` on that aren't in `dest` already,
+/// and where the following batch starts.
+fn next_batch(matches: &[CleanupMatch], next: usize, dest: &str) -> (Vec<CleanupMatch>, usize) {
+    let mut batch = Vec::new();
+    let mut next = next;
+    while let Some(m) = matches.get(next) {
+        if batch.len() == MOVE_BATCH {
+            break;
+        }
+        next += 1;
+        if m.mailbox_id != dest {
+            batch.push(m.clone());
+        }
+    }
+    (batch, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_match(email_id: &str, mailbox_id: &str) -> CleanupMatch {
+        CleanupMatch {
+            email_id: email_id.into(),
+            mailbox_id: mailbox_id.into(),
+        }
+    }
+
+    #[test]
+    fn address_pattern_matches_exact_sender_only() {
+        assert!(sender_matches("News <News@Shop.example>", "news@shop.example"));
+        assert!(!sender_matches("deals@shop.example", "news@shop.example"));
+    }
+
+    #[test]
+    fn domain_pattern_includes_subdomains() {
+        assert!(sender_matches("a@mail.shop.example", "shop.example"));
+        assert!(sender_matches("a@shop.example", "@shop.example"));
+        assert!(!sender_matches("a@notshop.example", "shop.example"));
+    }
+
+    #[test]
+    fn blank_pattern_matches_nothing() {
+        assert!(!sender_matches("a@shop.example", "  "));
+    }
+
+    #[test]
+    fn batches_skip_messages_already_in_the_destination() {
+        let matches: Vec<CleanupMatch> = (0..MOVE_BATCH + 2)
+            .map(|i| sample_match(&format!("e{i}"), if i == 1 { "archive" } else { "inbox" }))
+            .collect();
+        let (batch, next) = next_batch(&matches, 0, "archive");
+        assert_eq!(batch.len(), MOVE_BATCH);
+        assert_eq!(next, MOVE_BATCH + 1);
+        assert!(batch.iter().all(|m| m.mailbox_id == "inbox"));
+        let (batch, next) = next_batch(&matches, next, "archive");
+        assert_eq!((batch.len(), next), (1, matches.len()));
+        assert!(next_batch(&matches, next, "archive").0.is_empty());
+    }
+
+    #[test]
+    fn folder_counts_sorted_and_skip_empty() {
+        let matches = vec![
+            sample_match("e1", "inbox"),
+            sample_match("e2", "promo"),
+            sample_match("e3", "promo"),
+        ];
+        let folders = vec![
+            ("inbox".to_string(), "Inbox".to_string()),
+            ("promo".to_string(), "Promotions".to_string()),
+            ("sent".to_string(), "Sent".to_string()),
+        ];
+        assert_eq!(
+            count_by_folder(&matches, &folders),
+            vec![("Promotions".to_string(), 2), ("Inbox".to_string(), 1)]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Side-by-side comparison of two messages (two versions of a contract, two
+//! quotes), shown in place of the panes until it's closed.
+//!
+//! "Compare with open message" in a row's menu puts the message in the
+//! preview on the left and the row's message on the right. Bodies come from
+//! the cache, or the server when missing, and show as plain text so the
+//! lines sit side by side. With "Scroll together" on, scrolling one column
+//! moves the other to the same relative position. Escape closes.
+
+use cosmic::app::Task;
+use cosmic::iced::widget::scrollable::{self, RelativeOffset};
+use cosmic::widget;
+use neverlight_mail_core::client::JmapClient;
+use neverlight_mail_core::models::MessageSummary;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::{AppModel, Message};
+
+/// Offsets closer than this are the same position; keeps a linked column's
+/// echo from bouncing back.
+const SCROLL_EPSILON: f32 = 0.001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareSide {
+    Left,
+    Right,
+}
+
+impl CompareSide {
+    fn other(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    pub fn scroll_id(self) -> widget::Id {
+        match self {
+            Self::Left => widget::Id::new("compare-left"),
+            Self::Right => widget::Id::new("compare-right"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScrollSync {
+    #[default]
+    Linked,
+    Independent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompareBody {
+    Loading,
+    Loaded(String),
+    Failed(String),
+}
+
+/// One column of the comparison.
+#[derive(Debug, Clone)]
+pub struct ComparePane {
+    pub summary: MessageSummary,
+    pub body: CompareBody,
+}
+
+impl ComparePane {
+    fn new(summary: MessageSummary) -> Self {
+        Self {
+            summary,
+            body: CompareBody::Loading,
+        }
+    }
+}
+
+/// Relative scroll positions of the two columns, 0.0 (top) to 1.0 (bottom).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScrollLink {
+    pub sync: ScrollSync,
+    left: f32,
+    right: f32,
+}
+
+impl ScrollLink {
+    fn offset_mut(&mut self, side: CompareSide) -> &mut f32 {
+        match side {
+            CompareSide::Left => &mut self.left,
+            CompareSide::Right => &mut self.right,
+        }
+    }
+
+    /// Record `side` scrolled to `offset`; the offset the other column
+    /// should snap to, when scrolling is linked and it isn't there yet.
+    fn scrolled(&mut self, side: CompareSide, offset: f32) -> Option<f32> {
+        *self.offset_mut(side) = offset;
+        if self.sync == ScrollSync::Independent {
+            return None;
+        }
+        let other = self.offset_mut(side.other());
+        if (*other - offset).abs() < SCROLL_EPSILON {
+            return None;
+        }
+        *other = offset;
+        Some(offset)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub left: ComparePane,
+    pub right: ComparePane,
+    pub scroll: ScrollLink,
+}
+
+/// Plain-text body of `email_id`, cache first.
+async fn load_plain(
+    cache: Option<CacheHandle>,
+    client: Option<JmapClient>,
+    account_id: String,
+    email_id: String,
+) -> Result<String, String> {
+    if let Some(cache) = &cache {
+        let cached = cache.load_body(account_id.clone(), email_id.clone()).await;
+        if let Ok(Some((_, plain, _))) = cached {
+            return Ok(plain);
+        }
+    }
+    let Some(client) = client else {
+        return Err("Not connected".into());
+    };
+    let fetched = neverlight_mail_core::email::get_body(&client, &email_id).await;
+    let (markdown, plain, attachments) = fetched.map_err(|e| e.to_string())?;
+    if let Some(cache) = cache {
+        let saved = cache
+            .save_body(account_id, email_id, markdown, plain.clone(), attachments)
+            .await;
+        if let Err(e) = saved {
+            log::warn!("Failed to cache body: {}", e);
+        }
+    }
+    Ok(plain)
+}
+
+impl AppModel {
+    /// Compare the open message with the one at list index `index`.
+    pub(super) fn open_comparison(&mut self, index: usize) -> Task<Message> {
+        let open = self.selected_message.and_then(|i| self.messages.get(i));
+        let (Some(open), Some(other)) = (open, self.messages.get(index)) else {
+            self.status_message = "Open a message to compare this one with".into();
+            return Task::none();
+        };
+        if open.email_id == other.email_id {
+            self.status_message = "Pick a different message to compare with".into();
+            return Task::none();
+        }
+        let (left, right) = (open.clone(), other.clone());
+        let tasks = [&left, &right]
+            .into_iter()
+            .map(|summary| self.compare_body_task(summary))
+            .collect::<Vec<_>>();
+        self.comparison = Some(Comparison {
+            left: ComparePane::new(left),
+            right: ComparePane::new(right),
+            scroll: ScrollLink::default(),
+        });
+        cosmic::task::batch(tasks)
+    }
+
+    fn compare_body_task(&self, summary: &MessageSummary) -> Task<Message> {
+        let client = self
+            .account_index(&summary.account_id)
+            .and_then(|i| self.accounts[i].client.clone());
+        let load = load_plain(
+            self.cache.clone(),
+            client,
+            summary.account_id.clone(),
+            summary.email_id.clone(),
+        );
+        let email_id = summary.email_id.clone();
+        cosmic::task::future(async move {
+            Message::CompareBodyLoaded {
+                email_id,
+                result: load.await,
+            }
+        })
+    }
+
+    pub(super) fn handle_compare(&mut self, message: Message) -> Task<Message> {
+        let Some(comparison) = self.comparison.as_mut() else {
+            return Task::none();
+        };
+        match message {
+            Message::CompareBodyLoaded { email_id, result } => {
+                let body = match result {
+                    Ok(plain) => CompareBody::Loaded(plain),
+                    Err(e) => CompareBody::Failed(e),
+                };
+                for pane in [&mut comparison.left, &mut comparison.right] {
+                    if pane.summary.email_id == email_id {
+                        pane.body = body.clone();
+                    }
+                }
+            }
+            Message::CompareScrolled { side, offset } => {
+                if let Some(y) = comparison.scroll.scrolled(side, offset) {
+                    let offset = RelativeOffset { x: 0.0, y };
+                    return scrollable::snap_to(side.other().scroll_id(), offset);
+                }
+            }
+            Message::ToggleCompareScroll => {
+                comparison.scroll.sync = match comparison.scroll.sync {
+                    ScrollSync::Linked => ScrollSync::Independent,
+                    ScrollSync::Independent => ScrollSync::Linked,
+                };
+            }
+            Message::CloseComparison => self.comparison = None,
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linked_columns_follow_each_other_once() {
+        let mut scroll = ScrollLink::default();
+        assert_eq!(scroll.scrolled(CompareSide::Left, 0.4), Some(0.4));
+        // The right column's echo of the snap goes no further.
+        assert_eq!(scroll.scrolled(CompareSide::Right, 0.4), None);
+
+        scroll.sync = ScrollSync::Independent;
+        assert_eq!(scroll.scrolled(CompareSide::Right, 0.9), None);
+        assert_eq!(scroll.left, 0.4);
+    }
+}
@@ -2,13 +2,25 @@ use cosmic::app::Task;
 use cosmic::dialog::file_chooser;
 use cosmic::widget::text_editor;
 
-use super::{AppModel, ComposePhase, Message};
+use super::autocomplete;
+use super::drafts::{self, ComposeSnapshot, LocalDraft};
+use super::recipients::{self, ReplyChoice};
+use super::settings::{PostingStyle, QuoteSettings, SignaturePlacement};
+use super::send_rejects;
+use super::signatures::signature_block;
+use super::row_menu::move_message;
+use super::metrics::format_bytes;
+use super::toasts::Severity;
+use super::{AppModel, CloseIntent, ComposePhase, Message, MessageIdentity};
 use neverlight_mail_core::models::AttachmentData;
 use neverlight_mail_core::submit::{self, SendRequest};
 
-use crate::dnd_models::DraggedFiles;
+use crate::dnd_models::{ClipboardPaste, DraggedFiles};
 use crate::ui::compose_dialog::ComposeMode;
 
+/// File name stem for images pasted into compose.
+const PASTED_IMAGE_PREFIX: &str = "pasted-image-";
+
 /// Guess MIME type from file extension.
 fn mime_from_ext(path: &std::path::Path) -> &'static str {
     match path
@@ -61,16 +73,26 @@ impl AppModel {
                 self.compose_from = 0;
                 self.compose_to.clear();
                 self.compose_subject.clear();
-                self.compose_body = text_editor::Content::new();
+                self.compose_signatures = self.compose_signature_choices();
+                self.compose_body = match self.compose_signatures.selected_block() {
+                    Some(sig) => text_editor::Content::with_text(&format!("\n\n{sig}")),
+                    None => text_editor::Content::new(),
+                };
                 self.compose_in_reply_to = None;
                 self.compose_references = None;
                 self.compose_attachments.clear();
+                self.compose_server_draft = None;
+                self.compose_reply_source = None;
+                self.compose_reply_choice = None;
                 self.compose_error = None;
+                self.compose_rejected.clear();
                 self.compose_phase = ComposePhase::Open;
                 self.refresh_compose_cache();
+                self.compose_baseline = self.compose_snapshot();
             }
 
-            Message::ComposeReply => {
+            Message::ComposeReply | Message::ComposeReplyAll => {
+                let reply_all = matches!(message, Message::ComposeReplyAll);
                 if self.setup_model.is_some() || self.compose_phase.is_open() {
                     return Task::none();
                 }
@@ -93,11 +115,33 @@ impl AppModel {
                     (msg.clone(), self.preview_body.clone())
                 };
 
-                self.compose_mode = ComposeMode::Reply;
                 self.compose_account = self
                     .account_index(&msg.account_id)
                     .unwrap_or(self.active_account.unwrap_or(0));
-                self.compose_to = msg.from.clone();
+                if reply_all {
+                    self.compose_mode = ComposeMode::ReplyAll;
+                    let own = self
+                        .accounts
+                        .get(self.compose_account)
+                        .map(|a| a.config.email_addresses.as_slice())
+                        .unwrap_or_default();
+                    self.compose_to = recipients::reply_all_to(&msg.from, &msg.to, own);
+                    self.compose_reply_choice = None;
+                } else {
+                    self.compose_mode = ComposeMode::Reply;
+                    self.compose_reply_choice = ReplyChoice::new(
+                        &msg.from,
+                        msg.reply_to.as_deref(),
+                        self.settings.reply_target,
+                    );
+                    self.compose_to = match &self.compose_reply_choice {
+                        Some(choice) => choice.address().to_string(),
+                        None => msg.from.clone(),
+                    };
+                }
+
+                self.compose_from = self.own_address_in(&msg.to);
+                self.compose_signatures = self.compose_signature_choices();
 
                 let subj = &msg.subject;
                 self.compose_subject = if subj.starts_with("Re: ") {
@@ -106,9 +150,18 @@ impl AppModel {
                     format!("Re: {subj}")
                 };
 
-                let quoted = quote_body(&body_text, &msg.from, &msg.date);
-                self.compose_body =
-                    text_editor::Content::with_text(&format!("\n\n{quoted}"));
+                let (text, cursor_line) = reply_body(
+                    &body_text,
+                    &msg.from,
+                    &msg.date,
+                    self.compose_signatures.selected_block(),
+                    &self.settings.quoting,
+                );
+                self.compose_body = text_editor::Content::with_text(&text);
+                for _ in 0..cursor_line {
+                    self.compose_body
+                        .perform(text_editor::Action::Move(text_editor::Motion::Down));
+                }
 
                 self.compose_in_reply_to = Some(msg.message_id.clone());
                 self.compose_references = Some(build_references(
@@ -116,9 +169,13 @@ impl AppModel {
                     &msg.message_id,
                 ));
                 self.compose_attachments.clear();
+                self.compose_server_draft = None;
+                self.compose_reply_source = Some(MessageIdentity::of(msg));
                 self.compose_error = None;
+                self.compose_rejected.clear();
                 self.compose_phase = ComposePhase::Open;
                 self.refresh_compose_cache();
+                self.compose_baseline = self.compose_snapshot();
             }
 
             Message::ComposeForward => {
@@ -156,6 +213,8 @@ impl AppModel {
                 self.compose_account = self
                     .account_index(&msg.account_id)
                     .unwrap_or(self.active_account.unwrap_or(0));
+                self.compose_from = self.own_address_in(&msg.to);
+                self.compose_signatures = self.compose_signature_choices();
                 self.compose_to.clear();
 
                 let subj = &msg.subject;
@@ -170,33 +229,168 @@ impl AppModel {
                     &msg.from,
                     &msg.date,
                     &msg.subject,
+                    self.compose_signatures.selected_block(),
+                    &self.settings.quoting,
                 );
-                self.compose_body =
-                    text_editor::Content::with_text(&format!("\n\n{fwd}"));
+                self.compose_body = text_editor::Content::with_text(&fwd);
 
                 self.compose_in_reply_to = None;
                 self.compose_references = None;
                 self.compose_attachments = fwd_attachments;
+                self.compose_server_draft = None;
+                self.compose_reply_source = None;
+                self.compose_reply_choice = None;
                 self.compose_error = None;
+                self.compose_rejected.clear();
+                self.compose_phase = ComposePhase::Open;
+                self.refresh_compose_cache();
+                self.compose_baseline = self.compose_snapshot();
+            }
+
+            Message::ComposeResumeDraft => {
+                if self.setup_model.is_some() || self.compose_phase.is_open() {
+                    return Task::none();
+                }
+                let Some(draft) = self.local_drafts.pop() else {
+                    return Task::none();
+                };
+                if let Err(e) = drafts::save_drafts(&self.local_drafts) {
+                    log::warn!("Failed to update drafts file: {}", e);
+                }
+                self.compose_mode = if draft.in_reply_to.is_some() {
+                    ComposeMode::Reply
+                } else {
+                    ComposeMode::New
+                };
+                self.compose_account = self
+                    .account_index(&draft.account_id)
+                    .unwrap_or(self.active_account.unwrap_or(0));
+                self.compose_from = self
+                    .accounts
+                    .get(self.compose_account)
+                    .zip(draft.from.as_deref())
+                    .and_then(|(acct, from)| {
+                        acct.config.email_addresses.iter().position(|a| a == from)
+                    })
+                    .unwrap_or(0);
+                self.compose_to = draft.to;
+                self.compose_subject = draft.subject;
+                self.compose_signatures = self.compose_signature_choices();
+                self.compose_signatures.selected = self.compose_signatures.found_in(&draft.body);
+                self.compose_body = text_editor::Content::with_text(&draft.body);
+                self.compose_in_reply_to = draft.in_reply_to;
+                self.compose_references = draft.references;
+                self.compose_attachments.clear();
+                self.compose_server_draft = None;
+                self.compose_reply_source = None;
+                self.compose_reply_choice = None;
+                self.compose_error = (!draft.dropped_attachments.is_empty()).then(|| {
+                    format!(
+                        "Attachments aren't kept in drafts; attach again: {}",
+                        draft.dropped_attachments.join(", ")
+                    )
+                });
+                self.compose_rejected.clear();
                 self.compose_phase = ComposePhase::Open;
                 self.refresh_compose_cache();
+                // The draft left the saved list, so closing it again must prompt.
+                self.compose_baseline = ComposeSnapshot::default();
+            }
+
+            Message::ComposeEditServerDraft => {
+                if self.setup_model.is_some() || self.compose_phase.is_open() {
+                    return Task::none();
+                }
+                let Some(msg) = self.selected_message.and_then(|i| self.messages.get(i)) else {
+                    return Task::none();
+                };
+                let Some(account) = self.account_index(&msg.account_id) else {
+                    return Task::none();
+                };
+                let folders = &self.accounts[account].folders;
+                let drafts_id = neverlight_mail_core::mailbox::find_by_role(folders, "drafts");
+                if drafts_id.as_deref() != Some(msg.context_mailbox_id.as_str()) {
+                    self.status_message = "Only messages in Drafts can be edited".into();
+                    return Task::none();
+                }
+                let msg = msg.clone();
+
+                self.compose_mode = if msg.in_reply_to.is_some() {
+                    ComposeMode::Reply
+                } else {
+                    ComposeMode::New
+                };
+                self.compose_account = account;
+                self.compose_from = self.accounts[account]
+                    .config
+                    .email_addresses
+                    .iter()
+                    .position(|a| msg.from.contains(a.as_str()))
+                    .unwrap_or(0);
+                self.compose_to = msg.to;
+                self.compose_subject = msg.subject;
+                self.compose_signatures = self.compose_signature_choices();
+                self.compose_signatures.selected =
+                    self.compose_signatures.found_in(&self.preview_body);
+                self.compose_body = text_editor::Content::with_text(&self.preview_body);
+                self.compose_references = msg.in_reply_to.clone();
+                self.compose_in_reply_to = msg.in_reply_to;
+                self.compose_attachments.clear();
+                // Sending replaces the server copy: it goes to Trash afterwards.
+                self.compose_server_draft = Some(msg.email_id);
+                self.compose_reply_source = None;
+                self.compose_reply_choice = None;
+                self.compose_error = None;
+                self.compose_rejected.clear();
+                self.compose_phase = ComposePhase::Open;
+                self.refresh_compose_cache();
+                self.compose_baseline = self.compose_snapshot();
             }
 
             Message::ComposeAccountChanged(i) => {
                 self.compose_account = i;
                 self.compose_from = 0;
+                self.follow_compose_from();
                 self.refresh_compose_cache();
             }
+            Message::ComposeReplyTarget(target) => {
+                let Some(choice) = self.compose_reply_choice.as_mut() else {
+                    return Task::none();
+                };
+                choice.target = target;
+                self.compose_to = choice.address().to_string();
+                self.refresh_external_recipients();
+                self.refresh_compose_suggestions();
+            }
             Message::ComposeFromChanged(i) => {
                 self.compose_from = i;
+                self.follow_compose_from();
+            }
+            Message::ComposeSignatureChanged(i) => {
+                self.switch_compose_signature(i);
             }
             Message::ComposeToChanged(v) => {
                 self.compose_to = v;
+                let to = &self.compose_to;
+                self.compose_rejected.retain(|r| to.contains(r.as_str()));
+                self.refresh_external_recipients();
+                self.refresh_compose_suggestions();
+            }
+            Message::ComposeRecipientPicked(pick) => {
+                self.compose_to = autocomplete::complete_recipient(&self.compose_to, &pick);
+                self.refresh_external_recipients();
+                self.refresh_compose_suggestions();
             }
             Message::ComposeSubjectChanged(v) => {
                 self.compose_subject = v;
             }
             Message::ComposeBodyAction(action) => {
+                // Copied files can arrive as file:// text; attach them.
+                if let text_editor::Action::Edit(text_editor::Edit::Paste(text)) = &action {
+                    if let Some(paths) = pasted_file_paths(text) {
+                        return cosmic::task::future(read_paths_as_attachments(paths));
+                    }
+                }
                 self.compose_body.perform(action);
             }
 
@@ -292,7 +486,49 @@ impl AppModel {
                 });
             }
 
-            Message::ComposeSend => {
+            Message::ComposePaste => {
+                if self.compose_phase != ComposePhase::Open {
+                    return Task::none();
+                }
+                return cosmic::iced::clipboard::read_data::<ClipboardPaste>().map(|data| {
+                    cosmic::Action::App(match data {
+                        Some(paste) => Message::ComposePasted(paste),
+                        None => Message::Noop,
+                    })
+                });
+            }
+            Message::ComposePasted(ClipboardPaste::Image(data)) => {
+                let pasted = self
+                    .compose_attachments
+                    .iter()
+                    .filter(|a| a.filename.starts_with(PASTED_IMAGE_PREFIX))
+                    .count();
+                self.compose_attachments.push(AttachmentData {
+                    filename: format!("{PASTED_IMAGE_PREFIX}{}.png", pasted + 1),
+                    mime_type: "image/png".into(),
+                    data,
+                });
+            }
+            Message::ComposePasted(ClipboardPaste::Files(uri_list)) => {
+                let paths = parse_uri_list(&uri_list);
+                if paths.is_empty() {
+                    return Task::none();
+                }
+                return cosmic::task::future(read_paths_as_attachments(paths));
+            }
+
+            Message::ComposeSendWithoutRejected => {
+                self.send_without_rejected();
+                return self.dispatch(Message::ComposeSend);
+            }
+
+            Message::ComposeSend | Message::ComposeSendAndArchive => {
+                // The mass-send prompt's Send keeps a Send & Archive choice.
+                if matches!(message, Message::ComposeSendAndArchive) {
+                    self.compose_archive_after_send = self.compose_reply_source.clone();
+                } else if !matches!(self.compose_phase, ComposePhase::ConfirmSend(_)) {
+                    self.compose_archive_after_send = None;
+                }
                 if self.compose_to.trim().is_empty() {
                     self.compose_error = Some("Recipient is required".into());
                     return Task::none();
@@ -304,6 +540,38 @@ impl AppModel {
                     return Task::none();
                 }
 
+                let size = self.compose_send_size();
+                if let Some(limit) = size.limit.filter(|_| size.over_limit()) {
+                    self.compose_error = Some(format!(
+                        "This message is about {}, over this account's {} limit. \
+                         Remove or shrink attachments to send it.",
+                        format_bytes(size.estimate),
+                        format_bytes(limit),
+                    ));
+                    return Task::none();
+                }
+
+                let variables = self.compose_variables();
+                let unresolved = variables.unresolved();
+                if !unresolved.is_empty() {
+                    self.compose_error = Some(format!(
+                        "Nothing to fill {} with for this recipient. Write the To \
+                         address as Name <address> or remove the variable.",
+                        unresolved.join(", "),
+                    ));
+                    return Task::none();
+                }
+                let body_text = variables.fill(&body_text);
+
+                // Mass sends ask once; Send on that prompt comes back here.
+                let recipient_count = recipients::recipient_count(&self.compose_to);
+                if matches!(self.compose_phase, ComposePhase::ConfirmSend(_)) {
+                    self.compose_phase = ComposePhase::Open;
+                } else if self.settings.confirm_send_to(recipient_count) {
+                    self.compose_phase = ComposePhase::ConfirmSend(recipient_count);
+                    return Task::none();
+                }
+
                 let Some(acct) = self.accounts.get(self.compose_account) else {
                     self.compose_error = Some("No account selected".into());
                     return Task::none();
@@ -344,9 +612,12 @@ impl AppModel {
                     self.compose_error = Some("Sent folder not found".into());
                     return Task::none();
                 };
+                let trash_id = neverlight_mail_core::mailbox::find_by_role(&acct.folders, "trash");
+                let replaced_draft = self.compose_server_draft.clone().zip(trash_id);
 
                 self.compose_phase = ComposePhase::Sending;
                 self.compose_error = None;
+                self.compose_rejected.clear();
 
                 log::info!("JMAP send: from={}, to={}", from_addr, self.compose_to);
 
@@ -356,7 +627,7 @@ impl AppModel {
                     .map(|s| s.trim().to_string())
                     .filter(|s| !s.is_empty())
                     .collect();
-                let subject = self.compose_subject.clone();
+                let subject = variables.fill(&self.compose_subject);
                 let in_reply_to = self.compose_in_reply_to.clone();
                 let references = self.compose_references.clone();
 
@@ -377,6 +648,10 @@ impl AppModel {
                         ));
                     };
 
+                    // Headers and body go to the server as JSON (Email/set);
+                    // it does the RFC 2047 header encoding, picks the transfer
+                    // encoding and negotiates SMTPUTF8/8BITMIME on relay, so
+                    // nothing here depends on the character set.
                     let req = SendRequest {
                         identity_id: &identity.id,
                         from: &from_addr,
@@ -392,7 +667,21 @@ impl AppModel {
                     };
 
                     match submit::send(&client, &req).await {
-                        Ok(_email_id) => Message::SendComplete(Ok(())),
+                        Ok(_email_id) => {
+                            if let Some((draft_id, trash_id)) = replaced_draft {
+                                let moved = neverlight_mail_core::email::move_to(
+                                    &client,
+                                    &draft_id,
+                                    &drafts_mailbox_id,
+                                    &trash_id,
+                                )
+                                .await;
+                                if let Err(e) = moved {
+                                    log::warn!("Sent draft {} left in Drafts: {}", draft_id, e);
+                                }
+                            }
+                            Message::SendComplete(Ok(()))
+                        }
                         Err(e) => {
                             Message::SendComplete(Err(format!("Send failed: {e}")))
                         }
@@ -401,10 +690,90 @@ impl AppModel {
             }
 
             Message::ComposeCancel => {
+                return self.request_compose_close(CloseIntent::Cancel);
+            }
+
+            Message::WindowCloseRequested => {
+                self.save_window_state();
+                if std::mem::take(&mut self.folder_sync_times_dirty) {
+                    if let Err(e) = self.folder_sync_times.save() {
+                        log::warn!("Failed to save folder sync times: {}", e);
+                    }
+                }
+                return self.request_compose_close(CloseIntent::Quit);
+            }
+
+            Message::ComposeKeepEditing => {
+                if matches!(
+                    self.compose_phase,
+                    ComposePhase::ConfirmClose(_) | ComposePhase::ConfirmSend(_)
+                ) {
+                    self.compose_phase = ComposePhase::Open;
+                    self.compose_archive_after_send = None;
+                }
+            }
+
+            Message::ComposeDiscard => {
+                let ComposePhase::ConfirmClose(intent) = self.compose_phase else {
+                    return Task::none();
+                };
                 self.compose_phase = ComposePhase::Closed;
+                return close_intent_task(intent);
+            }
+
+            Message::ComposeSaveDraft => {
+                let ComposePhase::ConfirmClose(intent) = self.compose_phase else {
+                    return Task::none();
+                };
+                let account = self.accounts.get(self.compose_account);
+                let account_id = account.map(|a| a.config.id.clone()).unwrap_or_default();
+                let from = account
+                    .and_then(|a| a.config.email_addresses.get(self.compose_from))
+                    .cloned();
+                let dropped_attachments: Vec<String> = self
+                    .compose_attachments
+                    .iter()
+                    .map(|a| a.filename.clone())
+                    .collect();
+                self.local_drafts.push(LocalDraft {
+                    account_id,
+                    from,
+                    to: self.compose_to.clone(),
+                    subject: self.compose_subject.clone(),
+                    body: self.compose_body.text(),
+                    in_reply_to: self.compose_in_reply_to.clone(),
+                    references: self.compose_references.clone(),
+                    dropped_attachments,
+                    saved_at: drafts::unix_now(),
+                });
+                if let Err(e) = drafts::save_drafts(&self.local_drafts) {
+                    self.local_drafts.pop();
+                    log::error!("Failed to save draft: {}", e);
+                    self.compose_phase = ComposePhase::Open;
+                    self.compose_error = Some(format!("Failed to save draft: {e}"));
+                    return Task::none();
+                }
+                self.compose_phase = ComposePhase::Closed;
+                let dropped = self.local_drafts.last().map_or(0, |d| d.dropped_attachments.len());
+                self.status_message = match dropped {
+                    0 => "Draft saved".into(),
+                    n => format!("Draft saved without its {n} attachments"),
+                };
+                return close_intent_task(intent);
             }
 
             Message::SendComplete(Ok(())) => {
+                if let Some(acct) = self.accounts.get(self.compose_account) {
+                    let today = chrono::Local::now().date_naive();
+                    self.recipient_index
+                        .entry(acct.config.id.clone())
+                        .or_default()
+                        .record(&self.compose_to, Some(today));
+                    self.unsynced_sends
+                        .entry(acct.config.id.clone())
+                        .or_default()
+                        .push((self.compose_to.clone(), today));
+                }
                 self.compose_phase = ComposePhase::Closed;
                 self.compose_to.clear();
                 self.compose_subject.clear();
@@ -412,13 +781,27 @@ impl AppModel {
                 self.compose_in_reply_to = None;
                 self.compose_references = None;
                 self.compose_attachments.clear();
+                self.compose_server_draft = None;
+                self.compose_reply_source = None;
+                self.compose_reply_choice = None;
                 self.compose_error = None;
+                self.compose_rejected.clear();
                 self.status_message = "Message sent".into();
                 log::info!("JMAP send succeeded");
+                if let Some(original) = self.compose_archive_after_send.take() {
+                    let Some(archive) = self.role_mailbox(&original.account_id, "archive") else {
+                        let text = "Message sent, but there is no archive folder for the original";
+                        self.notify(Severity::Warning, text);
+                        return Task::none();
+                    };
+                    return self.dispatch(move_message(original, archive));
+                }
             }
 
             Message::SendComplete(Err(e)) => {
                 self.compose_phase = ComposePhase::Open;
+                self.compose_archive_after_send = None;
+                self.compose_rejected = send_rejects::rejected_recipients(&self.compose_to, &e);
                 self.compose_error = Some(format!("Send failed: {e}"));
                 log::error!("JMAP send failed: {e}");
             }
@@ -427,24 +810,147 @@ impl AppModel {
         }
         Task::none()
     }
+
+    /// Index of the compose account's address that `to` was sent to, so a
+    /// reply goes out from (and signs as) the same identity; 0 when none.
+    fn own_address_in(&self, to: &str) -> usize {
+        let Some(acct) = self.accounts.get(self.compose_account) else {
+            return 0;
+        };
+        let recipients: Vec<&str> = to.split(',').map(recipients::bare_address).collect();
+        acct.config
+            .email_addresses
+            .iter()
+            .position(|own| {
+                let own = recipients::bare_address(own);
+                recipients.iter().any(|r| r.eq_ignore_ascii_case(own))
+            })
+            .unwrap_or(0)
+    }
+
+    fn compose_snapshot(&self) -> ComposeSnapshot {
+        ComposeSnapshot {
+            to: self.compose_to.clone(),
+            subject: self.compose_subject.clone(),
+            body: self.compose_body.text(),
+            attachment_count: self.compose_attachments.len(),
+        }
+    }
+
+    /// Close compose (or quit) unless unsaved edits need a Discard / Save / Keep decision.
+    fn request_compose_close(&mut self, intent: CloseIntent) -> Task<Message> {
+        match (self.compose_phase, intent) {
+            // Escape / Cancel on a prompt backs out to editing.
+            (ComposePhase::ConfirmClose(_) | ComposePhase::ConfirmSend(_), CloseIntent::Cancel) => {
+                self.compose_phase = ComposePhase::Open;
+                return Task::none();
+            }
+            (ComposePhase::ConfirmClose(_), CloseIntent::Quit) => {
+                self.compose_phase = ComposePhase::ConfirmClose(CloseIntent::Quit);
+                return Task::none();
+            }
+            // A send still in flight is lost on quit unless it's saved.
+            (ComposePhase::Open | ComposePhase::ConfirmSend(_) | ComposePhase::Sending, _)
+                if drafts::compose_is_dirty(&self.compose_baseline, &self.compose_snapshot()) =>
+            {
+                self.compose_phase = ComposePhase::ConfirmClose(intent);
+                return Task::none();
+            }
+            _ => {}
+        }
+        self.compose_phase = ComposePhase::Closed;
+        close_intent_task(intent)
+    }
 }
 
-fn quote_body(body: &str, from: &str, date: &str) -> String {
-    let mut out = format!("On {date}, {from} wrote:\n");
+fn close_intent_task(intent: CloseIntent) -> Task<Message> {
+    match intent {
+        CloseIntent::Cancel => Task::none(),
+        CloseIntent::Quit => cosmic::iced::exit(),
+    }
+}
+
+fn quote_body(body: &str, from: &str, date: &str, style: &QuoteSettings) -> String {
+    let header = style.reply_header.replace("{date}", date).replace("{from}", from);
+    let mut out = format!("{header}\n");
     for line in body.lines() {
-        out.push_str("> ");
+        out.push_str(&style.quote_marker);
         out.push_str(line);
         out.push('\n');
     }
     out
 }
 
-fn forward_body(body: &str, from: &str, date: &str, subject: &str) -> String {
-    let mut out = String::from("---------- Forwarded message ----------\n");
+/// Full reply editor text and the line the cursor should start on; `sig` is
+/// the signature block for the From address.
+fn reply_body(
+    body: &str,
+    from: &str,
+    date: &str,
+    sig: Option<&str>,
+    style: &QuoteSettings,
+) -> (String, usize) {
+    let quoted = quote_body(body, from, date, style);
+    let (sig_above, sig_below) = match style.signature_placement {
+        SignaturePlacement::AboveQuote => (sig, None),
+        SignaturePlacement::BelowQuote => (None, sig),
+    };
+    let mut out = String::new();
+    let cursor_line = match style.posting {
+        PostingStyle::Top => {
+            out.push_str("\n\n");
+            if let Some(sig) = sig_above {
+                out.push_str(sig);
+                out.push('\n');
+            }
+            out.push_str(&quoted);
+            0
+        }
+        PostingStyle::Bottom => {
+            if let Some(sig) = sig_above {
+                out.push_str(sig);
+                out.push('\n');
+            }
+            out.push_str(&quoted);
+            out.push('\n');
+            let cursor_line = out.matches('\n').count();
+            out.push('\n');
+            cursor_line
+        }
+    };
+    if let Some(sig) = sig_below {
+        out.push('\n');
+        out.push_str(sig);
+    }
+    (out, cursor_line)
+}
+
+fn forward_body(
+    body: &str,
+    from: &str,
+    date: &str,
+    subject: &str,
+    sig: Option<&str>,
+    style: &QuoteSettings,
+) -> String {
+    let mut out = String::from("\n\n");
+    if let (Some(sig), SignaturePlacement::AboveQuote) = (sig, style.signature_placement) {
+        out.push_str(sig);
+        out.push('\n');
+    }
+    out.push_str(&style.forward_header);
+    out.push('\n');
     out.push_str(&format!("From: {from}\n"));
     out.push_str(&format!("Date: {date}\n"));
     out.push_str(&format!("Subject: {subject}\n\n"));
     out.push_str(body);
+    if let (Some(sig), SignaturePlacement::BelowQuote) = (sig, style.signature_placement) {
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push('\n');
+        out.push_str(sig);
+    }
     out
 }
 
@@ -471,8 +977,16 @@ fn parse_uri_list(uri_list: &str) -> Vec<String> {
         .collect()
 }
 
+/// Pasted text that is nothing but file:// URIs (files copied in a file
+/// manager), as paths.
+fn pasted_file_paths(text: &str) -> Option<Vec<String>> {
+    let lines = text.lines().filter(|l| !l.trim().is_empty()).count();
+    let paths = parse_uri_list(text);
+    (lines > 0 && paths.len() == lines).then_some(paths)
+}
+
 /// Read a list of file paths into AttachmentData.
-async fn read_paths_as_attachments(paths: Vec<String>) -> Message {
+pub(super) async fn read_paths_as_attachments(paths: Vec<String>) -> Message {
     let mut attachments = Vec::new();
     for p in &paths {
         let path = std::path::Path::new(p);
@@ -531,6 +1045,18 @@ mod tests {
         assert_eq!(paths, vec!["/home/user/my documents/report.pdf"]);
     }
 
+    #[test]
+    fn only_pure_file_uri_pastes_become_attachments() {
+        assert_eq!(
+            pasted_file_paths("file:///tmp/a.png\nfile:///tmp/b.pdf\n"),
+            Some(vec!["/tmp/a.png".to_string(), "/tmp/b.pdf".to_string()])
+        );
+        assert_eq!(pasted_file_paths("see file:///tmp/a.png"), None);
+        assert_eq!(pasted_file_paths("file:///tmp/a.png\nand some text"), None);
+        assert_eq!(pasted_file_paths("plain text"), None);
+        assert_eq!(pasted_file_paths(""), None);
+    }
+
     #[test]
     fn parse_uri_list_empty_input() {
         assert!(parse_uri_list("").is_empty());
@@ -571,4 +1097,63 @@ mod tests {
             "application/octet-stream"
         );
     }
+
+    fn sample_style() -> QuoteSettings {
+        QuoteSettings {
+            signature: "Sam\n".into(),
+            ..QuoteSettings::default()
+        }
+    }
+
+    fn sample_sig() -> Option<String> {
+        signature_block(&sample_style().signature)
+    }
+
+    #[test]
+    fn default_reply_matches_classic_layout() {
+        let (text, cursor) =
+            reply_body("hi\nthere", "a@x.example", "Mon", None, &QuoteSettings::default());
+        assert_eq!(text, "\n\nOn Mon, a@x.example wrote:\n> hi\n> there\n");
+        assert_eq!(cursor, 0);
+    }
+
+    #[test]
+    fn custom_header_and_marker() {
+        let style = QuoteSettings {
+            reply_header: "{from} ({date}):".into(),
+            quote_marker: "| ".into(),
+            ..QuoteSettings::default()
+        };
+        assert_eq!(quote_body("hi", "a", "Mon", &style), "a (Mon):\n| hi\n");
+    }
+
+    #[test]
+    fn top_posting_puts_signature_above_quote() {
+        let (text, _) = reply_body("hi", "a", "Mon", sample_sig().as_deref(), &sample_style());
+        assert_eq!(text, "\n\n-- \nSam\n\nOn Mon, a wrote:\n> hi\n");
+    }
+
+    #[test]
+    fn bottom_posting_starts_cursor_below_quote() {
+        let style = QuoteSettings {
+            posting: PostingStyle::Bottom,
+            signature_placement: SignaturePlacement::BelowQuote,
+            ..sample_style()
+        };
+        let (text, cursor) = reply_body("hi", "a", "Mon", sample_sig().as_deref(), &style);
+        assert_eq!(text, "On Mon, a wrote:\n> hi\n\n\n\n-- \nSam\n");
+        assert_eq!(text.lines().nth(cursor), Some(""));
+        assert_eq!(cursor, 3);
+    }
+
+    #[test]
+    fn forward_signature_below_follows_body() {
+        let style = QuoteSettings {
+            signature_placement: SignaturePlacement::BelowQuote,
+            ..sample_style()
+        };
+        let fwd = forward_body("body", "a", "Mon", "Hi", sample_sig().as_deref(), &style);
+        assert!(fwd.starts_with("\n\n---------- Forwarded message ----------\n"));
+        assert!(fwd.ends_with("body\n\n-- \nSam\n"));
+    }
 }
@@ -0,0 +1,319 @@
+//! `neverlight-mail compose --template report.md --to boss@example.com`: open the
+//! GUI with compose prefilled, for scripted mail that still gets a human
+//! look before it goes out.
+//!
+//! A template is Markdown with optional YAML-style front matter:
+//!
+//! ```text
+//! ---
+//! to: alice@example.com, bob@example.com
+//! subject: Weekly report
+//! attachments:
+//!   - report.pdf
+//! ---
+//! Body text...
+//! ```
+//!
+//! Only flat `key: value`, `[a, b]` and `- item` lists are understood; no
+//! YAML parser is pulled in for four keys. `--template -` reads stdin.
+//! Relative attachment paths resolve against the template's directory.
+//! `{first_name}` and the other variables in `template_vars` are left in
+//! place here and filled at send time; compose opened this way is the only
+//! one that fills them.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use cosmic::app::Task;
+
+use super::{AppModel, Message};
+
+use crate::ui::compose_dialog::ComposeMode;
+
+pub const USAGE: &str = "\
+Usage: neverlight-mail [compose [OPTIONS] | sync]
+
+compose opens the compose window prefilled; nothing is sent until you click Send.
+  --template FILE   Markdown body with optional front matter (to, cc, subject,
+                    attachments); '-' reads stdin
+  --to ADDR         Recipient (repeatable, or comma-separated)
+  --cc ADDR         Cc recipient (repeatable)
+  --subject TEXT    Subject line
+  --attach FILE     Attachment (repeatable)
+
+sync runs one sync pass without a window (for a systemd timer): refreshes
+folders and each Inbox in the cache and notifies about new mail.
+
+  -h, --help        Show this help";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComposeTemplate {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub attachments: Vec<PathBuf>,
+    pub body: String,
+}
+
+/// What the command line asked for; handed to `init` for the window modes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LaunchOptions {
+    #[default]
+    Window,
+    Compose(ComposeTemplate),
+    /// `sync`: headless, no window (see `headless_sync`).
+    Sync,
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .unwrap_or(value);
+    value
+        .split(',')
+        .map(unquote)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    ['"', '\'']
+        .iter()
+        .find_map(|&q| value.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+        .unwrap_or(value)
+}
+
+impl ComposeTemplate {
+    /// Parse a template; relative attachment paths are joined onto `base`.
+    pub fn parse(text: &str, base: &Path) -> Result<Self, String> {
+        let text = text.trim_start_matches('\u{feff}');
+        let mut template = Self::default();
+        let Some(rest) = text
+            .strip_prefix("---\n")
+            .or_else(|| text.strip_prefix("---\r\n"))
+        else {
+            template.body = text.to_string();
+            return Ok(template);
+        };
+
+        let mut lines = rest.split_inclusive('\n');
+        let mut list_key: Option<String> = None;
+        let mut consumed = 0;
+        let mut closed = false;
+        for line in lines.by_ref() {
+            consumed += line.len();
+            let trimmed = line.trim_end();
+            if trimmed == "---" || trimmed == "..." {
+                closed = true;
+                break;
+            }
+            if trimmed.trim().is_empty() || trimmed.trim_start().starts_with('#') {
+                continue;
+            }
+            if let Some(item) = trimmed.trim_start().strip_prefix("- ") {
+                let Some(key) = &list_key else {
+                    return Err(format!("List item without a key: {trimmed}"));
+                };
+                template.set(key, unquote(item), base)?;
+                continue;
+            }
+            let Some((key, value)) = trimmed.split_once(':') else {
+                return Err(format!("Expected `key: value` in front matter: {trimmed}"));
+            };
+            let key = key.trim().to_lowercase();
+            if value.trim().is_empty() {
+                list_key = Some(key);
+            } else {
+                list_key = None;
+                template.set(&key, value, base)?;
+            }
+        }
+        if !closed {
+            return Err("Front matter has no closing `---`".into());
+        }
+        template.body = rest[consumed..]
+            .trim_start_matches(['\r', '\n'])
+            .to_string();
+        Ok(template)
+    }
+
+    fn set(&mut self, key: &str, value: &str, base: &Path) -> Result<(), String> {
+        match key {
+            "to" => self.to.extend(split_list(value)),
+            "cc" => self.cc.extend(split_list(value)),
+            "subject" => self.subject = unquote(value).to_string(),
+            "attachments" | "attachment" => self
+                .attachments
+                .extend(split_list(value).into_iter().map(|p| base.join(p))),
+            other => return Err(format!("Unknown front matter key `{other}`")),
+        }
+        Ok(())
+    }
+}
+
+impl LaunchOptions {
+    /// Parse arguments after the program name. `--help` is handled by the
+    /// caller.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        match args.next().as_deref() {
+            None => return Ok(Self::default()),
+            Some("compose") => {}
+            Some("sync") => {
+                return match args.next() {
+                    None => Ok(Self::Sync),
+                    Some(other) => Err(format!("sync takes no options (got `{other}`)")),
+                };
+            }
+            Some(other) => return Err(format!("Unknown command `{other}`")),
+        }
+
+        let mut template = ComposeTemplate::default();
+        let mut extra = ComposeTemplate::default();
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{flag} needs a value"));
+            match flag.as_str() {
+                "--template" => template = read_template(&value()?)?,
+                "--to" => extra.to.extend(split_list(&value()?)),
+                "--cc" => extra.cc.extend(split_list(&value()?)),
+                "--subject" => extra.subject = value()?,
+                "--attach" => extra.attachments.push(PathBuf::from(value()?)),
+                other => return Err(format!("Unknown option `{other}`")),
+            }
+        }
+
+        // Flags add to the template; a --subject replaces its subject.
+        template.to.extend(extra.to);
+        template.cc.extend(extra.cc);
+        template.attachments.extend(extra.attachments);
+        if !extra.subject.is_empty() {
+            template.subject = extra.subject;
+        }
+        Ok(Self::Compose(template))
+    }
+}
+
+fn read_template(path: &str) -> Result<ComposeTemplate, String> {
+    if path == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Reading template from stdin: {e}"))?;
+        return ComposeTemplate::parse(&text, Path::new("."));
+    }
+    let path = Path::new(path);
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let base = path.parent().unwrap_or(Path::new("."));
+    ComposeTemplate::parse(&text, base).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+impl AppModel {
+    /// Open compose filled from a template (launch with `compose`).
+    pub(super) fn open_compose_template(&mut self, template: ComposeTemplate) -> Task<Message> {
+        let _ = self.handle_compose(Message::ComposeNew);
+        if !self.compose_phase.is_open() {
+            self.status_message =
+                "Finish account setup, then run `neverlight-mail compose` again".into();
+            return Task::none();
+        }
+        self.compose_mode = ComposeMode::Template;
+        // The baseline stays empty, so closing without sending asks first.
+        self.compose_to = template.to.join(", ");
+        self.compose_subject = template.subject;
+        let body = match self.compose_body.text().trim() {
+            "" => template.body,
+            sig => format!("{}\n\n{sig}\n", template.body.trim_end()),
+        };
+        self.compose_body = cosmic::widget::text_editor::Content::with_text(&body);
+        if !template.cc.is_empty() {
+            self.compose_error = Some(format!(
+                "Compose has no Cc line yet; add these to To if needed: {}",
+                template.cc.join(", ")
+            ));
+        }
+        self.refresh_compose_cache();
+
+        if template.attachments.is_empty() {
+            return Task::none();
+        }
+        let paths = template
+            .attachments
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        cosmic::task::future(super::compose::read_paths_as_attachments(paths))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn front_matter_fills_fields() {
+        let text = "---\nto: a@example.com, \"b@example.com\"\ncc: [c@example.com]\n\
+                    subject: 'Weekly report'\nattachments:\n  - report.pdf\n  - /tmp/q3.png\n---\n\n\
+                    Numbers are up.\n";
+        let t = ComposeTemplate::parse(text, Path::new("/home/me/reports")).unwrap();
+        assert_eq!(t.to, ["a@example.com", "b@example.com"]);
+        assert_eq!(t.cc, ["c@example.com"]);
+        assert_eq!(t.subject, "Weekly report");
+        assert_eq!(
+            t.attachments,
+            [
+                PathBuf::from("/home/me/reports/report.pdf"),
+                PathBuf::from("/tmp/q3.png")
+            ]
+        );
+        assert_eq!(t.body, "Numbers are up.\n");
+    }
+
+    #[test]
+    fn plain_markdown_is_all_body() {
+        let t = ComposeTemplate::parse("# Hi\n\n---\nnot front matter", Path::new(".")).unwrap();
+        assert_eq!(t.body, "# Hi\n\n---\nnot front matter");
+        assert!(t.to.is_empty());
+    }
+
+    #[test]
+    fn bad_front_matter_is_reported() {
+        assert!(ComposeTemplate::parse("---\nto: a@example.com\n", Path::new(".")).is_err());
+        assert!(ComposeTemplate::parse("---\nbcc: x@example.com\n---\n", Path::new(".")).is_err());
+    }
+
+    #[test]
+    fn flags_add_to_nothing_or_a_template() {
+        assert_eq!(
+            LaunchOptions::parse(args(&[])).unwrap(),
+            LaunchOptions::default()
+        );
+        let opts = LaunchOptions::parse(args(&[
+            "compose",
+            "--to",
+            "a@example.com,b@example.com",
+            "--subject",
+            "Hi",
+            "--attach",
+            "x.pdf",
+        ]))
+        .unwrap();
+        let LaunchOptions::Compose(t) = opts else {
+            panic!("expected compose, got {opts:?}");
+        };
+        assert_eq!(t.to, ["a@example.com", "b@example.com"]);
+        assert_eq!(t.subject, "Hi");
+        assert_eq!(t.attachments, [PathBuf::from("x.pdf")]);
+        assert!(LaunchOptions::parse(args(&["compose", "--to"])).is_err());
+        assert!(LaunchOptions::parse(args(&["send"])).is_err());
+        assert_eq!(LaunchOptions::parse(args(&["sync"])).unwrap(), LaunchOptions::Sync);
+        assert!(LaunchOptions::parse(args(&["sync", "--all"])).is_err());
+    }
+}
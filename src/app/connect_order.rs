@@ -0,0 +1,104 @@
+//! Startup connect order, so several accounts don't all discover sessions
+//! and sync folders at once.
+//!
+//! The account used last connects first and its Inbox opens from the cache;
+//! the others follow one at a time, `CONNECT_STAGGER` apart, each waiting
+//! further while a sync is still in flight. Cached folders and messages load
+//! for every account straight away, alongside the first paint. The last-used
+//! account is kept in `last-account.json` next to the thread overrides.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cosmic::app::Task;
+use neverlight_mail_core::config::AccountId;
+
+use super::json_store::{load_json, save_json};
+use super::{AppModel, ConnectionState, Message};
+
+/// Gap between one account's connect and the next.
+const CONNECT_STAGGER: Duration = Duration::from_secs(3);
+
+/// Indices of `account_ids` in connect order: `last` first, the rest in
+/// config order.
+pub(super) fn connect_order(account_ids: &[&str], last: Option<&str>) -> Vec<usize> {
+    let first = last.and_then(|last| account_ids.iter().position(|id| *id == last));
+    first
+        .into_iter()
+        .chain((0..account_ids.len()).filter(|i| Some(*i) != first))
+        .collect()
+}
+
+/// Delay before the `position`th account in connect order connects.
+pub(super) fn connect_delay(position: usize) -> Duration {
+    CONNECT_STAGGER * position as u32
+}
+
+pub(super) fn load_last_account() -> Option<AccountId> {
+    load_json(last_account_path())
+}
+
+fn save_last_account(account_id: &str) -> Result<(), String> {
+    save_json(last_account_path(), account_id)
+}
+
+fn last_account_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("neverlight-mail").join("last-account.json"))
+}
+
+impl AppModel {
+    /// Connect `account_id` once `delay` has passed.
+    pub(super) fn deferred_connect(account_id: AccountId, delay: Duration) -> Task<Message> {
+        cosmic::task::future(async move {
+            tokio::time::sleep(delay).await;
+            Message::DeferredConnect(account_id)
+        })
+    }
+
+    /// A staggered connect came due: start it, or wait another stagger
+    /// while a sync is in flight. Accounts paused, connected or reconnected
+    /// in the meantime are left alone.
+    pub(super) fn handle_deferred_connect(&mut self, account_id: AccountId) -> Task<Message> {
+        let Some(idx) = self.account_index(&account_id) else {
+            return Task::none();
+        };
+        let acct = &self.accounts[idx];
+        if acct.paused || acct.client.is_some() || acct.conn_state != ConnectionState::Connecting {
+            return Task::none();
+        }
+        if self.refresh_phase.is_in_flight() {
+            return Self::deferred_connect(account_id, CONNECT_STAGGER);
+        }
+        super::connect_account(acct.config.clone(), account_id)
+    }
+
+    /// Remember the account on screen for the next launch's connect order.
+    pub(super) fn remember_last_account(&mut self) {
+        let Some(acct) = self.active_account.and_then(|i| self.accounts.get(i)) else {
+            return;
+        };
+        if self.last_account.as_deref() == Some(acct.config.id.as_str()) {
+            return;
+        }
+        let id = acct.config.id.clone();
+        if let Err(e) = save_last_account(&id) {
+            log::warn!("Failed to save last account: {}", e);
+        }
+        self.last_account = Some(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_used_account_connects_first() {
+        let ids = ["a", "b", "c"];
+        assert_eq!(connect_order(&ids, Some("c")), [2, 0, 1]);
+        assert_eq!(connect_order(&ids, Some("gone")), [0, 1, 2]);
+        assert_eq!(connect_order(&ids, None), [0, 1, 2]);
+        assert_eq!(connect_delay(0), Duration::ZERO);
+        assert_eq!(connect_delay(2), CONNECT_STAGGER * 2);
+    }
+}
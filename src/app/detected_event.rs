@@ -0,0 +1,351 @@
+//! "Detected event" banner: spot a day + time in a message body
+//! ("meeting Tuesday 3pm", "call on March 5 at 14:30") and offer an .ics.
+//!
+//! Deliberately conservative — a line must name both a day and a clock time,
+//! and quoted reply text is ignored, so ordinary mail rarely trips it.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use cosmic::app::Task;
+
+use super::{AppModel, Message};
+
+/// Length of the generated calendar entry.
+const EVENT_LENGTH_MINUTES: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedEvent {
+    /// Message the event was found in (the banner only shows for it).
+    pub email_id: String,
+    pub start: NaiveDateTime,
+    /// Words from the body that produced the match, for the banner text.
+    pub phrase: String,
+}
+
+impl DetectedEvent {
+    /// "Tue, Oct 20 at 15:00".
+    pub fn when_label(&self) -> String {
+        self.start.format("%a, %b %-d at %H:%M").to_string()
+    }
+}
+
+fn weekday_from(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "monday" | "mon" => Weekday::Mon,
+        "tuesday" | "tue" | "tues" => Weekday::Tue,
+        "wednesday" | "wed" => Weekday::Wed,
+        "thursday" | "thu" | "thur" | "thurs" => Weekday::Thu,
+        "friday" | "fri" => Weekday::Fri,
+        "saturday" | "sat" => Weekday::Sat,
+        "sunday" | "sun" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn month_from(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    if word.len() < 3 {
+        return None;
+    }
+    let idx = MONTHS.iter().position(|m| word.starts_with(m))?;
+    // "march"/"mar" but not "mark"; "may" is its own full name.
+    const FULL: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june", "july", "august",
+        "september", "october", "november", "december",
+    ];
+    (word == MONTHS[idx] || word == FULL[idx] || (idx == 8 && word == "sept"))
+        .then_some(idx as u32 + 1)
+}
+
+/// Day of month from "5", "5th", "21st".
+fn day_of_month(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|d| (1..=31).contains(d))
+}
+
+/// Clock time from "3pm", "3:30pm", "15:00", or "3" followed by "pm".
+fn clock_time(word: &str, next: Option<&str>) -> Option<(NaiveTime, bool)> {
+    if word == "noon" {
+        return Some((NaiveTime::from_hms_opt(12, 0, 0)?, false));
+    }
+    let (num, meridiem, used_next) = if let Some(n) = word.strip_suffix("am") {
+        (n, Some(false), false)
+    } else if let Some(n) = word.strip_suffix("pm") {
+        (n, Some(true), false)
+    } else {
+        match next {
+            Some("am" | "a.m") => (word, Some(false), true),
+            Some("pm" | "p.m") => (word, Some(true), true),
+            _ => (word, None, false),
+        }
+    };
+    let (hour, minute) = match num.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm attached.
+        None if meridiem.is_some() => (num.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    Some((NaiveTime::from_hms_opt(hour, minute, 0)?, used_next))
+}
+
+/// Next date falling on `weekday`, counting today.
+fn upcoming(today: NaiveDate, weekday: Weekday, skip_week: bool) -> NaiveDate {
+    let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    let ahead = if skip_week && ahead == 0 { 7 } else { ahead };
+    today + Duration::days(i64::from(ahead))
+}
+
+/// Month/day without a year: this year, or next year if it already passed.
+fn month_day(today: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if this_year >= today {
+        Some(this_year)
+    } else {
+        NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+    }
+}
+
+/// Find the first line naming both a day and a time.
+pub fn detect(email_id: &str, body: &str, today: NaiveDate) -> Option<DetectedEvent> {
+    body.lines()
+        .filter(|line| !line.trim_start().starts_with('>'))
+        .find_map(|line| detect_in_line(line, today))
+        .map(|(start, phrase)| DetectedEvent {
+            email_id: email_id.to_string(),
+            start,
+            phrase,
+        })
+}
+
+fn detect_in_line(line: &str, today: NaiveDate) -> Option<(NaiveDateTime, String)> {
+    let raw: Vec<&str> = line.split_whitespace().collect();
+    let words: Vec<String> = raw
+        .iter()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != ':' && c != '-')
+                .trim_end_matches(':')
+                .to_ascii_lowercase()
+        })
+        .collect();
+
+    let mut date: Option<(NaiveDate, usize, usize)> = None;
+    let mut time: Option<(NaiveTime, usize, usize)> = None;
+
+    for (i, word) in words.iter().enumerate() {
+        let next = words.get(i + 1).map(String::as_str);
+        if date.is_none() {
+            let prev_next = i > 0 && words[i - 1] == "next";
+            let found = match word.as_str() {
+                "today" | "tonight" => Some((today, i, i)),
+                "tomorrow" => Some((today + Duration::days(1), i, i)),
+                w => {
+                    if let Some(wd) = weekday_from(w) {
+                        let start = if prev_next { i - 1 } else { i };
+                        Some((upcoming(today, wd, prev_next), start, i))
+                    } else if let Ok(d) = NaiveDate::parse_from_str(w, "%Y-%m-%d") {
+                        Some((d, i, i))
+                    } else if let Some(month) = month_from(w) {
+                        next.and_then(day_of_month)
+                            .and_then(|day| month_day(today, month, day))
+                            .map(|d| (d, i, i + 1))
+                    } else if let Some(day) = day_of_month(w) {
+                        next.and_then(month_from)
+                            .and_then(|month| month_day(today, month, day))
+                            .map(|d| (d, i, i + 1))
+                    } else {
+                        None
+                    }
+                }
+            };
+            if found.is_some() {
+                date = found;
+                continue;
+            }
+        }
+        if time.is_none() {
+            if let Some((t, used_next)) = clock_time(word, next) {
+                time = Some((t, i, if used_next { i + 1 } else { i }));
+            }
+        }
+        if date.is_some() && time.is_some() {
+            break;
+        }
+    }
+
+    let (day, d_start, d_end) = date?;
+    let (clock, t_start, t_end) = time?;
+    let start = d_start.min(t_start);
+    let end = d_end.max(t_end).min(raw.len() - 1);
+    let phrase = raw[start..=end].join(" ");
+    Some((day.and_time(clock), phrase))
+}
+
+/// Escape text for an iCalendar TEXT value (RFC 5545 §3.3.11).
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+/// Single-event calendar file. Times are floating (local wall clock), which
+/// is what "Tuesday 3pm" in a message means.
+pub fn build_ics(event: &DetectedEvent, summary: &str, description: &str, stamp: NaiveDateTime) -> String {
+    let fmt = "%Y%m%dT%H%M%S";
+    let end = event.start + Duration::minutes(EVENT_LENGTH_MINUTES);
+    [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Neverlight Mail//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@neverlight-mail", event.email_id),
+        format!("DTSTAMP:{}Z", stamp.format(fmt)),
+        format!("DTSTART:{}", event.start.format(fmt)),
+        format!("DTEND:{}", end.format(fmt)),
+        format!("SUMMARY:{}", ics_escape(summary)),
+        format!("DESCRIPTION:{}", ics_escape(description)),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ]
+    .join("\r\n")
+        + "\r\n"
+}
+
+impl AppModel {
+    pub(super) fn handle_detected_event(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::AddDetectedEventToCalendar => {
+                let Some(event) = self.detected_event.clone() else {
+                    return Task::none();
+                };
+                let (summary, description) = self
+                    .messages
+                    .iter()
+                    .find(|m| m.email_id == event.email_id)
+                    .map(|m| (m.subject.clone(), format!("From: {}\n{}", m.from, event.phrase)))
+                    .unwrap_or_else(|| ("Event".into(), event.phrase.clone()));
+                let ics = build_ics(&event, &summary, &description, chrono::Utc::now().naive_utc());
+                return cosmic::task::future(async move {
+                    let dir = std::env::temp_dir().join("neverlight-mail");
+                    let path = dir.join(format!("event-{}.ics", event.email_id));
+                    let result = match tokio::fs::create_dir_all(&dir).await {
+                        Ok(()) => tokio::fs::write(&path, ics)
+                            .await
+                            .map(|()| path.display().to_string())
+                            .map_err(|e| format!("Calendar export failed: {e}")),
+                        Err(e) => Err(format!("Calendar export failed: {e}")),
+                    };
+                    Message::CalendarFileReady(result)
+                });
+            }
+            Message::CalendarFileReady(Ok(path)) => {
+                // The desktop's default handler for text/calendar takes it from here.
+                neverlight_mail_core::mime::open_link(&format!("file://{path}"));
+                self.detected_event = None;
+                self.status_message = "Sent event to calendar".into();
+            }
+            Message::CalendarFileReady(Err(e)) => {
+                log::error!("{}", e);
+                self.status_message = e;
+            }
+            Message::DismissDetectedEvent => {
+                self.detected_event = None;
+            }
+            Message::ToggleEventDetection => {
+                self.settings.detect_events = !self.settings.detect_events;
+                if !self.settings.detect_events {
+                    self.detected_event = None;
+                }
+                if let Err(e) = self.settings.save() {
+                    log::warn!("Failed to save settings: {}", e);
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Friday, 16 October 2026.
+    fn sample_today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    fn start_of(body: &str) -> Option<NaiveDateTime> {
+        detect("e1", body, sample_today()).map(|e| e.start)
+    }
+
+    #[test]
+    fn weekday_and_pm_time() {
+        let event = detect("e1", "Can we do the meeting Tuesday 3pm?", sample_today()).unwrap();
+        assert_eq!(event.start, at(2026, 10, 20, 15, 0));
+        assert_eq!(event.phrase, "Tuesday 3pm?");
+    }
+
+    #[test]
+    fn next_weekday_on_same_day_skips_a_week() {
+        assert_eq!(start_of("friday at 9am"), Some(at(2026, 10, 16, 9, 0)));
+        assert_eq!(start_of("next Friday at 9am"), Some(at(2026, 10, 23, 9, 0)));
+    }
+
+    #[test]
+    fn month_day_and_24h_time() {
+        assert_eq!(start_of("Call on March 5th at 14:30."), Some(at(2027, 3, 5, 14, 30)));
+        assert_eq!(start_of("Dinner 31 Oct, 7:15 pm"), Some(at(2026, 10, 31, 19, 15)));
+    }
+
+    #[test]
+    fn iso_date_and_tomorrow() {
+        assert_eq!(start_of("Deploy 2026-11-02 08:00"), Some(at(2026, 11, 2, 8, 0)));
+        assert_eq!(start_of("tomorrow at noon"), Some(at(2026, 10, 17, 12, 0)));
+    }
+
+    #[test]
+    fn needs_both_day_and_time_on_one_line() {
+        assert_eq!(start_of("See you Tuesday.\nI'm free at 3pm."), None);
+        assert_eq!(start_of("We shipped 3 builds on Monday"), None);
+        assert_eq!(start_of("Mark said 5pm works"), None);
+    }
+
+    #[test]
+    fn quoted_reply_lines_are_ignored() {
+        assert_eq!(start_of("> meeting Tuesday 3pm\nSounds good"), None);
+    }
+
+    #[test]
+    fn ics_has_escaped_summary_and_one_hour_span() {
+        let event = DetectedEvent {
+            email_id: "e1".into(),
+            start: at(2026, 10, 20, 15, 0),
+            phrase: "Tuesday 3pm".into(),
+        };
+        let ics = build_ics(&event, "Sync; agenda, notes", "From: a@example.com", at(2026, 10, 16, 12, 0));
+        assert!(ics.contains("DTSTART:20261020T150000\r\n"));
+        assert!(ics.contains("DTEND:20261020T160000\r\n"));
+        assert!(ics.contains("SUMMARY:Sync\\; agenda\\, notes\r\n"));
+        assert!(ics.contains("UID:e1@neverlight-mail\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
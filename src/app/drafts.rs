@@ -0,0 +1,130 @@
+//! Local compose drafts — saved when the user closes a dirty compose dialog.
+//!
+//! Drafts live in a small JSON file under the XDG data dir. They are local
+//! only. Attachments are not persisted: only their names are kept, so the
+//! resumed draft can say what to attach again.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use neverlight_mail_core::config::AccountId;
+
+use super::json_store::{load_json, save_json};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalDraft {
+    pub account_id: AccountId,
+    /// From address picked in compose; drafts saved before it was kept
+    /// resume with the account's first address.
+    #[serde(default)]
+    pub from: Option<String>,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    #[serde(default)]
+    pub in_reply_to: Option<String>,
+    #[serde(default)]
+    pub references: Option<String>,
+    /// Names of attachments that weren't saved with the draft.
+    #[serde(default)]
+    pub dropped_attachments: Vec<String>,
+    /// Unix timestamp (seconds) of the last save.
+    pub saved_at: u64,
+}
+
+/// The compose fields that decide whether closing the dialog loses work.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComposeSnapshot {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub attachment_count: usize,
+}
+
+/// Compose is dirty when anything differs from the snapshot taken at open.
+pub fn compose_is_dirty(baseline: &ComposeSnapshot, current: &ComposeSnapshot) -> bool {
+    baseline != current
+}
+
+fn drafts_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("neverlight-mail").join("drafts.json"))
+}
+
+pub fn load_drafts() -> Vec<LocalDraft> {
+    load_json(drafts_path()).unwrap_or_default()
+}
+
+pub fn save_drafts(drafts: &[LocalDraft]) -> Result<(), String> {
+    save_json(drafts_path(), drafts)
+}
+
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_snapshot() -> ComposeSnapshot {
+        ComposeSnapshot {
+            to: "alice@example.com".into(),
+            subject: "Re: lunch".into(),
+            body: "\n\n> see you at noon\n".into(),
+            attachment_count: 0,
+        }
+    }
+
+    #[test]
+    fn untouched_compose_is_clean() {
+        let baseline = sample_snapshot();
+        assert!(!compose_is_dirty(&baseline, &sample_snapshot()));
+    }
+
+    #[test]
+    fn edited_body_is_dirty() {
+        let baseline = sample_snapshot();
+        let mut current = sample_snapshot();
+        current.body.insert_str(0, "Sounds good");
+        assert!(compose_is_dirty(&baseline, &current));
+    }
+
+    #[test]
+    fn added_attachment_is_dirty() {
+        let baseline = sample_snapshot();
+        let mut current = sample_snapshot();
+        current.attachment_count = 1;
+        assert!(compose_is_dirty(&baseline, &current));
+    }
+
+    #[test]
+    fn draft_roundtrips_through_json() {
+        let draft = LocalDraft {
+            account_id: "acct-1".into(),
+            from: Some("me+work@example.com".into()),
+            to: "bob@example.com".into(),
+            subject: "Plans".into(),
+            body: "Draft body".into(),
+            in_reply_to: Some("<m1@example.com>".into()),
+            references: None,
+            dropped_attachments: vec!["plan.pdf".into()],
+            saved_at: 1_700_000_000,
+        };
+        let json = serde_json::to_string(&draft).unwrap();
+        let parsed: LocalDraft = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, draft);
+    }
+
+    #[test]
+    fn drafts_saved_before_from_was_kept_still_load() {
+        let json = r#"{"account_id":"acct-1","to":"bob@example.com","subject":"Plans",
+            "body":"Draft body","saved_at":1700000000}"#;
+        let parsed: LocalDraft = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.from, None);
+        assert!(parsed.dropped_attachments.is_empty());
+    }
+}
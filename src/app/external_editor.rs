@@ -0,0 +1,200 @@
+//! "Edit in external editor" for the compose body (Ctrl+E in compose).
+//!
+//! The body is written to a temp file and the editor is run on it; when the
+//! process exits the file is read back into compose. The file goes in
+//! `$XDG_RUNTIME_DIR` (only the user can enter it) when there is one, under
+//! an unguessable name, and is created fresh with mode 0600: an existing
+//! file or a planted symlink at the name fails the edit instead of being
+//! written through. `"external_editor"` in
+//! settings.json names the command (the path is appended), otherwise
+//! `$VISUAL` or `$EDITOR` runs in a terminal through `xdg-terminal-exec`,
+//! since a window-less app has no terminal of its own. GUI editors have to
+//! stay in the foreground (`gvim -f`, `code --wait`) or the edit ends at
+//! once.
+//!
+//! If the compose body changed or compose closed while the editor was open,
+//! nothing is overwritten: the file is kept and its path reported.
+
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+
+use cosmic::app::Task;
+use cosmic::widget::text_editor;
+
+use super::{AppModel, ComposePhase, Message};
+
+/// Runs terminal editors from `$VISUAL`/`$EDITOR`.
+const TERMINAL_LAUNCHER: &str = "xdg-terminal-exec";
+
+/// An editor session in progress.
+#[derive(Debug, Clone)]
+pub struct ExternalEdit {
+    pub path: PathBuf,
+    /// Body as written to the file; compose must still hold it to take the
+    /// edited text back.
+    pub sent: String,
+}
+
+/// Program and arguments to run (the file path still to be appended).
+/// A configured command wins; otherwise the environment's editor in a
+/// terminal.
+fn editor_command(configured: Option<&str>, env_editor: Option<&str>) -> Option<Vec<String>> {
+    let words = |command: &str| -> Vec<String> {
+        command.split_whitespace().map(str::to_string).collect()
+    };
+    if let Some(command) = configured.filter(|c| !c.trim().is_empty()) {
+        return Some(words(command));
+    }
+    let editor = env_editor.filter(|e| !e.trim().is_empty())?;
+    let mut command = vec![TERMINAL_LAUNCHER.to_string()];
+    command.extend(words(editor));
+    Some(command)
+}
+
+fn env_editor() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.trim().is_empty()))
+}
+
+fn temp_path() -> PathBuf {
+    // RandomState is seeded from the OS, so the name can't be guessed.
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    let dir = dirs::runtime_dir().unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("neverlight-compose-{:016x}.txt", hasher.finish()))
+}
+
+/// Create `path` for this user only; never opens something already there.
+async fn write_new(path: &Path, body: &str) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .await?;
+    file.write_all(body.as_bytes()).await?;
+    file.flush().await
+}
+
+async fn run_editor(command: Vec<String>, path: PathBuf, body: String) -> Result<String, String> {
+    let Some((program, args)) = command.split_first() else {
+        return Err("No editor command".into());
+    };
+    write_new(&path, &body)
+        .await
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    let status = tokio::process::Command::new(program)
+        .args(args)
+        .arg(&path)
+        .status()
+        .await
+        .map_err(|e| format!("Failed to start {program}: {e}"))?;
+    if !status.success() {
+        return Err(format!("{program} exited with {status}"));
+    }
+    tokio::fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))
+}
+
+impl AppModel {
+    pub(super) fn handle_external_editor(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ComposeExternalEditor => {
+                if self.compose_phase != ComposePhase::Open {
+                    return Task::none();
+                }
+                if self.compose_external_edit.is_some() {
+                    self.status_message = "The external editor is already open".into();
+                    return Task::none();
+                }
+                let configured = self.settings.external_editor.as_deref();
+                let Some(command) = editor_command(configured, env_editor().as_deref()) else {
+                    self.compose_error = Some(
+                        "No external editor: set \"external_editor\" in settings.json, \
+                         or $VISUAL or $EDITOR"
+                            .into(),
+                    );
+                    return Task::none();
+                };
+                let edit = ExternalEdit {
+                    path: temp_path(),
+                    sent: self.compose_body.text(),
+                };
+                let (path, body) = (edit.path.clone(), edit.sent.clone());
+                self.compose_external_edit = Some(edit);
+                self.compose_error = None;
+                self.status_message = format!("Editing in {}...", command.join(" "));
+                return cosmic::task::future(async move {
+                    let result = run_editor(command, path.clone(), body).await;
+                    Message::ComposeExternalEditorDone { path, result }
+                });
+            }
+            Message::ComposeExternalEditorDone { path, result } => {
+                let Some(edit) = self.compose_external_edit.take_if(|e| e.path == path) else {
+                    return Task::none();
+                };
+                let text = match result {
+                    Ok(text) => text,
+                    Err(e) => {
+                        log::warn!("External editor: {}", e);
+                        self.compose_error = Some(e);
+                        let _ = std::fs::remove_file(&path);
+                        return Task::none();
+                    }
+                };
+                if !self.compose_phase.is_open() || self.compose_body.text() != edit.sent {
+                    self.status_message = format!(
+                        "Compose changed while the editor was open; edited text kept in {}",
+                        path.display()
+                    );
+                    return Task::none();
+                }
+                self.compose_body = text_editor::Content::with_text(&text);
+                self.status_message = "Body updated from the external editor".into();
+                let _ = std::fs::remove_file(&path);
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_command_wins_over_the_environment() {
+        assert_eq!(
+            editor_command(Some("gvim -f"), Some("vim")),
+            Some(vec!["gvim".to_string(), "-f".to_string()])
+        );
+    }
+
+    #[test]
+    fn environment_editor_runs_in_a_terminal() {
+        assert_eq!(
+            editor_command(None, Some("nvim -u NONE")),
+            Some(vec![
+                TERMINAL_LAUNCHER.to_string(),
+                "nvim".to_string(),
+                "-u".to_string(),
+                "NONE".to_string()
+            ])
+        );
+        assert_eq!(
+            editor_command(Some("  "), Some("vim")).map(|c| c.len()),
+            Some(2)
+        );
+        assert_eq!(editor_command(None, Some("")), None);
+        assert_eq!(editor_command(None, None), None);
+    }
+
+    #[test]
+    fn temp_names_are_not_reused() {
+        assert_ne!(temp_path(), temp_path());
+    }
+}
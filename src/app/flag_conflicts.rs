@@ -0,0 +1,254 @@
+//! Read/star conflicts between an in-flight local flag op and server state.
+//!
+//! A list reload that lands while a `set_flag` is still in flight can carry
+//! server flags that disagree with the optimistic local ones. A field still
+//! at its value from before our op is just a reload older than the op, and
+//! keeps the local value. Any other difference means someone else changed
+//! it: the configured policy picks a side, and every such conflict is kept
+//! in a short log shown in the diagnostics panel.
+
+use std::collections::VecDeque;
+
+use cosmic::app::Task;
+use neverlight_mail_core::store;
+use neverlight_mail_core::FlagOp;
+
+use super::settings::FlagConflictPolicy;
+use super::{AppModel, Message, MessageIdentity};
+
+/// Conflicts kept for the diagnostics panel.
+const MAX_LOGGED_CONFLICTS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagField {
+    Read,
+    Starred,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictWinner {
+    Local,
+    Server,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagConflict {
+    pub subject: String,
+    pub field: FlagField,
+    /// Value the winner left in place.
+    pub value: bool,
+    pub winner: ConflictWinner,
+}
+
+impl FlagConflict {
+    /// "Read on "Lunch?": server kept unread".
+    pub fn describe(&self) -> String {
+        let (field, state) = match (self.field, self.value) {
+            (FlagField::Read, true) => ("Read", "read"),
+            (FlagField::Read, false) => ("Read", "unread"),
+            (FlagField::Starred, true) => ("Star", "starred"),
+            (FlagField::Starred, false) => ("Star", "unstarred"),
+        };
+        let side = match self.winner {
+            ConflictWinner::Local => "local",
+            ConflictWinner::Server => "server",
+        };
+        format!("{field} on \"{}\": {side} kept {state}", self.subject)
+    }
+}
+
+/// Who wins under `policy`. There is no "newest wins": core doesn't expose
+/// a modseq or per-keyword change time to compare against.
+pub fn resolve(policy: FlagConflictPolicy) -> ConflictWinner {
+    match policy {
+        FlagConflictPolicy::LocalWins => ConflictWinner::Local,
+        FlagConflictPolicy::ServerWins => ConflictWinner::Server,
+    }
+}
+
+/// `server` with each field that still shows its value from before our op
+/// (`prev`) replaced by ours (`local`): the reload predates the op.
+fn without_stale_fields(
+    prev: (bool, bool),
+    local: (bool, bool),
+    server: (bool, bool),
+) -> (bool, bool) {
+    let keep = |prev: bool, local: bool, server: bool| {
+        if prev != local && server == prev {
+            local
+        } else {
+            server
+        }
+    };
+    (keep(prev.0, local.0, server.0), keep(prev.1, local.1, server.1))
+}
+
+/// Fields where local and server disagree, with the server's value.
+fn differing_fields(local: (bool, bool), server: (bool, bool)) -> Vec<(FlagField, bool)> {
+    let mut fields = Vec::new();
+    if local.0 != server.0 {
+        fields.push((FlagField::Read, server.0));
+    }
+    if local.1 != server.1 {
+        fields.push((FlagField::Starred, server.1));
+    }
+    fields
+}
+
+/// Ops that put the server back to `server` after our op overwrote it.
+fn restore_ops(ours: (bool, bool), server: (bool, bool)) -> Vec<FlagOp> {
+    differing_fields(ours, server)
+        .into_iter()
+        .map(|(field, value)| match field {
+            FlagField::Read => FlagOp::SetSeen(value),
+            FlagField::Starred => FlagOp::SetFlagged(value),
+        })
+        .collect()
+}
+
+impl AppModel {
+    /// Check freshly loaded rows against in-flight flag ops and apply the
+    /// conflict policy.
+    pub(super) fn reconcile_pending_flags(&mut self) {
+        if self.pending_local_flags.is_empty() {
+            return;
+        }
+        let policy = self.settings.flag_conflict_policy;
+        for msg in self.messages.iter_mut() {
+            let identity = MessageIdentity::of(msg);
+            let Some(&(prev, pending)) = self.pending_local_flags.get(&identity) else {
+                continue;
+            };
+            let local = store::flags_from_u8(pending);
+            let server = without_stale_fields(
+                store::flags_from_u8(prev),
+                local,
+                (msg.is_read, msg.is_starred),
+            );
+            msg.is_read = server.0;
+            msg.is_starred = server.1;
+            let fields = differing_fields(local, server);
+            if fields.is_empty() {
+                continue;
+            }
+            let winner = resolve(policy);
+            for (field, server_value) in fields {
+                let value = match winner {
+                    ConflictWinner::Local => !server_value,
+                    ConflictWinner::Server => server_value,
+                };
+                log::info!(
+                    "Flag conflict on {}: {:?} local={} server={} → {:?}",
+                    msg.email_id,
+                    field,
+                    !server_value,
+                    server_value,
+                    winner
+                );
+                if self.flag_conflicts.len() == MAX_LOGGED_CONFLICTS {
+                    self.flag_conflicts.pop_front();
+                }
+                self.flag_conflicts.push_back(FlagConflict {
+                    subject: msg.subject.clone(),
+                    field,
+                    value,
+                    winner,
+                });
+            }
+            match winner {
+                ConflictWinner::Local => {
+                    msg.is_read = local.0;
+                    msg.is_starred = local.1;
+                    self.server_won_flags.remove(&identity);
+                }
+                ConflictWinner::Server => {
+                    self.server_won_flags
+                        .insert(identity, store::flags_to_u8(server.0, server.1));
+                }
+            }
+        }
+    }
+
+    /// After our op lands, push the server-side values back that won the
+    /// conflict, then settle the cache on them.
+    pub(super) fn restore_server_flags(
+        &self,
+        message: &MessageIdentity,
+        ours: u8,
+        server: u8,
+    ) -> Task<Message> {
+        let Some(client) = self.client_for_account(&message.account_id) else {
+            return Task::none();
+        };
+        let cache = self.cache.clone();
+        let account_id = message.account_id.clone();
+        let email_id = message.email_id.clone();
+        let ops = restore_ops(store::flags_from_u8(ours), store::flags_from_u8(server));
+        cosmic::task::future(async move {
+            for op in &ops {
+                if let Err(e) = neverlight_mail_core::email::set_flag(&client, &email_id, op).await
+                {
+                    log::warn!("Failed to restore server flags for {}: {}", email_id, e);
+                }
+            }
+            if let Some(cache) = cache {
+                if let Err(e) = cache.clear_pending_op(account_id, email_id, server).await {
+                    log::warn!("Failed to clear pending op: {}", e);
+                }
+            }
+            Message::Noop
+        })
+    }
+}
+
+/// Newest first, for the diagnostics panel.
+pub fn recent_conflicts(log: &VecDeque<FlagConflict>, n: usize) -> Vec<String> {
+    log.iter().rev().take(n).map(FlagConflict::describe).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_picks_the_winner() {
+        assert_eq!(resolve(FlagConflictPolicy::LocalWins), ConflictWinner::Local);
+        assert_eq!(resolve(FlagConflictPolicy::ServerWins), ConflictWinner::Server);
+    }
+
+    #[test]
+    fn only_differing_fields_are_reported() {
+        assert_eq!(differing_fields((true, false), (true, false)), vec![]);
+        assert_eq!(
+            differing_fields((true, false), (false, true)),
+            vec![(FlagField::Read, false), (FlagField::Starred, true)]
+        );
+    }
+
+    #[test]
+    fn a_reload_older_than_our_op_is_not_a_conflict() {
+        // We marked it read; the reload still shows it unread.
+        let server = without_stale_fields((false, false), (true, false), (false, false));
+        assert_eq!(server, (true, false));
+        assert_eq!(differing_fields((true, false), server), vec![]);
+    }
+
+    #[test]
+    fn a_field_our_op_left_alone_can_still_conflict() {
+        // We marked it read; meanwhile someone starred it elsewhere.
+        let server = without_stale_fields((false, false), (true, false), (false, true));
+        assert_eq!(server, (true, true));
+        assert_eq!(differing_fields((true, false), server), vec![(FlagField::Starred, true)]);
+    }
+
+    #[test]
+    fn descriptions_name_the_winner() {
+        let conflict = FlagConflict {
+            subject: "Lunch?".into(),
+            field: FlagField::Read,
+            value: false,
+            winner: ConflictWinner::Server,
+        };
+        assert_eq!(conflict.describe(), "Read on \"Lunch?\": server kept unread");
+    }
+}
@@ -0,0 +1,154 @@
+//! RFC 3676 `format=flowed` decoding for received plain-text bodies.
+//!
+//! Core hands over decoded body text without the Content-Type parameters, so
+//! flowed bodies are recognised by their soft line breaks (a trailing space
+//! before the newline) rather than by `format=flowed` itself. That guess can
+//! misfire: a fixed plain-text body whose editor left single trailing spaces
+//! on most lines gets its lines joined. Lines ending in two or more spaces
+//! (markdown hard breaks, padded text) don't count towards the guess. Once
+//! core passes the Content-Type parameters through, decoding should require
+//! `format=flowed` and drop the heuristic. Sending flowed bodies needs the
+//! parameter on the outgoing part, which `SendRequest` can't express yet.
+
+/// RFC 3676 signature separator; never a soft break.
+const SIG_SEPARATOR: &str = "-- ";
+
+struct FlowedLine<'a> {
+    depth: usize,
+    text: &'a str,
+    soft: bool,
+}
+
+fn parse_line(raw: &str) -> FlowedLine<'_> {
+    let raw = raw.strip_suffix('\r').unwrap_or(raw);
+    let depth = raw.bytes().take_while(|&b| b == b'>').count();
+    let rest = &raw[depth..];
+    // Space-stuffing: one leading space was added by the sender.
+    let text = rest.strip_prefix(' ').unwrap_or(rest);
+    let soft = text.ends_with(' ') && text != SIG_SEPARATOR;
+    FlowedLine { depth, text, soft }
+}
+
+/// Whether `text` reads like a flowed body: most lines that continue into a
+/// non-blank line at the same quote depth end in a soft break, that is in
+/// exactly one space.
+fn looks_flowed(text: &str) -> bool {
+    let lines: Vec<FlowedLine> = text.lines().map(parse_line).collect();
+    let mut candidates = 0;
+    let mut soft = 0;
+    for pair in lines.windows(2) {
+        let (line, next) = (&pair[0], &pair[1]);
+        if line.text.trim().is_empty() || next.text.trim().is_empty() || line.depth != next.depth {
+            continue;
+        }
+        candidates += 1;
+        if line.soft && !line.text.ends_with("  ") {
+            soft += 1;
+        }
+    }
+    soft >= 2 && soft * 2 >= candidates
+}
+
+/// Join soft-broken lines into paragraphs, keeping quote depth.
+fn decode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut paragraph: Option<(usize, String)> = None;
+    for line in text.lines().map(parse_line) {
+        let (depth, mut joined) = match paragraph.take() {
+            // A depth change ends the paragraph even after a soft break.
+            Some((depth, joined)) if depth == line.depth => (depth, joined),
+            Some((depth, joined)) => {
+                push_line(&mut out, depth, &joined);
+                (line.depth, String::new())
+            }
+            None => (line.depth, String::new()),
+        };
+        joined.push_str(line.text);
+        if line.soft {
+            paragraph = Some((depth, joined));
+        } else {
+            push_line(&mut out, depth, &joined);
+        }
+    }
+    if let Some((depth, joined)) = paragraph {
+        push_line(&mut out, depth, &joined);
+    }
+    out
+}
+
+fn push_line(out: &mut String, depth: usize, text: &str) {
+    if depth > 0 {
+        out.push_str(&">".repeat(depth));
+        out.push(' ');
+    }
+    // Drop the soft-break space left when a depth change cuts a paragraph
+    // short; the signature separator keeps its space.
+    if text == SIG_SEPARATOR {
+        out.push_str(text);
+    } else {
+        out.push_str(text.trim_end_matches(' '));
+    }
+    out.push('\n');
+}
+
+/// Decode flowed plain bodies. The markdown body is replaced too when core
+/// passed the plain text straight through (no HTML part to render).
+pub(super) fn reflow_body(markdown: String, plain: String) -> (String, String) {
+    if !looks_flowed(&plain) {
+        return (markdown, plain);
+    }
+    let decoded = decode(&plain);
+    let markdown = if markdown == plain {
+        decoded.clone()
+    } else {
+        markdown
+    };
+    (markdown, decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_flowed() -> &'static str {
+        "Hello there, this is a long \nparagraph that was wrapped \nby the sender.\n\n-- \nSam\n"
+    }
+
+    #[test]
+    fn soft_breaks_join_into_paragraphs() {
+        assert_eq!(
+            decode(sample_flowed()),
+            "Hello there, this is a long paragraph that was wrapped by the sender.\n\n-- \nSam\n"
+        );
+    }
+
+    #[test]
+    fn quoted_paragraphs_keep_depth_and_stop_at_depth_change() {
+        let text = ">> deep \n>> quote\n> shallow \ntext\n";
+        assert_eq!(decode(text), ">> deep quote\n> shallow\ntext\n");
+    }
+
+    #[test]
+    fn space_stuffing_is_removed() {
+        assert_eq!(decode(" From the top\n >not a quote\n"), "From the top\n>not a quote\n");
+    }
+
+    #[test]
+    fn detects_flowed_but_not_hard_wrapped_text() {
+        assert!(looks_flowed(sample_flowed()));
+        assert!(!looks_flowed("Line one\nline two\nline three\n"));
+        // A single stray trailing space isn't enough.
+        assert!(!looks_flowed("Line one \nline two\nline three\n"));
+        // Markdown hard breaks end in two spaces, not one.
+        assert!(!looks_flowed("Line one  \nline two  \nline three\n"));
+    }
+
+    #[test]
+    fn reflow_replaces_passthrough_markdown_only() {
+        let plain = sample_flowed().to_string();
+        let (md, decoded) = reflow_body(plain.clone(), plain.clone());
+        assert_eq!(md, decoded);
+        let (md, _) = reflow_body("**rich**".into(), plain);
+        assert_eq!(md, "**rich**");
+    }
+}
@@ -0,0 +1,235 @@
+//! Per-folder activity for the sidebar tooltip: newest message date and a
+//! 30-day volume sparkline.
+//!
+//! Computed from cached summaries, so it covers what sync + backfill have
+//! stored rather than the whole server folder. Totals and unread counts come
+//! from the live `Folder` instead. There is no size estimate: summaries don't
+//! carry message sizes.
+//!
+//! Summaries are read newest first a page at a time, stopping at the first
+//! page that reaches past the sparkline window, so a quiet folder costs one
+//! small query. Cached folders are computed once at startup; after that only
+//! the folder that just synced is recomputed.
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use cosmic::app::Task;
+use neverlight_mail_core::config::AccountId;
+
+use super::metrics::MetricKind;
+use super::{AppModel, Message};
+
+/// Days covered by the sparkline, today included.
+pub const SPARK_DAYS: usize = 30;
+/// Cached summaries read per query.
+const ACTIVITY_PAGE: u32 = 200;
+/// Upper bound on cached summaries read per folder.
+const MAX_SCANNED_PER_FOLDER: u32 = 1000;
+const SPARK_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderActivity {
+    pub newest: Option<NaiveDate>,
+    /// Messages per day, oldest first; the last entry is today.
+    pub daily: [u32; SPARK_DAYS],
+}
+
+impl FolderActivity {
+    pub fn from_dates<'a>(dates: impl IntoIterator<Item = &'a str>, today: NaiveDate) -> Self {
+        let mut activity = Self {
+            newest: None,
+            daily: [0; SPARK_DAYS],
+        };
+        for day in dates.into_iter().filter_map(parse_summary_date) {
+            activity.newest = activity.newest.max(Some(day));
+            let age = (today - day).num_days();
+            if (0..SPARK_DAYS as i64).contains(&age) {
+                activity.daily[SPARK_DAYS - 1 - age as usize] += 1;
+            }
+        }
+        activity
+    }
+
+    pub fn recent_total(&self) -> u32 {
+        self.daily.iter().sum()
+    }
+
+    /// "▁▁▃█▂…" scaled to the busiest day; flat when there was no mail.
+    pub fn sparkline(&self) -> String {
+        let peak = self.daily.iter().copied().max().unwrap_or(0);
+        self.daily
+            .iter()
+            .map(|&n| match (n, peak) {
+                (0, _) | (_, 0) => SPARK_BARS[0],
+                _ => {
+                    let top = SPARK_BARS.len() - 1;
+                    // Any mail at all gets at least the second bar.
+                    SPARK_BARS[(n as usize * top).div_ceil(peak as usize).max(1)]
+                }
+            })
+            .collect()
+    }
+}
+
+/// Whether `day` is older than the sparkline reaches.
+fn before_window(day: NaiveDate, today: NaiveDate) -> bool {
+    (today - day).num_days() >= SPARK_DAYS as i64
+}
+
+/// A summary's date string, parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SummaryTime {
+    /// RFC 3339 or RFC 2822, with its offset.
+    Zoned(DateTime<FixedOffset>),
+    /// `YYYY-MM-DD[ HH:MM[:SS]]`: wall-clock time, no zone given. A bare
+    /// date is midnight.
+    Naive(NaiveDateTime),
+}
+
+/// Parse a summary's date string (RFC 3339, RFC 2822, or
+/// `YYYY-MM-DD[ HH:MM[:SS]]`).
+pub(super) fn parse_summary_time(date: &str) -> Option<SummaryTime> {
+    let date = date.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(date) {
+        return Some(SummaryTime::Zoned(dt));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(date) {
+        return Some(SummaryTime::Zoned(dt));
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(date, fmt) {
+            return Some(SummaryTime::Naive(dt));
+        }
+    }
+    let day = NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()?;
+    Some(SummaryTime::Naive(day.and_hms_opt(0, 0, 0)?))
+}
+
+/// Calendar day of a summary's date string.
+pub(super) fn parse_summary_date(date: &str) -> Option<NaiveDate> {
+    match parse_summary_time(date)? {
+        SummaryTime::Zoned(dt) => Some(dt.date_naive()),
+        SummaryTime::Naive(dt) => Some(dt.date()),
+    }
+}
+
+impl AppModel {
+    /// Recompute tooltip activity for `mailbox_ids` from the cache.
+    pub(super) fn load_folder_activity(
+        &self,
+        account_id: &str,
+        mailbox_ids: Vec<String>,
+    ) -> Task<Message> {
+        let Some(cache) = self.cache.clone().filter(|_| !mailbox_ids.is_empty()) else {
+            return Task::none();
+        };
+        let metrics = self.metrics.clone();
+        let account_id: AccountId = account_id.to_string();
+        cosmic::task::future(async move {
+            let today = chrono::Local::now().date_naive();
+            let mut activity = Vec::with_capacity(mailbox_ids.len());
+            'folders: for mailbox_id in mailbox_ids {
+                let mut dates = Vec::new();
+                let mut offset = 0;
+                while offset < MAX_SCANNED_PER_FOLDER {
+                    let page = metrics
+                        .timed(
+                            MetricKind::CacheQuery,
+                            cache.load_messages(
+                                account_id.clone(),
+                                mailbox_id.clone(),
+                                ACTIVITY_PAGE,
+                                offset,
+                            ),
+                        )
+                        .await;
+                    let page = match page {
+                        Ok(page) => page,
+                        Err(e) => {
+                            log::warn!("Folder activity: load failed for {}: {}", mailbox_id, e);
+                            continue 'folders;
+                        }
+                    };
+                    let len = page.len() as u32;
+                    let past_window = page
+                        .last()
+                        .and_then(|m| parse_summary_date(&m.date))
+                        .is_some_and(|day| before_window(day, today));
+                    dates.extend(page.into_iter().map(|m| m.date));
+                    if len < ACTIVITY_PAGE || past_window {
+                        break;
+                    }
+                    offset += len;
+                }
+                let folder = FolderActivity::from_dates(dates.iter().map(String::as_str), today);
+                activity.push((mailbox_id, folder));
+            }
+            Message::FolderActivityLoaded {
+                account_id,
+                activity,
+            }
+        })
+    }
+
+    pub(super) fn apply_folder_activity(
+        &mut self,
+        account_id: AccountId,
+        activity: Vec<(String, FolderActivity)>,
+    ) {
+        let per_account = self.folder_activity.entry(account_id).or_default();
+        per_account.extend(activity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_common_date_formats() {
+        assert_eq!(parse_summary_date("2026-03-04T10:00:00Z"), Some(day("2026-03-04")));
+        assert_eq!(
+            parse_summary_date("Wed, 4 Mar 2026 10:00:00 +0000"),
+            Some(day("2026-03-04"))
+        );
+        assert_eq!(parse_summary_date("2026-03-04 10:00"), Some(day("2026-03-04")));
+        assert_eq!(parse_summary_date("yesterday"), None);
+    }
+
+    #[test]
+    fn buckets_last_thirty_days_and_tracks_newest() {
+        let today = day("2026-03-31");
+        let activity = FolderActivity::from_dates(
+            ["2026-03-31", "2026-03-31", "2026-03-02", "2026-03-01", "garbage"],
+            today,
+        );
+        assert_eq!(activity.newest, Some(today));
+        assert_eq!(activity.daily[SPARK_DAYS - 1], 2);
+        assert_eq!(activity.daily[0], 1);
+        // 2026-03-01 is 30 days back: outside the window.
+        assert_eq!(activity.recent_total(), 3);
+    }
+
+    #[test]
+    fn window_ends_thirty_days_back() {
+        let today = day("2026-03-31");
+        assert!(!before_window(day("2026-03-02"), today));
+        assert!(before_window(day("2026-03-01"), today));
+        assert!(!before_window(day("2026-04-02"), today));
+    }
+
+    #[test]
+    fn sparkline_scales_to_peak() {
+        let mut activity = FolderActivity::from_dates([], day("2026-03-31"));
+        assert_eq!(activity.sparkline(), "▁".repeat(SPARK_DAYS));
+        activity.daily[SPARK_DAYS - 1] = 8;
+        activity.daily[SPARK_DAYS - 2] = 1;
+        let spark: Vec<char> = activity.sparkline().chars().collect();
+        assert_eq!(spark[SPARK_DAYS - 1], '█');
+        assert_eq!(spark[SPARK_DAYS - 2], '▂');
+        assert_eq!(spark[0], '▁');
+    }
+}
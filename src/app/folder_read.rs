@@ -0,0 +1,169 @@
+//! "Mark folder as read": every unread message in the open folder.
+//!
+//! Core only exposes per-message flag calls (`email::set_flag`,
+//! `update_flags`), so there is no single Email/set or one-statement cache
+//! update to issue. Instead the list and folder count flip at once, one
+//! background task walks the folder's cached unread messages, and a refresh
+//! settles the counts when it finishes. The account's flag queue is held
+//! meanwhile so single toggles don't race it.
+
+use std::collections::HashSet;
+
+use cosmic::app::Task;
+use neverlight_mail_core::store::{self, CacheHandle};
+use neverlight_mail_core::FlagOp;
+
+use super::{AppModel, MailboxIdentity, Message};
+
+/// Cached summaries read from the folder.
+const MAX_SCANNED: u32 = 50_000;
+
+/// (email id, flags once read) for each unread row, first copy wins.
+fn unread_targets<'a>(
+    rows: impl IntoIterator<Item = (&'a str, bool, bool)>,
+) -> Vec<(String, u8)> {
+    let mut seen = HashSet::new();
+    rows.into_iter()
+        .filter(|&(email_id, is_read, _)| !is_read && seen.insert(email_id))
+        .map(|(email_id, _, is_starred)| (email_id.to_string(), store::flags_to_u8(true, is_starred)))
+        .collect()
+}
+
+/// Mark each target read on the server and in the cache. Returns how many
+/// succeeded, or the first error with the failure count.
+async fn mark_read(
+    client: neverlight_mail_core::client::JmapClient,
+    cache: Option<CacheHandle>,
+    folder: MailboxIdentity,
+    mut targets: Vec<(String, u8)>,
+) -> Result<usize, String> {
+    if let Some(cache) = &cache {
+        let (account_id, mailbox_id) = (folder.account_id.clone(), folder.mailbox_id.clone());
+        let cached = cache.load_messages(account_id, mailbox_id, MAX_SCANNED, 0).await?;
+        let loaded = std::mem::take(&mut targets);
+        let cached_rows = cached
+            .iter()
+            .map(|m| (m.email_id.as_str(), m.is_read, m.is_starred));
+        let loaded_rows = loaded.iter().map(|(id, flags)| {
+            let (_, is_starred) = store::flags_from_u8(*flags);
+            (id.as_str(), false, is_starred)
+        });
+        targets = unread_targets(loaded_rows.chain(cached_rows));
+    }
+
+    let mut marked = 0;
+    let mut first_error = None;
+    for (email_id, flags) in &targets {
+        let account_id = folder.account_id.clone();
+        if let Some(cache) = &cache {
+            let op = "set_seen".to_string();
+            if let Err(e) = cache.update_flags(account_id.clone(), email_id.clone(), *flags, op).await {
+                log::warn!("Failed to update cache flags: {}", e);
+            }
+        }
+        let seen = FlagOp::SetSeen(true);
+        let result = neverlight_mail_core::email::set_flag(&client, email_id, &seen)
+            .await
+            .map_err(|e| e.to_string());
+        match &result {
+            Ok(_) => marked += 1,
+            Err(e) => {
+                first_error.get_or_insert(e.clone());
+            }
+        }
+        let Some(cache) = &cache else {
+            continue;
+        };
+        let settled = match result {
+            Ok(_) => cache.clear_pending_op(account_id, email_id.clone(), *flags).await,
+            Err(_) => cache.revert_pending_op(account_id, email_id.clone()).await,
+        };
+        if let Err(e) = settled {
+            log::warn!("Failed to settle pending op: {}", e);
+        }
+    }
+    match first_error {
+        None => Ok(marked),
+        Some(e) => Err(format!("{} of {} failed: {e}", targets.len() - marked, targets.len())),
+    }
+}
+
+impl AppModel {
+    pub(super) fn handle_folder_read(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::MarkFolderRead(folder) => {
+                let Some(client) = self.client_for_account(&folder.account_id) else {
+                    self.status_message = "Mark folder as read needs a connection".into();
+                    return Task::none();
+                };
+                if !self.flag_in_flight_accounts.insert(folder.account_id.clone()) {
+                    self.status_message = "Wait for pending flag updates to finish".into();
+                    return Task::none();
+                }
+
+                let loaded = unread_targets(
+                    self.messages
+                        .iter()
+                        .filter(|m| {
+                            m.account_id == folder.account_id
+                                && m.context_mailbox_id == folder.mailbox_id
+                        })
+                        .map(|m| (m.email_id.as_str(), m.is_read, m.is_starred)),
+                );
+                for msg in self.messages.iter_mut().filter(|m| {
+                    m.account_id == folder.account_id && m.context_mailbox_id == folder.mailbox_id
+                }) {
+                    msg.is_read = true;
+                }
+                if let Some(idx) = self.account_index(&folder.account_id) {
+                    let folders = &mut self.accounts[idx].folders;
+                    if let Some(f) = folders.iter_mut().find(|f| f.mailbox_id == folder.mailbox_id) {
+                        f.unread_count = 0;
+                    }
+                }
+                self.recompute_visible();
+                self.status_message = "Marking folder as read...".into();
+
+                let cache = self.cache.clone();
+                return cosmic::task::future(async move {
+                    let result = mark_read(client, cache, folder.clone(), loaded).await;
+                    Message::MarkFolderReadComplete { folder, result }
+                });
+            }
+            Message::MarkFolderReadComplete { folder, result } => {
+                self.flag_in_flight_accounts.remove(&folder.account_id);
+                match result {
+                    Ok(0) => self.status_message = "Nothing unread".into(),
+                    Ok(n) => self.status_message = format!("Marked {n} messages read"),
+                    Err(e) => {
+                        log::error!("Mark folder as read failed: {}", e);
+                        self.set_status_error(format!("Mark folder as read: {e}"));
+                    }
+                }
+                let next = self.try_run_next_flag_intent_for(&folder.account_id);
+                let refresh = self.handle_refresh_accounts(Some(&folder.account_id));
+                return cosmic::task::batch(vec![next, refresh]);
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unread_rows_once_each() {
+        let targets = unread_targets([
+            ("M1", false, true),
+            ("M2", true, false),
+            ("M3", false, false),
+            ("M1", false, false),
+        ]);
+        let ids: Vec<&str> = targets.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["M1", "M3"]);
+        assert_eq!(targets[0].1, store::flags_to_u8(true, true));
+    }
+}
@@ -0,0 +1,167 @@
+//! How often each folder is opened, and syncing the busiest ones at launch.
+//!
+//! Every switch to a folder counts one visit, kept in `folder-usage.json`
+//! next to the sync times (core owns the cache schema). The first time an
+//! account's folder list syncs after launch, its `WARM_UP_FOLDERS` most
+//! visited folders sync in the background, one after another and each with
+//! its own page size, so opening them later shows current mail straight
+//! from the cache. The open folder and Sent sync anyway and are skipped; a
+//! paused account isn't warmed up until it is resumed.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use cosmic::app::Task;
+use serde::{Deserialize, Serialize};
+
+use neverlight_mail_core::config::AccountId;
+
+use super::json_store::{load_json, save_json};
+use super::{AppModel, Message};
+
+/// Folders per account synced ahead of time.
+const WARM_UP_FOLDERS: usize = 3;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderUsage {
+    /// account_id → mailbox_id → times opened.
+    #[serde(default)]
+    folders: HashMap<AccountId, HashMap<String, u32>>,
+}
+
+impl FolderUsage {
+    pub fn record_open(&mut self, account_id: &str, mailbox_id: &str) {
+        let count = self
+            .folders
+            .entry(account_id.to_string())
+            .or_default()
+            .entry(mailbox_id.to_string())
+            .or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// Up to `n` of `candidates` that have been opened, most opened first;
+    /// ties keep candidate order.
+    pub fn most_used<'a>(
+        &self,
+        account_id: &str,
+        candidates: impl IntoIterator<Item = &'a str>,
+        n: usize,
+    ) -> Vec<&'a str> {
+        let Some(counts) = self.folders.get(account_id) else {
+            return Vec::new();
+        };
+        let mut used: Vec<(&str, u32)> = candidates
+            .into_iter()
+            .filter_map(|id| Some((id, *counts.get(id)?)))
+            .collect();
+        // Stable sort keeps candidate order among equal counts.
+        used.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        used.into_iter().take(n).map(|(id, _)| id).collect()
+    }
+
+    pub fn remove_account(&mut self, account_id: &str) {
+        self.folders.remove(account_id);
+    }
+
+    pub fn load() -> Self {
+        load_json(usage_path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_json(usage_path(), self)
+    }
+}
+
+fn usage_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("neverlight-mail").join("folder-usage.json"))
+}
+
+impl AppModel {
+    pub(super) fn record_folder_open(&mut self, account_id: &str, mailbox_id: &str) {
+        self.folder_usage.record_open(account_id, mailbox_id);
+        if let Err(e) = self.folder_usage.save() {
+            log::warn!("Failed to save folder usage: {}", e);
+        }
+    }
+
+    /// Sync the account's most used folders in the background, once per
+    /// launch; `None` when there's nothing to do.
+    pub(super) fn warm_up_folders(&mut self, idx: usize) -> Option<Task<Message>> {
+        let acct = self.accounts.get(idx)?;
+        let client = acct.client.clone()?;
+        let cache = self.cache.clone()?;
+        if acct.paused || !self.warmed_up_accounts.insert(acct.config.id.clone()) {
+            return None;
+        }
+        let aid = acct.config.id.clone();
+        let sent_id = neverlight_mail_core::mailbox::find_by_role(&acct.folders, "sent");
+        let open_id = self
+            .selected_folder
+            .filter(|_| self.active_account == Some(idx))
+            .and_then(|fi| acct.folders.get(fi))
+            .map(|f| f.mailbox_id.as_str());
+        let candidates = acct
+            .folders
+            .iter()
+            .map(|f| f.mailbox_id.as_str())
+            .filter(|&id| Some(id) != open_id && Some(id) != sent_id.as_deref());
+        let folders: Vec<(String, u32)> = self
+            .folder_usage
+            .most_used(&aid, candidates, WARM_UP_FOLDERS)
+            .into_iter()
+            .map(|mid| (mid.to_string(), self.page_size_for(&aid, mid)))
+            .collect();
+        if folders.is_empty() {
+            return None;
+        }
+        log::debug!("Warming up {} folders for {}", folders.len(), acct.config.label);
+        Some(cosmic::task::future(async move {
+            let mut synced = Vec::new();
+            for (mailbox_id, page_size) in folders {
+                match neverlight_mail_core::sync::sync_emails(
+                    &client, &cache, &aid, &mailbox_id, page_size,
+                )
+                .await
+                {
+                    Ok(_) => synced.push(mailbox_id),
+                    Err(e) => log::warn!("Warm-up sync of {} failed: {}", mailbox_id, e),
+                }
+            }
+            Message::FoldersWarmedUp {
+                account_id: aid,
+                mailbox_ids: synced,
+            }
+        }))
+    }
+
+    pub(super) fn handle_folders_warmed_up(
+        &mut self,
+        account_id: &str,
+        mailbox_ids: Vec<String>,
+    ) -> Task<Message> {
+        for mailbox_id in &mailbox_ids {
+            self.record_folder_synced(account_id, mailbox_id);
+        }
+        self.load_folder_activity(account_id, mailbox_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_used_folders_come_first() {
+        let mut usage = FolderUsage::default();
+        for mailbox in ["lists", "inbox", "lists", "work", "work", "old"] {
+            usage.record_open("acct-1", mailbox);
+        }
+        let candidates = ["inbox", "work", "lists", "old", "never"];
+        assert_eq!(usage.most_used("acct-1", candidates, 3), ["work", "lists", "inbox"]);
+        // Folders gone from the account aren't offered.
+        assert_eq!(usage.most_used("acct-1", ["old", "never"], 3), ["old"]);
+        usage.remove_account("acct-1");
+        assert!(usage.most_used("acct-1", candidates, 3).is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+//! Per-folder "last synced" timestamps.
+//!
+//! Recorded whenever a folder's messages sync successfully and persisted
+//! next to the drafts file, so cached mail shown at startup can say how old
+//! it is. Wall-clock Unix seconds, since `Instant` doesn't survive restarts.
+//! Syncs finish often, so the file is written at most every
+//! `SYNC_TIMES_SAVE_DELAY`, off the UI thread, and once more on quit.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use neverlight_mail_core::config::AccountId;
+
+use super::json_store::{load_json, save_json};
+
+/// How long recorded sync times wait before being written.
+pub const SYNC_TIMES_SAVE_DELAY: Duration = Duration::from_secs(30);
+
+/// Younger than this is shown as fresh.
+const FRESH_SECS: u64 = 15 * 60;
+/// Older than this is shown as stale.
+const STALE_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Never,
+    Fresh,
+    Aging,
+    Stale,
+}
+
+impl Freshness {
+    pub fn classify(last_synced: Option<u64>, now: u64) -> Self {
+        let Some(at) = last_synced else {
+            return Self::Never;
+        };
+        match now.saturating_sub(at) {
+            age if age < FRESH_SECS => Self::Fresh,
+            age if age < STALE_SECS => Self::Aging,
+            _ => Self::Stale,
+        }
+    }
+}
+
+/// "Updated 3 minutes ago" style label.
+pub fn synced_ago_label(last_synced: Option<u64>, now: u64) -> String {
+    let Some(at) = last_synced else {
+        return "Never synced".into();
+    };
+    let secs = now.saturating_sub(at);
+    let ago = match secs {
+        0..=59 => "just now".to_string(),
+        60..=119 => "1 minute ago".to_string(),
+        120..=3599 => format!("{} minutes ago", secs / 60),
+        3600..=7199 => "1 hour ago".to_string(),
+        7200..=86_399 => format!("{} hours ago", secs / 3600),
+        86_400..=172_799 => "1 day ago".to_string(),
+        _ => format!("{} days ago", secs / 86_400),
+    };
+    format!("Updated {ago}")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderSyncTimes {
+    /// account_id → mailbox_id → Unix seconds of the last successful sync.
+    #[serde(default)]
+    folders: HashMap<AccountId, HashMap<String, u64>>,
+}
+
+impl FolderSyncTimes {
+    pub fn get(&self, account_id: &str, mailbox_id: &str) -> Option<u64> {
+        self.folders.get(account_id)?.get(mailbox_id).copied()
+    }
+
+    pub fn record(&mut self, account_id: &str, mailbox_id: &str, at: u64) {
+        self.folders
+            .entry(account_id.to_string())
+            .or_default()
+            .insert(mailbox_id.to_string(), at);
+    }
+
+    pub fn remove_account(&mut self, account_id: &str) {
+        self.folders.remove(account_id);
+    }
+
+    pub fn load() -> Self {
+        load_json(sync_times_path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_json(sync_times_path(), self)
+    }
+}
+
+fn sync_times_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("neverlight-mail").join("folder-sync.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn never_synced_is_its_own_state() {
+        assert_eq!(Freshness::classify(None, NOW), Freshness::Never);
+        assert_eq!(synced_ago_label(None, NOW), "Never synced");
+    }
+
+    #[test]
+    fn freshness_thresholds() {
+        assert_eq!(Freshness::classify(Some(NOW - 60), NOW), Freshness::Fresh);
+        assert_eq!(Freshness::classify(Some(NOW - 30 * 60), NOW), Freshness::Aging);
+        assert_eq!(Freshness::classify(Some(NOW - 3 * 3600), NOW), Freshness::Stale);
+    }
+
+    #[test]
+    fn future_timestamp_counts_as_fresh() {
+        // Clock moved backwards since the last save.
+        assert_eq!(Freshness::classify(Some(NOW + 600), NOW), Freshness::Fresh);
+    }
+
+    #[test]
+    fn ago_label_units() {
+        assert_eq!(synced_ago_label(Some(NOW - 5), NOW), "Updated just now");
+        assert_eq!(synced_ago_label(Some(NOW - 7 * 60), NOW), "Updated 7 minutes ago");
+        assert_eq!(synced_ago_label(Some(NOW - 3600), NOW), "Updated 1 hour ago");
+        assert_eq!(synced_ago_label(Some(NOW - 3 * 86_400), NOW), "Updated 3 days ago");
+    }
+
+    #[test]
+    fn record_and_remove_account() {
+        let mut times = FolderSyncTimes::default();
+        times.record("acct-1", "inbox", NOW);
+        times.record("acct-2", "inbox", NOW - 10);
+        assert_eq!(times.get("acct-1", "inbox"), Some(NOW));
+        times.remove_account("acct-1");
+        assert_eq!(times.get("acct-1", "inbox"), None);
+        assert_eq!(times.get("acct-2", "inbox"), Some(NOW - 10));
+    }
+}
@@ -0,0 +1,154 @@
+//! Headless `AppModel` for update-loop tests: no compositor, accounts, cache
+//! or config on disk. Tasks returned by `update` are dropped, so a test plays
+//! the async side itself by sending the completion message
+//! (`CachedMessagesLoaded`, `SmartViewLoaded`, ...) with the epoch the model
+//! expects.
+//!
+//! The JMAP client and `CacheHandle` are concrete neverlight-mail-core types,
+//! so flows that need a live connection or cache stop at the point where the
+//! model would start the request.
+
+use cosmic::app::Core;
+use cosmic::Application;
+
+use super::startup::StoredState;
+use super::{AppModel, Message};
+
+/// A first-run model with nothing loaded.
+pub(super) fn headless() -> AppModel {
+    AppModel::new(Core::default(), None, StoredState::fresh())
+}
+
+/// Run each message through `update`, dropping the tasks.
+pub(super) fn send(app: &mut AppModel, messages: impl IntoIterator<Item = Message>) {
+    for message in messages {
+        let _ = app.update(message);
+    }
+}
+
+mod tests {
+    use cosmic::widget::text_editor;
+
+    use super::*;
+    use crate::app::{CloseIntent, ComposePhase, MenuAction, Phase, SmartView, SmartViewKind};
+
+    fn sample_starred_view() -> SmartView {
+        SmartView {
+            kind: SmartViewKind::Starred,
+            account_id: None,
+            group: None,
+        }
+    }
+
+    #[test]
+    fn shortcuts_overlay_opens_from_menu_and_closes() {
+        let mut app = headless();
+        send(&mut app, [Message::Menu(MenuAction::ShowShortcuts)]);
+        assert!(app.show_shortcuts);
+        send(&mut app, [Message::ToggleShortcuts]);
+        assert!(!app.show_shortcuts);
+    }
+
+    #[test]
+    fn message_actions_without_selection_are_ignored() {
+        let mut app = headless();
+        let status = app.status_message.clone();
+        send(
+            &mut app,
+            [
+                Message::Menu(MenuAction::ToggleRead),
+                Message::Menu(MenuAction::Archive),
+                Message::Menu(MenuAction::Trash),
+            ],
+        );
+        assert_eq!(app.status_message, status);
+        assert!(app.pending_move_intents.is_empty());
+        assert!(app.pending_flag_intents.is_empty());
+    }
+
+    #[test]
+    fn smart_view_without_cache_says_so() {
+        let mut app = headless();
+        send(&mut app, [Message::OpenSmartView(sample_starred_view())]);
+        assert_eq!(app.smart_view, Some(sample_starred_view()));
+        assert_eq!(app.selected_folder, None);
+        assert_eq!(app.status_message, "Smart views need the local cache");
+    }
+
+    #[test]
+    fn stale_smart_view_result_is_dropped() {
+        let mut app = headless();
+        app.smart_view = Some(sample_starred_view());
+        app.smart_view_epoch = 2;
+        send(
+            &mut app,
+            [Message::SmartViewLoaded {
+                epoch: 1,
+                result: Err("late".into()),
+            }],
+        );
+        assert_eq!(app.stale_apply_drop_count, 1);
+        assert_eq!(app.error_surface, None);
+
+        send(
+            &mut app,
+            [Message::SmartViewLoaded {
+                epoch: 2,
+                result: Ok(Vec::new()),
+            }],
+        );
+        assert_eq!(app.stale_apply_drop_count, 1);
+        assert_eq!(app.phase, Phase::Idle);
+        assert_eq!(app.status_message, "Starred: 0 messages");
+    }
+
+    #[test]
+    fn unread_only_needs_an_open_folder() {
+        let mut app = headless();
+        send(&mut app, [Message::ToggleUnreadOnly]);
+        assert!(app.unread_only_folders.is_empty());
+        assert_eq!(app.unread_only(), None);
+    }
+
+    #[test]
+    fn dirty_compose_asks_before_closing() {
+        let mut app = headless();
+        send(
+            &mut app,
+            [
+                Message::ComposeNew,
+                Message::ComposeToChanged("alice@example.com".into()),
+                Message::ComposeCancel,
+            ],
+        );
+        assert_eq!(app.compose_phase, ComposePhase::ConfirmClose(CloseIntent::Cancel));
+
+        send(&mut app, [Message::ComposeKeepEditing]);
+        assert_eq!(app.compose_phase, ComposePhase::Open);
+
+        send(&mut app, [Message::ComposeCancel, Message::ComposeDiscard]);
+        assert_eq!(app.compose_phase, ComposePhase::Closed);
+    }
+
+    #[test]
+    fn mass_send_confirms_once_then_sends() {
+        let mut app = headless();
+        app.settings.confirm_recipients_over = 2;
+        send(
+            &mut app,
+            [
+                Message::ComposeNew,
+                Message::ComposeToChanged("a@example.com, b@example.com, c@example.com".into()),
+            ],
+        );
+        app.compose_body = text_editor::Content::with_text("Hello all");
+
+        send(&mut app, [Message::ComposeSend]);
+        assert_eq!(app.compose_phase, ComposePhase::ConfirmSend(3));
+
+        // Confirming goes on to send, which stops here with no account.
+        send(&mut app, [Message::ComposeSend]);
+        assert_eq!(app.compose_phase, ComposePhase::Open);
+        assert_eq!(app.compose_error.as_deref(), Some("No account selected"));
+    }
+}
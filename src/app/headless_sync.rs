@@ -0,0 +1,152 @@
+//! `neverlight-mail sync`: one sync pass with no window, meant for a user
+//! systemd timer (see `resources/systemd/`).
+//!
+//! Each account that isn't paused connects, refreshes its folder list and
+//! the head page of its Inbox into the cache, and announces unread arrivals
+//! with the same coalesced notification the GUI shows. The GUI then opens on
+//! a warm cache. An Inbox the cache has never seen is filled silently rather
+//! than announcing a page of old mail.
+
+use std::collections::HashSet;
+
+use neverlight_mail_core::config::AccountConfig;
+use neverlight_mail_core::session::JmapSession;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::notifications::{self, NewMailBatch};
+use super::recipients::display_name;
+use super::settings::FolderNotify;
+use super::AppSettings;
+
+/// Positions in `head` of unread messages missing from `known`. Nothing is
+/// new when `known` is empty (first sync of the folder).
+fn arrivals<'a>(
+    known: &HashSet<String>,
+    head: impl IntoIterator<Item = (&'a str, bool)>,
+) -> Vec<usize> {
+    if known.is_empty() {
+        return Vec::new();
+    }
+    head.into_iter()
+        .enumerate()
+        .filter(|(_, (email_id, is_read))| !is_read && !known.contains(*email_id))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Sync one account; returns how many new messages were announced.
+async fn sync_account(
+    config: &AccountConfig,
+    settings: &AppSettings,
+    cache: &CacheHandle,
+) -> Result<usize, String> {
+    let (_session, client) = JmapSession::connect(config)
+        .await
+        .map_err(|e| format!("connect: {e}"))?;
+    let folders = neverlight_mail_core::sync::sync_mailboxes(&client, cache, &config.id)
+        .await
+        .map_err(|e| format!("folders: {e}"))?;
+    let Some(inbox) = folders.iter().find(|f| f.role.as_deref() == Some("inbox")) else {
+        return Ok(0);
+    };
+    let (aid, mid) = (config.id.clone(), inbox.mailbox_id.clone());
+    let page_size = settings.page_size(&aid, &inbox.path);
+
+    let known: HashSet<String> = cache
+        .load_messages(aid.clone(), mid.clone(), page_size, 0)
+        .await?
+        .into_iter()
+        .map(|m| m.email_id)
+        .collect();
+    neverlight_mail_core::sync::sync_emails(&client, cache, &aid, &mid, page_size)
+        .await
+        .map_err(|e| format!("Inbox: {e}"))?;
+    let head = cache.load_messages(aid, mid, page_size, 0).await?;
+
+    let new = arrivals(&known, head.iter().map(|m| (m.email_id.as_str(), m.is_read)));
+    let Some(&first) = new.first() else {
+        return Ok(0);
+    };
+    let rule = settings.notifications.folder_rule(&config.id, &inbox.path);
+    if rule == FolderNotify::Muted {
+        return Ok(new.len());
+    }
+    let batch = NewMailBatch {
+        folder_label: notifications::folder_label(
+            &config.label,
+            &inbox.name,
+            settings.account_color(&config.id),
+        ),
+        senders: new.iter().map(|&i| display_name(&head[i].from)).collect(),
+        first_subject: head[first].subject.clone(),
+        rule,
+    };
+    let (summary, body) = batch.text(&settings.notifications);
+    tokio::task::spawn_blocking(move || notifications::show(summary, body, rule))
+        .await
+        .map_err(|e| format!("notification: {e}"))?;
+    Ok(new.len())
+}
+
+/// Run one pass over every account; the process exit code (1 when any
+/// account failed).
+pub fn run_headless_sync() -> i32 {
+    let accounts = match neverlight_mail_core::config::resolve_all_accounts() {
+        Ok(accounts) => accounts,
+        Err(_) => {
+            eprintln!("neverlight-mail sync: no usable account; set one up in the app first");
+            return 1;
+        }
+    };
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("neverlight-mail sync: {e}");
+            return 1;
+        }
+    };
+    let cache = match CacheHandle::open("cosmic") {
+        Ok(cache) => cache,
+        Err(e) => {
+            eprintln!("neverlight-mail sync: cannot open cache: {e}");
+            return 1;
+        }
+    };
+
+    let settings = AppSettings::load();
+    let now = chrono::Local::now().naive_local();
+    let mut failed = false;
+    for config in &accounts {
+        if settings.paused_accounts.contains(&config.id)
+            || settings.schedule_covers(&config.id, now)
+        {
+            log::info!("[{}] Paused; skipping", config.label);
+            continue;
+        }
+        match runtime.block_on(sync_account(config, &settings, &cache)) {
+            Ok(new) => log::info!("[{}] Synced, {} new", config.label, new),
+            Err(e) => {
+                eprintln!("neverlight-mail sync: {}: {e}", config.label);
+                failed = true;
+            }
+        }
+    }
+    i32::from(failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unseen_unread_messages_arrive() {
+        let known: HashSet<String> = ["M2".to_string()].into();
+        let head = [("M1", false), ("M2", false), ("M3", true), ("M4", false)];
+        assert_eq!(arrivals(&known, head), [0, 3]);
+    }
+
+    #[test]
+    fn first_sync_announces_nothing() {
+        assert!(arrivals(&HashSet::new(), [("M1", false)]).is_empty());
+    }
+}
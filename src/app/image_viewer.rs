@@ -0,0 +1,277 @@
+//! Full-window viewer for image attachments: wheel zoom and drag pan (the
+//! iced image viewer), quarter-turn rotation, next/previous across the
+//! message's images, a slideshow timer, and save.
+//!
+//! Each image is decoded at full resolution only when shown, in a blocking
+//! task; rotation is applied to the pixels there too, so turning a large
+//! photo never decodes on the UI thread.
+
+use cosmic::app::Task;
+use cosmic::widget::image::Handle;
+use neverlight_mail_core::models::AttachmentData;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::attachments::{read_entry, AttachmentEntry};
+use super::thumbnails::{rgba_handle, ThumbnailTarget};
+use super::{AppModel, Message};
+
+/// Seconds each image stays up during a slideshow.
+pub const SLIDESHOW_SECS: u64 = 4;
+
+pub struct ImageViewer {
+    pub target: ThumbnailTarget,
+    /// Attachment indices of every image in the message (or browser list).
+    pub images: Vec<usize>,
+    /// Position of the shown image in `images`.
+    pub position: usize,
+    pub filename: String,
+    /// Clockwise quarter turns, 0–3.
+    pub quarter_turns: u8,
+    pub slideshow: bool,
+    pub epoch: u64,
+    /// `None` while the current image is still decoding.
+    pub handle: Option<Handle>,
+    pub error: Option<String>,
+}
+
+impl ImageViewer {
+    /// "3 / 7" when there's more than one image.
+    pub fn counter(&self) -> Option<String> {
+        (self.images.len() > 1).then(|| format!("{} / {}", self.position + 1, self.images.len()))
+    }
+}
+
+/// Position after moving `delta` images, wrapping at both ends.
+fn step_position(position: usize, len: usize, delta: isize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    (position as isize + delta).rem_euclid(len as isize) as usize
+}
+
+fn rotate(img: image::DynamicImage, quarter_turns: u8) -> image::DynamicImage {
+    match quarter_turns % 4 {
+        1 => img.rotate90(),
+        2 => img.rotate180(),
+        3 => img.rotate270(),
+        _ => img,
+    }
+}
+
+fn decode_full(data: &[u8], quarter_turns: u8) -> Result<Handle, String> {
+    image::load_from_memory(data)
+        .map(|img| rgba_handle(rotate(img, quarter_turns)))
+        .map_err(|e| e.to_string())
+}
+
+/// Where the shown image's bytes come from.
+enum ViewerSource {
+    Loaded(Vec<u8>),
+    /// A browser entry, read back from the cache.
+    Cached(CacheHandle, AttachmentEntry),
+}
+
+impl AppModel {
+    pub(super) fn handle_image_viewer(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ViewImage { target, index } => {
+                let images = self.viewer_images(&target);
+                let Some(position) = images.iter().position(|&i| i == index) else {
+                    return Task::none();
+                };
+                self.image_viewer = Some(ImageViewer {
+                    target,
+                    images,
+                    position,
+                    filename: String::new(),
+                    quarter_turns: 0,
+                    slideshow: false,
+                    epoch: 0,
+                    handle: None,
+                    error: None,
+                });
+                return self.load_viewer_image();
+            }
+            Message::ViewerImageLoaded { epoch, result } => {
+                let Some(viewer) = self.image_viewer.as_mut().filter(|v| v.epoch == epoch) else {
+                    return Task::none();
+                };
+                match result {
+                    Ok(handle) => viewer.handle = Some(handle),
+                    Err(e) => {
+                        log::warn!("Full-size decode failed for {}: {}", viewer.filename, e);
+                        viewer.error = Some(format!("Couldn't open image: {e}"));
+                    }
+                }
+            }
+            Message::ViewerNext => return self.step_viewer(1),
+            Message::ViewerPrevious => return self.step_viewer(-1),
+            Message::ViewerRotate => {
+                let Some(viewer) = self.image_viewer.as_mut() else {
+                    return Task::none();
+                };
+                viewer.quarter_turns = (viewer.quarter_turns + 1) % 4;
+                return self.load_viewer_image();
+            }
+            Message::ViewerToggleSlideshow => {
+                if let Some(viewer) = self.image_viewer.as_mut() {
+                    viewer.slideshow = !viewer.slideshow && viewer.images.len() > 1;
+                }
+            }
+            Message::ViewerSave => {
+                let Some(viewer) = &self.image_viewer else {
+                    return Task::none();
+                };
+                let Some(&index) = viewer.images.get(viewer.position) else {
+                    return Task::none();
+                };
+                let save = match &viewer.target {
+                    ThumbnailTarget::Preview { .. } => Message::SaveAttachment(index),
+                    ThumbnailTarget::Conversation { email_id } => {
+                        Message::SaveConversationAttachment {
+                            email_id: email_id.clone(),
+                            index,
+                        }
+                    }
+                    ThumbnailTarget::Browser { .. } => Message::SaveBrowserAttachment(index),
+                };
+                return self.dispatch(save);
+            }
+            Message::CloseImageViewer => {
+                self.image_viewer = None;
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// Attachments of the message `target` shows; the browser holds no bytes.
+    fn viewer_attachments(&self, target: &ThumbnailTarget) -> Vec<&AttachmentData> {
+        match target {
+            ThumbnailTarget::Preview { .. } => self.preview_attachments.iter().collect(),
+            ThumbnailTarget::Conversation { email_id } => self
+                .conversation
+                .iter()
+                .find(|e| &e.email_id == email_id)
+                .map(|e| e.attachments.iter().collect())
+                .unwrap_or_default(),
+            ThumbnailTarget::Browser { .. } => Vec::new(),
+        }
+    }
+
+    fn browser_entries(&self, epoch: u64) -> &[AttachmentEntry] {
+        self.attachment_browser
+            .as_ref()
+            .filter(|b| b.epoch == epoch)
+            .map(|b| b.entries())
+            .unwrap_or_default()
+    }
+
+    /// Attachment indices of the images `target` shows.
+    fn viewer_images(&self, target: &ThumbnailTarget) -> Vec<usize> {
+        let is_image: Vec<bool> = match target {
+            ThumbnailTarget::Browser { epoch } => {
+                self.browser_entries(*epoch).iter().map(|e| e.is_image).collect()
+            }
+            _ => self
+                .viewer_attachments(target)
+                .iter()
+                .map(|a| a.is_image())
+                .collect(),
+        };
+        is_image
+            .iter()
+            .enumerate()
+            .filter(|(_, image)| **image)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// File name and bytes of attachment `index` in `target`.
+    fn viewer_source(
+        &self,
+        target: &ThumbnailTarget,
+        index: usize,
+    ) -> Option<(String, ViewerSource)> {
+        if let ThumbnailTarget::Browser { epoch } = target {
+            let entry = self.browser_entries(*epoch).get(index)?;
+            let cache = self.cache.clone()?;
+            return Some((entry.filename.clone(), ViewerSource::Cached(cache, entry.clone())));
+        }
+        let att = self.viewer_attachments(target).get(index).copied()?;
+        Some((att.filename.clone(), ViewerSource::Loaded(att.data.clone())))
+    }
+
+    fn step_viewer(&mut self, delta: isize) -> Task<Message> {
+        let Some(viewer) = self.image_viewer.as_mut() else {
+            return Task::none();
+        };
+        if viewer.images.len() < 2 {
+            return Task::none();
+        }
+        viewer.position = step_position(viewer.position, viewer.images.len(), delta);
+        viewer.quarter_turns = 0;
+        self.load_viewer_image()
+    }
+
+    /// Decode the viewer's current image (with its rotation) in the background.
+    fn load_viewer_image(&mut self) -> Task<Message> {
+        let Some(viewer) = &self.image_viewer else {
+            return Task::none();
+        };
+        let Some((filename, source)) = viewer
+            .images
+            .get(viewer.position)
+            .and_then(|&i| self.viewer_source(&viewer.target, i))
+        else {
+            // The message or browser list changed under the viewer.
+            self.image_viewer = None;
+            return Task::none();
+        };
+        let quarter_turns = viewer.quarter_turns;
+
+        self.viewer_epoch += 1;
+        let epoch = self.viewer_epoch;
+        if let Some(viewer) = self.image_viewer.as_mut() {
+            viewer.filename = filename;
+            viewer.epoch = epoch;
+            viewer.handle = None;
+            viewer.error = None;
+        }
+        cosmic::task::future(async move {
+            let data = match source {
+                ViewerSource::Loaded(data) => data,
+                ViewerSource::Cached(cache, entry) => match read_entry(&cache, &entry).await {
+                    Ok(att) => att.data,
+                    Err(e) => return Message::ViewerImageLoaded { epoch, result: Err(e) },
+                },
+            };
+            let result = tokio::task::spawn_blocking(move || decode_full(&data, quarter_turns))
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()));
+            Message::ViewerImageLoaded { epoch, result }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stepping_wraps_both_ways() {
+        assert_eq!(step_position(0, 3, 1), 1);
+        assert_eq!(step_position(2, 3, 1), 0);
+        assert_eq!(step_position(0, 3, -1), 2);
+        assert_eq!(step_position(0, 0, 1), 0);
+    }
+
+    #[test]
+    fn rotation_turns_clockwise() {
+        let img = image::DynamicImage::new_rgba8(4, 2);
+        let turned = rotate(img.clone(), 1);
+        assert_eq!((turned.width(), turned.height()), (2, 4));
+        let full = rotate(img, 4);
+        assert_eq!((full.width(), full.height()), (4, 2));
+    }
+}
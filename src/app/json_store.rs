@@ -0,0 +1,55 @@
+//! Reading and writing the small JSON files kept in the config and data
+//! directories: settings, window state, drafts and per-account bookkeeping.
+//!
+//! A missing file loads as `None`; one that doesn't parse is logged and
+//! ignored, so the caller starts from its defaults.
+
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The value stored at `path`, if there is one that parses.
+pub(super) fn load_json<T: DeserializeOwned>(path: Option<PathBuf>) -> Option<T> {
+    let path = path?;
+    let text = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("Ignoring unreadable {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Write `value` to `path`, creating its directory first.
+pub(super) fn save_json<T: Serialize + ?Sized>(
+    path: Option<PathBuf>,
+    value: &T,
+) -> Result<(), String> {
+    let path = path.ok_or("No config or data directory available")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_values_load_back_and_garbage_is_ignored() {
+        let dir = std::env::temp_dir().join(format!("neverlight-json-{}", std::process::id()));
+        let path = dir.join("nested").join("store.json");
+        save_json(Some(path.clone()), &vec![1u32, 2, 3]).unwrap();
+        assert_eq!(load_json::<Vec<u32>>(Some(path.clone())), Some(vec![1, 2, 3]));
+
+        std::fs::write(&path, "{not json").unwrap();
+        assert_eq!(load_json::<Vec<u32>>(Some(path.clone())), None);
+        assert_eq!(load_json::<Vec<u32>>(Some(dir.join("missing.json"))), None);
+        assert!(save_json(None, &1u32).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
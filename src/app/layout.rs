@@ -1,34 +1,117 @@
+//! Pane split ratios, saved to core's `LayoutConfig`, and the layout presets
+//! from settings.json that View → Next layout (`l`) cycles through.
+//!
+//! Cycling goes through each preset in turn and then back to the layout
+//! that was on screen before the first press; dragging a divider starts
+//! over. The applied ratios are saved like a dragged layout, so the last
+//! one shown is what the next launch opens with.
+
 use cosmic::widget::pane_grid;
 
 use neverlight_mail_core::config::LayoutConfig;
 
 use super::AppModel;
 
+/// Where a run of "Next layout" presses stands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutCycle {
+    /// Preset on screen.
+    pub preset: usize,
+    /// (sidebar, list) ratios from before the cycle, restored at its end.
+    pub before: (f32, f32),
+}
+
+/// Next step of a cycle through `presets` presets: the preset to show, or
+/// `None` to go back to the layout from before.
+fn next_preset(cycle: Option<LayoutCycle>, presets: usize) -> Option<usize> {
+    let next = cycle.map_or(0, |c| c.preset + 1);
+    (next < presets).then_some(next)
+}
+
 impl AppModel {
-    /// Extract current split ratios from pane_grid layout tree and persist.
-    pub(super) fn save_layout(&self) {
-        fn extract_ratios(node: &pane_grid::Node) -> (f32, f32) {
-            match node {
-                pane_grid::Node::Split { ratio, a, b, .. } => {
-                    let sidebar_ratio = *ratio;
-                    // Inner split is in the 'b' branch
-                    let list_ratio = match b.as_ref() {
-                        pane_grid::Node::Split { ratio, .. } => *ratio,
-                        _ => 0.40,
-                    };
-                    // If 'a' is also a split (shouldn't be, but be safe), recurse
-                    let _ = a;
-                    (sidebar_ratio, list_ratio)
-                }
-                _ => (0.15, 0.40),
+    /// Current (sidebar, list) split ratios.
+    fn layout_ratios(&self) -> (f32, f32) {
+        match self.panes.layout() {
+            pane_grid::Node::Split { ratio, b, .. } => {
+                let sidebar_ratio = *ratio;
+                // Inner split is in the 'b' branch
+                let list_ratio = match b.as_ref() {
+                    pane_grid::Node::Split { ratio, .. } => *ratio,
+                    _ => 0.40,
+                };
+                (sidebar_ratio, list_ratio)
             }
+            _ => (0.15, 0.40),
         }
+    }
 
-        let (sidebar_ratio, list_ratio) = extract_ratios(self.panes.layout());
+    /// Extract current split ratios from pane_grid layout tree and persist.
+    pub(super) fn save_layout(&self) {
+        let (sidebar_ratio, list_ratio) = self.layout_ratios();
         let layout = LayoutConfig {
             sidebar_ratio,
             list_ratio,
         };
         layout.save();
     }
+
+    fn apply_layout_ratios(&mut self, (sidebar_ratio, list_ratio): (f32, f32)) {
+        let pane_grid::Node::Split { id: outer, b, .. } = self.panes.layout() else {
+            return;
+        };
+        let (outer, inner) = match b.as_ref() {
+            pane_grid::Node::Split { id: inner, .. } => (*outer, Some(*inner)),
+            _ => (*outer, None),
+        };
+        self.panes.resize(outer, sidebar_ratio);
+        if let Some(inner) = inner {
+            self.panes.resize(inner, list_ratio);
+        }
+        self.save_layout();
+    }
+
+    /// Show the next layout preset, or the layout from before the cycle
+    /// once every preset has had its turn.
+    pub(super) fn cycle_layout(&mut self) {
+        let presets = &self.settings.layout_presets;
+        if presets.is_empty() {
+            self.status_message = "No layout presets in settings.json".into();
+            return;
+        }
+        match next_preset(self.layout_cycle, presets.len()) {
+            Some(preset) => {
+                let ratios = presets[preset].ratios();
+                self.status_message = format!("Layout: {}", presets[preset].name);
+                let before = self.layout_cycle.map_or_else(|| self.layout_ratios(), |c| c.before);
+                self.layout_cycle = Some(LayoutCycle { preset, before });
+                self.apply_layout_ratios(ratios);
+            }
+            None => {
+                let Some(cycle) = self.layout_cycle.take() else {
+                    return;
+                };
+                self.status_message = "Layout: as before".into();
+                self.apply_layout_ratios(cycle.before);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_walks_the_presets_then_returns() {
+        let at = |preset| {
+            Some(LayoutCycle {
+                preset,
+                before: (0.15, 0.40),
+            })
+        };
+        assert_eq!(next_preset(None, 2), Some(0));
+        assert_eq!(next_preset(at(0), 2), Some(1));
+        assert_eq!(next_preset(at(1), 2), None);
+        assert_eq!(next_preset(None, 0), None);
+    }
 }
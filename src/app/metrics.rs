@@ -0,0 +1,196 @@
+//! Local-only health metrics for the diagnostics panel.
+//!
+//! Timings are recorded from inside task futures, so the collector is shared
+//! behind an `Arc` (like the backfill pause flag) rather than routed through
+//! `Message`. Nothing leaves the process; the panel is the only consumer.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::AppModel;
+
+/// Samples kept per metric.
+const WINDOW: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MetricKind {
+    /// `sync_mailboxes` / `Mailbox/get` round trip.
+    FolderSync,
+    /// `sync_emails` head-page sync.
+    MessageSync,
+    /// `Email/get` body fetch.
+    BodyFetch,
+    /// SQLite reads (message pages, cached bodies).
+    CacheQuery,
+}
+
+impl MetricKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::FolderSync => "Folder sync",
+            Self::MessageSync => "Message sync",
+            Self::BodyFetch => "Body fetch",
+            Self::CacheQuery => "Cache query",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricStats {
+    pub count: usize,
+    pub last: Duration,
+    pub avg: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl MetricStats {
+    fn from_samples(samples: &VecDeque<Duration>) -> Option<Self> {
+        let last = *samples.back()?;
+        let mut sorted: Vec<Duration> = samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        let total: Duration = sorted.iter().sum();
+        // Nearest-rank percentile.
+        let p95_rank = (count * 95).div_ceil(100).max(1);
+        Some(Self {
+            count,
+            last,
+            avg: total / count as u32,
+            p95: sorted[p95_rank - 1],
+            max: sorted[count - 1],
+        })
+    }
+
+    /// "last 120 ms · avg 95 · p95 240 (50)".
+    pub fn summary(&self) -> String {
+        format!(
+            "last {} ms · avg {} · p95 {} ({})",
+            self.last.as_millis(),
+            self.avg.as_millis(),
+            self.p95.as_millis(),
+            self.count
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct HealthMetrics {
+    samples: Mutex<HashMap<MetricKind, VecDeque<Duration>>>,
+}
+
+impl HealthMetrics {
+    pub fn record(&self, kind: MetricKind, elapsed: Duration) {
+        let Ok(mut samples) = self.samples.lock() else {
+            return;
+        };
+        let window = samples.entry(kind).or_default();
+        if window.len() == WINDOW {
+            window.pop_front();
+        }
+        window.push_back(elapsed);
+    }
+
+    /// Await `fut`, recording how long it took.
+    pub async fn timed<F: Future>(&self, kind: MetricKind, fut: F) -> F::Output {
+        let started = Instant::now();
+        let output = fut.await;
+        self.record(kind, started.elapsed());
+        output
+    }
+
+    /// Stats for every metric with at least one sample, in `MetricKind` order.
+    pub fn snapshot(&self) -> Vec<(MetricKind, MetricStats)> {
+        let Ok(samples) = self.samples.lock() else {
+            return Vec::new();
+        };
+        let mut out: Vec<(MetricKind, MetricStats)> = samples
+            .iter()
+            .filter_map(|(kind, window)| Some((*kind, MetricStats::from_samples(window)?)))
+            .collect();
+        out.sort_by_key(|(kind, _)| *kind);
+        out
+    }
+}
+
+impl AppModel {
+    /// Attachment bytes currently held for the preview, conversation, and
+    /// compose dialog. The attachments browser keeps only metadata.
+    pub(super) fn attachment_bytes_in_memory(&self) -> usize {
+        let preview: usize = self.preview_attachments.iter().map(|a| a.data.len()).sum();
+        let conversation: usize = self
+            .conversation
+            .iter()
+            .flat_map(|e| &e.attachments)
+            .map(|a| a.data.len())
+            .sum();
+        let compose: usize = self.compose_attachments.iter().map(|a| a.data.len()).sum();
+        preview + conversation + compose
+    }
+}
+
+/// "3.4 MB" style size for the attachment memory line.
+pub fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn stats_cover_last_avg_p95_and_max() {
+        let metrics = HealthMetrics::default();
+        for n in 1..=20 {
+            metrics.record(MetricKind::BodyFetch, ms(n * 10));
+        }
+        metrics.record(MetricKind::BodyFetch, ms(5));
+        let snapshot = metrics.snapshot();
+        let (kind, stats) = snapshot[0];
+        assert_eq!(kind, MetricKind::BodyFetch);
+        assert_eq!(stats.count, 21);
+        assert_eq!(stats.last, ms(5));
+        assert_eq!(stats.max, ms(200));
+        assert_eq!(stats.p95, ms(190));
+        assert_eq!(stats.avg, ms(2105) / 21);
+    }
+
+    #[test]
+    fn window_drops_oldest_samples() {
+        let metrics = HealthMetrics::default();
+        metrics.record(MetricKind::CacheQuery, ms(1_000));
+        for _ in 0..WINDOW {
+            metrics.record(MetricKind::CacheQuery, ms(1));
+        }
+        let (_, stats) = metrics.snapshot()[0];
+        assert_eq!(stats.count, WINDOW);
+        assert_eq!(stats.max, ms(1));
+    }
+
+    #[test]
+    fn snapshot_is_ordered_and_skips_empty_metrics() {
+        let metrics = HealthMetrics::default();
+        metrics.record(MetricKind::CacheQuery, ms(3));
+        metrics.record(MetricKind::FolderSync, ms(40));
+        let kinds: Vec<MetricKind> = metrics.snapshot().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(kinds, vec![MetricKind::FolderSync, MetricKind::CacheQuery]);
+    }
+
+    #[test]
+    fn bytes_use_binary_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 * 1024 * 1024 + 400 * 1024), "3.4 MB");
+    }
+}
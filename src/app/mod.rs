@@ -1,35 +1,129 @@
+mod account_groups;
 mod accounts;
 mod actions;
+mod aliases;
+mod attachment_scan;
+mod attachment_save;
+mod attachments;
+mod autocomplete;
 mod backfill;
 mod body;
+mod charset;
+mod cleanup;
+mod compare;
 mod compose;
+mod compose_template;
+mod connect_order;
+mod detected_event;
+mod drafts;
+mod external_editor;
+mod flag_conflicts;
+mod flowed;
+mod folder_activity;
+mod folder_read;
+mod folder_usage;
+#[cfg(test)]
+mod harness;
+mod freshness;
+mod headless_sync;
+mod image_viewer;
+mod json_store;
 mod layout;
+mod metrics;
 mod navigation;
+mod notifications;
+mod paged_markdown;
+mod participants;
+mod pause;
+mod prefetch;
+mod quick_look;
+mod quote_tools;
+mod recipients;
+mod row_labels;
+mod row_menu;
+mod saved_passwords;
 mod search;
+mod selection;
+mod send_rejects;
+mod send_size;
+mod settings;
+mod settings_transfer;
 mod setup;
+mod shortcuts;
+mod signatures;
+mod smart_views;
+mod snippets;
+mod startup;
 mod sync;
 mod sync_apply;
+mod sync_state;
+mod thread_overrides;
+mod template_vars;
+mod text_direction;
+mod thread_collapse;
+mod thread_trash;
+mod thumbnails;
+mod toasts;
+mod triage;
 mod types;
+mod visual_mode;
 mod watch;
+mod window_state;
 
+pub use account_groups::AccountSection;
+pub use attachment_scan::AttachmentScans;
+pub use attachments::{
+    attachment_risk, scan_summary, AttachmentBrowser, AttachmentScope, BrowserState,
+};
+pub use charset::ENCODING_LABELS;
+pub use cleanup::{CleanupAction, CleanupMatch, CleanupStage, CleanupWizard};
+pub use compare::{CompareBody, ComparePane, CompareSide, Comparison, ScrollSync};
+pub use compose_template::{LaunchOptions, USAGE};
+pub use detected_event::DetectedEvent;
+pub use flag_conflicts::recent_conflicts;
+pub use folder_activity::FolderActivity;
+pub use freshness::{synced_ago_label, FolderSyncTimes, Freshness};
+pub use headless_sync::run_headless_sync;
+pub use image_viewer::ImageViewer;
+pub use metrics::{format_bytes, MetricKind, MetricStats};
+pub use paged_markdown::PagedMarkdown;
+pub use aliases::plus_alias;
+pub use participants::{participants, shows_entry, PARTICIPANT_FILTER_MIN_MESSAGES};
+pub use quick_look::{QuickLook, QuickLookBody};
+pub use quote_tools::QuoteTool;
+pub use recipients::{external_warning, ReplyChoice};
+pub use row_labels::RowLabels;
+pub use row_menu::{RowAction, RowMenuAction};
+pub use selection::MessageSelection;
+pub use send_size::SendSize;
+pub use settings::{init_logging, AccountColor, AppSettings, FolderNotify, ReplyTarget};
+pub use shortcuts::{MenuAction, ShortcutContext};
+pub use smart_views::{SmartView, SmartViewKind};
+pub use sync_state::FolderSyncState;
+pub use template_vars::VariableFill;
+pub use text_direction::{detect_direction, TextDirection};
+pub use thumbnails::ThumbnailTarget;
+pub use triage::TriageAction;
+pub use visual_mode::VisualAction;
 pub use types::*;
-
-use std::collections::{HashMap, HashSet};
+pub use window_state::{WindowState, MIN_HEIGHT, MIN_WIDTH};
 
 use cosmic::app::{Core, Task};
 use cosmic::iced::keyboard;
 use cosmic::iced::{Event, Length, Subscription};
 use cosmic::widget;
-use cosmic::widget::{pane_grid, text_editor};
+use cosmic::widget::pane_grid;
 use cosmic::Element;
 
-use neverlight_mail_core::config::{AccountConfig, ConfigNeedsInput, LayoutConfig};
+use neverlight_mail_core::config::{AccountConfig, ConfigNeedsInput};
 use neverlight_mail_core::session::JmapSession;
 use neverlight_mail_core::setup::SetupModel;
 use neverlight_mail_core::store::CacheHandle;
 
 use crate::dnd_models::DraggedFiles;
-use crate::ui::compose_dialog::ComposeMode;
+
+use self::startup::StoredState;
+use self::toasts::Severity;
 
 /// Connect to an account via JMAP session discovery.
 fn connect_account(config: AccountConfig, account_id: String) -> Task<Message> {
@@ -51,7 +145,7 @@ fn connect_account(config: AccountConfig, account_id: String) -> Task<Message> {
 
 impl cosmic::Application for AppModel {
     type Executor = cosmic::executor::Default;
-    type Flags = ();
+    type Flags = LaunchOptions;
     type Message = Message;
 
     const APP_ID: &'static str = APP_ID;
@@ -64,7 +158,7 @@ impl cosmic::Application for AppModel {
         &mut self.core
     }
 
-    fn init(core: Core, _flags: Self::Flags) -> (Self, Task<Self::Message>) {
+    fn init(core: Core, flags: Self::Flags) -> (Self, Task<Self::Message>) {
         // Open cache synchronously (just opens a file, fast)
         let cache = match CacheHandle::open("cosmic") {
             Ok(c) => {
@@ -77,119 +171,24 @@ impl cosmic::Application for AppModel {
             }
         };
 
-        let layout = LayoutConfig::load();
-        let pane_config = pane_grid::Configuration::Split {
-            axis: pane_grid::Axis::Vertical,
-            ratio: layout.sidebar_ratio,
-            a: Box::new(pane_grid::Configuration::Pane(PaneKind::Sidebar)),
-            b: Box::new(pane_grid::Configuration::Split {
-                axis: pane_grid::Axis::Vertical,
-                ratio: layout.list_ratio,
-                a: Box::new(pane_grid::Configuration::Pane(PaneKind::MessageList)),
-                b: Box::new(pane_grid::Configuration::Pane(PaneKind::MessageView)),
-            }),
-        };
-        let panes = pane_grid::State::with_configuration(pane_config);
-
-        let mut app = AppModel {
-            core,
-            accounts: Vec::new(),
-            active_account: None,
-            cache: cache.clone(),
-            selected_folder: None,
-            selected_mailbox_id: None,
-            selected_folder_evicted: false,
-            messages: Vec::new(),
-            selected_message: None,
-            messages_offset: 0,
-            has_more_messages: false,
-            preview_body: String::new(),
-            preview_markdown: Vec::new(),
-            preview_attachments: Vec::new(),
-            preview_image_handles: Vec::new(),
-            conversation: Vec::new(),
-            active_conversation_id: None,
-            collapsed_threads: HashSet::new(),
-            visible_indices: Vec::new(),
-            thread_sizes: HashMap::new(),
-            pending_move_restore: HashMap::new(),
-            pending_flag_epochs: HashMap::new(),
-            pending_move_epochs: HashMap::new(),
-            search_abort: None,
-            folder_abort: None,
-            message_abort: None,
-            body_abort: None,
-            status_message: "Starting up...".into(),
-            error_surface: None,
-            phase: Phase::Loading,
-            folder_epoch: 0,
-            message_epoch: 0,
-            search_epoch: 0,
-            refresh_epoch: 0,
-            mutation_epoch: 0,
-            flag_epoch: 0,
-            body_epoch: 0,
-            refresh_phase: RefreshPhase::Idle,
-            refresh_accounts_outstanding: HashSet::new(),
-            refresh_started_at: None,
-            refresh_pending_after_sync: false,
-            mutation_in_flight_accounts: HashSet::new(),
-            flag_in_flight_accounts: HashSet::new(),
-            pending_move_intents: HashMap::new(),
-            pending_flag_intents: HashMap::new(),
-            notified_messages: HashSet::new(),
-            stale_apply_drop_count: 0,
-            toc_drift_count: 0,
-            postcondition_failure_count: 0,
-            refresh_timeout_count: 0,
-            refresh_stuck_count: 0,
-            reconnect_count: 0,
-            last_sync_at: None,
-            last_refresh_at: None,
-
-            search_phase: SearchPhase::Inactive,
-            search_query: String::new(),
-
-            compose_phase: ComposePhase::Closed,
-            compose_mode: ComposeMode::New,
-            compose_account: 0,
-            compose_from: 0,
-            compose_to: String::new(),
-            compose_subject: String::new(),
-            compose_body: text_editor::Content::new(),
-            compose_in_reply_to: None,
-            compose_references: None,
-            compose_attachments: Vec::new(),
-            compose_error: None,
-            compose_drag_hover: false,
-            compose_account_labels: Vec::new(),
-            compose_cached_from: Vec::new(),
-
-            setup_model: None,
-            setup_password_visible: false,
-            confirm_delete_account_id: None,
-            oauth_phase: OAuthSetupPhase::Inactive,
-            oauth_error: None,
-
-            folder_drag_target: None,
-            pending_body: None,
-            body_defer_retries: 0,
-            auto_read_suppressed: false,
-
-            panes,
-            diagnostics_collapsed: true,
-        };
+        let mut app = AppModel::new(core, cache.clone(), StoredState::load());
 
         let title_task = app.set_window_title("Nevermail".into());
-        let mut tasks = vec![title_task];
+        let mut tasks = vec![title_task, app.restore_window_state()];
 
         // Resolve config: env → file+keyring → show dialog
         match neverlight_mail_core::config::resolve_all_accounts() {
             Ok(account_configs) => {
+                let now = chrono::Local::now().naive_local();
+                let mut connecting = Vec::new();
                 for ac in account_configs {
                     let account_id = ac.id.clone();
+                    let paused = app.pause_wanted(&account_id, now);
                     let mut acct = AccountState::new(ac.clone());
-                    acct.conn_state = ConnectionState::Connecting;
+                    acct.paused = paused;
+                    if !paused {
+                        acct.conn_state = ConnectionState::Connecting;
+                    }
                     app.accounts.push(acct);
 
                     // Load cached folders for this account
@@ -204,9 +203,26 @@ impl cosmic::Application for AppModel {
                         }));
                     }
 
-                    // Start connecting
-                    let aid = account_id.clone();
-                    tasks.push(connect_account(ac, aid));
+                    // Paused accounts connect on resume
+                    if !paused {
+                        connecting.push(ac);
+                    }
+                }
+
+                // The last-used account opens and connects first; the rest
+                // are staggered behind it.
+                app.active_account =
+                    app.last_account.as_deref().and_then(|id| app.account_index(id));
+                let ids: Vec<&str> = connecting.iter().map(|ac| ac.id.as_str()).collect();
+                let order = connect_order::connect_order(&ids, app.last_account.as_deref());
+                for (position, i) in order.into_iter().enumerate() {
+                    let ac = connecting[i].clone();
+                    let account_id = ac.id.clone();
+                    tasks.push(if position == 0 {
+                        connect_account(ac, account_id)
+                    } else {
+                        Self::deferred_connect(account_id, connect_order::connect_delay(position))
+                    });
                 }
                 if app.accounts.is_empty() {
                     app.setup_model =
@@ -225,9 +241,33 @@ impl cosmic::Application for AppModel {
             }
         }
 
+        if let LaunchOptions::Compose(template) = flags {
+            tasks.push(app.open_compose_template(template));
+        }
+
         (app, cosmic::task::batch(tasks))
     }
 
+    fn header_start(&self) -> Vec<Element<'_, Self::Message>> {
+        let move_targets: Vec<&str> = self
+            .selected_message
+            .and_then(|i| self.messages.get(i))
+            .and_then(|msg| self.accounts.iter().find(|a| a.config.id == msg.account_id))
+            .map(|a| a.folders.iter().map(|f| f.path.as_str()).collect())
+            .unwrap_or_default();
+        vec![crate::ui::menu_bar::view(
+            &self.key_binds,
+            &self.settings,
+            self.open_folder_notify(),
+            self.unread_only(),
+            &move_targets,
+        )]
+    }
+
+    fn on_close_requested(&self, _id: cosmic::iced::window::Id) -> Option<Self::Message> {
+        Some(Message::WindowCloseRequested)
+    }
+
     fn dialog(&self) -> Option<Element<'_, Self::Message>> {
         if let Some(account_id) = &self.confirm_delete_account_id {
             let label = self
@@ -253,21 +293,122 @@ impl cosmic::Application for AppModel {
         if self.setup_model.is_some() {
             return Some(self.setup_dialog());
         }
+        if let Some(wizard) = &self.cleanup {
+            return Some(crate::ui::cleanup_dialog::view(wizard));
+        }
+        if let Some(confirm) = &self.trash_confirm {
+            let replies = match confirm.replies {
+                1 => "1 reply references this message".to_string(),
+                n => format!("{n} replies reference this message"),
+            };
+            let dialog = widget::dialog()
+                .title("Trash a message with replies?")
+                .body(format!(
+                    "{replies}. Trashing it alone leaves the rest of the conversation \
+                     without it."
+                ))
+                .primary_action(
+                    widget::button::destructive("Trash thread")
+                        .on_press(Message::TrashConfirmThread),
+                )
+                .secondary_action(
+                    widget::button::standard("Cancel").on_press(Message::TrashConfirmCancel),
+                )
+                .tertiary_action(
+                    widget::button::text("Trash message only")
+                        .on_press(Message::TrashConfirmMessage),
+                );
+            return Some(dialog.into());
+        }
+        if let ComposePhase::ConfirmClose(intent) = self.compose_phase {
+            let discard_label = match intent {
+                CloseIntent::Cancel => "Discard",
+                CloseIntent::Quit => "Discard and quit",
+            };
+            let dialog = widget::dialog()
+                .title("Unsaved message")
+                .body("This message has unsaved changes. Save it as a local draft?")
+                .primary_action(
+                    widget::button::suggested("Save draft").on_press(Message::ComposeSaveDraft),
+                )
+                .secondary_action(
+                    widget::button::standard("Keep editing").on_press(Message::ComposeKeepEditing),
+                )
+                .tertiary_action(
+                    widget::button::destructive(discard_label).on_press(Message::ComposeDiscard),
+                );
+            return Some(dialog.into());
+        }
+        if let ComposePhase::ConfirmSend(count) = self.compose_phase {
+            let dialog = widget::dialog()
+                .title(format!("Send to {count} recipients?"))
+                .body(format!(
+                    "This message goes to {count} people. Check that everyone on the To line should get it."
+                ))
+                .primary_action(
+                    widget::button::suggested("Send").on_press(Message::ComposeSend),
+                )
+                .secondary_action(
+                    widget::button::standard("Keep editing").on_press(Message::ComposeKeepEditing),
+                );
+            return Some(dialog.into());
+        }
+        if let Some(risky) = &self.risky_attachment {
+            let files: Vec<String> = risky
+                .risks
+                .iter()
+                .map(|(name, reason)| format!("{name}: {reason}"))
+                .collect();
+            let dialog = widget::dialog()
+                .title("Potentially dangerous attachment")
+                .body(format!(
+                    "{}\n\nFiles like this can run code on your computer. Only continue if you trust the sender and expected it.",
+                    files.join("\n")
+                ))
+                .primary_action(
+                    widget::button::standard("Cancel").on_press(Message::RiskyAttachmentCancel),
+                )
+                .secondary_action(
+                    widget::button::destructive(format!("{} anyway", risky.verb()))
+                        .on_press(Message::RiskyAttachmentConfirm),
+                );
+            return Some(dialog.into());
+        }
+        if let Some(look) = &self.quick_look {
+            return Some(crate::ui::quick_look::view(look));
+        }
+        if self.show_shortcuts {
+            let sheet = shortcuts::cheat_sheet(&self.settings.quick_look_key);
+            return Some(crate::ui::shortcuts_dialog::view(sheet));
+        }
         if self.compose_phase.is_open() {
+            let recipient_count = recipients::recipient_count(&self.compose_to);
             return Some(crate::ui::compose_dialog::view(
                 crate::ui::compose_dialog::ComposeViewState {
                     mode: &self.compose_mode,
                     account_labels: &self.compose_account_labels,
                     selected_account: self.compose_account,
                     from_addresses: &self.compose_cached_from,
+                    external_recipients: &self.compose_external_recipients,
+                    recipient_count,
+                    over_recipient_limit: self.settings.confirm_send_to(recipient_count),
+                    suggestions: &self.compose_suggestions,
+                    reply_choice: self.compose_reply_choice.as_ref(),
+                    send_size: self.compose_send_size(),
+                    variables: self.compose_variables(),
                     from_selected: self.compose_from,
+                    signature_labels: &self.compose_signatures.labels,
+                    signature_selected: self.compose_signatures.selected,
                     to: &self.compose_to,
                     subject: &self.compose_subject,
                     body: &self.compose_body,
                     attachments: &self.compose_attachments,
                     error: self.compose_error.as_deref(),
+                    rejected: &self.compose_rejected,
                     is_sending: self.compose_phase == ComposePhase::Sending,
                     drag_hover: self.compose_drag_hover,
+                    editing_externally: self.compose_external_edit.is_some(),
+                    can_send_and_archive: self.compose_reply_source.is_some(),
                 },
             ));
         }
@@ -277,7 +418,203 @@ impl cosmic::Application for AppModel {
     fn subscription(&self) -> Subscription<Self::Message> {
         let mut subs = Vec::new();
 
-        if self.search_phase.is_focused() {
+        // Window geometry, remembered for the next launch.
+        subs.push(cosmic::iced_futures::event::listen_raw(|event, _, _| match event {
+            Event::Window(cosmic::iced::window::Event::Resized(size)) => {
+                Some(Message::WindowResized(size))
+            }
+            Event::Window(cosmic::iced::window::Event::Moved(point)) => {
+                Some(Message::WindowMoved(point))
+            }
+            _ => None,
+        }));
+
+        if self.show_shortcuts {
+            // Cheat sheet open: "?" or Escape closes it, nothing else fires.
+            subs.push(cosmic::iced_futures::event::listen_raw(
+                |event, status, _| {
+                    if cosmic::iced_core::event::Status::Ignored != status {
+                        return None;
+                    }
+                    let Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) = event else {
+                        return None;
+                    };
+                    match key {
+                        keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                            Some(Message::ToggleShortcuts)
+                        }
+                        keyboard::Key::Character(ref c) if c.as_str() == "?" => {
+                            Some(Message::ToggleShortcuts)
+                        }
+                        _ => None,
+                    }
+                },
+            ));
+        } else if self.quick_look.is_some() {
+            // Quick look: its key or Escape closes it, the list stays put.
+            subs.push(cosmic::iced_futures::event::listen_raw(
+                |event, status, _| {
+                    if cosmic::iced_core::event::Status::Ignored != status {
+                        return None;
+                    }
+                    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event
+                    else {
+                        return None;
+                    };
+                    match key {
+                        keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                            Some(Message::CloseQuickLook)
+                        }
+                        keyboard::Key::Character(ref c)
+                            if !modifiers.control() && !modifiers.alt() =>
+                        {
+                            Some(Message::QuickLookKey(c.to_string()))
+                        }
+                        _ => None,
+                    }
+                },
+            ));
+        } else if self.image_viewer.is_some() {
+            // The viewer covers the panes; its keys replace the list shortcuts.
+            subs.push(cosmic::iced_futures::event::listen_raw(
+                |event, status, _| {
+                    if cosmic::iced_core::event::Status::Ignored != status {
+                        return None;
+                    }
+                    let Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) = event else {
+                        return None;
+                    };
+                    match key {
+                        keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                            Some(Message::CloseImageViewer)
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::ArrowRight) => {
+                            Some(Message::ViewerNext)
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::ArrowLeft) => {
+                            Some(Message::ViewerPrevious)
+                        }
+                        keyboard::Key::Character(ref c) if c.as_str() == "r" => {
+                            Some(Message::ViewerRotate)
+                        }
+                        keyboard::Key::Character(ref c) if c.as_str() == " " => {
+                            Some(Message::ViewerToggleSlideshow)
+                        }
+                        _ => None,
+                    }
+                },
+            ));
+            if let Some(viewer) = self.image_viewer.as_ref().filter(|v| v.slideshow) {
+                subs.push(Subscription::run_with_id(
+                    format!("slideshow-{}", viewer.epoch),
+                    cosmic::iced_futures::stream::channel(1, |mut output| async move {
+                        use futures::SinkExt;
+                        let period = std::time::Duration::from_secs(image_viewer::SLIDESHOW_SECS);
+                        let mut interval = tokio::time::interval(period);
+                        interval.tick().await; // skip immediate first tick
+                        loop {
+                            interval.tick().await;
+                            let _ = output.send(Message::ViewerNext).await;
+                        }
+                    }),
+                ));
+            }
+        } else if self.comparison.is_some() {
+            // The comparison covers the panes; Escape closes it.
+            subs.push(cosmic::iced_futures::event::listen_raw(
+                |event, status, _| {
+                    if cosmic::iced_core::event::Status::Ignored != status {
+                        return None;
+                    }
+                    match event {
+                        Event::Keyboard(keyboard::Event::KeyPressed {
+                            key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                            ..
+                        }) => Some(Message::CloseComparison),
+                        _ => None,
+                    }
+                },
+            ));
+        } else if self.triage.is_some() {
+            // Triage: the list is all there is, and these keys drive it.
+            subs.push(cosmic::iced_futures::event::listen_raw(
+                |event, status, _| {
+                    if cosmic::iced_core::event::Status::Ignored != status {
+                        return None;
+                    }
+                    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event
+                    else {
+                        return None;
+                    };
+                    if modifiers.control() || modifiers.alt() {
+                        return None;
+                    }
+                    match key {
+                        keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                            Some(Message::ToggleTriage)
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+                            Some(Message::SelectionDown)
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+                            Some(Message::SelectionUp)
+                        }
+                        keyboard::Key::Character(ref c) => match c.as_str() {
+                            "j" => Some(Message::SelectionDown),
+                            "k" => Some(Message::SelectionUp),
+                            "e" => Some(Message::Triage(TriageAction::Archive)),
+                            "#" => Some(Message::Triage(TriageAction::Trash)),
+                            "s" => Some(Message::Triage(TriageAction::Star)),
+                            "d" => Some(Message::Triage(TriageAction::Defer)),
+                            "t" => Some(Message::ToggleTriage),
+                            "?" => Some(Message::ToggleShortcuts),
+                            _ => None,
+                        },
+                        _ => None,
+                    }
+                },
+            ));
+        } else if self.visual.is_some() {
+            // Visual mode: j/k stretch the range, these keys act on it.
+            subs.push(cosmic::iced_futures::event::listen_raw(
+                |event, status, _| {
+                    if cosmic::iced_core::event::Status::Ignored != status {
+                        return None;
+                    }
+                    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event
+                    else {
+                        return None;
+                    };
+                    if modifiers.control() || modifiers.alt() {
+                        return None;
+                    }
+                    match key {
+                        keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                            Some(Message::Visual(VisualAction::Cancel))
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+                            Some(Message::SelectionDown)
+                        }
+                        keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+                            Some(Message::SelectionUp)
+                        }
+                        keyboard::Key::Character(ref c) => match c.as_str() {
+                            "j" => Some(Message::SelectionDown),
+                            "k" => Some(Message::SelectionUp),
+                            "V" | "v" if modifiers.shift() => {
+                                Some(Message::Visual(VisualAction::Toggle))
+                            }
+                            "e" => Some(Message::Visual(VisualAction::Archive)),
+                            "#" => Some(Message::Visual(VisualAction::Trash)),
+                            "s" => Some(Message::Visual(VisualAction::Star)),
+                            "?" => Some(Message::ToggleShortcuts),
+                            _ => None,
+                        },
+                        _ => None,
+                    }
+                },
+            ));
+        } else if self.search_phase.is_focused() {
             // When search input has focus, only intercept Escape.
             subs.push(cosmic::iced_futures::event::listen_raw(
                 |event, status, _| {
@@ -303,57 +640,67 @@ impl cosmic::Application for AppModel {
                     match event {
                         Event::Keyboard(keyboard::Event::KeyPressed {
                             key, modifiers, ..
-                        }) => match key {
-                            keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
-                                Some(Message::SelectionDown)
-                            }
-                            keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
-                                Some(Message::SelectionUp)
-                            }
-                            keyboard::Key::Named(keyboard::key::Named::Enter) => {
-                                Some(Message::ActivateSelection)
-                            }
-                            keyboard::Key::Character(ref c)
-                                if c.as_str() == "/" && !modifiers.control() =>
-                            {
-                                Some(Message::SearchActivate)
-                            }
-                            keyboard::Key::Character(ref c)
-                                if c.as_str() == "j" && !modifiers.control() =>
-                            {
-                                Some(Message::SelectionDown)
-                            }
-                            keyboard::Key::Character(ref c)
-                                if c.as_str() == "k" && !modifiers.control() =>
-                            {
-                                Some(Message::SelectionUp)
-                            }
-                            keyboard::Key::Character(ref c) if c.as_str() == " " => {
-                                Some(Message::ToggleThreadCollapse)
-                            }
-                            keyboard::Key::Character(ref c)
-                                if c.as_str() == "c" && !modifiers.control() =>
-                            {
-                                Some(Message::ComposeNew)
+                        }) => {
+                            if let Some(action) = shortcuts::action_for_key(&key, modifiers) {
+                                return Some(Message::Menu(action));
                             }
-                            keyboard::Key::Character(ref c)
-                                if c.as_str() == "r" && !modifiers.control() =>
-                            {
-                                Some(Message::ComposeReply)
+                            match key {
+                                keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
+                                    Some(Message::SelectionDown)
+                                }
+                                keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
+                                    Some(Message::SelectionUp)
+                                }
+                                keyboard::Key::Named(keyboard::key::Named::Enter) => {
+                                    Some(Message::ActivateSelection)
+                                }
+                                // Not in the binding table: "/" and "?" need Shift
+                                // on many layouts.
+                                keyboard::Key::Character(ref c)
+                                    if c.as_str() == "/" && !modifiers.control() =>
+                                {
+                                    Some(Message::SearchActivate)
+                                }
+                                keyboard::Key::Character(ref c)
+                                    if c.as_str() == "?" && !modifiers.control() =>
+                                {
+                                    Some(Message::ToggleShortcuts)
+                                }
+                                keyboard::Key::Character(ref c)
+                                    if c.as_str() == "j" && !modifiers.control() =>
+                                {
+                                    Some(Message::SelectionDown)
+                                }
+                                keyboard::Key::Character(ref c)
+                                    if c.as_str() == "k" && !modifiers.control() =>
+                                {
+                                    Some(Message::SelectionUp)
+                                }
+                                // Quick look, or `z` collapsing a thread.
+                                keyboard::Key::Character(ref c)
+                                    if !modifiers.control() && !modifiers.alt() =>
+                                {
+                                    Some(Message::QuickLookKey(c.to_string()))
+                                }
+                                // Ignored unless compose is open.
+                                keyboard::Key::Character(ref c)
+                                    if c.as_str() == "e" && modifiers.control() =>
+                                {
+                                    Some(Message::ComposeExternalEditor)
+                                }
+                                // Text fields take Ctrl+V themselves when the
+                                // clipboard holds text.
+                                keyboard::Key::Character(ref c)
+                                    if c.as_str() == "v" && modifiers.control() =>
+                                {
+                                    Some(Message::ComposePaste)
+                                }
+                                keyboard::Key::Named(keyboard::key::Named::Escape) => {
+                                    Some(Message::SearchClear)
+                                }
+                                _ => None,
                             }
-                            keyboard::Key::Character(ref c)
-                                if c.as_str() == "f" && !modifiers.control() =>
-                            {
-                                Some(Message::ComposeForward)
-                            }
-                            keyboard::Key::Named(keyboard::key::Named::Escape) => {
-                                Some(Message::SearchClear)
-                            }
-                            keyboard::Key::Named(keyboard::key::Named::F5) => {
-                                Some(Message::Refresh)
-                            }
-                            _ => None,
-                        },
+                        }
                         _ => None,
                     }
                 },
@@ -362,6 +709,9 @@ impl cosmic::Application for AppModel {
 
         // Per-account EventSource push streams
         for (i, acct) in self.accounts.iter().enumerate() {
+            if acct.paused {
+                continue;
+            }
             if let Some(client) = &acct.client {
                 let client = client.clone();
                 let account_id = acct.config.id.clone();
@@ -378,18 +728,28 @@ impl cosmic::Application for AppModel {
 
         // Per-account backfill streams
         for (i, acct) in self.accounts.iter().enumerate() {
-            if acct.backfill_active {
+            if acct.backfill_active && !acct.paused {
                 if let (Some(client), Some(cache)) = (&acct.client, &self.cache) {
-                    let mailbox_ids: Vec<String> =
-                        acct.folders.iter().map(|f| f.mailbox_id.clone()).collect();
-                    if !mailbox_ids.is_empty() {
+                    let folders: Vec<(String, Option<u32>)> = acct
+                        .folders
+                        .iter()
+                        .map(|f| {
+                            let limit = self.settings.history_limit(
+                                &acct.config.id,
+                                &f.path,
+                                acct.config.max_messages_per_mailbox,
+                            );
+                            (f.mailbox_id.clone(), limit)
+                        })
+                        .collect();
+                    if !folders.is_empty() {
                         subs.push(Subscription::run_with_id(
                             format!("backfill-{}", i),
                             backfill::backfill_stream(
                                 client.clone(),
                                 cache.clone(),
                                 acct.config.id.clone(),
-                                mailbox_ids,
+                                folders,
                                 acct.config.max_messages_per_mailbox,
                                 acct.backfill_pause.clone(),
                             ),
@@ -399,19 +759,111 @@ impl cosmic::Application for AppModel {
             }
         }
 
-        // Periodic full sync fallback (5 minutes)
-        let has_any_client = self.accounts.iter().any(|a| a.client.is_some());
-        if has_any_client {
+        // Per-account body prefetch streams
+        let prefetch = &self.settings.prefetch;
+        for (i, acct) in self.accounts.iter().enumerate() {
+            if !acct.prefetch_active || acct.paused || prefetch.messages_per_folder == 0 {
+                continue;
+            }
+            let (Some(client), Some(cache)) = (&acct.client, &self.cache) else {
+                continue;
+            };
             subs.push(Subscription::run_with_id(
-                "periodic-sync",
-                cosmic::iced_futures::stream::channel(1, |mut output| async move {
+                format!("prefetch-{}", i),
+                prefetch::prefetch_stream(
+                    client.clone(),
+                    cache.clone(),
+                    acct.config.id.clone(),
+                    prefetch::prefetch_folders(&acct.folders),
+                    prefetch.messages_per_folder,
+                    std::time::Duration::from_millis(prefetch.delay_ms),
+                    acct.backfill_pause.clone(),
+                ),
+            ));
+        }
+
+        // Per-account periodic sync. The period is part of the id so changing
+        // the interval (or push health) restarts the timer.
+        for acct in &self.accounts {
+            if acct.client.is_none() || acct.paused {
+                continue;
+            }
+            let interval = self.settings.sync_interval(&acct.config.id);
+            let Some(period) = interval.timer_period(acct.push_healthy) else {
+                continue;
+            };
+            let account_id = acct.config.id.clone();
+            subs.push(Subscription::run_with_id(
+                format!("periodic-sync-{}-{}", account_id, period.as_secs()),
+                cosmic::iced_futures::stream::channel(1, move |mut output| async move {
                     use futures::SinkExt;
-                    let mut interval =
-                        tokio::time::interval(std::time::Duration::from_secs(5 * 60));
+                    let mut interval = tokio::time::interval(period);
                     interval.tick().await; // skip immediate first tick
                     loop {
                         interval.tick().await;
-                        let _ = output.send(Message::Refresh).await;
+                        let _ = output.send(Message::PeriodicSync(account_id.clone())).await;
+                    }
+                }),
+            ));
+        }
+
+        // Relative dates in the list ("5 min ago") move on each minute.
+        if !self.messages.is_empty() {
+            subs.push(Subscription::run_with_id(
+                "row-labels",
+                cosmic::iced_futures::stream::channel(1, |mut output| async move {
+                    use futures::SinkExt;
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                    interval.tick().await; // labels were just computed
+                    loop {
+                        interval.tick().await;
+                        let _ = output.send(Message::RowLabelsTick).await;
+                    }
+                }),
+            ));
+        }
+
+        // Count a failed folder's "retrying in 8s" down.
+        if self.accounts.iter().any(|a| a.has_retry_countdown()) {
+            subs.push(Subscription::run_with_id(
+                "sync-retry-countdown",
+                cosmic::iced_futures::stream::channel(1, |mut output| async move {
+                    use futures::SinkExt;
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+                    loop {
+                        interval.tick().await;
+                        let _ = output.send(Message::SyncRetryTick).await;
+                    }
+                }),
+            ));
+        }
+
+        // Debounced write of folder sync times.
+        if self.folder_sync_times_dirty {
+            subs.push(Subscription::run_with_id(
+                "sync-times-flush",
+                cosmic::iced_futures::stream::channel(1, |mut output| async move {
+                    use futures::SinkExt;
+                    let mut interval = tokio::time::interval(freshness::SYNC_TIMES_SAVE_DELAY);
+                    interval.tick().await; // the first tick fires at once
+                    loop {
+                        interval.tick().await;
+                        let _ = output.send(Message::FlushFolderSyncTimes).await;
+                    }
+                }),
+            ));
+        }
+
+        // Re-check pause schedules each minute.
+        if !self.settings.pause_schedules.is_empty() {
+            subs.push(Subscription::run_with_id(
+                "pause-schedule",
+                cosmic::iced_futures::stream::channel(1, |mut output| async move {
+                    use futures::SinkExt;
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+                    loop {
+                        interval.tick().await;
+                        let _ = output.send(Message::PauseScheduleTick).await;
                     }
                 }),
             ));
@@ -421,13 +873,25 @@ impl cosmic::Application for AppModel {
     }
 
     fn view(&self) -> Element<'_, Self::Message> {
+        let now = drafts::unix_now();
         let main_content = widget::PaneGrid::new(&self.panes, |_pane, kind, _is_maximized| {
             let body: Element<'_, Self::Message> = match kind {
                 PaneKind::Sidebar => crate::ui::sidebar::view(
                     &self.accounts,
-                    self.active_account,
-                    self.selected_folder,
-                    self.folder_drag_target,
+                    &self.settings.account_colors,
+                    self.account_sections(),
+                    crate::ui::sidebar::SidebarSelection {
+                        active_account: self.active_account,
+                        selected_folder: self.selected_folder,
+                        smart_view: self.smart_view.as_ref(),
+                        drag_target: self.folder_drag_target,
+                    },
+                    self.local_drafts.len(),
+                    crate::ui::sidebar::FolderFreshness {
+                        times: &self.folder_sync_times,
+                        activity: &self.folder_activity,
+                        now,
+                    },
                     crate::ui::sidebar::DiagnosticsState {
                         collapsed: self.diagnostics_collapsed,
                         phase: self.phase,
@@ -443,31 +907,46 @@ impl cosmic::Application for AppModel {
                         last_sync_at: self.last_sync_at,
                         last_refresh_at: self.last_refresh_at,
                         refresh_in_flight: self.refresh_phase.is_in_flight(),
+                        metrics: self.metrics.snapshot(),
+                        attachment_bytes: self.attachment_bytes_in_memory(),
+                        flag_conflicts: recent_conflicts(&self.flag_conflicts, 5),
                     },
                 ),
-                PaneKind::MessageList => crate::ui::message_list::view(
-                    crate::ui::message_list::MessageListState {
-                        messages: &self.messages,
-                        visible_indices: &self.visible_indices,
-                        selected: self.selected_message,
-                        has_more: self.has_more_messages && !self.search_phase.is_active(),
-                        collapsed_threads: &self.collapsed_threads,
-                        thread_sizes: &self.thread_sizes,
-                        search_active: self.search_phase.is_active(),
-                        search_query: &self.search_query,
-                    },
-                ),
+                PaneKind::MessageList => {
+                    crate::ui::message_list::view(self.message_list_state(now))
+                }
                 PaneKind::MessageView => {
+                    if let Some(browser) = &self.attachment_browser {
+                        return pane_grid::Content::new(crate::ui::attachments_view::view(
+                            browser,
+                            &self.attachment_scans,
+                        ));
+                    }
                     let selected_msg = self
                         .selected_message
                         .and_then(|i| self.messages.get(i).map(|msg| (i, msg)));
+                    let own_addresses = selected_msg
+                        .and_then(|(_, msg)| self.account_index(&msg.account_id))
+                        .and_then(|i| self.accounts.get(i))
+                        .map(|a| a.config.email_addresses.as_slice())
+                        .unwrap_or_default();
+                    let preview = crate::ui::message_view::Preview {
+                        body: &self.preview_markdown,
+                        plain: &self.preview_body,
+                        attachments: &self.preview_attachments,
+                        image_handles: &self.preview_image_handles,
+                        attachment_hashes: &self.preview_attachment_hashes,
+                        detected_event: self.detected_event.as_ref(),
+                        encoding: self.preview_encoding,
+                        scans: &self.attachment_scans,
+                    };
                     crate::ui::message_view::view(
-                        &self.preview_markdown,
+                        preview,
                         selected_msg,
-                        &self.preview_attachments,
-                        &self.preview_image_handles,
                         &self.conversation,
                         self.active_conversation_id.as_deref(),
+                        self.conversation_sender_filter.as_deref(),
+                        own_addresses,
                     )
                 }
             };
@@ -477,15 +956,35 @@ impl cosmic::Application for AppModel {
         .width(Length::Fill)
         .height(Length::Fill);
 
-        let status_bar = widget::container(widget::text::caption(&self.status_message))
+        let mut status_row = widget::row()
+            .spacing(16)
+            .push(widget::text::caption(&self.status_message).width(Length::Fill));
+        if let Some(prefetch) = self.prefetch_status() {
+            status_row = status_row.push(widget::text::caption(prefetch));
+        }
+        let status_bar = widget::container(status_row)
             .padding([4, 8])
             .width(Length::Fill);
 
-        let content: Element<'_, Self::Message> = widget::column()
-            .push(main_content)
-            .push(status_bar)
-            .height(Length::Fill)
-            .into();
+        let overlays = (&self.image_viewer, &self.comparison, &self.triage);
+        let main_area: Element<'_, Self::Message> = match overlays {
+            (Some(viewer), _, _) => crate::ui::image_viewer::view(viewer),
+            (None, Some(comparison), _) => crate::ui::compare_view::view(comparison),
+            (None, None, Some(session)) => crate::ui::triage_view::view(
+                self.triage_left(),
+                session.done,
+                crate::ui::message_list::view(self.message_list_state(now)),
+            ),
+            (None, None, None) => main_content.into(),
+        };
+
+        let content: Element<'_, Self::Message> = widget::toaster(
+            &self.toasts,
+            widget::column()
+                .push(main_area)
+                .push(status_bar)
+                .height(Length::Fill),
+        );
 
         // WARNING: DO NOT move this into dialog(). COSMIC dialog overlays don't
         // register drag_destinations with the Wayland compositor — dnd_destination
@@ -505,27 +1004,56 @@ impl cosmic::Application for AppModel {
     }
 
     fn update(&mut self, message: Self::Message) -> Task<Self::Message> {
+        let task = self.route(message);
+        if self.queued_toasts.is_empty() {
+            return task;
+        }
+        cosmic::task::batch(vec![task, self.flush_toasts()])
+    }
+}
+
+impl AppModel {
+    /// Hand `message` to its handler.
+    fn route(&mut self, message: Message) -> Task<Message> {
         match message {
             // Compose
             Message::ComposeNew
             | Message::ComposeReply
+            | Message::ComposeReplyAll
             | Message::ComposeForward
             | Message::ComposeAccountChanged(_)
             | Message::ComposeFromChanged(_)
+            | Message::ComposeSignatureChanged(_)
             | Message::ComposeToChanged(_)
             | Message::ComposeSubjectChanged(_)
             | Message::ComposeBodyAction(_)
             | Message::ComposeAttach
             | Message::ComposeAttachLoaded(_)
             | Message::ComposeRemoveAttachment(_)
+            | Message::ComposeRecipientPicked(_)
             | Message::ComposeFilesDropped(_)
+            | Message::ComposePaste
+            | Message::ComposePasted(_)
             | Message::ComposeFileTransfer(_)
             | Message::ComposeFileTransferResolved(_)
             | Message::ComposeDragEnter
             | Message::ComposeDragLeave
             | Message::ComposeSend
+            | Message::ComposeSendAndArchive
+            | Message::ComposeSendWithoutRejected
             | Message::ComposeCancel
+            | Message::ComposeDiscard
+            | Message::ComposeSaveDraft
+            | Message::ComposeKeepEditing
+            | Message::ComposeResumeDraft
+            | Message::ComposeEditServerDraft
+            | Message::ComposeReplyTarget(_)
+            | Message::WindowCloseRequested
             | Message::SendComplete(_) => self.handle_compose(message),
+            Message::ComposeQuoteTool(tool) => self.handle_quote_tool(tool),
+            Message::ComposeExternalEditor | Message::ComposeExternalEditorDone { .. } => {
+                self.handle_external_editor(message)
+            }
 
             // Setup
             Message::SetupLabelChanged(_)
@@ -533,11 +1061,20 @@ impl cosmic::Application for AppModel {
             | Message::SetupUsernameChanged(_)
             | Message::SetupTokenChanged(_)
             | Message::SetupPasswordVisibilityToggled
+            | Message::SetupSyncIntervalChanged(_)
+            | Message::SetupInternalDomainsChanged(_)
+            | Message::SetupAccountGroupChanged(_)
+            | Message::SetupAccountColorChanged(_)
+            | Message::SetupSignatureAddressChanged(_)
+            | Message::SetupSignatureAction(_)
             | Message::SetupEmailAddressesChanged(_)
             | Message::SetupSubmit
             | Message::SetupCancel
             | Message::SetupOAuthStart
             | Message::SetupOAuthTokensReceived(_) => self.handle_setup(message),
+            Message::SetupFindSavedPassword
+            | Message::SetupSavedPasswordsFound(_)
+            | Message::SetupUseSavedPassword(_) => self.handle_saved_passwords(message),
 
             // Account management
             Message::AccountAdd
@@ -546,6 +1083,15 @@ impl cosmic::Application for AppModel {
             | Message::ConfirmDeleteAccount
             | Message::CancelDeleteAccount
             | Message::ToggleAccountCollapse(_) => self.handle_account_management(message),
+            Message::ToggleAccountPause(_) | Message::PauseScheduleTick => self.handle_pause(message),
+            Message::ToggleGroupCollapse(_) => self.handle_account_groups(message),
+            Message::MarkFolderRead(_) | Message::MarkFolderReadComplete { .. } => {
+                self.handle_folder_read(message)
+            }
+
+            Message::WindowResized(_)
+            | Message::WindowSizeChecked { .. }
+            | Message::WindowMoved(_) => self.handle_window_state(message),
 
             // Sync / connection / folder selection
             Message::AccountConnected { .. }
@@ -553,10 +1099,12 @@ impl cosmic::Application for AppModel {
             | Message::CachedMessagesLoaded { .. }
             | Message::SyncFoldersComplete { .. }
             | Message::SyncMessagesComplete { .. }
+            | Message::FoldersWarmedUp { .. }
             | Message::SelectFolder(_, _)
             | Message::LoadMoreMessages
             | Message::ForceReconnect(_)
-            | Message::Refresh => self.handle_sync(message),
+            | Message::Refresh
+            | Message::PeriodicSync(_) => self.handle_sync(message),
 
             // Body / attachment viewing
             Message::ViewBody(_)
@@ -565,11 +1113,16 @@ impl cosmic::Application for AppModel {
             | Message::ThreadLoaded { .. }
             | Message::ConversationBodyLoaded { .. }
             | Message::SetActiveConversation(_)
+            | Message::ContinueReading(_)
+            | Message::ViewWithEncoding(_)
+            | Message::JumpToConversationEntry(_)
+            | Message::ToggleConversationEntry(_)
+            | Message::ConversationCompose { .. }
             | Message::SaveConversationAttachment { .. }
             | Message::LinkClicked(_)
             | Message::CopyBody
-            | Message::SaveAttachment(_)
-            | Message::SaveAttachmentComplete(_) => self.handle_body(message),
+            | Message::SaveAttachment(_) => self.handle_body(message),
+            Message::FilterConversationSender(_) => self.handle_participant_filter(message),
 
             // Flag / move actions
             Message::ToggleRead(_)
@@ -580,13 +1133,23 @@ impl cosmic::Application for AppModel {
             | Message::Archive(_)
             | Message::RunFlagIntent(_)
             | Message::RunMoveIntent(_)
+            | Message::BulkArchive
+            | Message::BulkTrash
+            | Message::BulkMarkRead
+            | Message::MarkThreadRead { .. }
             | Message::DragMessageToFolder { .. }
             | Message::FolderDragEnter(_)
             | Message::FolderDragLeave
             | Message::FlagOpComplete { .. }
             | Message::MoveOpComplete { .. }
+            | Message::UndoMove(_)
+            | Message::UndoMoveComplete { .. }
             => self.handle_actions(message),
 
+            // Multi-select
+            Message::ToggleMessageSelected(_)
+            | Message::ClearMessageSelection => self.handle_selection(message),
+
             // Keyboard navigation
             Message::SelectionUp
             | Message::SelectionDown
@@ -597,29 +1160,150 @@ impl cosmic::Application for AppModel {
             Message::SearchActivate
             | Message::SearchQueryChanged(_)
             | Message::SearchExecute
+            | Message::SearchRefine
+            | Message::SearchRemoveTerm(_)
             | Message::SearchResultsLoaded { .. }
             | Message::SearchClear => self.handle_search(message),
 
+            Message::OpenSmartView(_)
+            | Message::SmartViewLoaded { .. }
+            | Message::ToggleUnreadOnly => self.handle_smart_view(message),
+            Message::FilterAlias(_) => self.handle_alias_filter(message),
+            Message::MergeIntoThread | Message::SplitFromThread(_) => {
+                self.handle_thread_overrides(message)
+            }
+
             // EventSource push events
             Message::PushStateChanged(_)
             | Message::PushError(_, _)
-            | Message::PushEnded(_) => self.handle_watch(message),
+            | Message::PushEnded(_)
+            | Message::PushDeltaLoaded { .. }
+            | Message::PushFoldersLoaded { .. } => self.handle_watch(message),
+            Message::FlushMailNotifications => self.flush_mail_notifications(),
+            Message::SetFolderNotify(rule) => self.set_folder_notify(rule),
 
             // Backfill progress
             Message::BackfillProgress { .. }
             | Message::BackfillComplete(_)
             | Message::BackfillTrigger { .. } => self.handle_backfill(message),
+            Message::DeferredConnect(account_id) => self.handle_deferred_connect(account_id),
+            Message::PrefetchProgress { .. } | Message::PrefetchComplete(_) => {
+                self.handle_prefetch(message)
+            }
 
             // Pane layout
             Message::PaneResized(pane_grid::ResizeEvent { split, ratio }) => {
                 self.panes.resize(split, ratio);
+                self.layout_cycle = None;
                 self.save_layout();
                 Task::none()
             }
+            Message::CycleLayout => {
+                self.cycle_layout();
+                Task::none()
+            }
+            Message::Menu(action) => self.handle_menu_action(action),
+            Message::RowMenu { index, action } => self.handle_row_menu(index, action),
+            Message::TrashConfirmMessage
+            | Message::TrashConfirmThread
+            | Message::TrashConfirmCancel => self.handle_thread_trash(message),
+            Message::CloseToast(id) => {
+                self.toasts.remove(id);
+                Task::none()
+            }
+            Message::ShowAttachments(_)
+            | Message::CloseAttachments
+            | Message::AttachmentsLoaded { .. }
+            | Message::SaveBrowserAttachment(_)
+            | Message::OpenBrowserAttachment(_)
+            | Message::BrowserAttachmentsRead { .. }
+            | Message::OpenAttachmentReady(_)
+            | Message::RiskyAttachmentConfirm
+            | Message::RiskyAttachmentCancel
+            | Message::AttachmentsScanned { .. } => self.handle_attachments(message),
+            Message::SaveAllAttachments(_)
+            | Message::SaveAttachmentComplete(_)
+            | Message::SaveAllAttachmentsComplete(_) => self.handle_attachment_save(message),
+            Message::ExportSettings
+            | Message::ImportSettings
+            | Message::SettingsExported(_)
+            | Message::SettingsImported(_) => self.handle_settings_transfer(message),
+            Message::CleanupOpen
+            | Message::CleanupPatternChanged(_)
+            | Message::CleanupScan
+            | Message::CleanupScanned { .. }
+            | Message::CleanupRun(_)
+            | Message::CleanupStepDone { .. }
+            | Message::CleanupClose => self.handle_cleanup(message),
+            Message::ThumbnailsReady { target, thumbnails } => {
+                self.apply_thumbnails(target, thumbnails);
+                Task::none()
+            }
+            Message::ViewImage { .. }
+            | Message::ViewerImageLoaded { .. }
+            | Message::ViewerNext
+            | Message::ViewerPrevious
+            | Message::ViewerRotate
+            | Message::ViewerToggleSlideshow
+            | Message::ViewerSave
+            | Message::CloseImageViewer => self.handle_image_viewer(message),
+            Message::RecipientIndexLoaded { account_id, mut index } => {
+                if let Some(pending) = self.unsynced_sends.get_mut(&account_id) {
+                    index.carry_unsynced(pending);
+                }
+                self.recipient_index.insert(account_id, index);
+                if self.compose_phase.is_open() {
+                    self.refresh_compose_suggestions();
+                }
+                Task::none()
+            }
+            Message::FolderActivityLoaded {
+                account_id,
+                activity,
+            } => {
+                self.apply_folder_activity(account_id, activity);
+                Task::none()
+            }
+            Message::AddDetectedEventToCalendar
+            | Message::CalendarFileReady(_)
+            | Message::DismissDetectedEvent
+            | Message::ToggleEventDetection => self.handle_detected_event(message),
+            Message::ToggleProtocolLog => {
+                self.settings.protocol_log = !self.settings.protocol_log;
+                if let Err(e) = self.settings.save() {
+                    log::warn!("Failed to save settings: {}", e);
+                }
+                self.status_message = match (self.settings.protocol_log, settings::protocol_log_path()) {
+                    (true, Some(path)) => format!("Protocol log on next launch: {}", path.display()),
+                    (true, None) => "Protocol log needs a data directory".into(),
+                    (false, _) => "Protocol log off from next launch".into(),
+                };
+                Task::none()
+            }
             Message::ToggleDiagnostics => {
                 self.diagnostics_collapsed = !self.diagnostics_collapsed;
                 Task::none()
             }
+            Message::RowLabelsTick => {
+                self.refresh_row_labels();
+                Task::none()
+            }
+            Message::FlushFolderSyncTimes => self.flush_folder_sync_times(),
+            // Only here to redraw the countdown.
+            Message::SyncRetryTick => Task::none(),
+            Message::ToggleShortcuts => {
+                self.show_shortcuts = !self.show_shortcuts;
+                Task::none()
+            }
+            Message::ToggleTriage | Message::Triage(_) => self.handle_triage(message),
+            Message::Visual(action) => self.handle_visual(action),
+            Message::CompareBodyLoaded { .. }
+            | Message::CompareScrolled { .. }
+            | Message::ToggleCompareScroll
+            | Message::CloseComparison => self.handle_compare(message),
+            Message::QuickLookKey(_)
+            | Message::QuickLookLoaded { .. }
+            | Message::CloseQuickLook => self.handle_quick_look(message),
             Message::Noop => Task::none(),
         }
     }
@@ -639,15 +1323,56 @@ impl AppModel {
         }
     }
 
+    fn message_list_state(&self, now: u64) -> crate::ui::message_list::MessageListState<'_> {
+        crate::ui::message_list::MessageListState {
+            messages: &self.messages,
+            visible_indices: &self.visible_indices,
+            selected: self.selected_message,
+            has_more: self.has_more_messages && !self.search_phase.is_active(),
+            collapsed_threads: &self.collapsed_threads,
+            thread_sizes: &self.thread_sizes,
+            row_labels: &self.row_labels,
+            attachment_counts: &self.attachment_counts,
+            snippets: &self.snippets,
+            search_active: self.search_phase.is_active(),
+            search_query: &self.search_query,
+            search_terms: &self.search_terms,
+            selection: &self.message_selection,
+            show_sync_status: !self.search_phase.is_active() && self.selected_folder.is_some(),
+            unread_only: self.unread_only(),
+            alias_filter: self.alias_filter.as_deref(),
+            move_targets: self
+                .accounts
+                .iter()
+                .map(|a| {
+                    let paths = a.folders.iter().map(|f| f.path.as_str()).collect();
+                    (a.config.id.as_str(), paths)
+                })
+                .collect(),
+            last_synced: self.selected_mailbox_sync_time(),
+            account_colors: self
+                .messages
+                .split_first()
+                .is_some_and(|(first, rest)| rest.iter().any(|m| m.account_id != first.account_id))
+                .then_some(&self.settings.account_colors),
+            now,
+        }
+    }
+
     pub(super) fn set_status_error(&mut self, message: String) {
         self.error_surface = Some(ErrorSurface::Status {
             message: message.clone(),
         });
+        self.notify(Severity::Error, message.clone());
         self.status_message = message;
         self.phase = Phase::Error;
     }
 
     pub(super) fn set_recoverable_action_error(&mut self, error: RecoverableActionError) {
+        let retry = match error.retry {
+            RetryAction::Refresh => Message::Refresh,
+        };
+        self.notify_with_action(Severity::Error, error.message.clone(), "Retry", retry);
         self.status_message = error.message.clone();
         self.error_surface = Some(ErrorSurface::RecoverableAction(error));
     }
@@ -659,12 +1384,7 @@ impl AppModel {
     pub(super) fn is_busy(&self) -> bool {
         self.active_account
             .and_then(|i| self.accounts.get(i))
-            .is_some_and(|a| {
-                matches!(
-                    a.conn_state,
-                    ConnectionState::Connecting | ConnectionState::Syncing
-                )
-            })
+            .is_some_and(|a| a.conn_state == ConnectionState::Connecting || a.is_syncing())
     }
 
     /// Dispatch a message through the update loop (for recursive calls from handlers).
@@ -43,6 +43,7 @@ impl AppModel {
                 };
                 if let Some(&real_index) = self.visible_indices.get(new_vis_pos) {
                     self.selected_message = Some(real_index);
+                    self.extend_visual();
                     return self.dispatch(Message::ViewBody(real_index));
                 }
             }
@@ -60,6 +61,7 @@ impl AppModel {
                 };
                 if let Some(&real_index) = self.visible_indices.get(new_vis_pos) {
                     self.selected_message = Some(real_index);
+                    self.extend_visual();
                     return self.dispatch(Message::ViewBody(real_index));
                 }
             }
@@ -76,7 +78,19 @@ impl AppModel {
                         if let Some(ref tid) = msg.thread_id {
                             let size = self.thread_sizes.get(tid).copied().unwrap_or(1);
                             if size > 1 {
-                                if self.collapsed_threads.contains(tid) {
+                                let collapse = !self.collapsed_threads.contains(tid);
+                                if let Some(folder) = self.open_folder() {
+                                    self.saved_collapsed_threads.set(
+                                        &folder.account_id,
+                                        &folder.mailbox_id,
+                                        tid,
+                                        collapse,
+                                    );
+                                    if let Err(e) = self.saved_collapsed_threads.save() {
+                                        log::warn!("Failed to save collapsed threads: {}", e);
+                                    }
+                                }
+                                if !collapse {
                                     self.collapsed_threads.remove(tid);
                                 } else {
                                     self.collapsed_threads.insert(tid.clone());
@@ -107,6 +121,10 @@ impl AppModel {
     /// Rebuild `visible_indices` and `thread_sizes` based on current messages
     /// and collapsed state.
     pub(super) fn recompute_visible(&mut self) {
+        for msg in &mut self.messages {
+            self.thread_overrides.apply(msg);
+        }
+        self.update_row_labels();
         self.thread_sizes.clear();
         for msg in &self.messages {
             if let Some(ref tid) = msg.thread_id {
@@ -114,8 +132,24 @@ impl AppModel {
             }
         }
 
+        // The open message stays listed after it's read.
+        let unread_only = self.unread_only() == Some(true);
+        let other_alias: Vec<bool> = match &self.alias_filter {
+            Some(alias) => self
+                .messages
+                .iter()
+                .map(|m| self.message_alias(m).as_ref() != Some(alias))
+                .collect(),
+            None => Vec::new(),
+        };
         self.visible_indices.clear();
         for (i, msg) in self.messages.iter().enumerate() {
+            if unread_only && msg.is_read && self.selected_message != Some(i) {
+                continue;
+            }
+            if other_alias.get(i) == Some(&true) {
+                continue;
+            }
             if msg.thread_depth > 0 {
                 if let Some(ref tid) = msg.thread_id {
                     if self.collapsed_threads.contains(tid) {
@@ -140,8 +174,10 @@ impl AppModel {
             self.preview_markdown.clear();
             self.preview_attachments.clear();
             self.preview_image_handles.clear();
+            self.preview_attachment_hashes.clear();
             self.conversation.clear();
             self.active_conversation_id = None;
+            self.conversation_sender_filter = None;
         }
     }
 }
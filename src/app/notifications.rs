@@ -0,0 +1,298 @@
+//! Desktop notifications for new mail, coalesced per folder.
+//!
+//! A burst of arrivals (a mailing list digest, catching up after suspend)
+//! would otherwise pop one notification per message. New messages are held
+//! for `COALESCE_WINDOW` from the first arrival and then shown as a single
+//! "12 new messages in Work/INBOX" notification per folder, listing the
+//! busiest senders.
+//!
+//! `"notifications"` in settings.json trims that down: sender only, or a
+//! bare "New mail" that says nothing on a lock screen. Each folder can also
+//! be made silent (no sound) or muted (no notification) from View → Folder
+//! notifications; the rule is checked before anything is queued.
+//!
+//! Notifications can't be colored, so an account with an accent color gets
+//! it as a colored circle before the folder label.
+
+use std::time::Duration;
+
+use cosmic::app::Task;
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::models::MessageSummary;
+
+use super::recipients::display_name;
+use super::settings::{AccountColor, FolderNotify, NotificationPrivacy, NotificationSettings};
+use super::{AppModel, Message, MessageIdentity};
+
+/// How long after the first new message further arrivals are merged in.
+pub const COALESCE_WINDOW: Duration = Duration::from_secs(5);
+/// Senders named in a coalesced notification before "and N others".
+const TOP_SENDERS: usize = 3;
+
+/// New messages waiting for the coalescing window to close, per folder.
+#[derive(Debug, Clone, Default)]
+pub struct NewMailBatch {
+    /// "Work/INBOX".
+    pub folder_label: String,
+    /// Sender display names, in arrival order.
+    pub senders: Vec<String>,
+    /// Subject of the first message, shown when it's the only one.
+    pub first_subject: String,
+    /// The folder's rule; a muted folder never gets a batch.
+    pub rule: FolderNotify,
+}
+
+impl NewMailBatch {
+    /// Notification summary and body.
+    pub(super) fn text(&self, settings: &NotificationSettings) -> (String, String) {
+        if settings.privacy == NotificationPrivacy::Hidden {
+            return ("New mail".into(), String::new());
+        }
+        match self.senders.as_slice() {
+            [sender] => (
+                format!("New message in {}", self.folder_label),
+                match snippet(&self.first_subject, settings.snippet_chars) {
+                    Some(subject) if settings.privacy == NotificationPrivacy::Full => {
+                        format!("{sender}: {subject}")
+                    }
+                    _ => sender.clone(),
+                },
+            ),
+            senders => (
+                format!("{} new messages in {}", senders.len(), self.folder_label),
+                format!("From {}", sender_list(senders, TOP_SENDERS)),
+            ),
+        }
+    }
+}
+
+/// "Work/INBOX", led by the account color's circle when it has one.
+pub(super) fn folder_label(account: &str, folder: &str, color: Option<AccountColor>) -> String {
+    match color {
+        Some(color) => format!("{} {account}/{folder}", color.emoji()),
+        None => format!("{account}/{folder}"),
+    }
+}
+
+/// `text` cut to `max_chars` with "…"; `None` when nothing is left to show.
+fn snippet(text: &str, max_chars: usize) -> Option<String> {
+    let text = text.trim();
+    if max_chars == 0 || text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= max_chars {
+        return Some(text.to_string());
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+/// Senders by message count (ties in arrival order), e.g.
+/// "Alice (5), Bob (3), Carol and 2 others".
+fn sender_list(senders: &[String], top: usize) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for sender in senders {
+        match counts.iter_mut().find(|(s, _)| *s == sender) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((sender, 1)),
+        }
+    }
+    // Stable sort keeps arrival order among equal counts.
+    counts.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+
+    let named: Vec<String> = counts
+        .iter()
+        .take(top)
+        .map(|&(s, n)| if n > 1 { format!("{s} ({n})") } else { s.to_string() })
+        .collect();
+    let others = counts.len().saturating_sub(top);
+    match others {
+        0 => named.join(", "),
+        1 => format!("{} and 1 other", named.join(", ")),
+        n => format!("{} and {n} others", named.join(", ")),
+    }
+}
+
+pub(super) fn show(summary: String, body: String, rule: FolderNotify) {
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .appname("Nevermail")
+        .summary(&summary)
+        .body(&body)
+        .icon("mail-unread");
+    if rule == FolderNotify::Silent {
+        notification.hint(notify_rust::Hint::SuppressSound(true));
+    }
+    if let Err(e) = notification.show() {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
+impl AppModel {
+    /// Queue notifications for unread messages not announced before; starts
+    /// the coalescing timer when nothing was waiting.
+    pub(super) fn queue_new_mail<'a>(
+        &mut self,
+        account_id: &str,
+        mailbox_id: &str,
+        arrived: impl IntoIterator<Item = &'a MessageSummary>,
+    ) -> Task<Message> {
+        let Some(acct) = self.account_index(account_id).and_then(|i| self.accounts.get(i)) else {
+            return Task::none();
+        };
+        let folder = acct.folders.iter().find(|f| f.mailbox_id == mailbox_id);
+        let rule = folder.map_or(FolderNotify::Notify, |f| {
+            self.settings.notifications.folder_rule(account_id, &f.path)
+        });
+        if rule == FolderNotify::Muted {
+            return Task::none();
+        }
+        let folder_name = folder.map_or(mailbox_id, |f| f.name.as_str());
+        let color = self.settings.account_color(account_id);
+        let folder_label = folder_label(&acct.config.label, folder_name, color);
+
+        let was_idle = self.pending_notifications.is_empty();
+        let key: (AccountId, String) = (account_id.to_string(), mailbox_id.to_string());
+        for msg in arrived.into_iter().filter(|m| !m.is_read) {
+            let identity = MessageIdentity::of(msg);
+            if !self.notified_messages.insert(identity) {
+                continue;
+            }
+            let batch = self
+                .pending_notifications
+                .entry(key.clone())
+                .or_insert_with(|| NewMailBatch {
+                    folder_label: folder_label.clone(),
+                    first_subject: msg.subject.clone(),
+                    rule,
+                    ..NewMailBatch::default()
+                });
+            batch.senders.push(display_name(&msg.from));
+        }
+
+        if !was_idle || self.pending_notifications.is_empty() {
+            return Task::none();
+        }
+        cosmic::task::future(async {
+            tokio::time::sleep(COALESCE_WINDOW).await;
+            Message::FlushMailNotifications
+        })
+    }
+
+    /// The open folder's (account id, folder path, name).
+    fn open_folder_path(&self) -> Option<(AccountId, String, String)> {
+        let open = self.open_folder()?;
+        let acct = self.account_index(&open.account_id).and_then(|i| self.accounts.get(i))?;
+        let folder = acct.folders.iter().find(|f| f.mailbox_id == open.mailbox_id)?;
+        Some((open.account_id, folder.path.clone(), folder.name.clone()))
+    }
+
+    /// Notification rule of the open folder, for the View menu.
+    pub(super) fn open_folder_notify(&self) -> Option<FolderNotify> {
+        let (account_id, path, _) = self.open_folder_path()?;
+        Some(self.settings.notifications.folder_rule(&account_id, &path))
+    }
+
+    pub(super) fn set_folder_notify(&mut self, rule: FolderNotify) -> Task<Message> {
+        let Some((account_id, path, name)) = self.open_folder_path() else {
+            self.status_message = "Open a folder to set its notifications".into();
+            return Task::none();
+        };
+        self.settings.notifications.set_folder_rule(&account_id, &path, rule);
+        if let Err(e) = self.settings.save() {
+            log::warn!("Failed to save settings: {}", e);
+        }
+        self.status_message = match rule {
+            FolderNotify::Notify => format!("New mail in {name} will notify"),
+            FolderNotify::Silent => format!("New mail in {name} will notify without sound"),
+            FolderNotify::Muted => format!("New mail in {name} won't notify"),
+        };
+        Task::none()
+    }
+
+    /// Show one notification per folder for everything queued.
+    pub(super) fn flush_mail_notifications(&mut self) -> Task<Message> {
+        let notices: Vec<(String, String, FolderNotify)> = self
+            .pending_notifications
+            .drain()
+            .map(|(_, batch)| {
+                let (summary, body) = batch.text(&self.settings.notifications);
+                (summary, body, batch.rule)
+            })
+            .collect();
+        if notices.is_empty() {
+            return Task::none();
+        }
+        cosmic::task::future(async move {
+            // D-Bus round trips; keep them off the UI thread.
+            let shown = tokio::task::spawn_blocking(move || {
+                for (summary, body, rule) in notices {
+                    show(summary, body, rule);
+                }
+            })
+            .await;
+            if let Err(e) = shown {
+                log::error!("Notification task failed: {}", e);
+            }
+            Message::Noop
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_batch(senders: &[&str]) -> NewMailBatch {
+        NewMailBatch {
+            folder_label: "Work/INBOX".into(),
+            senders: senders.iter().map(|s| s.to_string()).collect(),
+            first_subject: "Lunch?".into(),
+            rule: FolderNotify::Notify,
+        }
+    }
+
+    #[test]
+    fn single_message_shows_sender_and_subject() {
+        let (summary, body) = sample_batch(&["Alice"]).text(&NotificationSettings::default());
+        assert_eq!(summary, "New message in Work/INBOX");
+        assert_eq!(body, "Alice: Lunch?");
+    }
+
+    #[test]
+    fn privacy_settings_trim_the_notification() {
+        let batch = sample_batch(&["Alice"]);
+        let mut settings = NotificationSettings {
+            privacy: NotificationPrivacy::SenderOnly,
+            ..NotificationSettings::default()
+        };
+        assert_eq!(batch.text(&settings).1, "Alice");
+        settings.privacy = NotificationPrivacy::Hidden;
+        assert_eq!(batch.text(&settings), ("New mail".to_string(), String::new()));
+        settings.privacy = NotificationPrivacy::Full;
+        settings.snippet_chars = 3;
+        assert_eq!(batch.text(&settings).1, "Alice: Lun…");
+        settings.snippet_chars = 0;
+        assert_eq!(batch.text(&settings).1, "Alice");
+    }
+
+    #[test]
+    fn burst_is_one_notification_with_top_senders() {
+        let batch = sample_batch(&["Bob", "Alice", "Alice", "Carol", "Dan", "Alice", "Eve"]);
+        let (summary, body) = batch.text(&NotificationSettings::default());
+        assert_eq!(summary, "7 new messages in Work/INBOX");
+        assert_eq!(body, "From Alice (3), Bob, Carol and 2 others");
+    }
+
+    #[test]
+    fn colored_accounts_lead_with_their_circle() {
+        assert_eq!(folder_label("Work", "INBOX", None), "Work/INBOX");
+        assert_eq!(folder_label("Work", "INBOX", Some(AccountColor::Blue)), "🔵 Work/INBOX");
+    }
+
+    #[test]
+    fn sender_list_without_overflow() {
+        let senders = vec!["Alice".to_string(), "Bob".to_string()];
+        assert_eq!(sender_list(&senders, 3), "Alice, Bob");
+    }
+}
@@ -0,0 +1,82 @@
+//! Long message bodies rendered a page at a time.
+//!
+//! The markdown widget lays out every item on each frame, so multi-megabyte
+//! digests used to be capped at 200 items and dropped to plain text. The
+//! body is now parsed in full but only the first `PAGE_ITEMS` items are
+//! shown; "Continue reading" adds the next page, keeping the formatting.
+
+use cosmic::widget::markdown;
+
+/// Markdown items shown per "Continue reading" step.
+pub const PAGE_ITEMS: usize = 200;
+
+#[derive(Debug, Clone, Default)]
+pub struct PagedMarkdown {
+    items: Vec<markdown::Item>,
+    shown: usize,
+}
+
+impl PagedMarkdown {
+    pub fn new(items: Vec<markdown::Item>) -> Self {
+        let shown = items.len().min(PAGE_ITEMS);
+        Self { items, shown }
+    }
+
+    /// Items on screen: the first pages only.
+    pub fn visible(&self) -> &[markdown::Item] {
+        &self.items[..self.shown]
+    }
+
+    /// Items still behind "Continue reading".
+    pub fn remaining(&self) -> usize {
+        self.items.len() - self.shown
+    }
+
+    pub fn show_more(&mut self) {
+        self.shown = self.items.len().min(self.shown + PAGE_ITEMS);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.shown = 0;
+    }
+}
+
+impl FromIterator<markdown::Item> for PagedMarkdown {
+    fn from_iter<I: IntoIterator<Item = markdown::Item>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_body(paragraphs: usize) -> PagedMarkdown {
+        let text: Vec<String> = (0..paragraphs).map(|i| format!("Paragraph {i}")).collect();
+        markdown::parse(&text.join("\n\n")).collect()
+    }
+
+    #[test]
+    fn short_bodies_show_everything() {
+        let body = sample_body(3);
+        assert_eq!(body.visible().len(), 3);
+        assert_eq!(body.remaining(), 0);
+    }
+
+    #[test]
+    fn long_bodies_page_until_done() {
+        let mut body = sample_body(PAGE_ITEMS * 2 + 5);
+        assert_eq!(body.visible().len(), PAGE_ITEMS);
+        assert_eq!(body.remaining(), PAGE_ITEMS + 5);
+        body.show_more();
+        body.show_more();
+        assert_eq!(body.remaining(), 0);
+        body.show_more();
+        assert_eq!(body.visible().len(), PAGE_ITEMS * 2 + 5);
+    }
+}
@@ -0,0 +1,108 @@
+//! Per-sender filter for the conversation view.
+//!
+//! In a long thread the header chips list everyone who wrote in it; picking
+//! one hides the other senders' cards (and their thread-map rows) so one
+//! person's side of a 60-message thread reads straight through. The filter
+//! is keyed by address, so "Ann <ann@x>" and "ann@x" are the same person,
+//! and is dropped whenever another thread opens.
+
+use cosmic::app::Task;
+
+use super::{AppModel, ConversationEntry, Message};
+
+/// Chips show once a thread has this many messages.
+pub const PARTICIPANT_FILTER_MIN_MESSAGES: usize = 3;
+
+/// Someone who wrote in the open thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Participant {
+    /// Lowercased address; the filter value.
+    pub key: String,
+    /// `From` header of their first message, for the chip label.
+    pub from: String,
+    pub count: usize,
+}
+
+/// The address in a `Name <addr>` header, lowercased; the whole header when
+/// it has no angle brackets.
+pub fn sender_key(from: &str) -> String {
+    let addr = match (from.rfind('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &from[start + 1..end],
+        _ => from,
+    };
+    addr.trim().to_lowercase()
+}
+
+/// Senders of a thread's `From` headers, most messages first (ties keep
+/// thread order).
+pub fn participants<'a>(froms: impl IntoIterator<Item = &'a str>) -> Vec<Participant> {
+    let mut people: Vec<Participant> = Vec::new();
+    for from in froms {
+        let key = sender_key(from);
+        match people.iter_mut().find(|p| p.key == key) {
+            Some(p) => p.count += 1,
+            None => people.push(Participant {
+                key,
+                from: from.to_string(),
+                count: 1,
+            }),
+        }
+    }
+    people.sort_by(|a, b| b.count.cmp(&a.count));
+    people
+}
+
+/// Whether `entry` passes the sender filter.
+pub fn shows_entry(filter: Option<&str>, entry: &ConversationEntry) -> bool {
+    filter.is_none_or(|key| sender_key(&entry.summary.from) == key)
+}
+
+impl AppModel {
+    pub(super) fn handle_participant_filter(&mut self, message: Message) -> Task<Message> {
+        if let Message::FilterConversationSender(key) = message {
+            self.conversation_sender_filter = key;
+            // Keep reply/forward aimed at a message that is still on screen.
+            let filter = self.conversation_sender_filter.as_deref();
+            let active_shown = self
+                .conversation
+                .iter()
+                .find(|e| Some(e.email_id.as_str()) == self.active_conversation_id.as_deref())
+                .is_some_and(|e| shows_entry(filter, e));
+            if !active_shown {
+                if let Some(last) = self.conversation.iter().rev().find(|e| shows_entry(filter, e)) {
+                    self.active_conversation_id = Some(last.email_id.clone());
+                }
+            }
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_identify_senders() {
+        assert_eq!(sender_key("Ann Lee <Ann@Example.com>"), "ann@example.com");
+        assert_eq!(sender_key("ann@example.com"), "ann@example.com");
+        assert_eq!(sender_key("\"Lee, Ann\" <ann@example.com>"), "ann@example.com");
+    }
+
+    #[test]
+    fn busiest_sender_first_and_counted_once() {
+        let people = participants([
+            "Bob <bob@example.com>",
+            "Ann <ann@example.com>",
+            "ann@example.com",
+            "Cy <cy@example.com>",
+        ]);
+        let summary: Vec<(&str, usize)> =
+            people.iter().map(|p| (p.key.as_str(), p.count)).collect();
+        assert_eq!(
+            summary,
+            [("ann@example.com", 2), ("bob@example.com", 1), ("cy@example.com", 1)]
+        );
+        assert_eq!(people[0].from, "Ann <ann@example.com>");
+    }
+}
@@ -0,0 +1,96 @@
+//! Per-account sync pausing ("work account off on weekends").
+//!
+//! A paused account keeps its cached folders and messages browsable but gets
+//! no periodic sync, push stream, backfill, or reconnects. Pauses come from
+//! the sidebar toggle (persisted) or the account's schedule in settings.json;
+//! resuming by hand during a scheduled pause holds until that window ends.
+
+use cosmic::app::Task;
+
+use super::{AppModel, ConnectionState, Message};
+
+impl AppModel {
+    pub(super) fn pause_wanted(&self, account_id: &str, now: chrono::NaiveDateTime) -> bool {
+        self.settings.paused_accounts.contains(account_id)
+            || (self.settings.schedule_covers(account_id, now)
+                && !self.pause_overrides.contains(account_id))
+    }
+
+    pub(super) fn handle_pause(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ToggleAccountPause(account_id) => {
+                let Some(idx) = self.account_index(&account_id) else {
+                    return Task::none();
+                };
+                let now = chrono::Local::now().naive_local();
+                if self.accounts[idx].paused {
+                    self.settings.paused_accounts.remove(&account_id);
+                    if self.settings.schedule_covers(&account_id, now) {
+                        self.pause_overrides.insert(account_id);
+                    }
+                } else {
+                    self.settings.paused_accounts.insert(account_id.clone());
+                    self.pause_overrides.remove(&account_id);
+                }
+                if let Err(e) = self.settings.save() {
+                    log::warn!("Failed to save settings: {}", e);
+                }
+                return self.apply_pause_states();
+            }
+            Message::PauseScheduleTick => {
+                let now = chrono::Local::now().naive_local();
+                let settings = &self.settings;
+                self.pause_overrides
+                    .retain(|id| settings.schedule_covers(id, now));
+                return self.apply_pause_states();
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// Bring every account's `paused` flag in line with settings, resuming
+    /// (reconnect or sync) accounts that just came off pause.
+    pub(super) fn apply_pause_states(&mut self) -> Task<Message> {
+        let now = chrono::Local::now().naive_local();
+        let mut resumed = Vec::new();
+        for idx in 0..self.accounts.len() {
+            let wanted = self.pause_wanted(&self.accounts[idx].config.id, now);
+            let acct = &mut self.accounts[idx];
+            if acct.paused == wanted {
+                continue;
+            }
+            acct.paused = wanted;
+            if wanted {
+                log::info!("Sync paused for {}", acct.config.label);
+                acct.push_healthy = false;
+                self.status_message = format!("{}: sync paused", acct.config.label);
+            } else {
+                log::info!("Sync resumed for {}", acct.config.label);
+                self.status_message = format!("{}: sync resumed", acct.config.label);
+                resumed.push(idx);
+            }
+        }
+
+        let mut tasks = Vec::new();
+        for idx in resumed {
+            let acct = &mut self.accounts[idx];
+            let account_id = acct.config.id.clone();
+            if acct.client.is_some() {
+                // The push stream restarts with the subscription; catch up now.
+                acct.push_healthy = true;
+                tasks.push(self.handle_refresh_accounts(Some(&account_id)));
+            } else {
+                acct.conn_state = ConnectionState::Connecting;
+                tasks.push(super::connect_account(acct.config.clone(), account_id));
+            }
+        }
+        cosmic::task::batch(tasks)
+    }
+
+    pub(super) fn account_paused(&self, account_id: &str) -> bool {
+        self.account_index(account_id)
+            .and_then(|idx| self.accounts.get(idx))
+            .is_some_and(|a| a.paused)
+    }
+}
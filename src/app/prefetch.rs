@@ -0,0 +1,179 @@
+//! Background body prefetch: after a folder sync, download the bodies (and
+//! attachments) of the newest messages in each folder, so they open
+//! offline.
+//!
+//! One stream per account walks its folders, newest messages first, up to
+//! `prefetch.messages_per_folder` each; bodies already cached are skipped.
+//! Fetches are spaced by `prefetch.delay_ms` and wait while a head sync
+//! holds the account's backfill pause flag. Trash and Junk are left out.
+//! Each body fetched yields a list preview; cached bodies are only checked
+//! for, not read for one, so a relaunch doesn't reload every blob.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic::app::Task;
+use futures::SinkExt;
+use neverlight_mail_core::client::JmapClient;
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::models::Folder;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::{snippets, AppModel, Message};
+
+/// Folder roles whose mail isn't worth keeping offline.
+const SKIPPED_ROLES: &[&str] = &["trash", "junk"];
+
+/// Mailboxes of `folders` to prefetch, Inbox first.
+pub(super) fn prefetch_folders(folders: &[Folder]) -> Vec<String> {
+    let mut picked: Vec<&Folder> = folders
+        .iter()
+        .filter(|f| !f.role.as_deref().is_some_and(|r| SKIPPED_ROLES.contains(&r)))
+        .collect();
+    picked.sort_by_key(|f| f.role.as_deref() != Some("inbox"));
+    picked.into_iter().map(|f| f.mailbox_id.clone()).collect()
+}
+
+/// Downloads missing bodies for the newest `per_folder` messages of each of
+/// `folders`, reporting progress after each message.
+pub(super) fn prefetch_stream(
+    client: JmapClient,
+    cache: CacheHandle,
+    account_id: AccountId,
+    folders: Vec<String>,
+    per_folder: u32,
+    delay: Duration,
+    pause: Arc<AtomicBool>,
+) -> impl futures::Stream<Item = Message> {
+    cosmic::iced_futures::stream::channel(10, move |mut output| async move {
+        let mut messages = Vec::new();
+        for mailbox_id in folders {
+            match cache
+                .load_messages(account_id.clone(), mailbox_id.clone(), per_folder, 0)
+                .await
+            {
+                Ok(listed) => messages.extend(listed.into_iter().map(|m| (m.email_id, m.subject))),
+                Err(e) => log::warn!("prefetch: failed to list {}: {}", mailbox_id, e),
+            }
+        }
+        // A message filed in two folders is fetched once.
+        let mut seen = std::collections::HashSet::new();
+        messages.retain(|(id, _)| seen.insert(id.clone()));
+
+        let total = messages.len();
+        for (done, (email_id, subject)) in messages.into_iter().enumerate() {
+            while pause.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+            let cached = cache
+                .load_body(account_id.clone(), email_id.clone())
+                .await
+                .is_ok_and(|body| body.is_some());
+            let mut plain_body = None;
+            if !cached {
+                match neverlight_mail_core::email::get_body(&client, &email_id).await {
+                    Ok((md_body, plain, attachments)) => {
+                        if let Err(e) = cache
+                            .save_body(
+                                account_id.clone(),
+                                email_id.clone(),
+                                md_body,
+                                plain.clone(),
+                                attachments,
+                            )
+                            .await
+                        {
+                            log::warn!("prefetch: failed to cache {}: {}", email_id, e);
+                        }
+                        plain_body = Some(plain);
+                    }
+                    Err(e) => log::warn!("prefetch: failed to fetch {}: {}", email_id, e),
+                }
+                tokio::time::sleep(delay).await;
+            }
+            let snippet = plain_body
+                .and_then(|plain| snippets::excerpt(&plain, &subject))
+                .map(|snippet| (email_id, snippet));
+            let _ = output
+                .send(Message::PrefetchProgress {
+                    account_id: account_id.clone(),
+                    done: done + 1,
+                    total,
+                    snippet,
+                })
+                .await;
+        }
+        let _ = output
+            .send(Message::PrefetchComplete(account_id.clone()))
+            .await;
+    })
+}
+
+impl AppModel {
+    pub(super) fn handle_prefetch(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::PrefetchProgress {
+                ref account_id,
+                done,
+                total,
+                snippet,
+            } => {
+                if let Some(idx) = self.account_index(account_id) {
+                    self.accounts[idx].prefetch_progress = Some((done, total));
+                }
+                if let Some((email_id, snippet)) = snippet {
+                    self.insert_snippet(email_id, snippet);
+                }
+            }
+            Message::PrefetchComplete(ref account_id) => {
+                if let Some(idx) = self.account_index(account_id) {
+                    self.accounts[idx].prefetch_active = false;
+                    self.accounts[idx].prefetch_progress = None;
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// "Downloading for offline 12/150" while any account is prefetching.
+    pub(super) fn prefetch_status(&self) -> Option<String> {
+        let (done, total) = self
+            .accounts
+            .iter()
+            .filter(|a| a.prefetch_active && !a.paused)
+            .filter_map(|a| a.prefetch_progress)
+            .fold((0, 0), |(d, t), (done, total)| (d + done, t + total));
+        (total > 0).then(|| format!("Downloading for offline {done}/{total}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(mailbox_id: &str, role: Option<&str>) -> Folder {
+        Folder {
+            mailbox_id: mailbox_id.to_string(),
+            path: mailbox_id.to_string(),
+            name: mailbox_id.to_string(),
+            role: role.map(str::to_string),
+            sort_order: 0,
+            unread_count: 0,
+            total_count: 0,
+        }
+    }
+
+    #[test]
+    fn inbox_goes_first_and_trash_and_junk_are_skipped() {
+        let folders = [
+            folder("Archive", Some("archive")),
+            folder("Trash", Some("trash")),
+            folder("Lists", None),
+            folder("Inbox", Some("inbox")),
+            folder("Spam", Some("junk")),
+        ];
+        assert_eq!(prefetch_folders(&folders), ["Inbox", "Archive", "Lists"]);
+    }
+}
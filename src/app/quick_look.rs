@@ -0,0 +1,149 @@
+//! Quick look: a glance at the selected row's body in a popup.
+//!
+//! The key from `"quick_look_key"` in settings.json (Space unless set, the
+//! Finder habit; otherwise a single character) opens it, and the same key
+//! or Escape closes it. Threads collapse on `z`, unless that's the setting.
+//! Only the cache is read: a message that hasn't been downloaded says so
+//! rather than fetching. Nothing is marked read, and the selection and the
+//! preview pane stay as they were. Keys already bound to an action (see
+//! `shortcuts`) keep their action.
+
+use cosmic::app::Task;
+use neverlight_mail_core::models::MessageSummary;
+
+use super::{AppModel, Message};
+
+pub const DEFAULT_KEY: &str = "space";
+
+/// Collapses or expands the selected row's thread.
+pub const THREAD_COLLAPSE_KEY: &str = "z";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuickLookBody {
+    Loading,
+    Loaded(String),
+    /// Not in the cache; quick look doesn't fetch.
+    NotDownloaded,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct QuickLook {
+    pub summary: MessageSummary,
+    pub body: QuickLookBody,
+}
+
+/// The key press text `setting` stands for: "space" is " ".
+fn pressed_text(setting: &str) -> &str {
+    if setting.eq_ignore_ascii_case("space") {
+        " "
+    } else {
+        setting
+    }
+}
+
+fn is_quick_look_key(setting: &str, pressed: &str) -> bool {
+    pressed_text(setting) == pressed
+}
+
+/// How the cheat sheet writes the key.
+pub fn key_label(setting: &str) -> String {
+    match pressed_text(setting) {
+        " " => "Space".into(),
+        key => key.into(),
+    }
+}
+
+impl AppModel {
+    pub(super) fn handle_quick_look(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::QuickLookKey(pressed) => {
+                let setting = self.settings.quick_look_key.as_str();
+                if !is_quick_look_key(setting, &pressed) {
+                    if self.quick_look.is_none() && pressed == THREAD_COLLAPSE_KEY {
+                        return self.dispatch(Message::ToggleThreadCollapse);
+                    }
+                    return Task::none();
+                }
+                if self.quick_look.take().is_some() {
+                    return Task::none();
+                }
+                return self.open_quick_look();
+            }
+            Message::QuickLookLoaded { email_id, result } => {
+                let Some(look) = self
+                    .quick_look
+                    .as_mut()
+                    .filter(|look| look.summary.email_id == email_id)
+                else {
+                    return Task::none();
+                };
+                look.body = match result {
+                    Ok(Some(plain)) => QuickLookBody::Loaded(plain),
+                    Ok(None) => QuickLookBody::NotDownloaded,
+                    Err(e) => QuickLookBody::Failed(e),
+                };
+            }
+            Message::CloseQuickLook => self.quick_look = None,
+            _ => {}
+        }
+        Task::none()
+    }
+
+    fn open_quick_look(&mut self) -> Task<Message> {
+        let Some(summary) = self.selected_message.and_then(|i| self.messages.get(i)) else {
+            return Task::none();
+        };
+        let summary = summary.clone();
+        let Some(cache) = self.cache.clone() else {
+            self.quick_look = Some(QuickLook {
+                summary,
+                body: QuickLookBody::NotDownloaded,
+            });
+            return Task::none();
+        };
+        let (account_id, email_id) = (summary.account_id.clone(), summary.email_id.clone());
+        self.quick_look = Some(QuickLook {
+            summary,
+            body: QuickLookBody::Loading,
+        });
+        cosmic::task::future(async move {
+            let result = cache
+                .load_body(account_id, email_id.clone())
+                .await
+                .map(|body| body.map(|(_, plain, _)| plain))
+                .map_err(|e| e.to_string());
+            Message::QuickLookLoaded { email_id, result }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_setting_matches_the_space_bar() {
+        assert!(is_quick_look_key("space", " "));
+        assert!(is_quick_look_key("Space", " "));
+        assert!(!is_quick_look_key("space", "s"));
+    }
+
+    #[test]
+    fn default_key_is_the_space_bar() {
+        assert!(is_quick_look_key(DEFAULT_KEY, " "));
+        assert!(!is_quick_look_key(DEFAULT_KEY, THREAD_COLLAPSE_KEY));
+    }
+
+    #[test]
+    fn character_setting_matches_only_itself() {
+        assert!(is_quick_look_key("q", "q"));
+        assert!(!is_quick_look_key("q", " "));
+    }
+
+    #[test]
+    fn space_is_labelled_by_name() {
+        assert_eq!(key_label("space"), "Space");
+        assert_eq!(key_label("q"), "q");
+    }
+}
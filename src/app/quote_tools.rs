@@ -0,0 +1,186 @@
+//! Compose toolbar for quoted text: add or remove a quote level on the
+//! selected lines, strip every quoted line, and re-wrap long quoted lines.
+//!
+//! Quote and Unquote work on the selection (select whole lines); the
+//! selection is replaced by the edited text. Strip and Rewrap work on the
+//! whole body. A line counts as quoted when it starts with `>` or the
+//! configured quote marker, after any leading spaces.
+
+use std::sync::Arc;
+
+use cosmic::app::Task;
+use cosmic::widget::text_editor;
+
+use super::{AppModel, ComposePhase, Message};
+
+/// Quoted lines longer than this many characters are re-wrapped.
+const REWRAP_WIDTH: usize = 72;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteTool {
+    Quote,
+    Unquote,
+    StripQuotes,
+    Rewrap,
+}
+
+/// `text` with `marker` in front of every line.
+fn quote_lines(text: &str, marker: &str) -> String {
+    map_lines(text, |line| format!("{marker}{line}"))
+}
+
+/// `text` with one quote level removed from each quoted line.
+fn unquote_lines(text: &str, marker: &str) -> String {
+    map_lines(text, |line| {
+        let rest = line
+            .strip_prefix(marker)
+            .or_else(|| line.strip_prefix(marker.trim_end()))
+            .or_else(|| line.strip_prefix("> "))
+            .or_else(|| line.strip_prefix('>'));
+        rest.unwrap_or(line).to_string()
+    })
+}
+
+fn is_quoted(line: &str, marker: &str) -> bool {
+    let line = line.trim_start();
+    let marker = marker.trim();
+    line.starts_with('>') || (!marker.is_empty() && line.starts_with(marker))
+}
+
+/// `text` without its quoted lines; blank lines left stacked up by the
+/// removal are merged.
+fn strip_quotes(text: &str, marker: &str) -> String {
+    let mut out: Vec<&str> = Vec::new();
+    for line in text.lines().filter(|line| !is_quoted(line, marker)) {
+        let blank = line.trim().is_empty();
+        if blank && out.last().is_some_and(|prev| prev.trim().is_empty()) {
+            continue;
+        }
+        out.push(line);
+    }
+    let mut joined = out.join("\n");
+    if text.ends_with('\n') {
+        joined.push('\n');
+    }
+    joined
+}
+
+/// Leading quote prefix of `line` (`>`, `> > `, `| `...), or `None` when
+/// the line isn't quoted.
+fn quote_prefix<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    if !is_quoted(line, marker) {
+        return None;
+    }
+    let marker_chars: Vec<char> = marker.trim().chars().collect();
+    let end = line
+        .char_indices()
+        .find(|(_, c)| *c != ' ' && *c != '>' && !marker_chars.contains(c))
+        .map_or(line.len(), |(i, _)| i);
+    Some(&line[..end])
+}
+
+/// `text` with quoted lines over `REWRAP_WIDTH` broken at spaces, each
+/// continuation keeping the line's quote prefix. Words longer than the
+/// width (URLs) stay whole.
+fn rewrap_quotes(text: &str, marker: &str) -> String {
+    map_lines(text, |line| {
+        let Some(prefix) = quote_prefix(line, marker) else {
+            return line.to_string();
+        };
+        if line.chars().count() <= REWRAP_WIDTH {
+            return line.to_string();
+        }
+        let room = REWRAP_WIDTH.saturating_sub(prefix.chars().count()).max(1);
+        let mut wrapped: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for word in line[prefix.len()..].split_whitespace() {
+            let len = current.chars().count();
+            if len > 0 && len + 1 + word.chars().count() > room {
+                wrapped.push(format!("{prefix}{current}"));
+                current.clear();
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        wrapped.push(format!("{prefix}{current}"));
+        wrapped.join("\n")
+    })
+}
+
+/// Apply `f` to every line of `text`, keeping a trailing newline.
+fn map_lines(text: &str, f: impl Fn(&str) -> String) -> String {
+    let mut out = text.lines().map(f).collect::<Vec<_>>().join("\n");
+    if text.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+impl AppModel {
+    pub(super) fn handle_quote_tool(&mut self, tool: QuoteTool) -> Task<Message> {
+        if self.compose_phase != ComposePhase::Open {
+            return Task::none();
+        }
+        let marker = self.settings.quoting.quote_marker.clone();
+        match tool {
+            QuoteTool::Quote | QuoteTool::Unquote => {
+                let Some(selected) = self.compose_body.selection() else {
+                    self.status_message = "Select the lines to quote or unquote".into();
+                    return Task::none();
+                };
+                let edited = match tool {
+                    QuoteTool::Quote => quote_lines(&selected, &marker),
+                    _ => unquote_lines(&selected, &marker),
+                };
+                self.compose_body
+                    .perform(text_editor::Action::Edit(text_editor::Edit::Paste(Arc::new(
+                        edited,
+                    ))));
+            }
+            QuoteTool::StripQuotes | QuoteTool::Rewrap => {
+                let body = self.compose_body.text();
+                let edited = match tool {
+                    QuoteTool::StripQuotes => strip_quotes(&body, &marker),
+                    _ => rewrap_quotes(&body, &marker),
+                };
+                if edited != body {
+                    self.compose_body = text_editor::Content::with_text(&edited);
+                }
+            }
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_and_unquote_round_trip() {
+        let text = "first\n> already quoted\n";
+        let quoted = quote_lines(text, "> ");
+        assert_eq!(quoted, "> first\n> > already quoted\n");
+        assert_eq!(unquote_lines(&quoted, "> "), text);
+        assert_eq!(unquote_lines(">bare\nplain", "> "), "bare\nplain");
+        assert_eq!(unquote_lines("| custom", "| "), "custom");
+    }
+
+    #[test]
+    fn strip_quotes_drops_quoted_lines_and_extra_blanks() {
+        let text = "Thanks!\n\nOn Monday, Bob wrote:\n> question\n>> older\n\n-- \nMe\n";
+        assert_eq!(strip_quotes(text, "> "), "Thanks!\n\nOn Monday, Bob wrote:\n\n-- \nMe\n");
+    }
+
+    #[test]
+    fn long_quoted_lines_wrap_under_their_prefix() {
+        let long = format!("> > {}", "word ".repeat(20).trim_end());
+        let wrapped = rewrap_quotes(&format!("{long}\nunquoted {}", "x ".repeat(40)), "> ");
+        let lines: Vec<&str> = wrapped.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[..2].iter().all(|l| l.starts_with("> > ") && l.chars().count() <= 72));
+        assert!(lines[2].starts_with("unquoted"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Compose recipient checks: Outlook-style "external recipient" warnings,
+//! the reply-all To line, the Reply-To or sender choice for replies, and
+//! the recipient count behind the mass-send confirmation.
+//!
+//! Each account can list its internal domains in settings; any To address
+//! outside those domains gets called out in the compose dialog. Accounts
+//! with no list configured never warn.
+
+use std::collections::HashSet;
+
+use super::settings::ReplyTarget;
+
+/// Split a comma-separated domain list as typed in the account dialog.
+pub fn parse_domain_list(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(|d| d.trim().trim_start_matches('@').to_ascii_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect()
+}
+
+/// Bare address from `Name <addr>` or `addr`.
+pub(super) fn bare_address(recipient: &str) -> &str {
+    let recipient = recipient.trim();
+    match (recipient.rfind('<'), recipient.rfind('>')) {
+        (Some(open), Some(close)) if open < close => recipient[open + 1..close].trim(),
+        _ => recipient,
+    }
+}
+
+/// Entries of a comma-separated To field. Commas inside a quoted display
+/// name (`"Smith, Alice" <a@x>`) or angle brackets don't split.
+pub(super) fn split_recipients(to: &str) -> Vec<&str> {
+    let mut entries = Vec::new();
+    let (mut start, mut quoted, mut bracketed) = (0, false, false);
+    for (i, c) in to.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '<' if !quoted => bracketed = true,
+            '>' if !quoted => bracketed = false,
+            ',' if !quoted && !bracketed => {
+                entries.push(to[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    entries.push(to[start..].trim());
+    entries.retain(|e| !e.is_empty());
+    entries
+}
+
+/// Display name from `Name <addr>`, falling back to the address.
+pub(super) fn display_name(from: &str) -> String {
+    let name = from
+        .split('<')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .trim_matches('"')
+        .trim();
+    if name.is_empty() {
+        bare_address(from).to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Whether `domain` is one of `domains` or a subdomain of one.
+pub(super) fn domain_in(domain: &str, domains: &[String]) -> bool {
+    domains.iter().any(|d| {
+        domain == d.as_str()
+            || domain
+                .strip_suffix(d.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Addresses in a comma-separated To field that fall outside `internal`.
+/// Empty when no internal domains are configured.
+pub fn external_recipients(to: &str, internal: &[String]) -> Vec<String> {
+    if internal.is_empty() {
+        return Vec::new();
+    }
+    to.split(',')
+        .map(bare_address)
+        // Half-typed entries without an '@' aren't judged yet.
+        .filter(|addr| {
+            addr.rsplit_once('@')
+                .is_some_and(|(_, d)| !domain_in(&d.to_ascii_lowercase(), internal))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Addresses in a comma-separated To field; half-typed entries don't count.
+pub fn recipient_count(to: &str) -> usize {
+    to.split(',').filter(|r| bare_address(r).contains('@')).count()
+}
+
+/// To line for reply-all: the sender, then everyone they addressed, without
+/// our own addresses or repeats. Falls back to the sender when that leaves
+/// nobody (a note to self).
+pub(super) fn reply_all_to(from: &str, to: &str, own: &[String]) -> String {
+    let own: HashSet<String> = own.iter().map(|a| a.to_ascii_lowercase()).collect();
+    let mut seen = HashSet::new();
+    let recipients: Vec<&str> = std::iter::once(from)
+        .chain(to.split(','))
+        .map(str::trim)
+        .filter(|r| {
+            let addr = bare_address(r).to_ascii_lowercase();
+            addr.contains('@') && !own.contains(&addr) && seen.insert(addr)
+        })
+        .collect();
+    if recipients.is_empty() {
+        return from.trim().to_string();
+    }
+    recipients.join(", ")
+}
+
+/// A reply to a message whose Reply-To isn't its sender: the compose
+/// dialog offers both, starting from the configured default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplyChoice {
+    pub reply_to: String,
+    pub sender: String,
+    pub target: ReplyTarget,
+}
+
+impl ReplyChoice {
+    /// `None` when there's no Reply-To or it only names the sender.
+    pub(super) fn new(from: &str, reply_to: Option<&str>, target: ReplyTarget) -> Option<Self> {
+        let reply_to = reply_to.map(str::trim).filter(|r| !r.is_empty())?;
+        let sender = bare_address(from);
+        if reply_to
+            .split(',')
+            .all(|r| bare_address(r).eq_ignore_ascii_case(sender))
+        {
+            return None;
+        }
+        Some(Self {
+            reply_to: reply_to.to_string(),
+            sender: from.trim().to_string(),
+            target,
+        })
+    }
+
+    /// To line for the chosen target.
+    pub fn address(&self) -> &str {
+        match self.target {
+            ReplyTarget::ReplyTo => &self.reply_to,
+            ReplyTarget::Sender => &self.sender,
+        }
+    }
+}
+
+/// Banner text for the compose dialog.
+pub fn external_warning(external: &[String]) -> String {
+    match external {
+        [one] => format!("External recipient: {one}"),
+        many => format!("{} external recipients: {}", many.len(), many.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_internal() -> Vec<String> {
+        parse_domain_list("corp.example, @Partner.example")
+    }
+
+    #[test]
+    fn quoted_commas_dont_split_recipients() {
+        assert_eq!(
+            split_recipients("\"Smith, Alice\" <a@x.example>, bob@x.example,"),
+            ["\"Smith, Alice\" <a@x.example>", "bob@x.example"]
+        );
+        assert!(split_recipients(" , ").is_empty());
+    }
+
+    #[test]
+    fn display_names_fall_back_to_address() {
+        assert_eq!(display_name("\"Alice Smith\" <alice@x.example>"), "Alice Smith");
+        assert_eq!(display_name("<bob@x.example>"), "bob@x.example");
+        assert_eq!(display_name("carol@x.example"), "carol@x.example");
+    }
+
+    #[test]
+    fn domain_list_normalizes_entries() {
+        assert_eq!(sample_internal(), vec!["corp.example", "partner.example"]);
+        assert!(parse_domain_list(" , ").is_empty());
+    }
+
+    #[test]
+    fn no_internal_domains_never_warns() {
+        assert!(external_recipients("someone@elsewhere.example", &[]).is_empty());
+    }
+
+    #[test]
+    fn flags_only_outside_addresses() {
+        let external = external_recipients(
+            "alice@corp.example, Bob <bob@gmail.example>, carol@mail.corp.example",
+            &sample_internal(),
+        );
+        assert_eq!(external, vec!["bob@gmail.example"]);
+    }
+
+    #[test]
+    fn half_typed_address_is_ignored() {
+        assert!(external_recipients("alic", &sample_internal()).is_empty());
+    }
+
+    #[test]
+    fn domain_match_is_case_insensitive_and_not_suffix_only() {
+        let internal = sample_internal();
+        assert!(external_recipients("Dave@CORP.EXAMPLE", &internal).is_empty());
+        assert_eq!(
+            external_recipients("eve@notcorp.example", &internal),
+            vec!["eve@notcorp.example"]
+        );
+    }
+
+    #[test]
+    fn reply_all_drops_own_addresses_and_repeats() {
+        let own = vec!["Me@corp.example".to_string()];
+        let to = reply_all_to(
+            "Alice <alice@corp.example>",
+            "me@corp.example, Bob <bob@corp.example>, ALICE@corp.example",
+            &own,
+        );
+        assert_eq!(to, "Alice <alice@corp.example>, Bob <bob@corp.example>");
+    }
+
+    #[test]
+    fn reply_all_to_own_note_keeps_the_sender() {
+        let own = vec!["me@corp.example".to_string()];
+        assert_eq!(
+            reply_all_to("me@corp.example", "me@corp.example", &own),
+            "me@corp.example"
+        );
+    }
+
+    #[test]
+    fn reply_choice_only_when_reply_to_names_someone_else() {
+        let from = "Alice <alice@x.example>";
+        assert_eq!(ReplyChoice::new(from, None, ReplyTarget::ReplyTo), None);
+        assert_eq!(ReplyChoice::new(from, Some("ALICE@x.example"), ReplyTarget::ReplyTo), None);
+
+        let list = Some("dev-list@lists.example");
+        let mut choice = ReplyChoice::new(from, list, ReplyTarget::ReplyTo).unwrap();
+        assert_eq!(choice.address(), "dev-list@lists.example");
+        choice.target = ReplyTarget::Sender;
+        assert_eq!(choice.address(), from);
+    }
+
+    #[test]
+    fn count_skips_half_typed_entries() {
+        assert_eq!(recipient_count("a@x.example, Bob <b@y.example>, car"), 2);
+        assert_eq!(recipient_count(""), 0);
+    }
+
+    #[test]
+    fn warning_text_pluralizes() {
+        assert_eq!(
+            external_warning(&["a@x.example".into()]),
+            "External recipient: a@x.example"
+        );
+        assert_eq!(
+            external_warning(&["a@x.example".into(), "b@y.example".into()]),
+            "2 external recipients: a@x.example, b@y.example"
+        );
+    }
+}
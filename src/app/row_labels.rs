@@ -0,0 +1,218 @@
+//! Display strings for message list rows ("Alice Smith <alice@example.com>
+//! — 14:05"), computed when messages load so the view only borrows them.
+//!
+//! Labels are keyed by email id, so reordering, inserting or removing rows
+//! never leaves them out of step with `messages`. Relative dates go stale as
+//! time passes; a minute tick recomputes every label.
+//!
+//! Subjects are cleaned for the one-line row: line breaks and other control
+//! characters become spaces, bidi overrides that would flip the rest of the
+//! row are dropped, and stacked combining marks ("Zalgo" text) are cut to a
+//! few per letter. Emoji, including ZWJ sequences and variation selectors,
+//! are left alone.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Datelike, Local, TimeZone};
+use neverlight_mail_core::models::MessageSummary;
+
+use super::folder_activity::{parse_summary_time, SummaryTime};
+use super::recipients::{bare_address, display_name};
+use super::AppModel;
+
+/// Combining marks kept on one letter; more is decoration, not language.
+const MAX_COMBINING_MARKS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowLabels {
+    pub subject: String,
+    /// Sender and date, ready to show.
+    pub meta: String,
+}
+
+impl RowLabels {
+    fn new<Tz: TimeZone>(msg: &MessageSummary, now: &DateTime<Tz>) -> Self {
+        Self {
+            subject: row_subject(&msg.subject),
+            meta: format!("{} — {}", row_sender(&msg.from), relative_date(&msg.date, now)),
+        }
+    }
+}
+
+/// "Alice Smith <alice@example.com>", or the bare address when there's no
+/// name. The address stays visible so a borrowed display name can't pass
+/// for someone else.
+fn row_sender(from: &str) -> String {
+    let name = display_name(from);
+    let address = bare_address(from);
+    if name == address {
+        name
+    } else {
+        format!("{name} <{address}>")
+    }
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+/// Embedding, override and isolate controls.
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Subject as one clean line for a list row.
+fn row_subject(subject: &str) -> String {
+    let mut out = String::with_capacity(subject.len());
+    let mut marks = 0;
+    for c in subject.chars() {
+        if is_bidi_control(c) {
+            continue;
+        }
+        if is_combining_mark(c) {
+            marks += 1;
+            if marks <= MAX_COMBINING_MARKS {
+                out.push(c);
+            }
+            continue;
+        }
+        marks = 0;
+        if c.is_control() || c.is_whitespace() {
+            if !out.is_empty() && !out.ends_with(' ') {
+                out.push(' ');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// "Just now", "12 min ago", "14:05", "Yesterday", "Tue", "Mar 4",
+/// "2024-03-04"; unparseable dates are shown as they came.
+fn relative_date<Tz: TimeZone>(date: &str, now: &DateTime<Tz>) -> String {
+    // Wall-clock times in the viewer's zone; zoneless dates already are.
+    let at = match parse_summary_time(date) {
+        Some(SummaryTime::Zoned(parsed)) => parsed.with_timezone(&now.timezone()).naive_local(),
+        Some(SummaryTime::Naive(at)) => at,
+        None => return date.trim().to_string(),
+    };
+    let now = now.naive_local();
+    let age = now - at;
+    let (day, today) = (at.date(), now.date());
+    let days = (today - day).num_days();
+
+    if (0..60).contains(&age.num_seconds()) {
+        "Just now".into()
+    } else if (1..60).contains(&age.num_minutes()) {
+        format!("{} min ago", age.num_minutes())
+    } else if days == 0 {
+        at.format("%H:%M").to_string()
+    } else if days == 1 {
+        "Yesterday".into()
+    } else if (2..7).contains(&days) {
+        at.format("%a").to_string()
+    } else if day.year() == today.year() {
+        at.format("%b %-d").to_string()
+    } else {
+        at.format("%Y-%m-%d").to_string()
+    }
+}
+
+impl AppModel {
+    /// Label rows that don't have one yet and forget rows that are gone.
+    pub(super) fn update_row_labels(&mut self) {
+        let now = Local::now();
+        let present: HashSet<&str> = self.messages.iter().map(|m| m.email_id.as_str()).collect();
+        self.row_labels.retain(|id, _| present.contains(id.as_str()));
+        for msg in &self.messages {
+            if !self.row_labels.contains_key(&msg.email_id) {
+                self.row_labels
+                    .insert(msg.email_id.clone(), RowLabels::new(msg, &now));
+            }
+        }
+    }
+
+    /// Recompute every label (relative dates moved on).
+    pub(super) fn refresh_row_labels(&mut self) {
+        let now = Local::now();
+        self.row_labels = self
+            .messages
+            .iter()
+            .map(|m| (m.email_id.clone(), RowLabels::new(m, &now)))
+            .collect::<HashMap<_, _>>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn sample_now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-03-12T15:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn recent_messages_read_relative() {
+        let now = sample_now();
+        assert_eq!(relative_date("2026-03-12T15:29:30Z", &now), "Just now");
+        assert_eq!(relative_date("2026-03-12T15:18:00Z", &now), "12 min ago");
+        assert_eq!(relative_date("2026-03-12T09:05:00Z", &now), "09:05");
+    }
+
+    #[test]
+    fn older_messages_fall_back_to_calendar_labels() {
+        let now = sample_now();
+        assert_eq!(relative_date("Wed, 11 Mar 2026 22:00:00 +0000", &now), "Yesterday");
+        assert_eq!(relative_date("2026-03-09T08:00:00Z", &now), "Mon");
+        assert_eq!(relative_date("2026-01-04T08:00:00Z", &now), "Jan 4");
+        assert_eq!(relative_date("2025-12-30T08:00:00Z", &now), "2025-12-30");
+    }
+
+    #[test]
+    fn zoneless_dates_are_wall_clock() {
+        let now = sample_now();
+        assert_eq!(relative_date("2026-03-12 09:05", &now), "09:05");
+        assert_eq!(relative_date("2026-03-11 22:00:00", &now), "Yesterday");
+    }
+
+    #[test]
+    fn sender_keeps_the_address() {
+        assert_eq!(
+            row_sender("\"Alice Smith\" <alice@x.example>"),
+            "Alice Smith <alice@x.example>"
+        );
+        assert_eq!(row_sender("bob@x.example"), "bob@x.example");
+    }
+
+    #[test]
+    fn subjects_are_cleaned_to_one_line() {
+        assert_eq!(row_subject(" Re:\tweekly\r\n  report "), "Re: weekly report");
+        assert_eq!(row_subject("Invoice \u{202E}fdp.exe"), "Invoice fdp.exe");
+        assert_eq!(
+            row_subject("Z\u{0301}\u{0302}\u{0303}\u{0304}\u{0305}a"),
+            "Z\u{0301}\u{0302}\u{0303}a"
+        );
+        // Family emoji: ZWJ sequence, kept whole.
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467} 🎉";
+        assert_eq!(row_subject(family), family);
+    }
+
+    #[test]
+    fn future_and_unparseable_dates() {
+        let now = sample_now();
+        assert_eq!(relative_date("2026-03-12T18:00:00Z", &now), "18:00");
+        assert_eq!(relative_date(" sometime ", &now), "sometime");
+    }
+}
@@ -0,0 +1,206 @@
+//! Right-click menu on message list rows.
+//!
+//! Each entry carries the row it was opened on, so it acts on that message
+//! without the row being opened first. On a row that is part of the bulk
+//! selection, read/archive/trash/spam/move act on the whole selection
+//! instead; reply and forward always answer the clicked message. Replying to
+//! a row that isn't open opens it and starts compose once its body is in.
+
+use cosmic::app::Task;
+use cosmic::widget::menu;
+
+use super::{AppModel, MailboxIdentity, Message, MessageIdentity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RowAction {
+    Reply,
+    ReplyAll,
+    Forward,
+    ToggleRead,
+    ToggleStar,
+    Archive,
+    MarkSpam,
+    /// Move to the account's folder at this index.
+    MoveTo(usize),
+    Trash,
+    /// Open side by side with the message in the preview.
+    Compare,
+}
+
+impl RowAction {
+    /// Compose actions, which need the message body loaded.
+    pub(super) fn compose_message(self) -> Option<Message> {
+        match self {
+            Self::Reply => Some(Message::ComposeReply),
+            Self::ReplyAll => Some(Message::ComposeReplyAll),
+            Self::Forward => Some(Message::ComposeForward),
+            _ => None,
+        }
+    }
+}
+
+/// A row menu entry: `action` on the message at list index `index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RowMenuAction {
+    pub index: usize,
+    pub action: RowAction,
+}
+
+impl menu::action::MenuAction for RowMenuAction {
+    type Message = Message;
+
+    fn message(&self) -> Message {
+        Message::RowMenu {
+            index: self.index,
+            action: self.action,
+        }
+    }
+}
+
+/// Compose waiting for the body of `email_id` to load.
+#[derive(Debug, Clone)]
+pub struct PendingRowCompose {
+    pub email_id: String,
+    pub action: RowAction,
+}
+
+impl AppModel {
+    pub(super) fn handle_row_menu(&mut self, index: usize, action: RowAction) -> Task<Message> {
+        let Some(msg) = self.messages.get(index) else {
+            return Task::none();
+        };
+        let identity = MessageIdentity::of(msg);
+
+        if action == RowAction::Compare {
+            return self.open_comparison(index);
+        }
+        if let Some(compose) = action.compose_message() {
+            if self.selected_message == Some(index) && !self.body_loading() {
+                // Within an open conversation, answer this message.
+                if self.conversation.iter().any(|e| e.email_id == identity.email_id) {
+                    self.active_conversation_id = Some(identity.email_id);
+                }
+                return self.dispatch(compose);
+            }
+            let open = self.dispatch(Message::ViewBody(index));
+            self.pending_row_compose = Some(PendingRowCompose {
+                email_id: identity.email_id,
+                action,
+            });
+            return open;
+        }
+
+        if self.message_selection.contains(&identity) {
+            return match action {
+                RowAction::ToggleRead => self.dispatch(Message::BulkMarkRead),
+                RowAction::Archive => self.dispatch(Message::BulkArchive),
+                RowAction::Trash => self.dispatch(Message::BulkTrash),
+                RowAction::MarkSpam => self.bulk_move_selection("junk"),
+                RowAction::MoveTo(folder) => self.move_selection_to(&identity.account_id, folder),
+                // Stars stay per message.
+                RowAction::ToggleStar => self.dispatch(Message::ToggleStar(index)),
+                RowAction::Reply
+                | RowAction::ReplyAll
+                | RowAction::Forward
+                | RowAction::Compare => Task::none(),
+            };
+        }
+
+        match action {
+            RowAction::ToggleRead => self.dispatch(Message::ToggleRead(index)),
+            RowAction::ToggleStar => self.dispatch(Message::ToggleStar(index)),
+            RowAction::Archive => self.dispatch(Message::Archive(index)),
+            RowAction::Trash => self.dispatch(Message::Trash(index)),
+            RowAction::MarkSpam => {
+                let Some(junk) = self.role_mailbox(&identity.account_id, "junk") else {
+                    self.status_message = "Spam folder not found".into();
+                    return Task::none();
+                };
+                self.dispatch(move_message(identity, junk))
+            }
+            RowAction::MoveTo(folder) => {
+                let Some(dest) = self.folder_mailbox(&identity.account_id, folder) else {
+                    return Task::none();
+                };
+                self.dispatch(move_message(identity, dest))
+            }
+            RowAction::Reply | RowAction::ReplyAll | RowAction::Forward | RowAction::Compare => {
+                Task::none()
+            }
+        }
+    }
+
+    /// Whether the open message's body (or conversation) is still loading.
+    fn body_loading(&self) -> bool {
+        self.body_abort.is_some() || self.conversation.iter().any(|e| !e.loaded)
+    }
+
+    fn folder_mailbox(&self, account_id: &str, folder: usize) -> Option<String> {
+        let acct = self.accounts.get(self.account_index(account_id)?)?;
+        Some(acct.folders.get(folder)?.mailbox_id.clone())
+    }
+
+    /// Move the selected messages of `account_id` to its folder `folder`.
+    fn move_selection_to(&mut self, account_id: &str, folder: usize) -> Task<Message> {
+        let Some(dest) = self.folder_mailbox(account_id, folder) else {
+            return Task::none();
+        };
+        let (moving, skipped): (Vec<MessageIdentity>, Vec<MessageIdentity>) = self
+            .message_selection
+            .identities()
+            .cloned()
+            .partition(|id| id.account_id == account_id);
+        self.message_selection.clear();
+        if !skipped.is_empty() {
+            self.status_message =
+                format!("{} from other accounts left in place", skipped.len());
+        }
+        let tasks = moving
+            .into_iter()
+            .map(|identity| self.dispatch(move_message(identity, dest.clone())))
+            .collect::<Vec<_>>();
+        cosmic::task::batch(tasks)
+    }
+
+    /// Start a compose queued from the row menu, now that `email_id` is
+    /// loaded. A different message having loaded drops it.
+    pub(super) fn run_pending_row_compose(&mut self, email_id: &str) -> Task<Message> {
+        let Some(pending) = self.pending_row_compose.take() else {
+            return Task::none();
+        };
+        if pending.email_id != email_id {
+            return Task::none();
+        }
+        match pending.action.compose_message() {
+            Some(compose) => self.dispatch(compose),
+            None => Task::none(),
+        }
+    }
+}
+
+pub(super) fn move_message(message: MessageIdentity, dest_mailbox: String) -> Message {
+    Message::DragMessageToFolder {
+        source: MailboxIdentity {
+            account_id: message.account_id.clone(),
+            mailbox_id: message.mailbox_id.clone(),
+        },
+        dest: MailboxIdentity {
+            account_id: message.account_id.clone(),
+            mailbox_id: dest_mailbox,
+        },
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_reply_and_forward_wait_for_the_body() {
+        assert!(RowAction::Reply.compose_message().is_some());
+        assert!(RowAction::Forward.compose_message().is_some());
+        assert!(RowAction::Archive.compose_message().is_none());
+        assert!(RowAction::MoveTo(2).compose_message().is_none());
+    }
+}
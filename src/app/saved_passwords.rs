@@ -0,0 +1,183 @@
+//! "Find saved password" in the setup dialog: look for an app password that
+//! another tool already stored in the Secret Service keyring.
+//!
+//! Items are matched on the server host and username under the attribute
+//! names other tools use: `server`/`user` (libsecret's network-password
+//! schema, used by GNOME apps) and `service`/`username` (`secret-tool`
+//! conventions and keyring-rs). Both are required: a host-only match would
+//! decrypt other accounts' passwords on the same server, and raise an unlock
+//! prompt for each. Locked items are unlocked through the desktop's own
+//! prompt. Picking one only fills the Token field; the
+//! account then stores it like a typed password.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use cosmic::app::Task;
+use secret_service::{EncryptionType, SecretService};
+
+use super::{AppModel, Message};
+
+/// A keyring item that looks like this account's password.
+#[derive(Clone)]
+pub struct SavedPassword {
+    pub label: String,
+    secret: String,
+}
+
+// Keep secrets out of logs and debug output.
+impl fmt::Debug for SavedPassword {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SavedPassword")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Host part of the JMAP session URL.
+fn server_host(jmap_url: &str) -> Option<String> {
+    let url = url::Url::parse(jmap_url.trim()).ok()?;
+    Some(url.host_str()?.to_lowercase())
+}
+
+/// Attribute sets to search, one per schema.
+fn attribute_queries(host: &str, username: &str) -> Vec<Vec<(&'static str, String)>> {
+    const SCHEMAS: [(&str, &str); 2] = [("server", "user"), ("service", "username")];
+    SCHEMAS
+        .into_iter()
+        .map(|(server_key, user_key)| {
+            vec![
+                (server_key, host.to_string()),
+                (user_key, username.trim().to_string()),
+            ]
+        })
+        .collect()
+}
+
+async fn find_saved_passwords(
+    host: String,
+    username: String,
+) -> Result<Vec<SavedPassword>, String> {
+    let service = SecretService::connect(EncryptionType::Dh)
+        .await
+        .map_err(|e| format!("Keyring unavailable: {e}"))?;
+    let mut found: Vec<SavedPassword> = Vec::new();
+    for query in attribute_queries(&host, &username) {
+        let attributes: HashMap<&str, &str> =
+            query.iter().map(|(key, value)| (*key, value.as_str())).collect();
+        let items = service
+            .search_items(attributes)
+            .await
+            .map_err(|e| format!("Keyring search failed: {e}"))?;
+        for item in items.unlocked.iter().chain(items.locked.iter()) {
+            if item.is_locked().await.unwrap_or(true) && item.unlock().await.is_err() {
+                continue;
+            }
+            let (Ok(label), Ok(secret)) = (item.get_label().await, item.get_secret().await) else {
+                continue;
+            };
+            let Ok(secret) = String::from_utf8(secret) else {
+                continue;
+            };
+            if !found.iter().any(|p| p.secret == secret) {
+                found.push(SavedPassword { label, secret });
+            }
+        }
+    }
+    Ok(found)
+}
+
+impl AppModel {
+    pub(super) fn handle_saved_passwords(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::SetupFindSavedPassword => {
+                let Some(model) = &self.setup_model else {
+                    return Task::none();
+                };
+                let Some(host) = server_host(&model.jmap_url) else {
+                    self.setup_saved_passwords_note =
+                        Some("Enter the JMAP session URL first".into());
+                    return Task::none();
+                };
+                if model.username.trim().is_empty() {
+                    self.setup_saved_passwords_note = Some("Enter the username first".into());
+                    return Task::none();
+                }
+                let username = model.username.clone();
+                self.setup_saved_passwords_note = Some("Searching the keyring...".into());
+                return cosmic::task::future(async move {
+                    Message::SetupSavedPasswordsFound(find_saved_passwords(host, username).await)
+                });
+            }
+            Message::SetupSavedPasswordsFound(result) => {
+                match result {
+                    Ok(found) if found.is_empty() => {
+                        self.setup_saved_passwords_note =
+                            Some("No saved password for this server".into());
+                        self.setup_saved_passwords.clear();
+                    }
+                    Ok(found) => {
+                        self.setup_saved_passwords_note = None;
+                        self.setup_saved_passwords = found;
+                    }
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        self.setup_saved_passwords_note = Some(e);
+                        self.setup_saved_passwords.clear();
+                    }
+                }
+            }
+            Message::SetupUseSavedPassword(index) => {
+                let Some(saved) = self.setup_saved_passwords.get(index) else {
+                    return Task::none();
+                };
+                let secret = saved.secret.clone();
+                self.setup_saved_passwords_note = Some(format!("Using \"{}\"", saved.label));
+                self.setup_saved_passwords.clear();
+                return self.dispatch(Message::SetupTokenChanged(secret));
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_comes_from_the_session_url() {
+        assert_eq!(
+            server_host("https://API.fastmail.com/jmap/session").as_deref(),
+            Some("api.fastmail.com")
+        );
+        assert_eq!(server_host("not a url"), None);
+    }
+
+    #[test]
+    fn every_query_names_the_user() {
+        let queries = attribute_queries("mail.example.com", " me@example.com ");
+        assert_eq!(queries.len(), 2);
+        assert_eq!(
+            queries[0],
+            [("server", "mail.example.com".to_string()), ("user", "me@example.com".to_string())]
+        );
+        assert_eq!(
+            queries[1],
+            [
+                ("service", "mail.example.com".to_string()),
+                ("username", "me@example.com".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn secrets_stay_out_of_debug_output() {
+        let saved = SavedPassword {
+            label: "Fastmail".into(),
+            secret: "hunter2".into(),
+        };
+        assert!(!format!("{saved:?}").contains("hunter2"));
+    }
+}
@@ -1,16 +1,333 @@
+//! Full-text search over the cache, with refinement.
+//!
+//! A search is a list of terms. The first comes from the search box; once
+//! results are showing, what is typed next narrows them (the terms are
+//! AND-ed) and each term shows as a chip that can be removed on its own.
+//! Core's `cache.search` takes a single query string, so every term is
+//! searched separately and the results are intersected, keeping the first
+//! term's order.
+//!
+//! Words go to the cache's FTS5 index as typed when they are plain words,
+//! `invoi*` prefixes, `AND`/`OR`/`NOT` between words, `NEAR(…)` or balanced
+//! parentheses. Anything else (`c++`, `e-mail`, a stray `OR`, an unmatched
+//! `(`) is quoted, so it is searched for instead of failing as syntax, and
+//! double quotes keep a phrase together. Field filters narrow what the
+//! words found:
+//!
+//! - `from:`, `to:`, `subject:` — the field contains the value (any case);
+//!   the value is searched for as a word too
+//! - `before:2024-01-01` (earlier days only), `after:2024-01-01` (that day
+//!   on)
+//! - `is:unread`, `is:read`, `is:starred`
+//! - `has:attachment`
+//!
+//! Core's `cache.search` takes a query string, not SQL, so filters run on its
+//! results and a search needs at least one word. Core's summaries don't say
+//! whether a message has attachments, so for `has:attachment` the matches
+//! are checked against their cached bodies; messages whose body was never
+//! downloaded drop out and are counted in the status line.
+
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
 use cosmic::app::Task;
 use cosmic::widget;
 use futures::future::{AbortHandle, Abortable};
+use neverlight_mail_core::models::MessageSummary;
+
+use super::folder_activity::parse_summary_date;
+use super::{AppModel, Message, Phase, SearchPhase};
+
+/// What a search found.
+#[derive(Debug, Clone)]
+pub struct SearchHits {
+    pub messages: Vec<MessageSummary>,
+    /// Attachment counts learned from cached bodies (`has:attachment`).
+    pub attachment_counts: Vec<(String, usize)>,
+    /// Matches left out because their body isn't cached.
+    pub uncached: usize,
+}
+
+/// A field filter from the search box; text values are lowercase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    From(String),
+    To(String),
+    Subject(String),
+    /// Sent before this day.
+    Before(NaiveDate),
+    /// Sent on this day or later.
+    After(NaiveDate),
+    Unread,
+    Read,
+    Starred,
+}
+
+impl Filter {
+    fn matches(&self, msg: &MessageSummary) -> bool {
+        let day = || parse_summary_date(&msg.date);
+        match self {
+            Self::From(value) => msg.from.to_lowercase().contains(value),
+            Self::To(value) => msg.to.to_lowercase().contains(value),
+            Self::Subject(value) => msg.subject.to_lowercase().contains(value),
+            Self::Before(limit) => day().is_some_and(|day| day < *limit),
+            Self::After(limit) => day().is_some_and(|day| day >= *limit),
+            Self::Unread => !msg.is_read,
+            Self::Read => msg.is_read,
+            Self::Starred => msg.is_starred,
+        }
+    }
+}
+
+/// Terms split into queries for the cache and filters on what they find.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SearchQuery {
+    /// One cache query per term that has words, every word quoted.
+    text: Vec<String>,
+    filters: Vec<Filter>,
+    has_attachment: bool,
+}
+
+impl SearchQuery {
+    fn parse(terms: &[String]) -> Result<Self, String> {
+        let mut query = Self::default();
+        for term in terms {
+            let mut text = Vec::new();
+            for word in words(term) {
+                let split = word.text.split_once(':').filter(|(_, v)| !v.is_empty());
+                let Some((key, value)) = split else {
+                    text.push(word);
+                    continue;
+                };
+                let lower = value.to_lowercase();
+                let filter = match (key.to_ascii_lowercase().as_str(), lower.as_str()) {
+                    ("has", "attachment") => {
+                        query.has_attachment = true;
+                        continue;
+                    }
+                    ("is", "unread") => Filter::Unread,
+                    ("is", "read") => Filter::Read,
+                    ("is", "starred" | "flagged") => Filter::Starred,
+                    ("from", _) => Filter::From(lower.clone()),
+                    ("to", _) => Filter::To(lower.clone()),
+                    ("subject", _) => Filter::Subject(lower.clone()),
+                    ("before", _) => Filter::Before(parse_day(value)?),
+                    ("after", _) => Filter::After(parse_day(value)?),
+                    _ => {
+                        text.push(word);
+                        continue;
+                    }
+                };
+                if matches!(filter, Filter::From(_) | Filter::To(_) | Filter::Subject(_)) {
+                    text.push(Word {
+                        text: value.to_string(),
+                        quoted: true,
+                    });
+                }
+                query.filters.push(filter);
+            }
+            if !text.is_empty() {
+                query.text.push(fts_query(&text));
+            }
+        }
+        Ok(query)
+    }
 
-use super::{AppModel, ComposePhase, Message, Phase, SearchPhase};
+    fn matches(&self, msg: &MessageSummary) -> bool {
+        self.filters.iter().all(|filter| filter.matches(msg))
+    }
+}
+
+/// A word of a term, without its double quotes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Word {
+    text: String,
+    /// Written in double quotes: searched for literally.
+    quoted: bool,
+}
+
+/// Words of a term; double quotes keep spaces inside a word and are
+/// dropped, so no word carries one.
+fn words(term: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut word = Word {
+        text: String::new(),
+        quoted: false,
+    };
+    let mut in_quotes = false;
+    for c in term.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                word.quoted = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !word.text.is_empty() {
+                    words.push(std::mem::replace(
+                        &mut word,
+                        Word {
+                            text: String::new(),
+                            quoted: false,
+                        },
+                    ));
+                }
+            }
+            c => word.text.push(c),
+        }
+    }
+    if !word.text.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// What a piece of an FTS5 query is to its parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FtsToken {
+    Term,
+    Operator,
+    Open,
+    Close,
+    Comma,
+}
+
+/// Letters, digits, `_` and anything non-ASCII: an FTS5 bareword.
+fn is_bareword(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_alphanumeric() || c == '_' || !c.is_ascii())
+}
+
+fn fts_quoted(text: &str) -> String {
+    format!("\"{text}\"")
+}
+
+/// A bare piece of a word: an operator, a bareword (or `prefix*`) as is,
+/// anything else quoted.
+fn fts_piece(piece: &str) -> (String, FtsToken) {
+    if matches!(piece, "AND" | "OR" | "NOT") {
+        return (piece.to_string(), FtsToken::Operator);
+    }
+    let stem = piece.strip_suffix('*').unwrap_or(piece);
+    if is_bareword(stem) {
+        (piece.to_string(), FtsToken::Term)
+    } else {
+        (fts_quoted(piece), FtsToken::Term)
+    }
+}
+
+/// A group with no term in it (`()`, `NEAR()`) is searched for as text.
+fn fold_empty_group(tokens: &mut Vec<(String, FtsToken)>, open: usize, near: bool) {
+    if tokens[open..].iter().any(|t| t.1 == FtsToken::Term) {
+        return;
+    }
+    let start = if near { open - 1 } else { open };
+    let text: String = tokens.drain(start..).map(|(text, _)| text).collect();
+    tokens.push((fts_quoted(&text), FtsToken::Term));
+}
+
+/// `words` as one FTS5 query that can't be a syntax error.
+fn fts_query(words: &[Word]) -> String {
+    let bare = || words.iter().filter(|w| !w.quoted).map(|w| w.text.as_str());
+    // Parentheses group only when every one is matched; commas only
+    // separate distance inside a NEAR group.
+    let mut depth = 0i32;
+    let balanced = bare()
+        .flat_map(str::chars)
+        .all(|c| {
+            depth += match c {
+                '(' => 1,
+                ')' => -1,
+                _ => 0,
+            };
+            depth >= 0
+        })
+        && depth == 0;
+
+    let mut tokens: Vec<(String, FtsToken)> = Vec::new();
+    // Open groups: where each one's `(` is in `tokens`, and whether it's NEAR's.
+    let mut groups: Vec<(usize, bool)> = Vec::new();
+    for word in words {
+        if word.quoted {
+            tokens.push((fts_quoted(&word.text), FtsToken::Term));
+            continue;
+        }
+        let mut piece = String::new();
+        for c in word.text.chars() {
+            let kind = match c {
+                '(' if balanced => FtsToken::Open,
+                ')' if balanced => FtsToken::Close,
+                ',' if groups.last().is_some_and(|&(_, near)| near) => FtsToken::Comma,
+                c => {
+                    piece.push(c);
+                    continue;
+                }
+            };
+            let near = kind == FtsToken::Open && piece == "NEAR";
+            if !piece.is_empty() {
+                tokens.push(fts_piece(&std::mem::take(&mut piece)));
+            }
+            if kind == FtsToken::Open {
+                groups.push((tokens.len(), near));
+            }
+            tokens.push((c.to_string(), kind));
+            if kind == FtsToken::Close {
+                if let Some((open, near)) = groups.pop() {
+                    fold_empty_group(&mut tokens, open, near);
+                }
+            }
+        }
+        if !piece.is_empty() {
+            tokens.push(fts_piece(&piece));
+        }
+    }
+    // An operator needs a term on each side; a stray one is a word.
+    for i in 0..tokens.len() {
+        if tokens[i].1 != FtsToken::Operator {
+            continue;
+        }
+        let before = i.checked_sub(1).map(|j| tokens[j].1);
+        let after = tokens.get(i + 1).map(|t| t.1);
+        let joins = matches!(before, Some(FtsToken::Term | FtsToken::Close))
+            && matches!(after, Some(FtsToken::Term | FtsToken::Open));
+        if !joins {
+            tokens[i] = (fts_quoted(&tokens[i].0), FtsToken::Term);
+        }
+    }
+    let pieces: Vec<String> = tokens.into_iter().map(|(text, _)| text).collect();
+    pieces.join(" ")
+}
+
+fn parse_day(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Search dates are written 2024-01-31, not \"{value}\""))
+}
 
 fn should_apply_search_results(
     current_epoch: u64,
     incoming_epoch: u64,
-    current_query: &str,
-    incoming_query: &str,
+    current_terms: &[String],
+    incoming_terms: &[String],
 ) -> bool {
-    current_epoch == incoming_epoch && current_query.trim() == incoming_query
+    current_epoch == incoming_epoch && current_terms == incoming_terms
+}
+
+/// `terms` with `query` AND-ed on; `None` when it adds nothing.
+fn refined_terms(terms: &[String], query: &str) -> Option<Vec<String>> {
+    let query = query.trim();
+    if query.is_empty() || terms.iter().any(|t| t.eq_ignore_ascii_case(query)) {
+        return None;
+    }
+    let mut refined = terms.to_vec();
+    refined.push(query.to_string());
+    Some(refined)
+}
+
+/// `"invoice" + "march"` for the status line.
+fn terms_label(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|t| format!("\"{t}\""))
+        .collect::<Vec<_>>()
+        .join(" + ")
 }
 
 impl AppModel {
@@ -32,60 +349,61 @@ impl AppModel {
                 if query.is_empty() {
                     return Task::none();
                 }
-                if let Some(cache) = &self.cache {
-                    let cache = cache.clone();
-                    let account_id = self.active_account_id();
-                    self.search_epoch = self.search_epoch.saturating_add(1);
-                    let epoch = self.search_epoch;
-                    self.status_message = "Searching...".into();
-                    self.phase = Phase::Searching;
-                    if let Some(handle) = self.search_abort.take() {
-                        handle.abort();
-                    }
-                    let (abort_handle, abort_reg) = AbortHandle::new_pair();
-                    self.search_abort = Some(abort_handle);
-                    return cosmic::task::future(async move {
-                        match Abortable::new(cache.search(account_id, query.clone()), abort_reg).await {
-                            Ok(result) => Message::SearchResultsLoaded {
-                                query,
-                                epoch,
-                                result,
-                            },
-                            Err(_) => Message::Noop,
-                        }
-                    });
+                return self.run_search(vec![query]);
+            }
+            Message::SearchRefine => {
+                let Some(terms) = refined_terms(&self.search_terms, &self.search_query) else {
+                    return Task::none();
+                };
+                self.search_query.clear();
+                return self.run_search(terms);
+            }
+            Message::SearchRemoveTerm(index) => {
+                if index >= self.search_terms.len() {
+                    return Task::none();
+                }
+                let mut terms = self.search_terms.clone();
+                terms.remove(index);
+                if terms.is_empty() {
+                    return self.dispatch(Message::SearchClear);
                 }
+                return self.run_search(terms);
             }
             Message::SearchResultsLoaded {
-                query,
+                terms,
                 epoch,
-                result: Ok(results),
+                result: Ok(hits),
             } => {
-                if !should_apply_search_results(
-                    self.search_epoch,
-                    epoch,
-                    self.search_query.as_str(),
-                    query.as_str(),
-                ) {
+                let current = &self.search_terms;
+                if !should_apply_search_results(self.search_epoch, epoch, current, &terms) {
                     self.stale_apply_drop_count = self.stale_apply_drop_count.saturating_add(1);
                     return Task::none();
                 }
                 self.search_abort = None;
-                let count = results.len();
-                self.messages = results;
+                let count = hits.messages.len();
+                self.messages = hits.messages;
+                self.attachment_counts.extend(hits.attachment_counts);
                 self.selected_message = None;
                 self.preview_body.clear();
                 self.preview_markdown.clear();
                 self.preview_attachments.clear();
                 self.preview_image_handles.clear();
+                self.preview_attachment_hashes.clear();
                 self.collapsed_threads.clear();
                 self.has_more_messages = false;
                 self.recompute_visible();
                 self.search_phase = SearchPhase::Results;
+                let label = terms_label(&terms);
                 if count > 0 {
-                    self.status_message = format!("Search: {} results for \"{}\"", count, query);
+                    self.status_message = format!("Search: {} results for {}", count, label);
                 } else {
-                    self.status_message = format!("Search: no results for \"{}\"", query);
+                    self.status_message = format!("Search: no results for {}", label);
+                }
+                if hits.uncached > 0 {
+                    self.status_message.push_str(&format!(
+                        " ({} not downloaded yet, so not checked for attachments)",
+                        hits.uncached
+                    ));
                 }
                 self.clear_error_surface();
                 self.phase = Phase::Idle;
@@ -111,16 +429,20 @@ impl AppModel {
                     }
                     self.search_phase = SearchPhase::Inactive;
                     self.search_query.clear();
-                    // Restore previous folder view
+                    self.search_terms.clear();
+                    // Restore previous folder or smart view
+                    if self.smart_view.is_some() {
+                        return self.load_smart_view();
+                    }
                     if let Some(acct_idx) = self.active_account {
                         if let Some(folder_idx) = self.selected_folder {
                             self.phase = Phase::Loading;
                             return self.dispatch(Message::SelectFolder(acct_idx, folder_idx));
                         }
                     }
-                } else {
-                    // Not searching — Escape cancels compose dialog
-                    self.compose_phase = ComposePhase::Closed;
+                } else if self.compose_phase.is_open() {
+                    // Not searching — Escape cancels compose dialog (guarded if dirty)
+                    return self.dispatch(Message::ComposeCancel);
                 }
             }
 
@@ -128,25 +450,182 @@ impl AppModel {
         }
         Task::none()
     }
+
+    /// Search the cache for messages matching every one of `terms`.
+    fn run_search(&mut self, terms: Vec<String>) -> Task<Message> {
+        let Some(cache) = self.cache.clone() else {
+            return Task::none();
+        };
+        let query = match SearchQuery::parse(&terms) {
+            Ok(query) => query,
+            Err(e) => {
+                self.status_message = e;
+                return Task::none();
+            }
+        };
+        if query.text.is_empty() {
+            self.status_message = "Add a word to search for; filters only narrow a search".into();
+            return Task::none();
+        }
+        let account_id = self.active_account_id();
+        self.search_terms = terms.clone();
+        self.search_epoch = self.search_epoch.saturating_add(1);
+        let epoch = self.search_epoch;
+        self.status_message = "Searching...".into();
+        self.phase = Phase::Searching;
+        if let Some(handle) = self.search_abort.take() {
+            handle.abort();
+        }
+        let (abort_handle, abort_reg) = AbortHandle::new_pair();
+        self.search_abort = Some(abort_handle);
+        cosmic::task::future(async move {
+            let search = async {
+                let Some((first, rest)) = query.text.split_first() else {
+                    return Ok(Vec::new());
+                };
+                let mut results = cache.search(account_id.clone(), first.clone()).await?;
+                for term in rest {
+                    let matching: HashSet<(String, String)> = cache
+                        .search(account_id.clone(), term.clone())
+                        .await?
+                        .into_iter()
+                        .map(|m| (m.account_id, m.email_id))
+                        .collect();
+                    results.retain(|m| {
+                        matching.contains(&(m.account_id.clone(), m.email_id.clone()))
+                    });
+                }
+                results.retain(|m| query.matches(m));
+                Ok::<_, String>(results)
+            };
+            let search = async {
+                let messages = search.await?;
+                if !query.has_attachment {
+                    return Ok(SearchHits {
+                        messages,
+                        attachment_counts: Vec::new(),
+                        uncached: 0,
+                    });
+                }
+                let mut hits = SearchHits {
+                    messages: Vec::new(),
+                    attachment_counts: Vec::new(),
+                    uncached: 0,
+                };
+                for msg in messages {
+                    let body = cache.load_body(msg.account_id.clone(), msg.email_id.clone());
+                    match body.await? {
+                        Some((_, _, attachments)) if !attachments.is_empty() => {
+                            hits.attachment_counts.push((msg.email_id.clone(), attachments.len()));
+                            hits.messages.push(msg);
+                        }
+                        Some(_) => {}
+                        None => hits.uncached += 1,
+                    }
+                }
+                Ok::<_, String>(hits)
+            };
+            match Abortable::new(search, abort_reg).await {
+                Ok(result) => Message::SearchResultsLoaded {
+                    terms,
+                    epoch,
+                    result,
+                },
+                Err(_) => Message::Noop,
+            }
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::should_apply_search_results;
+    use super::*;
+
+    fn terms(list: &[&str]) -> Vec<String> {
+        list.iter().map(|t| t.to_string()).collect()
+    }
 
     #[test]
     fn search_results_apply_when_epoch_and_query_match() {
-        assert!(should_apply_search_results(8, 8, "inbox", "inbox"));
-        assert!(should_apply_search_results(8, 8, "  inbox  ", "inbox"));
+        assert!(should_apply_search_results(8, 8, &terms(&["inbox"]), &terms(&["inbox"])));
+        let refined = terms(&["inbox", "march"]);
+        assert!(should_apply_search_results(8, 8, &refined, &refined));
     }
 
     #[test]
     fn search_results_drop_on_epoch_mismatch() {
-        assert!(!should_apply_search_results(9, 8, "inbox", "inbox"));
+        assert!(!should_apply_search_results(9, 8, &terms(&["inbox"]), &terms(&["inbox"])));
     }
 
     #[test]
     fn search_results_drop_on_query_mismatch() {
-        assert!(!should_apply_search_results(8, 8, "inbox", "sent"));
+        assert!(!should_apply_search_results(8, 8, &terms(&["inbox"]), &terms(&["sent"])));
+        let refined = terms(&["inbox", "march"]);
+        assert!(!should_apply_search_results(8, 8, &refined, &terms(&["inbox"])));
+    }
+
+    #[test]
+    fn refining_adds_new_trimmed_terms_only() {
+        let first = terms(&["invoice"]);
+        assert_eq!(refined_terms(&first, "  march "), Some(terms(&["invoice", "march"])));
+        assert_eq!(refined_terms(&first, "Invoice"), None);
+        assert_eq!(refined_terms(&first, "   "), None);
+        assert_eq!(terms_label(&terms(&["invoice", "march"])), "\"invoice\" + \"march\"");
+    }
+
+    #[test]
+    fn has_attachment_is_taken_out_of_the_text_terms() {
+        let query = SearchQuery::parse(&terms(&["invoice Has:Attachment", "march"])).unwrap();
+        assert_eq!(query.text, terms(&["invoice", "march"]));
+        assert!(query.has_attachment);
+        let query = SearchQuery::parse(&terms(&["has:attachment"])).unwrap();
+        assert!(query.text.is_empty() && query.has_attachment);
+        let query = SearchQuery::parse(&terms(&["has:attachments"])).unwrap();
+        assert_eq!(query.text, terms(&["\"has:attachments\""]));
+        assert!(!query.has_attachment);
+    }
+
+    #[test]
+    fn field_filters_are_parsed_and_values_quoted() {
+        let query = SearchQuery::parse(&terms(&[
+            "from:Alice subject:\"Q1 invoice\" is:unread before:2024-02-01 report",
+        ]))
+        .unwrap();
+        assert_eq!(query.text, terms(&["\"Alice\" \"Q1 invoice\" report"]));
+        let day = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        assert_eq!(
+            query.filters,
+            [
+                Filter::From("alice".into()),
+                Filter::Subject("q1 invoice".into()),
+                Filter::Unread,
+                Filter::Before(day),
+            ]
+        );
+        assert!(SearchQuery::parse(&terms(&["after:yesterday"])).is_err());
+    }
+
+    fn fts(term: &str) -> String {
+        fts_query(&words(term))
+    }
+
+    #[test]
+    fn fts_syntax_passes_through() {
+        assert_eq!(fts("invoi*"), "invoi*");
+        assert_eq!(fts("a OR b"), "a OR b");
+        assert_eq!(fts("(a OR b) NOT c"), "( a OR b ) NOT c");
+        assert_eq!(fts("NEAR(alice invoice, 5)"), "NEAR ( alice invoice , 5 )");
+        assert_eq!(fts("café résumé"), "café résumé");
+    }
+
+    #[test]
+    fn fts_punctuation_is_quoted() {
+        assert_eq!(fts("c++ e-mail"), "\"c++\" \"e-mail\"");
+        assert_eq!(fts("OR invoice"), "\"OR\" invoice");
+        assert_eq!(fts("invoice OR"), "invoice \"OR\"");
+        assert_eq!(fts("(draft"), "\"(draft\"");
+        assert_eq!(fts("\"a OR b\" x,y"), "\"a OR b\" \"x,y\"");
+        assert_eq!(fts("() NEAR()"), "\"()\" \"NEAR()\"");
+        assert_eq!(fts("a,b NEAR(x y)"), "\"a,b\" NEAR ( x y )");
     }
 }
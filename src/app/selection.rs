@@ -0,0 +1,146 @@
+//! Multi-select for the message list.
+//!
+//! Keyed by `MessageIdentity` rather than list index, so a selection survives
+//! `LoadMoreMessages` appends and sync reloads that reshuffle `messages`.
+
+use std::collections::HashMap;
+
+use cosmic::app::Task;
+
+use super::{AppModel, Message, MessageIdentity};
+
+#[derive(Debug, Clone, Default)]
+pub struct MessageSelection {
+    /// Selected message → page it was selected from (for the toolbar summary).
+    pages: HashMap<MessageIdentity, u32>,
+}
+
+impl MessageSelection {
+    /// Toggle membership; returns whether the message is now selected.
+    pub fn toggle(&mut self, identity: MessageIdentity, page: u32) -> bool {
+        if self.pages.remove(&identity).is_some() {
+            return false;
+        }
+        self.pages.insert(identity, page);
+        true
+    }
+
+    pub fn contains(&self, identity: &MessageIdentity) -> bool {
+        self.pages.contains_key(identity)
+    }
+
+    pub fn remove(&mut self, identity: &MessageIdentity) {
+        self.pages.remove(identity);
+    }
+
+    pub fn clear(&mut self) {
+        self.pages.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    pub fn identities(&self) -> impl Iterator<Item = &MessageIdentity> {
+        self.pages.keys()
+    }
+
+    pub fn page_count(&self) -> usize {
+        let mut pages: Vec<u32> = self.pages.values().copied().collect();
+        pages.sort_unstable();
+        pages.dedup();
+        pages.len()
+    }
+
+    /// "3 selected" or "12 selected across 3 pages".
+    pub fn summary_label(&self) -> String {
+        match self.page_count() {
+            0 | 1 => format!("{} selected", self.len()),
+            pages => format!("{} selected across {} pages", self.len(), pages),
+        }
+    }
+}
+
+/// Page a list index falls on (pages are appended in order by LoadMore).
+pub fn page_for_index(index: usize, page_size: u32) -> u32 {
+    (index / page_size.max(1) as usize) as u32
+}
+
+impl AppModel {
+    pub(super) fn handle_selection(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ToggleMessageSelected(index) => {
+                let Some(msg) = self.messages.get(index) else {
+                    return Task::none();
+                };
+                let identity = MessageIdentity::of(msg);
+                let page_size = self.page_size_for(&identity.account_id, &identity.mailbox_id);
+                self.message_selection
+                    .toggle(identity, page_for_index(index, page_size));
+            }
+            Message::ClearMessageSelection => {
+                self.message_selection.clear();
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neverlight_mail_core::store::DEFAULT_PAGE_SIZE;
+
+    fn sample_identity(email_id: &str) -> MessageIdentity {
+        MessageIdentity {
+            account_id: "acct-1".into(),
+            mailbox_id: "inbox".into(),
+            email_id: email_id.into(),
+        }
+    }
+
+    #[test]
+    fn toggle_adds_then_removes() {
+        let mut sel = MessageSelection::default();
+        assert!(sel.toggle(sample_identity("e1"), 0));
+        assert!(sel.contains(&sample_identity("e1")));
+        assert!(!sel.toggle(sample_identity("e1"), 0));
+        assert!(sel.is_empty());
+    }
+
+    #[test]
+    fn summary_counts_distinct_pages() {
+        let mut sel = MessageSelection::default();
+        sel.toggle(sample_identity("e1"), 0);
+        sel.toggle(sample_identity("e2"), 0);
+        assert_eq!(sel.summary_label(), "2 selected");
+        sel.toggle(sample_identity("e3"), 2);
+        assert_eq!(sel.summary_label(), "3 selected across 2 pages");
+    }
+
+    #[test]
+    fn same_email_in_other_mailbox_is_distinct() {
+        let mut sel = MessageSelection::default();
+        sel.toggle(sample_identity("e1"), 0);
+        let other = MessageIdentity {
+            mailbox_id: "archive".into(),
+            ..sample_identity("e1")
+        };
+        assert!(!sel.contains(&other));
+    }
+
+    #[test]
+    fn page_for_index_uses_page_size() {
+        let page = DEFAULT_PAGE_SIZE as usize;
+        assert_eq!(page_for_index(0, DEFAULT_PAGE_SIZE), 0);
+        assert_eq!(page_for_index(page - 1, DEFAULT_PAGE_SIZE), 0);
+        assert_eq!(page_for_index(page, DEFAULT_PAGE_SIZE), 1);
+        assert_eq!(page_for_index(page * 3 + 1, DEFAULT_PAGE_SIZE), 3);
+        assert_eq!(page_for_index(250, 200), 1);
+    }
+}
@@ -0,0 +1,74 @@
+//! Recipients a failed send was refused for.
+//!
+//! A JMAP server refuses the submission as a whole when any recipient is
+//! invalid (`invalidRecipients`), naming the addresses in its error, so
+//! nothing reaches anyone. The named To addresses are picked out of the
+//! error; compose lists them and offers to send to the others. Per-recipient
+//! results after the server accepts (bounces, deferred relays) aren't
+//! reported by core and still arrive as bounce mail.
+
+use super::recipients::bare_address;
+use super::AppModel;
+
+/// The To recipients (as typed) that `error` names.
+pub(super) fn rejected_recipients(to: &str, error: &str) -> Vec<String> {
+    let error = error.to_ascii_lowercase();
+    to.split(',')
+        .map(str::trim)
+        .filter(|r| {
+            let address = bare_address(r).to_ascii_lowercase();
+            address.contains('@') && mentions(&error, &address)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// `address` appears in `text` on its own, not as the tail of a longer one.
+fn mentions(text: &str, address: &str) -> bool {
+    let part_of_address = |c: char| c.is_ascii_alphanumeric() || "._+-".contains(c);
+    text.match_indices(address).any(|(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + address.len()..].chars().next();
+        !before.is_some_and(part_of_address)
+            && !after.is_some_and(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// `to` without the recipients in `rejected`.
+pub(super) fn without_recipients(to: &str, rejected: &[String]) -> String {
+    to.split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty() && !rejected.iter().any(|x| x == r))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl AppModel {
+    /// Drop the refused recipients from To and send again.
+    pub(super) fn send_without_rejected(&mut self) {
+        let rejected = std::mem::take(&mut self.compose_rejected);
+        self.compose_to = without_recipients(&self.compose_to, &rejected);
+        self.refresh_external_recipients();
+        self.refresh_compose_suggestions();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipients_named_in_the_error_are_rejected() {
+        let to = "Alice <alice@example.com>, ba@example.com, a@example.com";
+        let error = "invalidRecipients: [\"A@example.com\"] (no such user)";
+        assert_eq!(rejected_recipients(to, error), ["a@example.com"]);
+        assert!(rejected_recipients(to, "Network error").is_empty());
+    }
+
+    #[test]
+    fn sending_to_the_others_drops_only_the_rejected() {
+        let to = "Alice <alice@example.com>, a@example.com,";
+        let rejected = ["a@example.com".to_string()];
+        assert_eq!(without_recipients(to, &rejected), "Alice <alice@example.com>");
+    }
+}
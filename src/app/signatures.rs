@@ -0,0 +1,280 @@
+//! Signatures per From address.
+//!
+//! `quoting.signatures` in settings.json maps an address to its signature;
+//! addresses without an entry use `quoting.signature`. They're edited in the
+//! account dialog. Compose starts with the signature of the From address
+//! and swaps it when From changes or another one is picked, as long as the
+//! block is still in the body as inserted; otherwise the new one is added at
+//! the end.
+
+use std::collections::HashMap;
+
+use cosmic::widget::text_editor;
+
+use super::recipients::bare_address;
+use super::settings::QuoteSettings;
+use super::AppModel;
+
+/// First entry of the compose signature dropdown.
+const NO_SIGNATURE: &str = "No signature";
+
+/// Settings key for `address` (`Name <addr>` or bare).
+fn key(address: &str) -> String {
+    bare_address(address).to_ascii_lowercase()
+}
+
+/// `-- ` separated signature, or `None` when `signature` is blank.
+pub(super) fn signature_block(signature: &str) -> Option<String> {
+    let sig = signature.trim_end();
+    (!sig.is_empty()).then(|| format!("-- \n{sig}\n"))
+}
+
+/// `body` with the `old` signature block replaced by `new`. A block that is
+/// no longer in the body is left to the writer and `new` goes at the end.
+pub(super) fn swap_signature(body: &str, old: Option<&str>, new: Option<&str>) -> String {
+    let found = old.and_then(|old| Some((body.find(old)?, old.len())));
+    if let Some((start, len)) = found {
+        let mut end = start + len;
+        if new.is_none() && body[end..].starts_with('\n') {
+            end += 1;
+        }
+        return format!("{}{}{}", &body[..start], new.unwrap_or_default(), &body[end..]);
+    }
+    let Some(new) = new else {
+        return body.to_string();
+    };
+    let mut out = body.trim_end_matches('\n').to_string();
+    out.push_str("\n\n");
+    out.push_str(new);
+    out
+}
+
+/// Signatures offered in compose: "No signature", then each From address
+/// of the account that has one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignatureChoices {
+    /// Dropdown labels; the addresses after the first.
+    pub labels: Vec<String>,
+    /// `-- ` blocks, one per label; `None` for "No signature".
+    blocks: Vec<Option<String>>,
+    /// The signature currently in the body.
+    pub selected: usize,
+}
+
+impl SignatureChoices {
+    pub(super) fn new(addresses: &[String], style: &QuoteSettings) -> Self {
+        let mut choices = Self {
+            labels: vec![NO_SIGNATURE.to_string()],
+            blocks: vec![None],
+            selected: 0,
+        };
+        for address in addresses {
+            if let Some(block) = signature_block(style.signature_for(&key(address))) {
+                choices.labels.push(address.clone());
+                choices.blocks.push(Some(block));
+            }
+        }
+        choices
+    }
+
+    /// Index of `address`'s signature, or "No signature".
+    pub(super) fn for_address(&self, address: &str) -> usize {
+        self.labels
+            .iter()
+            .skip(1)
+            .position(|label| label.eq_ignore_ascii_case(address))
+            .map_or(0, |i| i + 1)
+    }
+
+    /// Index of the first signature found in `body`, for drafts reopened
+    /// with one already in place.
+    pub(super) fn found_in(&self, body: &str) -> usize {
+        self.blocks
+            .iter()
+            .position(|block| block.as_deref().is_some_and(|b| body.contains(b)))
+            .unwrap_or(0)
+    }
+
+    pub(super) fn block(&self, index: usize) -> Option<&str> {
+        self.blocks.get(index)?.as_deref()
+    }
+
+    pub(super) fn selected_block(&self) -> Option<&str> {
+        self.block(self.selected)
+    }
+}
+
+/// Signature editing in the account dialog, one From address at a time.
+#[derive(Debug, Default)]
+pub struct SignatureEditor {
+    /// From addresses of the account being set up.
+    pub addresses: Vec<String>,
+    pub selected: usize,
+    pub editor: text_editor::Content,
+    /// Text per lowercased address, including edits not saved yet.
+    texts: HashMap<String, String>,
+}
+
+impl SignatureEditor {
+    /// Editor for the comma-separated `addresses`, starting from each one's
+    /// current signature.
+    pub(super) fn new(addresses: &str, style: &QuoteSettings) -> Self {
+        let mut editor = Self::default();
+        editor.set_addresses(addresses, style);
+        editor
+    }
+
+    /// Follow an edit of the account's address list; signatures typed for
+    /// addresses still listed are kept.
+    pub(super) fn set_addresses(&mut self, addresses: &str, style: &QuoteSettings) {
+        self.stash();
+        let current = self.addresses.get(self.selected).cloned();
+        self.addresses = addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(str::to_string)
+            .collect();
+        for address in &self.addresses {
+            self.texts
+                .entry(key(address))
+                .or_insert_with(|| style.signature_for(&key(address)).to_string());
+        }
+        self.selected = current
+            .and_then(|c| self.addresses.iter().position(|a| *a == c))
+            .unwrap_or(0);
+        self.load();
+    }
+
+    pub(super) fn select(&mut self, index: usize) {
+        if index < self.addresses.len() {
+            self.stash();
+            self.selected = index;
+            self.load();
+        }
+    }
+
+    /// Signatures for the listed addresses, by settings key, as typed.
+    pub(super) fn finish(mut self) -> Vec<(String, String)> {
+        self.stash();
+        self.addresses
+            .iter()
+            .map(String::as_str)
+            .map(key)
+            .filter_map(|a| self.texts.remove_entry(&a))
+            .collect()
+    }
+
+    fn stash(&mut self) {
+        if let Some(address) = self.addresses.get(self.selected) {
+            let text = self.editor.text();
+            self.texts.insert(key(address), text);
+        }
+    }
+
+    fn load(&mut self) {
+        let text = self
+            .addresses
+            .get(self.selected)
+            .and_then(|a| self.texts.get(&key(a)))
+            .map_or("", String::as_str);
+        self.editor = text_editor::Content::with_text(text);
+    }
+}
+
+impl AppModel {
+    /// Signatures for the compose account's From addresses, with the
+    /// selected From address's one picked.
+    pub(super) fn compose_signature_choices(&self) -> SignatureChoices {
+        let addresses = self
+            .accounts
+            .get(self.compose_account)
+            .map(|a| a.config.email_addresses.as_slice())
+            .unwrap_or_default();
+        let mut choices = SignatureChoices::new(addresses, &self.settings.quoting);
+        if let Some(from) = addresses.get(self.compose_from) {
+            choices.selected = choices.for_address(from);
+        }
+        choices
+    }
+
+    /// Put signature `index` in the compose body in place of the current one.
+    pub(super) fn switch_compose_signature(&mut self, index: usize) {
+        if index < self.compose_signatures.labels.len() {
+            let mut next = self.compose_signatures.clone();
+            next.selected = index;
+            self.replace_compose_signature(next);
+        }
+    }
+
+    /// Switch to the signature of the From address just picked.
+    pub(super) fn follow_compose_from(&mut self) {
+        let next = self.compose_signature_choices();
+        self.replace_compose_signature(next);
+    }
+
+    fn replace_compose_signature(&mut self, next: SignatureChoices) {
+        let old = self.compose_signatures.selected_block();
+        if old != next.selected_block() {
+            let body = swap_signature(&self.compose_body.text(), old, next.selected_block());
+            self.compose_body = text_editor::Content::with_text(&body);
+        }
+        self.compose_signatures = next;
+    }
+
+    /// Save the account dialog's signatures; one matching the default
+    /// signature needs no entry of its own.
+    pub(super) fn save_setup_signatures(&mut self) {
+        let editor = std::mem::take(&mut self.setup_signatures);
+        let quoting = &mut self.settings.quoting;
+        for (address, text) in editor.finish() {
+            if text.trim_end() == quoting.signature.trim_end() {
+                quoting.signatures.remove(&address);
+            } else {
+                quoting.signatures.insert(address, text);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style() -> QuoteSettings {
+        let mut style = QuoteSettings {
+            signature: "Sam\n".into(),
+            ..QuoteSettings::default()
+        };
+        style.signatures.insert("sam@work.example".into(), "Sam Lee, Acme".into());
+        style.signatures.insert("sam@quiet.example".into(), String::new());
+        style
+    }
+
+    #[test]
+    fn each_address_gets_its_own_signature() {
+        let addresses = ["sam@home.example", "Sam@Work.example", "sam@quiet.example"]
+            .map(String::from);
+        let choices = SignatureChoices::new(&addresses, &style());
+        assert_eq!(choices.labels, [NO_SIGNATURE, "sam@home.example", "Sam@Work.example"]);
+        assert_eq!(choices.block(1), Some("-- \nSam\n"));
+        assert_eq!(choices.for_address("sam@work.example"), 2);
+        assert_eq!(choices.for_address("sam@quiet.example"), 0);
+        assert_eq!(choices.found_in("Hi\n\n-- \nSam Lee, Acme\n"), 2);
+    }
+
+    #[test]
+    fn swapping_replaces_the_block_in_place() {
+        let body = "Hi\n\n-- \nSam\n\nOn Mon, a wrote:\n> hi\n";
+        let work = Some("-- \nSam Lee\n");
+        let swapped = swap_signature(body, Some("-- \nSam\n"), work);
+        assert_eq!(swapped, "Hi\n\n-- \nSam Lee\n\nOn Mon, a wrote:\n> hi\n");
+        assert_eq!(
+            swap_signature(&swapped, work, None),
+            "Hi\n\nOn Mon, a wrote:\n> hi\n"
+        );
+        // An edited-away block leaves the body alone; a new one goes last.
+        assert_eq!(swap_signature("Hi\n", Some("-- \nSam\n"), None), "Hi\n");
+        assert_eq!(swap_signature("Hi\n", None, work), "Hi\n\n-- \nSam Lee\n");
+    }
+}
@@ -0,0 +1,280 @@
+//! Smart views: message lists built from every folder of an account (or of
+//! all accounts) rather than one mailbox, e.g. everything starred or unread.
+//! Also the per-folder "unread only" filter.
+//!
+//! The cache only answers per-mailbox queries, so a view reads each folder's
+//! cached summaries and filters them here. Trash and Junk are left out, a
+//! message filed in several mailboxes shows once, and the result is a flat
+//! newest-first list. Opening a folder leaves the view. The inboxes view
+//! reads only inbox-role folders and backs a group's "All Work inboxes".
+//!
+//! "Unread only" filters the folder's loaded pages instead of the query, so
+//! paging keeps its offsets and Load more still fetches the next page.
+
+use std::collections::HashSet;
+
+use chrono::DateTime;
+use cosmic::app::Task;
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::models::MessageSummary;
+use neverlight_mail_core::store::CacheHandle;
+
+use super::{AppModel, MailboxIdentity, Message, Phase};
+
+/// Cached summaries read per folder.
+const MAX_PER_FOLDER: u32 = 5000;
+/// Folder roles a smart view never reads.
+const SKIPPED_ROLES: [&str; 2] = ["trash", "junk"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmartViewKind {
+    Starred,
+    Unread,
+    /// Everything in the inbox-role folders.
+    Inboxes,
+}
+
+impl SmartViewKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Starred => "Starred",
+            Self::Unread => "Unread",
+            Self::Inboxes => "Inboxes",
+        }
+    }
+
+    fn matches(self, msg: &MessageSummary) -> bool {
+        match self {
+            Self::Starred => msg.is_starred,
+            Self::Unread => !msg.is_read,
+            Self::Inboxes => true,
+        }
+    }
+
+    fn reads_folder(self, role: Option<&str>) -> bool {
+        match self {
+            Self::Inboxes => role == Some("inbox"),
+            _ => !role.is_some_and(|r| SKIPPED_ROLES.contains(&r)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmartView {
+    pub kind: SmartViewKind,
+    /// `None` spans every account (of `group`, when set).
+    pub account_id: Option<AccountId>,
+    /// Sidebar account group the view is limited to.
+    pub group: Option<String>,
+}
+
+/// Sort key for a summary date; unparseable dates sort last.
+pub(super) fn date_key(date: &str) -> i64 {
+    let date = date.trim();
+    DateTime::parse_from_rfc3339(date)
+        .or_else(|_| DateTime::parse_from_rfc2822(date))
+        .map(|dt| dt.timestamp())
+        .unwrap_or(i64::MIN)
+}
+
+/// Matching messages from several folders: one row per message, flat
+/// (threads don't nest across folders), newest first.
+fn collect_matches(
+    kind: SmartViewKind,
+    folders: impl IntoIterator<Item = Vec<MessageSummary>>,
+) -> Vec<MessageSummary> {
+    let mut seen = HashSet::new();
+    let mut rows: Vec<MessageSummary> = folders
+        .into_iter()
+        .flatten()
+        .filter(|m| kind.matches(m))
+        .filter(|m| seen.insert((m.account_id.clone(), m.email_id.clone())))
+        .map(|mut m| {
+            m.thread_depth = 0;
+            m
+        })
+        .collect();
+    rows.sort_by_key(|m| std::cmp::Reverse(date_key(&m.date)));
+    rows
+}
+
+async fn load_view(
+    cache: CacheHandle,
+    kind: SmartViewKind,
+    mailboxes: Vec<(AccountId, String)>,
+) -> Result<Vec<MessageSummary>, String> {
+    let mut folders = Vec::with_capacity(mailboxes.len());
+    for (account_id, mailbox_id) in mailboxes {
+        folders.push(
+            cache
+                .load_messages(account_id, mailbox_id, MAX_PER_FOLDER, 0)
+                .await?,
+        );
+    }
+    Ok(collect_matches(kind, folders))
+}
+
+impl AppModel {
+    pub(super) fn handle_smart_view(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::OpenSmartView(view) => {
+                if let Some(handle) = self.folder_abort.take() {
+                    handle.abort();
+                }
+                self.abort_message_sync();
+                // Late folder loads must not land in the view.
+                self.folder_epoch = self.folder_epoch.saturating_add(1);
+                self.message_epoch = self.message_epoch.saturating_add(1);
+                if let Some(idx) = view.account_id.as_deref().and_then(|id| self.account_index(id)) {
+                    self.active_account = Some(idx);
+                }
+                self.selected_folder = None;
+                self.selected_mailbox_id = None;
+                self.selected_folder_evicted = false;
+                self.message_selection.clear();
+                self.visual = None;
+                self.attachment_browser = None;
+                self.clear_selected_folder_projection();
+                self.smart_view = Some(view);
+                return self.load_smart_view();
+            }
+            Message::SmartViewLoaded { epoch, result } => {
+                if epoch != self.smart_view_epoch || self.smart_view.is_none() {
+                    self.stale_apply_drop_count = self.stale_apply_drop_count.saturating_add(1);
+                    return Task::none();
+                }
+                self.phase = Phase::Idle;
+                match result {
+                    Ok(rows) => {
+                        let label = self
+                            .smart_view
+                            .as_ref()
+                            .map(|v| match &v.group {
+                                Some(group) => format!("{group} {}", v.kind.label().to_lowercase()),
+                                None => v.kind.label().to_string(),
+                            })
+                            .unwrap_or_default();
+                        self.status_message = format!("{label}: {} messages", rows.len());
+                        let prev_email_id = self
+                            .selected_message
+                            .and_then(|i| self.messages.get(i).map(|m| m.email_id.clone()));
+                        self.messages = rows;
+                        self.selected_message = prev_email_id
+                            .and_then(|eid| self.messages.iter().position(|m| m.email_id == eid));
+                        self.has_more_messages = false;
+                        self.recompute_visible();
+                    }
+                    Err(e) => {
+                        log::error!("Smart view load failed: {}", e);
+                        self.set_status_error(format!("Couldn't load view: {}", e));
+                    }
+                }
+            }
+            Message::ToggleUnreadOnly => {
+                let Some(folder) = self.open_folder() else {
+                    return Task::none();
+                };
+                if !self.unread_only_folders.remove(&folder) {
+                    self.unread_only_folders.insert(folder);
+                }
+                self.recompute_visible();
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// (Re)read the open smart view from the cache.
+    pub(super) fn load_smart_view(&mut self) -> Task<Message> {
+        let Some(view) = &self.smart_view else {
+            return Task::none();
+        };
+        let Some(cache) = self.cache.clone() else {
+            self.status_message = "Smart views need the local cache".into();
+            return Task::none();
+        };
+        let kind = view.kind;
+        let members = view.group.as_deref().map(|g| self.group_members(g));
+        let mailboxes: Vec<(AccountId, String)> = self
+            .accounts
+            .iter()
+            .filter(|a| view.account_id.as_ref().is_none_or(|id| *id == a.config.id))
+            .filter(|a| members.as_ref().is_none_or(|m| m.contains(&a.config.id)))
+            .flat_map(|a| {
+                a.folders
+                    .iter()
+                    .filter(|f| kind.reads_folder(f.role.as_deref()))
+                    .map(|f| (a.config.id.clone(), f.mailbox_id.clone()))
+            })
+            .collect();
+
+        self.smart_view_epoch = self.smart_view_epoch.saturating_add(1);
+        let epoch = self.smart_view_epoch;
+        self.phase = Phase::Loading;
+        cosmic::task::future(async move {
+            let result = load_view(cache, kind, mailboxes).await;
+            Message::SmartViewLoaded { epoch, result }
+        })
+    }
+
+    /// Drop rows that stopped matching after a flag change. The open message
+    /// stays (reading it in the Unread view mustn't yank it away).
+    pub(super) fn prune_smart_view(&mut self) {
+        let Some(kind) = self.smart_view.as_ref().map(|v| v.kind) else {
+            return;
+        };
+        let prev_email_id = self
+            .selected_message
+            .and_then(|i| self.messages.get(i).map(|m| m.email_id.clone()));
+        let keep = |m: &MessageSummary| {
+            kind.matches(m) || prev_email_id.as_deref() == Some(m.email_id.as_str())
+        };
+        if self.messages.iter().all(keep) {
+            return;
+        }
+        self.messages.retain(keep);
+        self.selected_message =
+            prev_email_id.and_then(|eid| self.messages.iter().position(|m| m.email_id == eid));
+        self.recompute_visible();
+    }
+
+    /// The folder on screen; `None` in search results or a smart view.
+    pub(super) fn open_folder(&self) -> Option<MailboxIdentity> {
+        if self.search_phase.is_active() || self.smart_view.is_some() {
+            return None;
+        }
+        let account = self.active_account.and_then(|i| self.accounts.get(i))?;
+        Some(MailboxIdentity {
+            account_id: account.config.id.clone(),
+            mailbox_id: self.selected_mailbox_id.clone()?,
+        })
+    }
+
+    /// `Some(on)` when the folder on screen can be filtered to unread.
+    pub(super) fn unread_only(&self) -> Option<bool> {
+        self.open_folder()
+            .map(|f| self.unread_only_folders.contains(&f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inboxes_view_reads_only_inboxes() {
+        assert!(SmartViewKind::Inboxes.reads_folder(Some("inbox")));
+        assert!(!SmartViewKind::Inboxes.reads_folder(None));
+        assert!(SmartViewKind::Starred.reads_folder(None));
+        assert!(!SmartViewKind::Starred.reads_folder(Some("trash")));
+    }
+
+    #[test]
+    fn unparseable_dates_sort_last() {
+        assert!(date_key("2026-03-01T10:00:00Z") > date_key("soon"));
+        assert_eq!(
+            date_key("Sun, 1 Mar 2026 10:00:00 +0000"),
+            date_key("2026-03-01T10:00:00Z")
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! One-line body previews for the message list, made when a body is opened
+//! or prefetched.
+//!
+//! The excerpt is the reader's own text: quoted lines, "... wrote:"
+//! attributions, the signature and stray HTML tags are dropped, whitespace
+//! is collapsed, and an opening line that just repeats the subject is
+//! skipped. Summaries carry no preview, so messages whose body hasn't been
+//! seen yet show none.
+
+use std::collections::HashSet;
+
+use super::AppModel;
+
+/// Longest excerpt, in characters, before it's cut with an ellipsis.
+const SNIPPET_CHARS: usize = 120;
+
+/// Previews kept in memory. Past this, those for rows not in the current
+/// list are dropped.
+const MAX_SNIPPETS: usize = 2000;
+
+/// Preview of `plain` for a list row, or `None` when nothing is left once
+/// quotes and the signature are gone.
+pub(super) fn excerpt(plain: &str, subject: &str) -> Option<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut len = 0;
+    for line in plain.lines() {
+        let line = line.trim();
+        if line == "--" {
+            break;
+        }
+        if line.starts_with('>') || line.ends_with("wrote:") {
+            continue;
+        }
+        for word in strip_tags(line).split_whitespace() {
+            len += word.chars().count() + 1;
+            words.push(word.to_string());
+        }
+        if len > SNIPPET_CHARS + subject.len() {
+            break;
+        }
+    }
+    let text = words.join(" ");
+    let text = without_subject(&text, subject);
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= SNIPPET_CHARS {
+        return Some(text.to_string());
+    }
+    let cut: String = text.chars().take(SNIPPET_CHARS).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+/// `text` without a leading copy of `subject` (minus any "Re:"/"Fwd:").
+fn without_subject<'a>(text: &'a str, subject: &str) -> &'a str {
+    let mut subject = subject.trim();
+    while let Some(rest) = ["Re:", "RE:", "Fwd:", "FW:", "Fw:"]
+        .iter()
+        .find_map(|prefix| subject.strip_prefix(prefix))
+    {
+        subject = rest.trim_start();
+    }
+    if subject.is_empty() {
+        return text;
+    }
+    match text.get(..subject.len()) {
+        Some(head) if head.eq_ignore_ascii_case(subject) => text[subject.len()..]
+            .trim_start_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation()),
+        _ => text,
+    }
+}
+
+/// `line` with anything that looks like an HTML tag removed. A `<` that
+/// doesn't open a tag (`a < b`, `<3`) is kept.
+fn strip_tags(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('<') {
+        let after = &rest[open + 1..];
+        let opens_tag = after
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        match after.find('>').filter(|_| opens_tag) {
+            Some(close) => {
+                out.push_str(&rest[..open]);
+                rest = &after[close + 1..];
+                // `<br>` and `<p>` separate words; `</a>;` doesn't.
+                if !rest.starts_with(|c: char| c.is_ascii_punctuation()) {
+                    out.push(' ');
+                }
+            }
+            None => {
+                out.push_str(&rest[..open + 1]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+impl AppModel {
+    /// Keep the list preview for a body just opened in the preview pane.
+    pub(super) fn remember_snippet(&mut self, email_id: &str, plain: &str) {
+        let subject = self
+            .messages
+            .iter()
+            .find(|m| m.email_id == email_id)
+            .map_or("", |m| m.subject.as_str());
+        if let Some(snippet) = excerpt(plain, subject) {
+            self.insert_snippet(email_id.to_string(), snippet);
+        }
+    }
+
+    /// Store a preview, first trimming the map to the listed rows when it's
+    /// full.
+    pub(super) fn insert_snippet(&mut self, email_id: String, snippet: String) {
+        if self.snippets.len() >= MAX_SNIPPETS && !self.snippets.contains_key(&email_id) {
+            let listed: HashSet<&str> = self.messages.iter().map(|m| m.email_id.as_str()).collect();
+            self.snippets.retain(|id, _| listed.contains(id.as_str()));
+        }
+        self.snippets.insert(email_id, snippet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_attribution_and_signature_are_left_out() {
+        let body = "Sounds good,\nsee you then.\n\nOn Mon, Bob wrote:\n> Lunch?\n\n-- \nAlice\n";
+        assert_eq!(
+            excerpt(body, "Lunch").as_deref(),
+            Some("Sounds good, see you then.")
+        );
+        assert_eq!(excerpt("> only a quote\n", "x"), None);
+    }
+
+    #[test]
+    fn subject_repeat_and_tags_are_dropped() {
+        let body = "Quarterly report: <b>numbers</b> are in.\nSee <a href=\"x\">here</a>; a < b";
+        assert_eq!(
+            excerpt(body, "Re: Quarterly report").as_deref(),
+            Some("numbers are in. See here; a < b")
+        );
+    }
+
+    #[test]
+    fn long_bodies_are_cut_with_an_ellipsis() {
+        let body = "word ".repeat(100);
+        let snippet = excerpt(&body, "").unwrap();
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.chars().count() <= SNIPPET_CHARS + 1);
+    }
+}
@@ -0,0 +1,258 @@
+//! Building `AppModel`: everything `init` reads from disk is gathered into
+//! `StoredState` first, so tests can start a model from fresh defaults
+//! without touching the user's config, cache or keyring.
+
+use std::collections::{HashMap, HashSet};
+
+use cosmic::app::Core;
+use cosmic::widget;
+use cosmic::widget::{pane_grid, text_editor};
+use neverlight_mail_core::config::{AccountId, LayoutConfig};
+use neverlight_mail_core::store::CacheHandle;
+
+use crate::ui::compose_dialog::ComposeMode;
+
+use super::attachment_scan::AttachmentScans;
+use super::connect_order;
+use super::drafts::{self, ComposeSnapshot, LocalDraft};
+use super::folder_usage::FolderUsage;
+use super::freshness::FolderSyncTimes;
+use super::settings::{self, AppSettings};
+use super::signatures::{SignatureChoices, SignatureEditor};
+use super::thread_collapse::CollapsedThreads;
+use super::thread_overrides::ThreadOverrides;
+use super::window_state::WindowState;
+use super::{
+    metrics, selection, shortcuts, AppModel, ComposePhase, Message, OAuthSetupPhase, PagedMarkdown,
+    PaneKind, Phase, RefreshPhase, SearchPhase,
+};
+
+/// GUI state persisted between runs.
+pub(super) struct StoredState {
+    pub settings: AppSettings,
+    pub local_drafts: Vec<LocalDraft>,
+    pub folder_sync_times: FolderSyncTimes,
+    pub folder_usage: FolderUsage,
+    pub thread_overrides: ThreadOverrides,
+    pub collapsed_threads: CollapsedThreads,
+    pub last_account: Option<AccountId>,
+    pub window_state: WindowState,
+    pub sidebar_ratio: f32,
+    pub list_ratio: f32,
+}
+
+impl StoredState {
+    pub fn load() -> Self {
+        let layout = LayoutConfig::load();
+        Self {
+            settings: AppSettings::load(),
+            local_drafts: drafts::load_drafts(),
+            folder_sync_times: FolderSyncTimes::load(),
+            folder_usage: FolderUsage::load(),
+            thread_overrides: ThreadOverrides::load(),
+            collapsed_threads: CollapsedThreads::load(),
+            last_account: connect_order::load_last_account(),
+            window_state: WindowState::load().unwrap_or_default(),
+            sidebar_ratio: layout.sidebar_ratio,
+            list_ratio: layout.list_ratio,
+        }
+    }
+
+    /// First-run state, as if nothing had been saved.
+    #[cfg(test)]
+    pub fn fresh() -> Self {
+        Self {
+            settings: AppSettings::default(),
+            local_drafts: Vec::new(),
+            folder_sync_times: FolderSyncTimes::default(),
+            folder_usage: FolderUsage::default(),
+            thread_overrides: ThreadOverrides::default(),
+            collapsed_threads: CollapsedThreads::default(),
+            last_account: None,
+            window_state: WindowState::default(),
+            sidebar_ratio: 0.15,
+            list_ratio: 0.40,
+        }
+    }
+}
+
+impl AppModel {
+    /// A model with no accounts yet; `init` adds them and starts connecting.
+    pub(super) fn new(core: Core, cache: Option<CacheHandle>, stored: StoredState) -> Self {
+        let pane_config = pane_grid::Configuration::Split {
+            axis: pane_grid::Axis::Vertical,
+            ratio: stored.sidebar_ratio,
+            a: Box::new(pane_grid::Configuration::Pane(PaneKind::Sidebar)),
+            b: Box::new(pane_grid::Configuration::Split {
+                axis: pane_grid::Axis::Vertical,
+                ratio: stored.list_ratio,
+                a: Box::new(pane_grid::Configuration::Pane(PaneKind::MessageList)),
+                b: Box::new(pane_grid::Configuration::Pane(PaneKind::MessageView)),
+            }),
+        };
+        let panes = pane_grid::State::with_configuration(pane_config);
+
+        AppModel {
+            core,
+            accounts: Vec::new(),
+            active_account: None,
+            cache,
+            selected_folder: None,
+            selected_mailbox_id: None,
+            selected_folder_evicted: false,
+            messages: Vec::new(),
+            selected_message: None,
+            message_selection: selection::MessageSelection::default(),
+            messages_offset: 0,
+            has_more_messages: false,
+            preview_body: String::new(),
+            preview_markdown: PagedMarkdown::default(),
+            preview_received: None,
+            preview_encoding: 0,
+            preview_attachments: Vec::new(),
+            preview_image_handles: Vec::new(),
+            preview_attachment_hashes: Vec::new(),
+            attachment_browser: None,
+            risky_attachment: None,
+            attachment_scans: AttachmentScans::default(),
+            metrics: std::sync::Arc::new(metrics::HealthMetrics::default()),
+            detected_event: None,
+            attachment_epoch: 0,
+            cleanup: None,
+            folder_activity: HashMap::new(),
+            cleanup_epoch: 0,
+            image_viewer: None,
+            viewer_epoch: 0,
+            conversation: Vec::new(),
+            active_conversation_id: None,
+            conversation_sender_filter: None,
+            collapsed_threads: HashSet::new(),
+            collapsed_groups: HashSet::new(),
+            visible_indices: Vec::new(),
+            thread_sizes: HashMap::new(),
+            row_labels: HashMap::new(),
+            attachment_counts: HashMap::new(),
+            snippets: HashMap::new(),
+            pending_move_restore: HashMap::new(),
+            pending_local_flags: HashMap::new(),
+            server_won_flags: HashMap::new(),
+            flag_conflicts: std::collections::VecDeque::new(),
+            undoable_moves: std::collections::VecDeque::new(),
+            trash_confirm: None,
+            pending_flag_epochs: HashMap::new(),
+            pending_move_epochs: HashMap::new(),
+            search_abort: None,
+            folder_abort: None,
+            message_abort: None,
+            body_abort: None,
+            status_message: "Starting up...".into(),
+            error_surface: None,
+            phase: Phase::Loading,
+            folder_epoch: 0,
+            message_epoch: 0,
+            push_epoch: 0,
+            search_epoch: 0,
+            smart_view_epoch: 0,
+            refresh_epoch: 0,
+            mutation_epoch: 0,
+            flag_epoch: 0,
+            body_epoch: 0,
+            refresh_phase: RefreshPhase::Idle,
+            refresh_accounts_outstanding: HashSet::new(),
+            refresh_started_at: None,
+            refresh_pending_after_sync: false,
+            mutation_in_flight_accounts: HashSet::new(),
+            flag_in_flight_accounts: HashSet::new(),
+            pending_move_intents: HashMap::new(),
+            pending_flag_intents: HashMap::new(),
+            notified_messages: HashSet::new(),
+            pending_notifications: HashMap::new(),
+            pause_overrides: HashSet::new(),
+            stale_apply_drop_count: 0,
+            toc_drift_count: 0,
+            postcondition_failure_count: 0,
+            refresh_timeout_count: 0,
+            refresh_stuck_count: 0,
+            reconnect_count: 0,
+            last_sync_at: None,
+            folder_sync_times: stored.folder_sync_times,
+            folder_sync_times_dirty: false,
+            folder_usage: stored.folder_usage,
+            warmed_up_accounts: HashSet::new(),
+            thread_overrides: stored.thread_overrides,
+            saved_collapsed_threads: stored.collapsed_threads,
+            last_account: stored.last_account,
+            last_refresh_at: None,
+
+            search_phase: SearchPhase::Inactive,
+            search_query: String::new(),
+            search_terms: Vec::new(),
+
+            smart_view: None,
+            unread_only_folders: HashSet::new(),
+            alias_filter: None,
+
+            compose_phase: ComposePhase::Closed,
+            compose_mode: ComposeMode::New,
+            compose_account: 0,
+            compose_from: 0,
+            compose_to: String::new(),
+            compose_subject: String::new(),
+            compose_body: text_editor::Content::new(),
+            compose_in_reply_to: None,
+            compose_references: None,
+            compose_attachments: Vec::new(),
+            compose_reply_source: None,
+            compose_reply_choice: None,
+            compose_archive_after_send: None,
+            compose_server_draft: None,
+            compose_error: None,
+            compose_drag_hover: false,
+            compose_external_edit: None,
+            pending_row_compose: None,
+            compose_baseline: ComposeSnapshot::default(),
+            local_drafts: stored.local_drafts,
+            compose_account_labels: Vec::new(),
+            compose_cached_from: Vec::new(),
+            compose_rejected: Vec::new(),
+            compose_signatures: SignatureChoices::default(),
+            compose_external_recipients: Vec::new(),
+            compose_suggestions: Vec::new(),
+            recipient_index: HashMap::new(),
+            unsynced_sends: HashMap::new(),
+
+            toasts: widget::toaster::Toasts::new(Message::CloseToast),
+            queued_toasts: Vec::new(),
+            key_binds: shortcuts::key_binds(),
+            settings: stored.settings,
+
+            setup_model: None,
+            setup_password_visible: false,
+            setup_sync_interval: settings::SyncInterval::default().choice_index(),
+            setup_internal_domains: String::new(),
+            setup_account_group: String::new(),
+            setup_account_color: 0,
+            setup_signatures: SignatureEditor::default(),
+            setup_saved_passwords: Vec::new(),
+            setup_saved_passwords_note: None,
+            confirm_delete_account_id: None,
+            oauth_phase: OAuthSetupPhase::Inactive,
+            oauth_error: None,
+
+            folder_drag_target: None,
+            pending_body: None,
+            body_defer_retries: 0,
+            auto_read_suppressed: false,
+
+            panes,
+            layout_cycle: None,
+            diagnostics_collapsed: true,
+            show_shortcuts: false,
+            triage: None,
+            visual: None,
+            comparison: None,
+            quick_look: None,
+            window_state: stored.window_state,
+        }
+    }
+}
@@ -4,7 +4,6 @@
 //! Handler implementations live in `sync_apply.rs`.
 
 use cosmic::app::Task;
-use neverlight_mail_core::store::DEFAULT_PAGE_SIZE;
 use std::time::{Duration, Instant};
 
 use super::{AppModel, Message, Phase};
@@ -50,7 +49,18 @@ impl AppModel {
     pub(super) fn handle_sync(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::CachedFoldersLoaded { account_id, result: Ok(folders) } => {
-                return self.handle_cached_folders_ok(account_id, folders);
+                // Folders already counted are kept current by their syncs.
+                let known = self.folder_activity.get(&account_id);
+                let mailbox_ids = folders
+                    .iter()
+                    .filter(|f| !known.is_some_and(|k| k.contains_key(&f.mailbox_id)))
+                    .map(|f| f.mailbox_id.clone())
+                    .collect();
+                let activity = self.load_folder_activity(&account_id, mailbox_ids);
+                let apply = self.handle_cached_folders_ok(account_id.clone(), folders);
+                // Folders are in place now, so the Sent folder can be found.
+                let recipients = self.load_recipient_index(&account_id);
+                return cosmic::task::batch(vec![apply, activity, recipients]);
             }
             Message::CachedFoldersLoaded { result: Err(e), .. } => {
                 log::warn!("Failed to load cached folders: {}", e);
@@ -91,7 +101,8 @@ impl AppModel {
                 }
 
                 let count = messages.len();
-                self.has_more_messages = count as u32 == DEFAULT_PAGE_SIZE;
+                let page_size = self.page_size_for(&account_id, &mailbox_id);
+                self.has_more_messages = count as u32 == page_size;
                 self.folder_abort = None;
 
                 let prev_email_id = self.selected_message.and_then(|i| {
@@ -101,8 +112,17 @@ impl AppModel {
                 if self.messages_offset == 0 {
                     self.messages = messages;
                 } else {
+                    // Push merges shift server offsets, so a later page can
+                    // overlap rows already in the list.
+                    let mut messages = messages;
+                    messages.retain(|m| {
+                        !self.messages.iter().any(|e| {
+                            e.email_id == m.email_id && e.context_mailbox_id == m.context_mailbox_id
+                        })
+                    });
                     self.messages.extend(messages);
                 }
+                self.reconcile_pending_flags();
 
                 if self.messages_offset == 0 {
                     if let Some(ref eid) = prev_email_id {
@@ -162,10 +182,24 @@ impl AppModel {
                 epoch,
                 result: Ok(()),
             } => {
-                return self.handle_sync_messages_ok(account_id, mailbox_id.clone(), epoch);
+                // Cache changed even when the result is stale for the view.
+                let mut tasks = vec![self.load_folder_activity(&account_id, vec![mailbox_id.clone()])];
+                if self.is_contact_source(&account_id, mailbox_id) {
+                    tasks.push(self.load_recipient_index(&account_id));
+                }
+                tasks.push(self.handle_sync_messages_ok(account_id, mailbox_id.clone(), epoch));
+                return cosmic::task::batch(tasks);
+            }
+            Message::SyncMessagesComplete {
+                ref account_id,
+                ref mailbox_id,
+                epoch,
+                result: Err(ref e),
+            } => {
+                return self.handle_sync_messages_err(account_id, mailbox_id, epoch, e);
             }
-            Message::SyncMessagesComplete { ref account_id, epoch, result: Err(ref e), .. } => {
-                return self.handle_sync_messages_err(account_id, epoch, e);
+            Message::FoldersWarmedUp { ref account_id, mailbox_ids } => {
+                return self.handle_folders_warmed_up(account_id, mailbox_ids);
             }
 
             Message::SelectFolder(acct_idx, folder_idx) => {
@@ -180,6 +214,10 @@ impl AppModel {
                 return self.handle_refresh();
             }
 
+            Message::PeriodicSync(ref account_id) => {
+                return self.handle_refresh_accounts(Some(account_id));
+            }
+
             Message::ForceReconnect(ref account_id) => {
                 return self.handle_force_reconnect(account_id);
             }
@@ -3,13 +3,23 @@
 use cosmic::app::Task;
 use futures::future::{AbortHandle, Abortable};
 use neverlight_mail_core::models::Folder;
-use neverlight_mail_core::store::DEFAULT_PAGE_SIZE;
 use std::time::Instant;
 
-use super::{AppModel, ConnectionState, Message, Phase, RefreshPhase};
+use super::autocomplete::index_contacts;
+use super::metrics::MetricKind;
+use super::{AppModel, ConnectionState, FolderSyncState, Message, Phase, RefreshPhase};
 use super::sync::{mark_refresh_account_complete, refresh_has_timed_out, REFRESH_STUCK_TIMEOUT};
 
 impl AppModel {
+    /// Page size for a folder, from settings.json `paging` / `folder_paging`.
+    pub(super) fn page_size_for(&self, account_id: &str, mailbox_id: &str) -> u32 {
+        let path = self
+            .account_index(account_id)
+            .and_then(|i| self.accounts[i].folders.iter().find(|f| f.mailbox_id == mailbox_id))
+            .map_or("", |f| f.path.as_str());
+        self.settings.page_size(account_id, path)
+    }
+
     pub(super) fn handle_cached_folders_ok(
         &mut self,
         account_id: String,
@@ -26,8 +36,9 @@ impl AppModel {
         self.accounts[idx].folders = folders;
         self.accounts[idx].rebuild_folder_map();
 
-        // Auto-select INBOX of first account if nothing selected
-        if self.active_account.is_some() {
+        // Auto-select INBOX of the last-used account (or the first to load)
+        // if nothing is selected
+        if self.selected_folder.is_some() || self.active_account.is_some_and(|a| a != idx) {
             return Task::none();
         }
         let Some(fi) = self.accounts[idx].folders.iter().position(|f| f.path == "INBOX") else {
@@ -43,10 +54,12 @@ impl AppModel {
             return Task::none();
         };
         let cache = cache.clone();
+        let metrics = self.metrics.clone();
         let aid = account_id.clone();
         self.folder_epoch = self.folder_epoch.saturating_add(1);
         let epoch = self.folder_epoch;
         self.messages_offset = 0;
+        let page_size = self.page_size_for(&aid, &mailbox_id);
         if let Some(handle) = self.folder_abort.take() {
             handle.abort();
         }
@@ -54,11 +67,9 @@ impl AppModel {
         self.folder_abort = Some(abort_handle);
         cosmic::task::future(async move {
             match Abortable::new(
-                cache.load_messages(
-                    aid.clone(),
-                    mailbox_id.clone(),
-                    DEFAULT_PAGE_SIZE,
-                    0,
+                metrics.timed(
+                    MetricKind::CacheQuery,
+                    cache.load_messages(aid.clone(), mailbox_id.clone(), page_size, 0),
                 ),
                 abort_reg,
             )
@@ -87,7 +98,15 @@ impl AppModel {
             return Task::none();
         };
         self.accounts[idx].client = Some(client.clone());
+        if self.accounts[idx].paused {
+            // Paused while connecting: keep the session, skip the sync.
+            self.accounts[idx].conn_state = ConnectionState::Connected;
+            return Task::none();
+        }
         self.accounts[idx].conn_state = ConnectionState::Syncing;
+        // Failures from the old session are retried by this one.
+        self.accounts[idx].folder_sync.clear();
+        self.accounts[idx].push_healthy = true;
         if self.accounts[idx].reconnect_attempts > 0 {
             self.reconnect_count = self.reconnect_count.saturating_add(1);
         }
@@ -110,21 +129,26 @@ impl AppModel {
         self.phase = Phase::Loading;
 
         let cache = self.cache.clone();
+        let metrics = self.metrics.clone();
         let aid = account_id.clone();
         let mut tasks: Vec<Task<Message>> = Vec::new();
 
         self.refresh_epoch = self.refresh_epoch.saturating_add(1);
         let epoch = self.refresh_epoch;
         tasks.push(cosmic::task::future(async move {
-            let result = if let Some(ref cache) = cache {
-                neverlight_mail_core::sync::sync_mailboxes(&client, cache, &aid)
-                    .await
-                    .map_err(|e| e.to_string())
-            } else {
-                neverlight_mail_core::mailbox::fetch_all(&client)
-                    .await
-                    .map_err(|e| e.to_string())
-            };
+            let result = metrics
+                .timed(MetricKind::FolderSync, async {
+                    if let Some(ref cache) = cache {
+                        neverlight_mail_core::sync::sync_mailboxes(&client, cache, &aid)
+                            .await
+                            .map_err(|e| e.to_string())
+                    } else {
+                        neverlight_mail_core::mailbox::fetch_all(&client)
+                            .await
+                            .map_err(|e| e.to_string())
+                    }
+                })
+                .await;
             Message::SyncFoldersComplete {
                 account_id: aid,
                 epoch,
@@ -240,6 +264,10 @@ impl AppModel {
         if !self.accounts[idx].backfill_active && self.accounts[idx].client.is_some() {
             self.accounts[idx].backfill_active = true;
         }
+        // Each folder sync starts a prefetch pass unless one is running.
+        if self.accounts[idx].client.is_some() {
+            self.accounts[idx].prefetch_active = true;
+        }
         self.clear_error_surface();
         self.last_refresh_at = Some(Instant::now());
         self.status_message = format!(
@@ -267,6 +295,7 @@ impl AppModel {
             }
         }
         self.revalidate_selected_folder();
+        let warm_up = self.warm_up_folders(idx);
 
         // If this is the active account, sync the selected folder's messages
         let can_fetch = self.active_account == Some(idx)
@@ -282,34 +311,35 @@ impl AppModel {
             let mailbox_id = self.accounts[idx].folders[fi].mailbox_id.clone();
             let client = self.accounts[idx].client.clone().expect("checked above");
             let cache = self.cache.clone();
+            let metrics = self.metrics.clone();
             let mid = mailbox_id.clone();
             let aid = account_id.clone();
             let aid_for_cache = aid.clone();
+            let page_size = self.page_size_for(&aid, &mailbox_id);
             self.message_epoch = self.message_epoch.saturating_add(1);
             let message_epoch = self.message_epoch;
-            if let Some(handle) = self.message_abort.take() {
-                handle.abort();
-            }
+            self.abort_message_sync();
+            self.accounts[idx].set_folder_sync(&mailbox_id, FolderSyncState::SyncingHeaders);
             let (abort_handle, abort_reg) = AbortHandle::new_pair();
             self.message_abort = Some(abort_handle);
             let fetch_task = cosmic::task::future(async move {
                 let result = match Abortable::new(
-                    async {
+                    metrics.timed(MetricKind::MessageSync, async {
                         if let Some(ref cache) = cache {
                             neverlight_mail_core::sync::sync_emails(
-                                &client, cache, &aid_for_cache, &mid, DEFAULT_PAGE_SIZE,
+                                &client, cache, &aid_for_cache, &mid, page_size,
                             )
                             .await
                             .map_err(|e| e.to_string())
                         } else {
                             neverlight_mail_core::email::query_and_get(
-                                &client, &mid, DEFAULT_PAGE_SIZE, 0,
+                                &client, &mid, page_size, 0,
                             )
                             .await
                             .map(|(msgs, _)| msgs)
                             .map_err(|e| e.to_string())
                         }
-                    },
+                    }),
                     abort_reg,
                 )
                 .await
@@ -340,6 +370,7 @@ impl AppModel {
             if let Some(t) = sent_task {
                 tasks.push(t);
             }
+            tasks.extend(warm_up);
             // Defer the pending refresh until after the message sync completes.
             // Dispatching it here would race: the second refresh's SyncFoldersComplete
             // bumps message_epoch and aborts the sync_emails we just started, causing
@@ -350,10 +381,11 @@ impl AppModel {
             return cosmic::task::batch(tasks);
         }
 
+        let mut tasks: Vec<Task<Message>> = warm_up.into_iter().collect();
         if refresh_completed && had_pending {
-            return self.dispatch(Message::Refresh);
+            tasks.push(self.dispatch(Message::Refresh));
         }
-        Task::none()
+        cosmic::task::batch(tasks)
     }
 
     /// Fire-and-forget sync of the Sent folder into cache.
@@ -364,6 +396,7 @@ impl AppModel {
         let client = acct.client.clone()?;
         let cache = self.cache.clone()?;
         let sent_id = neverlight_mail_core::mailbox::find_by_role(&acct.folders, "sent")?;
+        let inbox_id = neverlight_mail_core::mailbox::find_by_role(&acct.folders, "inbox");
 
         // Skip if this is already the selected folder (it'll be synced normally)
         let selected_mailbox = self.selected_folder
@@ -374,14 +407,15 @@ impl AppModel {
         }
 
         let aid = account_id.to_string();
+        let page_size = self.page_size_for(&aid, &sent_id);
         log::debug!("Background sync: pre-caching Sent folder for {}", acct.config.label);
         Some(cosmic::task::future(async move {
             if let Err(e) = neverlight_mail_core::sync::sync_emails(
-                &client, &cache, &aid, &sent_id, DEFAULT_PAGE_SIZE,
+                &client, &cache, &aid, &sent_id, page_size,
             ).await {
                 log::warn!("Background Sent sync failed: {}", e);
             }
-            Message::Noop
+            index_contacts(cache, aid, Some(sent_id), inbox_id).await
         }))
     }
 
@@ -401,6 +435,7 @@ impl AppModel {
             self.accounts[idx].conn_state = ConnectionState::Error(e.clone());
             self.accounts[idx].last_error = Some(e.clone());
             self.accounts[idx].client = None;
+            self.accounts[idx].reset_sync();
             let label = &self.accounts[idx].config.label;
             if self.accounts[idx].folders.is_empty() {
                 self.status_message = format!("{}: Failed to load folders: {}", label, e);
@@ -448,6 +483,13 @@ impl AppModel {
         mailbox_id: String,
         epoch: u64,
     ) -> Task<Message> {
+        // The cache is current even if this result is stale for the view.
+        self.record_folder_synced(&account_id, &mailbox_id);
+        if epoch == self.message_epoch {
+            if let Some(idx) = self.account_index(&account_id) {
+                self.accounts[idx].set_folder_sync(&mailbox_id, FolderSyncState::Idle);
+            }
+        }
         if epoch != self.message_epoch {
             self.stale_apply_drop_count = self.stale_apply_drop_count.saturating_add(1);
             return Task::none();
@@ -469,7 +511,12 @@ impl AppModel {
             return Task::none();
         }
         if let Some(idx) = self.active_account {
-            if let Some(acct) = self.accounts.get_mut(idx) {
+            // A folder list sync still in flight keeps the account Syncing.
+            if let Some(acct) = self
+                .accounts
+                .get_mut(idx)
+                .filter(|a| a.conn_state != ConnectionState::Syncing)
+            {
                 acct.conn_state = ConnectionState::Connected;
             }
         }
@@ -494,6 +541,7 @@ impl AppModel {
             let mailbox_id = self.accounts[acct_idx].folders[fi].mailbox_id.clone();
             let cache = self.cache.clone().expect("checked above");
             let aid = self.active_account_id();
+            let page_size = self.page_size_for(&aid, &mailbox_id);
             self.messages_offset = 0;
             self.folder_epoch = self.folder_epoch.saturating_add(1);
             let folder_epoch = self.folder_epoch;
@@ -507,7 +555,7 @@ impl AppModel {
                     cache.load_messages(
                         aid.clone(),
                         mailbox_id.clone(),
-                        DEFAULT_PAGE_SIZE,
+                        page_size,
                         0,
                     ),
                     abort_reg,
@@ -551,6 +599,7 @@ impl AppModel {
     pub(super) fn handle_sync_messages_err(
         &mut self,
         account_id: &str,
+        mailbox_id: &str,
         epoch: u64,
         e: &str,
     ) -> Task<Message> {
@@ -566,10 +615,18 @@ impl AppModel {
             acct.conn_state = ConnectionState::Error(e.to_string());
             acct.last_error = Some(e.to_string());
             acct.client = None;
+            acct.reset_sync();
             let label = &acct.config.label;
             log::error!("Message sync failed for '{}': {} — dropping client", label, e);
 
             let delay = acct.reconnect_backoff();
+            acct.set_folder_sync(
+                mailbox_id,
+                FolderSyncState::Error {
+                    message: e.to_string(),
+                    retry_at: Some(Instant::now() + delay),
+                },
+            );
             let aid = account_id.to_string();
             self.status_message = format!("Sync failed: {}", e);
             self.set_status_error(self.status_message.clone());
@@ -589,20 +646,28 @@ impl AppModel {
         acct_idx: usize,
         folder_idx: usize,
     ) -> Task<Message> {
+        let previous_mailbox_id = self.selected_mailbox_id.take();
+        self.smart_view = None;
         self.active_account = Some(acct_idx);
+        self.remember_last_account();
         self.selected_folder = Some(folder_idx);
         self.selected_mailbox_id = self
             .accounts
             .get(acct_idx)
             .and_then(|acct| acct.folders.get(folder_idx))
             .map(|f| f.mailbox_id.clone());
+        // Selection persists across reloads of the same folder only.
+        let switched = self.selected_mailbox_id != previous_mailbox_id;
+        if switched {
+            self.message_selection.clear();
+            self.visual = None;
+            self.attachment_browser = None;
+        }
         self.selected_folder_evicted = false;
         if let Some(handle) = self.folder_abort.take() {
             handle.abort();
         }
-        if let Some(handle) = self.message_abort.take() {
-            handle.abort();
-        }
+        self.abort_message_sync();
         self.folder_epoch = self.folder_epoch.saturating_add(1);
         let folder_epoch = self.folder_epoch;
         self.message_epoch = self.message_epoch.saturating_add(1);
@@ -613,6 +678,7 @@ impl AppModel {
         self.preview_markdown.clear();
         self.preview_attachments.clear();
         self.preview_image_handles.clear();
+        self.preview_attachment_hashes.clear();
         self.messages_offset = 0;
         self.has_more_messages = false;
         self.collapsed_threads.clear();
@@ -628,6 +694,11 @@ impl AppModel {
         let mailbox_id = folder.mailbox_id.clone();
         let folder_name = folder.name.clone();
         let aid = acct.config.id.clone();
+        if switched {
+            self.record_folder_open(&aid, &mailbox_id);
+        }
+        self.collapsed_threads = self.saved_collapsed_threads.folder(&aid, &mailbox_id);
+        let page_size = self.page_size_for(&aid, &mailbox_id);
         let mut tasks: Vec<Task<Message>> = Vec::new();
 
         if let Some(cache) = &self.cache {
@@ -641,7 +712,7 @@ impl AppModel {
                     cache.load_messages(
                         aid2.clone(),
                         mid.clone(),
-                        DEFAULT_PAGE_SIZE,
+                        page_size,
                         0,
                     ),
                     abort_reg,
@@ -660,36 +731,38 @@ impl AppModel {
             }));
         }
 
-        if let Some(client) = &acct.client {
+        // Paused accounts browse the cache only.
+        if let Some(client) = acct.client.as_ref().filter(|_| !acct.paused) {
             let client = client.clone();
             let cache = self.cache.clone();
+            let metrics = self.metrics.clone();
             let aid2 = aid.clone();
             let aid_for_cache = aid2.clone();
             let mid = mailbox_id.clone();
             if let Some(acct_mut) = self.accounts.get_mut(acct_idx) {
-                acct_mut.conn_state = ConnectionState::Syncing;
+                acct_mut.set_folder_sync(&mailbox_id, FolderSyncState::SyncingHeaders);
             }
             self.status_message = format!("Loading {}...", folder_name);
             let (abort_handle, abort_reg) = AbortHandle::new_pair();
             self.message_abort = Some(abort_handle);
             tasks.push(cosmic::task::future(async move {
                 let result = match Abortable::new(
-                    async {
+                    metrics.timed(MetricKind::MessageSync, async {
                         if let Some(ref cache) = cache {
                             neverlight_mail_core::sync::sync_emails(
-                                &client, cache, &aid_for_cache, &mid, DEFAULT_PAGE_SIZE,
+                                &client, cache, &aid_for_cache, &mid, page_size,
                             )
                             .await
                             .map_err(|e| e.to_string())
                         } else {
                             neverlight_mail_core::email::query_and_get(
-                                &client, &mid, DEFAULT_PAGE_SIZE, 0,
+                                &client, &mid, page_size, 0,
                             )
                             .await
                             .map(|(msgs, _)| msgs)
                             .map_err(|e| e.to_string())
                         }
-                    },
+                    }),
                     abort_reg,
                 )
                 .await
@@ -722,9 +795,6 @@ impl AppModel {
     }
 
     pub(super) fn handle_load_more_messages(&mut self) -> Task<Message> {
-        self.messages_offset += DEFAULT_PAGE_SIZE;
-        let offset = self.messages_offset;
-
         let Some(acct_idx) = self.active_account else {
             return Task::none();
         };
@@ -741,6 +811,9 @@ impl AppModel {
         let cache = cache.clone();
         let aid = self.active_account_id();
         let mid = mailbox_id.clone();
+        let page_size = self.page_size_for(&aid, &mid);
+        self.messages_offset += page_size;
+        let offset = self.messages_offset;
         let epoch = self.folder_epoch;
         if let Some(handle) = self.folder_abort.take() {
             handle.abort();
@@ -752,7 +825,7 @@ impl AppModel {
                 cache.load_messages(
                     aid.clone(),
                     mid.clone(),
-                    DEFAULT_PAGE_SIZE,
+                    page_size,
                     offset,
                 ),
                 abort_reg,
@@ -772,6 +845,15 @@ impl AppModel {
     }
 
     pub(super) fn handle_refresh(&mut self) -> Task<Message> {
+        let refresh = self.handle_refresh_accounts(None);
+        cosmic::task::batch([refresh, self.load_smart_view()])
+    }
+
+    /// Refresh every connected account, or just `only` (per-account timers).
+    /// A refresh queued behind an in-flight one always covers all accounts.
+    /// Folder counts for an account come back from one Mailbox/get (JMAP's
+    /// answer to IMAP LIST-STATUS), not one request per folder.
+    pub(super) fn handle_refresh_accounts(&mut self, only: Option<&str>) -> Task<Message> {
         if self.refresh_phase.is_in_flight() {
             if refresh_has_timed_out(self.refresh_started_at, self.refresh_phase.is_timeout_reported()) {
                 self.refresh_phase.mark_timeout_reported();
@@ -779,6 +861,11 @@ impl AppModel {
                 self.refresh_stuck_count = self.refresh_stuck_count.saturating_add(1);
                 self.finish_refresh();
                 self.refresh_accounts_outstanding.clear();
+                for acct in &mut self.accounts {
+                    if acct.conn_state == ConnectionState::Syncing {
+                        acct.conn_state = ConnectionState::Connected;
+                    }
+                }
                 self.refresh_epoch = self.refresh_epoch.saturating_add(1);
                 log::warn!("Refresh stuck ({}s timeout), force-clearing and restarting", REFRESH_STUCK_TIMEOUT.as_secs());
             } else {
@@ -792,21 +879,29 @@ impl AppModel {
         let mut tasks: Vec<Task<Message>> = Vec::new();
         self.refresh_accounts_outstanding.clear();
         for acct in &self.accounts {
+            if only.is_some_and(|id| id != acct.config.id) || acct.paused {
+                continue;
+            }
             if let Some(client) = &acct.client {
                 let client = client.clone();
                 let cache = self.cache.clone();
+                let metrics = self.metrics.clone();
                 let aid = acct.config.id.clone();
                 self.refresh_accounts_outstanding.insert(aid.clone());
                 tasks.push(cosmic::task::future(async move {
-                    let result = if let Some(ref cache) = cache {
-                        neverlight_mail_core::sync::sync_mailboxes(&client, cache, &aid)
-                            .await
-                            .map_err(|e| e.to_string())
-                    } else {
-                        neverlight_mail_core::mailbox::fetch_all(&client)
-                            .await
-                            .map_err(|e| e.to_string())
-                    };
+                    let result = metrics
+                        .timed(MetricKind::FolderSync, async {
+                            if let Some(ref cache) = cache {
+                                neverlight_mail_core::sync::sync_mailboxes(&client, cache, &aid)
+                                    .await
+                                    .map_err(|e| e.to_string())
+                            } else {
+                                neverlight_mail_core::mailbox::fetch_all(&client)
+                                    .await
+                                    .map_err(|e| e.to_string())
+                            }
+                        })
+                        .await;
                     Message::SyncFoldersComplete {
                         account_id: aid,
                         epoch: refresh_epoch,
@@ -816,9 +911,12 @@ impl AppModel {
             }
         }
         if !tasks.is_empty() {
-            // Pause backfill during head sync to avoid contention
-            for acct in &self.accounts {
+            for acct in &mut self.accounts {
+                // Pause backfill during head sync to avoid contention
                 acct.backfill_pause.store(true, std::sync::atomic::Ordering::Relaxed);
+                if self.refresh_accounts_outstanding.contains(&acct.config.id) {
+                    acct.conn_state = ConnectionState::Syncing;
+                }
             }
             self.refresh_phase = RefreshPhase::InFlight { pending: false, timeout_reported: false };
             self.refresh_started_at = Some(Instant::now());
@@ -837,7 +935,7 @@ impl AppModel {
             return Task::none();
         };
         let acct = &mut self.accounts[idx];
-        if matches!(acct.conn_state, ConnectionState::Connecting | ConnectionState::Syncing) {
+        if acct.paused || acct.conn_state == ConnectionState::Connecting || acct.is_syncing() {
             return Task::none();
         }
         if acct.client.is_some() && matches!(acct.conn_state, ConnectionState::Connected) {
@@ -0,0 +1,150 @@
+//! What sync is doing, per account and per folder.
+//!
+//! `ConnectionState` describes the JMAP session, with `Syncing` while its
+//! folder list is being fetched. Work per folder is tracked here: a
+//! `FolderSyncState` per mailbox, so the sidebar and status pill say which folder is syncing
+//! or failed instead of one account-wide "Syncing...". Backfill progress is
+//! read from `backfill_progress`, which the backfill stream already keeps.
+
+use std::time::{Duration, Instant};
+
+use super::{AccountState, AppModel, ConnectionState};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FolderSyncState {
+    Idle,
+    /// Head page (new mail, flag changes) being fetched.
+    SyncingHeaders,
+    /// History walk: `position` of `total` messages cached so far.
+    Backfilling { position: u32, total: u32 },
+    /// Last sync failed; the reconnect that retries it is due at `retry_at`.
+    Error {
+        message: String,
+        retry_at: Option<Instant>,
+    },
+}
+
+impl FolderSyncState {
+    /// "failed, retrying in 12s"; "retrying" once the time has passed.
+    pub fn retry_label(retry_at: Option<Instant>, now: Instant) -> String {
+        match retry_at.map(|at| at.saturating_duration_since(now)) {
+            Some(wait) if wait >= Duration::from_secs(1) => {
+                format!("failed, retrying in {}s", wait.as_secs())
+            }
+            Some(_) => "failed, retrying".into(),
+            None => "failed".into(),
+        }
+    }
+}
+
+impl AccountState {
+    /// Sync status of one mailbox. Header syncs and errors win over backfill.
+    pub fn folder_sync(&self, mailbox_id: &str) -> FolderSyncState {
+        if let Some(state) = self.folder_sync.get(mailbox_id) {
+            return state.clone();
+        }
+        match self.backfill_progress.get(mailbox_id) {
+            Some(&(position, total)) => FolderSyncState::Backfilling { position, total },
+            None => FolderSyncState::Idle,
+        }
+    }
+
+    /// Folder list or a folder's head page in flight (backfill doesn't count:
+    /// it runs for hours and yields to both).
+    pub fn is_syncing(&self) -> bool {
+        self.conn_state == ConnectionState::Syncing
+            || self
+                .folder_sync
+                .values()
+                .any(|s| *s == FolderSyncState::SyncingHeaders)
+    }
+
+    /// A failed folder shows a "retrying in 8s" countdown.
+    pub fn has_retry_countdown(&self) -> bool {
+        self.folder_sync.values().any(|s| {
+            matches!(
+                s,
+                FolderSyncState::Error {
+                    retry_at: Some(_),
+                    ..
+                }
+            )
+        })
+    }
+
+    pub(super) fn set_folder_sync(&mut self, mailbox_id: &str, state: FolderSyncState) {
+        if state == FolderSyncState::Idle {
+            self.folder_sync.remove(mailbox_id);
+        } else {
+            self.folder_sync.insert(mailbox_id.to_string(), state);
+        }
+    }
+
+    /// The client is gone: nothing is in flight any more. Errors stay until
+    /// the retry.
+    pub(super) fn reset_sync(&mut self) {
+        self.folder_sync
+            .retain(|_, s| matches!(s, FolderSyncState::Error { .. }));
+    }
+
+    /// Status pill detail: "Syncing folders...", "Syncing Work...",
+    /// "Sent: failed, retrying in 8s". `None` when idle.
+    pub fn sync_summary(&self, now: Instant) -> Option<String> {
+        let name = |mailbox_id: &str| {
+            self.folders
+                .iter()
+                .find(|f| f.mailbox_id == mailbox_id)
+                .map(|f| f.name.clone())
+                .unwrap_or_else(|| mailbox_id.to_string())
+        };
+        if self.conn_state == ConnectionState::Syncing {
+            return Some("Syncing folders...".into());
+        }
+        if let Some(id) = self
+            .folder_sync
+            .iter()
+            .find(|(_, s)| **s == FolderSyncState::SyncingHeaders)
+            .map(|(id, _)| id)
+        {
+            return Some(format!("Syncing {}...", name(id)));
+        }
+        self.folder_sync.iter().find_map(|(id, s)| match s {
+            FolderSyncState::Error { retry_at, .. } => Some(format!(
+                "{}: {}",
+                name(id),
+                FolderSyncState::retry_label(*retry_at, now)
+            )),
+            _ => None,
+        })
+    }
+}
+
+impl AppModel {
+    /// Abort the in-flight head-page sync. Its completion never arrives, so
+    /// the folder it was syncing goes back to idle here.
+    pub(super) fn abort_message_sync(&mut self) {
+        if let Some(handle) = self.message_abort.take() {
+            handle.abort();
+        }
+        for acct in &mut self.accounts {
+            acct.folder_sync
+                .retain(|_, s| *s != FolderSyncState::SyncingHeaders);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_label_counts_down() {
+        let now = Instant::now();
+        assert_eq!(
+            FolderSyncState::retry_label(Some(now + Duration::from_secs(12)), now),
+            "failed, retrying in 12s"
+        );
+        assert_eq!(FolderSyncState::retry_label(Some(now), now), "failed, retrying");
+        assert_eq!(FolderSyncState::retry_label(None, now), "failed");
+    }
+}
@@ -0,0 +1,149 @@
+//! Template variables in compose: `{first_name}`, `{last_name}`, `{name}`
+//! and `{email}` in the subject or body are filled from the first To
+//! recipient when the message is sent.
+//!
+//! Only compose opened from a template (`neverlight-mail compose
+//! --template`) fills them. Replies and forwards quote mail that may
+//! contain the same braces, and that text goes out as written.
+//!
+//! The name comes from the To field itself (`Alice Smith <alice@…>`) or,
+//! for a bare address, from the address book. "Smith, Alice" is read last
+//! name first. Compose previews the filled lines; a variable with nothing
+//! to fill it (no name known for the recipient) blocks Send until it's
+//! removed or the recipient gets a name. Other `{…}` text is left alone.
+
+use super::recipients::{bare_address, display_name, split_recipients};
+use super::AppModel;
+
+use crate::ui::compose_dialog::ComposeMode;
+
+/// Variables understood, without braces.
+const VARIABLES: [&str; 4] = ["first_name", "last_name", "name", "email"];
+/// Filled lines shown in the compose preview.
+const PREVIEW_LINES: usize = 3;
+
+/// The variables used in compose, what they fill in with, and a preview.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VariableFill {
+    /// Each variable used, in `VARIABLES` order, and its value if known.
+    pub used: Vec<(&'static str, Option<String>)>,
+    /// Subject and body lines with variables, filled in.
+    pub preview: Vec<String>,
+}
+
+impl VariableFill {
+    /// Variables in `subject` and `body` filled from `recipient`
+    /// (`Name <addr>` or a bare address); `known_name` is the address
+    /// book's name for it.
+    pub fn new(subject: &str, body: &str, recipient: &str, known_name: Option<&str>) -> Self {
+        let address = bare_address(recipient);
+        let name = match display_name(recipient) {
+            named if named != address => Some(named),
+            _ => known_name.map(str::to_string),
+        };
+        let (first, last) = name.as_deref().map(split_name).unwrap_or_default();
+        let used = VARIABLES
+            .into_iter()
+            .filter(|var| subject.contains(&token(var)) || body.contains(&token(var)))
+            .map(|var| {
+                let value = match var {
+                    "first_name" => first.clone(),
+                    "last_name" => last.clone(),
+                    "name" => name.clone(),
+                    _ => Some(address.to_string()).filter(|a| a.contains('@')),
+                };
+                (var, value)
+            })
+            .collect();
+        let mut fill = Self {
+            used,
+            preview: Vec::new(),
+        };
+        fill.preview = std::iter::once(subject)
+            .chain(body.lines())
+            .filter(|line| VARIABLES.iter().any(|var| line.contains(&token(var))))
+            .take(PREVIEW_LINES)
+            .map(|line| fill.fill(line))
+            .collect();
+        fill
+    }
+
+    /// Variables with nothing to fill them, as `{first_name}`.
+    pub fn unresolved(&self) -> Vec<String> {
+        self.used
+            .iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(var, _)| token(var))
+            .collect()
+    }
+
+    /// `text` with every known variable filled in.
+    pub fn fill(&self, text: &str) -> String {
+        self.used
+            .iter()
+            .filter_map(|(var, value)| Some((var, value.as_deref()?)))
+            .fold(text.to_string(), |text, (var, value)| text.replace(&token(var), value))
+    }
+}
+
+fn token(var: &str) -> String {
+    format!("{{{var}}}")
+}
+
+/// (first, last) of a display name; "Smith, Alice" is last name first. A
+/// single word is a first name only.
+fn split_name(name: &str) -> (Option<String>, Option<String>) {
+    let (first, last) = match name.split_once(',') {
+        Some((last, first)) => (first.trim(), Some(last.trim())),
+        None => match name.trim().rsplit_once(' ') {
+            Some((first, last)) => (first.split(' ').next().unwrap_or(first), Some(last.trim())),
+            None => (name.trim(), None),
+        },
+    };
+    let word = |s: &str| Some(s.to_string()).filter(|s| !s.is_empty());
+    (word(first), last.and_then(word))
+}
+
+impl AppModel {
+    /// Template variables in compose, filled from the first To recipient;
+    /// none outside template compose.
+    pub(super) fn compose_variables(&self) -> VariableFill {
+        if self.compose_mode != ComposeMode::Template {
+            return VariableFill::default();
+        }
+        let body = self.compose_body.text();
+        let recipients = split_recipients(&self.compose_to);
+        let recipient = recipients.first().copied().unwrap_or_default();
+        let known_name = self
+            .accounts
+            .get(self.compose_account)
+            .and_then(|a| self.recipient_index.get(&a.config.id))
+            .and_then(|index| index.name_for(bare_address(recipient)));
+        VariableFill::new(&self.compose_subject, &body, recipient, known_name.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables_fill_from_the_recipient_or_the_address_book() {
+        let body = "Hi {first_name},\n\nAttached is the {quarter} report.\n";
+        let fill = VariableFill::new("For {name}", body, "Alice Smith <alice@example.com>", None);
+        assert_eq!(fill.fill(body), "Hi Alice,\n\nAttached is the {quarter} report.\n");
+        assert_eq!(fill.preview, ["For Alice Smith", "Hi Alice,"]);
+        assert!(fill.unresolved().is_empty());
+
+        let fill = VariableFill::new("", "Dear {last_name}", "bob@example.com", Some("Jones, Bob"));
+        assert_eq!(fill.fill("Dear {last_name}"), "Dear Jones");
+    }
+
+    #[test]
+    fn unknown_names_are_reported() {
+        let fill = VariableFill::new("", "Hi {first_name} ({email})", "carol@example.com", None);
+        assert_eq!(fill.unresolved(), ["{first_name}"]);
+        assert_eq!(fill.preview, ["Hi {first_name} (carol@example.com)"]);
+        assert_eq!(VariableFill::new("", "No variables", "", None), VariableFill::default());
+    }
+}
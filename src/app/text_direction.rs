@@ -0,0 +1,68 @@
+//! Right-to-left detection for message bodies.
+//!
+//! The text shaper picks each paragraph's direction from its first strong
+//! character, so an Arabic or Hebrew mail whose lines open with a Latin
+//! name, URL or product code comes out left-aligned line by line. The
+//! message view instead takes one direction for the whole body, from the
+//! balance of strong letters near its start, and right-aligns RTL bodies.
+
+/// Bodies are judged on this many leading characters.
+const SAMPLE_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
+}
+
+/// Hebrew, Arabic, Syriac, Thaana, N'Ko and related blocks, plus the
+/// Hebrew/Arabic presentation forms.
+fn is_rtl_letter(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}'
+    ) && c.is_alphabetic()
+}
+
+/// Direction of `text`: RTL when it has more right-to-left letters than
+/// left-to-right ones. Digits, punctuation and quoted-reply markers count
+/// for neither.
+pub fn detect_direction(text: &str) -> TextDirection {
+    let (mut rtl, mut ltr) = (0usize, 0usize);
+    for c in text.chars().take(SAMPLE_CHARS).filter(|c| c.is_alphabetic()) {
+        if is_rtl_letter(c) {
+            rtl += 1;
+        } else {
+            ltr += 1;
+        }
+    }
+    if rtl > ltr {
+        TextDirection::Rtl
+    } else {
+        TextDirection::Ltr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hebrew_and_arabic_bodies_are_rtl() {
+        assert_eq!(detect_direction("שלום, מה שלומך?"), TextDirection::Rtl);
+        assert_eq!(detect_direction("مرحبا بك في الاجتماع"), TextDirection::Rtl);
+    }
+
+    #[test]
+    fn mostly_rtl_text_with_latin_words_stays_rtl() {
+        let body = "Fastmail: الاجتماع غدا في المكتب الرئيسي 10:00";
+        assert_eq!(detect_direction(body), TextDirection::Rtl);
+    }
+
+    #[test]
+    fn latin_and_letterless_text_is_ltr() {
+        assert_eq!(detect_direction("See you at 10, شكرا"), TextDirection::Ltr);
+        assert_eq!(detect_direction("> 12:30 -- 555-0100"), TextDirection::Ltr);
+        assert_eq!(detect_direction(""), TextDirection::Ltr);
+    }
+}
@@ -0,0 +1,101 @@
+//! Collapsed threads remembered per folder, so a long muted thread stays
+//! folded after a folder switch or restart.
+//!
+//! `collapsed_threads` holds the folder on screen; selecting a folder loads
+//! its saved set and each toggle writes it back. The cache schema belongs to
+//! neverlight-mail-core, so the sets live in a small file next to the thread
+//! overrides. Each folder keeps its most recent collapses only, so threads
+//! that have long left the folder don't pile up.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use neverlight_mail_core::config::AccountId;
+use serde::{Deserialize, Serialize};
+
+use super::json_store::{load_json, save_json};
+
+/// Collapsed threads kept per folder; the oldest go first.
+const MAX_PER_FOLDER: usize = 500;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollapsedThreads {
+    /// account_id → mailbox_id → thread ids, oldest collapse first.
+    #[serde(default)]
+    accounts: HashMap<AccountId, HashMap<String, Vec<String>>>,
+}
+
+impl CollapsedThreads {
+    /// Threads collapsed in the folder.
+    pub fn folder(&self, account_id: &str, mailbox_id: &str) -> HashSet<String> {
+        self.accounts
+            .get(account_id)
+            .and_then(|folders| folders.get(mailbox_id))
+            .map(|threads| threads.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn set(&mut self, account_id: &str, mailbox_id: &str, thread_id: &str, collapsed: bool) {
+        let folders = self.accounts.entry(account_id.to_string()).or_default();
+        let threads = folders.entry(mailbox_id.to_string()).or_default();
+        threads.retain(|t| t != thread_id);
+        if collapsed {
+            threads.push(thread_id.to_string());
+            let excess = threads.len().saturating_sub(MAX_PER_FOLDER);
+            threads.drain(..excess);
+        } else if threads.is_empty() {
+            folders.remove(mailbox_id);
+        }
+    }
+
+    pub fn remove_account(&mut self, account_id: &str) {
+        self.accounts.remove(account_id);
+    }
+
+    pub fn load() -> Self {
+        load_json(collapsed_path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_json(collapsed_path(), self)
+    }
+}
+
+fn collapsed_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("neverlight-mail").join("collapsed-threads.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapses_are_kept_per_folder() {
+        let mut collapsed = CollapsedThreads::default();
+        collapsed.set("acct-1", "INBOX", "T1", true);
+        collapsed.set("acct-1", "Lists", "T2", true);
+        assert_eq!(collapsed.folder("acct-1", "INBOX"), HashSet::from(["T1".to_string()]));
+        assert!(collapsed.folder("acct-2", "INBOX").is_empty());
+
+        collapsed.set("acct-1", "INBOX", "T1", false);
+        assert!(collapsed.folder("acct-1", "INBOX").is_empty());
+        let json = serde_json::to_string(&collapsed).unwrap();
+        let back: CollapsedThreads = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, collapsed);
+    }
+
+    #[test]
+    fn oldest_collapses_are_dropped_past_the_cap() {
+        let mut collapsed = CollapsedThreads::default();
+        for i in 0..=MAX_PER_FOLDER {
+            collapsed.set("acct-1", "INBOX", &format!("T{i}"), true);
+        }
+        // Re-collapsing moves a thread to the newest end.
+        collapsed.set("acct-1", "INBOX", "T1", true);
+        collapsed.set("acct-1", "INBOX", "new", true);
+        let folder = collapsed.folder("acct-1", "INBOX");
+        assert_eq!(folder.len(), MAX_PER_FOLDER);
+        assert!(!folder.contains("T0") && !folder.contains("T2"));
+        assert!(folder.contains("T1") && folder.contains("new"));
+    }
+}
@@ -0,0 +1,294 @@
+//! Manual fixes for broken threading: "Merge into thread" and "Split from
+//! thread".
+//!
+//! Thread ids come from the server through the cache, and the cache schema
+//! belongs to neverlight-mail-core, so the overrides live in a small file
+//! next to the drafts instead of a cache table. They are applied over every
+//! loaded summary before the list groups threads, and over `load_thread`
+//! results before a conversation is built; a merged message's original
+//! thread is loaded alongside so it can be pulled in. Each override keeps
+//! the server's thread id, so splitting a merged message sends it home.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use cosmic::app::Task;
+use neverlight_mail_core::config::AccountId;
+use neverlight_mail_core::models::MessageSummary;
+use serde::{Deserialize, Serialize};
+
+use super::folder_activity::parse_summary_date;
+use super::json_store::{load_json, save_json};
+use super::{AppModel, Message};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ThreadOverride {
+    /// Moved into `into`; `from` is the server's thread, if it had one.
+    Merged { from: Option<String>, into: String },
+    /// Taken out of the server's thread `from` to stand alone.
+    Split { from: String },
+}
+
+impl ThreadOverride {
+    fn thread_id(&self, email_id: &str) -> String {
+        match self {
+            Self::Merged { into, .. } => into.clone(),
+            Self::Split { .. } => own_thread_id(email_id),
+        }
+    }
+
+    fn server_thread(&self) -> Option<&str> {
+        match self {
+            Self::Merged { from, .. } => from.as_deref(),
+            Self::Split { from } => Some(from),
+        }
+    }
+}
+
+/// Thread id for a message that stands alone (split, or a merge target the
+/// server never threaded).
+fn own_thread_id(email_id: &str) -> String {
+    format!("local:{email_id}")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadOverrides {
+    /// account_id → email_id → override.
+    #[serde(default)]
+    accounts: HashMap<AccountId, HashMap<String, ThreadOverride>>,
+}
+
+impl ThreadOverrides {
+    /// The overridden thread of `email_id`, if there is an override.
+    pub fn thread_of(&self, account_id: &str, email_id: &str) -> Option<String> {
+        let entry = self.accounts.get(account_id)?.get(email_id)?;
+        Some(entry.thread_id(email_id))
+    }
+
+    /// Point `msg` at its overridden thread, as a top-level row.
+    pub fn apply(&self, msg: &mut MessageSummary) {
+        if let Some(thread_id) = self.thread_of(&msg.account_id, &msg.email_id) {
+            msg.thread_id = Some(thread_id);
+            msg.thread_depth = 0;
+        }
+    }
+
+    /// Move `email_id` (now in `current`) into `into`. Moving a message back
+    /// into its server thread just drops the override.
+    pub fn merge(&mut self, account_id: &str, email_id: &str, current: Option<&str>, into: &str) {
+        let entries = self.accounts.entry(account_id.to_string()).or_default();
+        let from = match entries.get(email_id) {
+            Some(entry) => entry.server_thread().map(str::to_string),
+            None => current.map(str::to_string),
+        };
+        if from.as_deref() == Some(into) {
+            entries.remove(email_id);
+        } else {
+            let into = into.to_string();
+            entries.insert(email_id.to_string(), ThreadOverride::Merged { from, into });
+        }
+    }
+
+    /// Take `email_id` out of `current`; returns its thread afterwards. A
+    /// merged message goes back to its server thread.
+    pub fn split(&mut self, account_id: &str, email_id: &str, current: &str) -> Option<String> {
+        let entries = self.accounts.entry(account_id.to_string()).or_default();
+        match entries.get(email_id) {
+            Some(ThreadOverride::Merged { from, .. }) => {
+                let from = from.clone();
+                entries.remove(email_id);
+                from
+            }
+            Some(ThreadOverride::Split { .. }) => Some(own_thread_id(email_id)),
+            None => {
+                let from = current.to_string();
+                entries.insert(email_id.to_string(), ThreadOverride::Split { from });
+                Some(own_thread_id(email_id))
+            }
+        }
+    }
+
+    /// Server threads to load for `thread_id`: itself plus the threads its
+    /// merged-in messages came from.
+    pub fn threads_to_load(&self, account_id: &str, thread_id: &str) -> Vec<String> {
+        let mut threads = vec![thread_id.to_string()];
+        let Some(entries) = self.accounts.get(account_id) else {
+            return threads;
+        };
+        for entry in entries.values() {
+            if let ThreadOverride::Merged { from: Some(from), into } = entry {
+                if into == thread_id && !threads.contains(from) {
+                    threads.push(from.clone());
+                }
+            }
+        }
+        threads
+    }
+
+    /// `load_thread` results for `threads_to_load(thread_id)`, overridden and
+    /// cut down to the messages now in `thread_id`, oldest day first.
+    pub fn regroup(&self, msgs: Vec<MessageSummary>, thread_id: &str) -> Vec<MessageSummary> {
+        let mut thread: Vec<MessageSummary> = Vec::new();
+        for mut msg in msgs {
+            self.apply(&mut msg);
+            let ours = msg.thread_id.as_deref() == Some(thread_id);
+            if ours && !thread.iter().any(|m| m.email_id == msg.email_id) {
+                thread.push(msg);
+            }
+        }
+        thread.sort_by_key(|m| parse_summary_date(&m.date));
+        thread
+    }
+
+    pub fn remove_account(&mut self, account_id: &str) {
+        self.accounts.remove(account_id);
+    }
+
+    pub fn load() -> Self {
+        load_json(overrides_path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_json(overrides_path(), self)
+    }
+}
+
+fn overrides_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("neverlight-mail").join("thread-overrides.json"))
+}
+
+impl AppModel {
+    /// Give every loaded copy of `email_id` its new thread.
+    fn rethread(&mut self, email_id: &str, thread_id: Option<String>) {
+        for msg in self.messages.iter_mut().filter(|m| m.email_id == email_id) {
+            msg.thread_id = thread_id.clone();
+            msg.thread_depth = 0;
+        }
+    }
+
+    /// Persist, regroup the list and rebuild the open conversation.
+    fn thread_overrides_changed(&mut self, status: String) -> Task<Message> {
+        if let Err(e) = self.thread_overrides.save() {
+            log::warn!("Failed to save thread overrides: {}", e);
+        }
+        self.recompute_visible();
+        self.status_message = status;
+        match self.selected_message {
+            Some(index) => self.dispatch(Message::ViewBody(index)),
+            None => Task::none(),
+        }
+    }
+
+    pub(super) fn handle_thread_overrides(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::MergeIntoThread => {
+                let Some(target) = self.selected_message.and_then(|i| self.messages.get(i)) else {
+                    self.status_message = "Open a message in the thread to merge into".into();
+                    return Task::none();
+                };
+                if self.message_selection.is_empty() {
+                    self.status_message = "Select the messages to merge first".into();
+                    return Task::none();
+                }
+                let account_id = target.account_id.clone();
+                let target_id = target.email_id.clone();
+                let into = target
+                    .thread_id
+                    .clone()
+                    .unwrap_or_else(|| own_thread_id(&target_id));
+
+                let mut members: Vec<(String, Option<String>)> = self
+                    .message_selection
+                    .identities()
+                    .filter(|id| id.account_id == account_id)
+                    .filter_map(|id| self.messages.iter().find(|m| m.email_id == id.email_id))
+                    .map(|m| (m.email_id.clone(), m.thread_id.clone()))
+                    .collect();
+                let skipped = self
+                    .message_selection
+                    .identities()
+                    .filter(|id| id.account_id != account_id)
+                    .count();
+                let merged = members.iter().filter(|(id, _)| *id != target_id).count();
+                if target.thread_id.is_none() {
+                    members.push((target_id, None));
+                }
+                for (email_id, current) in members {
+                    self.thread_overrides
+                        .merge(&account_id, &email_id, current.as_deref(), &into);
+                    self.rethread(&email_id, Some(into.clone()));
+                }
+                self.message_selection.clear();
+                let mut status = format!("Merged {merged} messages into this thread");
+                if skipped > 0 {
+                    status.push_str(&format!(" ({skipped} from other accounts left alone)"));
+                }
+                return self.thread_overrides_changed(status);
+            }
+            Message::SplitFromThread(email_id) => {
+                let msg = self
+                    .conversation
+                    .iter()
+                    .map(|e| &e.summary)
+                    .chain(self.messages.iter())
+                    .find(|m| m.email_id == email_id);
+                let Some(msg) = msg else {
+                    return Task::none();
+                };
+                let Some(current) = msg.thread_id.clone() else {
+                    self.status_message = "Message is not part of a thread".into();
+                    return Task::none();
+                };
+                let account_id = msg.account_id.clone();
+                let thread_id = self.thread_overrides.split(&account_id, &email_id, &current);
+                self.rethread(&email_id, thread_id);
+                return self.thread_overrides_changed("Split from thread".into());
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_then_split_returns_to_the_server_thread() {
+        let mut overrides = ThreadOverrides::default();
+        overrides.merge("acct-1", "M2", Some("T2"), "T1");
+        assert_eq!(overrides.thread_of("acct-1", "M2").as_deref(), Some("T1"));
+        assert_eq!(overrides.threads_to_load("acct-1", "T1"), ["T1", "T2"]);
+
+        assert_eq!(overrides.split("acct-1", "M2", "T1").as_deref(), Some("T2"));
+        assert_eq!(overrides.thread_of("acct-1", "M2"), None);
+        assert_eq!(overrides.threads_to_load("acct-1", "T1"), ["T1"]);
+    }
+
+    #[test]
+    fn split_message_stands_alone_until_merged_back() {
+        let mut overrides = ThreadOverrides::default();
+        assert_eq!(overrides.split("acct-1", "M3", "T1").as_deref(), Some("local:M3"));
+        assert_eq!(overrides.thread_of("acct-1", "M3").as_deref(), Some("local:M3"));
+        assert_eq!(overrides.thread_of("acct-2", "M3"), None);
+
+        // Merging elsewhere remembers the server thread, not the split id.
+        overrides.merge("acct-1", "M3", Some("local:M3"), "T9");
+        assert_eq!(overrides.threads_to_load("acct-1", "T9"), ["T9", "T1"]);
+        overrides.merge("acct-1", "M3", Some("T9"), "T1");
+        assert_eq!(overrides.thread_of("acct-1", "M3"), None);
+    }
+
+    #[test]
+    fn overrides_survive_a_save_round_trip() {
+        let mut overrides = ThreadOverrides::default();
+        overrides.merge("acct-1", "M2", None, "T1");
+        overrides.split("acct-1", "M4", "T1");
+        let json = serde_json::to_string(&overrides).unwrap();
+        let back: ThreadOverrides = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, overrides);
+        assert_eq!(back.threads_to_load("acct-1", "T1"), ["T1"]);
+    }
+}
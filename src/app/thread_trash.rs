@@ -0,0 +1,149 @@
+//! Conversation-safe trash: trashing a message that later messages in the
+//! list reply to asks first, and offers to trash the whole thread instead.
+//!
+//! Replies are found through `In-Reply-To` among the loaded messages of the
+//! same account, followed down the chain, so a reply to a reply counts too.
+//! Replies outside the loaded list (other folders, older pages) aren't seen.
+//! Bulk trash and the sender cleanup wizard don't ask.
+
+use std::collections::HashSet;
+
+use cosmic::app::Task;
+use neverlight_mail_core::models::MessageSummary;
+
+use super::{AppModel, Message, MessageIdentity};
+
+/// A trash waiting on the reply warning.
+#[derive(Debug, Clone)]
+pub struct TrashConfirm {
+    pub message: MessageIdentity,
+    pub thread_id: String,
+    /// Loaded messages that reply to it, directly or further down.
+    pub replies: usize,
+}
+
+/// How many of `links` (Message-ID, In-Reply-To) reply to `root`'s
+/// message, directly or through other replies.
+fn count_replies(links: &[(&str, Option<&str>)], root: &str) -> usize {
+    let mut ids: HashSet<&str> = HashSet::from([root]);
+    let mut found: HashSet<usize> = HashSet::new();
+    loop {
+        let before = found.len();
+        for (i, &(message_id, in_reply_to)) in links.iter().enumerate() {
+            if message_id == root || found.contains(&i) {
+                continue;
+            }
+            if in_reply_to.is_some_and(|parent| ids.contains(parent)) {
+                found.insert(i);
+                ids.insert(message_id);
+            }
+        }
+        if found.len() == before {
+            return found.len();
+        }
+    }
+}
+
+/// Replies to `messages[index]` among the loaded messages of its account.
+fn replies_to(messages: &[MessageSummary], index: usize) -> usize {
+    let Some(root) = messages.get(index) else {
+        return 0;
+    };
+    let links: Vec<(&str, Option<&str>)> = messages
+        .iter()
+        .filter(|m| m.account_id == root.account_id)
+        .map(|m| (m.message_id.as_str(), m.in_reply_to.as_deref()))
+        .collect();
+    count_replies(&links, &root.message_id)
+}
+
+impl AppModel {
+    /// Ask before trashing `index` if loaded messages reply to it.
+    pub(super) fn trash_confirm_for_index(&self, index: usize) -> Option<TrashConfirm> {
+        let msg = self.messages.get(index)?;
+        let thread_id = msg.thread_id.clone()?;
+        let replies = replies_to(&self.messages, index);
+        if replies == 0 {
+            return None;
+        }
+        Some(TrashConfirm {
+            message: MessageIdentity::of(msg),
+            thread_id,
+            replies,
+        })
+    }
+
+    pub(super) fn handle_thread_trash(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::TrashConfirmMessage => {
+                let Some(confirm) = self.trash_confirm.take() else {
+                    return Task::none();
+                };
+                let Some(index) = self.message_index(&confirm.message) else {
+                    return Task::none();
+                };
+                return self.trash_index(index);
+            }
+            Message::TrashConfirmThread => {
+                let Some(confirm) = self.trash_confirm.take() else {
+                    return Task::none();
+                };
+                let indices: Vec<usize> = self
+                    .messages
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, m)| {
+                        m.account_id == confirm.message.account_id
+                            && m.thread_id.as_deref() == Some(confirm.thread_id.as_str())
+                    })
+                    .map(|(i, _)| i)
+                    .collect();
+                // Intents carry identities, so later indices stay valid
+                // while the first moves take messages out of the list.
+                let intents: Vec<_> = indices
+                    .into_iter()
+                    .filter_map(|i| self.trash_intent_for_index(i))
+                    .collect();
+                let count = intents.len();
+                let tasks: Vec<_> = intents
+                    .into_iter()
+                    .map(|intent| self.queue_or_start_move(intent))
+                    .collect();
+                self.status_message = format!("Trashing {count} messages in the thread");
+                return cosmic::task::batch(tasks);
+            }
+            Message::TrashConfirmCancel => {
+                self.trash_confirm = None;
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    fn message_index(&self, identity: &MessageIdentity) -> Option<usize> {
+        self.messages.iter().position(|m| {
+            m.account_id == identity.account_id
+                && m.context_mailbox_id == identity.mailbox_id
+                && m.email_id == identity.email_id
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_are_followed_down_the_chain() {
+        let links = [
+            ("<c@x>", Some("<b@x>")),
+            ("<b@x>", Some("<a@x>")),
+            ("<a@x>", None),
+            ("<d@x>", Some("<other@x>")),
+            ("<e@x>", Some("<c@x>")),
+        ];
+        assert_eq!(count_replies(&links, "<a@x>"), 3);
+        assert_eq!(count_replies(&links, "<c@x>"), 1);
+        assert_eq!(count_replies(&links, "<e@x>"), 0);
+    }
+}
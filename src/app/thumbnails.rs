@@ -0,0 +1,293 @@
+//! Off-thread decoding for image attachments.
+//!
+//! Handing raw attachment bytes to the image widget decodes them at full
+//! resolution while drawing, which stalls the UI on large photos. Instead a
+//! blocking task shrinks each image to fit `THUMBNAIL_MAX`, keeps the result
+//! as a PNG in the user cache dir keyed by content hash, and returns raw RGBA
+//! the renderer can upload as-is. Full resolution is decoded only when an
+//! image is opened in the viewer.
+//!
+//! The hash is SHA-256, so file names stay valid across builds. The directory
+//! is held under `THUMBNAIL_CACHE_MAX`: after each new thumbnail the least
+//! recently used ones are removed.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use cosmic::app::Task;
+use cosmic::widget::image::Handle;
+use neverlight_mail_core::models::AttachmentData;
+use sha2::{Digest, Sha256};
+
+use super::{AppModel, Message};
+
+/// Longest edge of a preview thumbnail, in pixels.
+pub const THUMBNAIL_MAX: u32 = 480;
+
+/// Total size the thumbnail directory is trimmed back to.
+const THUMBNAIL_CACHE_MAX: u64 = 64 * 1024 * 1024;
+
+/// Which view a batch of thumbnails belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThumbnailTarget {
+    Preview { email_id: String },
+    Conversation { email_id: String },
+    Browser { epoch: u64 },
+}
+
+/// Size that fits `(width, height)` inside `max`×`max`, keeping the aspect
+/// ratio. Never upscales.
+fn thumbnail_size(width: u32, height: u32, max: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= max {
+        return (width, height);
+    }
+    let scale = |side: u32| ((u64::from(side) * u64::from(max)) / u64::from(longest)).max(1) as u32;
+    (scale(width), scale(height))
+}
+
+/// Hex SHA-256 of an attachment, stable across runs and Rust releases.
+pub(super) fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// File name for a thumbnail of `data`.
+fn cache_key(data: &[u8]) -> String {
+    format!("{}.png", content_hash(data))
+}
+
+fn thumbnail_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|d| d.join("neverlight-mail").join("thumbnails"))
+}
+
+pub(super) fn rgba_handle(img: image::DynamicImage) -> Handle {
+    let rgba = img.into_rgba8();
+    let (width, height) = rgba.dimensions();
+    Handle::from_rgba(width, height, rgba.into_raw())
+}
+
+fn save_thumbnail(img: &image::DynamicImage, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    img.save_with_format(path, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())
+}
+
+/// Files to delete, oldest first, to bring `files` ((path, last used, size))
+/// down to `cap` bytes.
+fn eviction_order(mut files: Vec<(PathBuf, SystemTime, u64)>, cap: u64) -> Vec<PathBuf> {
+    let mut total: u64 = files.iter().map(|(_, _, len)| len).sum();
+    files.sort_by_key(|(_, used, _)| *used);
+    let mut evicted = Vec::new();
+    for (path, _, len) in files {
+        if total <= cap {
+            break;
+        }
+        total = total.saturating_sub(len);
+        evicted.push(path);
+    }
+    evicted
+}
+
+/// Trim the thumbnail directory back to `THUMBNAIL_CACHE_MAX`.
+fn prune_thumbnails(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let files = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((entry.path(), meta.modified().ok()?, meta.len()))
+        })
+        .collect();
+    for path in eviction_order(files, THUMBNAIL_CACHE_MAX) {
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to remove thumbnail {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Mark a cached thumbnail as just used, for eviction.
+fn touch(path: &Path) {
+    let touched = std::fs::File::options()
+        .write(true)
+        .open(path)
+        .and_then(|f| f.set_modified(SystemTime::now()));
+    if let Err(e) = touched {
+        log::debug!("Failed to touch thumbnail {}: {}", path.display(), e);
+    }
+}
+
+/// Thumbnail for `data`, from the disk cache when present. Blocking.
+pub(super) fn load_or_make_thumbnail(data: &[u8]) -> Result<Handle, String> {
+    let path = thumbnail_dir().map(|d| d.join(cache_key(data)));
+    if let Some((path, cached)) = path.as_ref().and_then(|p| {
+        let bytes = std::fs::read(p).ok()?;
+        Some((p, image::load_from_memory(&bytes).ok()?))
+    }) {
+        touch(path);
+        return Ok(rgba_handle(cached));
+    }
+    let full = image::load_from_memory(data).map_err(|e| e.to_string())?;
+    let (width, height) = thumbnail_size(full.width(), full.height(), THUMBNAIL_MAX);
+    let thumb = if (width, height) == (full.width(), full.height()) {
+        full
+    } else {
+        full.thumbnail_exact(width, height)
+    };
+    if let Some(path) = path {
+        // A failed write only costs a re-decode next time.
+        if let Err(e) = save_thumbnail(&thumb, &path) {
+            log::warn!("Failed to cache thumbnail {}: {}", path.display(), e);
+        } else if let Some(dir) = path.parent() {
+            prune_thumbnails(dir);
+        }
+    }
+    Ok(rgba_handle(thumb))
+}
+
+/// `(index, bytes)` of every image attachment.
+fn image_attachments<'a>(
+    attachments: impl IntoIterator<Item = &'a AttachmentData>,
+) -> Vec<(usize, Vec<u8>)> {
+    attachments
+        .into_iter()
+        .enumerate()
+        .filter(|(_, a)| a.is_image())
+        .map(|(i, a)| (i, a.data.clone()))
+        .collect()
+}
+
+/// Decode thumbnails for `attachments` in the background.
+pub(super) fn load_thumbnails<'a>(
+    target: ThumbnailTarget,
+    attachments: impl IntoIterator<Item = &'a AttachmentData>,
+) -> Task<Message> {
+    let images = image_attachments(attachments);
+    if images.is_empty() {
+        return Task::none();
+    }
+    cosmic::task::future(async move {
+        let decoded = tokio::task::spawn_blocking(move || {
+            images
+                .into_iter()
+                .filter_map(|(index, data)| match load_or_make_thumbnail(&data) {
+                    Ok(handle) => Some((index, handle)),
+                    Err(e) => {
+                        log::warn!("Thumbnail decode failed for attachment {}: {}", index, e);
+                        None
+                    }
+                })
+                .collect()
+        })
+        .await;
+        match decoded {
+            Ok(thumbnails) => Message::ThumbnailsReady { target, thumbnails },
+            Err(e) => {
+                log::error!("Thumbnail task failed: {}", e);
+                Message::Noop
+            }
+        }
+    })
+}
+
+/// Place `handles` at their attachment indices in a view's parallel list.
+fn fill_handles(slots: &mut Vec<Option<Handle>>, len: usize, handles: Vec<(usize, Handle)>) {
+    slots.resize(len, None);
+    for (index, handle) in handles {
+        if let Some(slot) = slots.get_mut(index) {
+            *slot = Some(handle);
+        }
+    }
+}
+
+impl AppModel {
+    pub(super) fn apply_thumbnails(
+        &mut self,
+        target: ThumbnailTarget,
+        thumbnails: Vec<(usize, Handle)>,
+    ) {
+        match target {
+            ThumbnailTarget::Preview { email_id } => {
+                let current = self
+                    .selected_message
+                    .and_then(|i| self.messages.get(i))
+                    .map(|m| m.email_id.as_str());
+                if current != Some(email_id.as_str()) {
+                    self.stale_apply_drop_count = self.stale_apply_drop_count.saturating_add(1);
+                    return;
+                }
+                let len = self.preview_attachments.len();
+                fill_handles(&mut self.preview_image_handles, len, thumbnails);
+            }
+            ThumbnailTarget::Conversation { email_id } => {
+                if let Some(entry) = self.conversation.iter_mut().find(|e| e.email_id == email_id) {
+                    let len = entry.attachments.len();
+                    fill_handles(&mut entry.image_handles, len, thumbnails);
+                }
+            }
+            ThumbnailTarget::Browser { epoch } => {
+                if let Some(browser) = self
+                    .attachment_browser
+                    .as_mut()
+                    .filter(|b| b.epoch == epoch)
+                {
+                    let len = browser.entries().len();
+                    fill_handles(&mut browser.thumbnails, len, thumbnails);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_images_keep_their_size() {
+        assert_eq!(thumbnail_size(320, 200, THUMBNAIL_MAX), (320, 200));
+        assert_eq!(thumbnail_size(480, 480, THUMBNAIL_MAX), (480, 480));
+    }
+
+    #[test]
+    fn large_images_fit_the_longest_edge() {
+        assert_eq!(thumbnail_size(4000, 3000, 480), (480, 360));
+        assert_eq!(thumbnail_size(1000, 4000, 480), (120, 480));
+    }
+
+    #[test]
+    fn extreme_aspect_ratios_keep_one_pixel() {
+        assert_eq!(thumbnail_size(10_000, 2, 480), (480, 1));
+    }
+
+    #[test]
+    fn cache_key_depends_on_content() {
+        assert_eq!(cache_key(b"abc"), cache_key(b"abc"));
+        assert_ne!(cache_key(b"abc"), cache_key(b"abd"));
+        assert_eq!(
+            cache_key(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad.png"
+        );
+    }
+
+    #[test]
+    fn eviction_drops_least_recently_used_until_under_cap() {
+        let at = |secs| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        let files = vec![
+            (PathBuf::from("new.png"), at(30), 40),
+            (PathBuf::from("old.png"), at(10), 40),
+            (PathBuf::from("mid.png"), at(20), 40),
+        ];
+        assert_eq!(
+            eviction_order(files.clone(), 50),
+            vec![PathBuf::from("old.png"), PathBuf::from("mid.png")]
+        );
+        assert!(eviction_order(files, 120).is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+//! In-app toast stack for outcomes that shouldn't be lost.
+//!
+//! `status_message` is a single line that the next progress update
+//! overwrites, so an error could flash past unread. Errors and warnings now
+//! also land on the toast stack (the cosmic toaster the move Undo toasts
+//! already use), each with a severity that sets how long it stays and an
+//! optional button such as Retry. The status line keeps reporting progress.
+//!
+//! Handlers queue toasts with `notify`; `update` pushes the queue once the
+//! message is handled, so the toaster's dismiss timers get their task even
+//! from handlers that don't return one. A toast identical to one already
+//! queued in the same update is dropped.
+
+use std::time::Duration;
+
+use cosmic::app::Task;
+use cosmic::widget;
+
+use super::{AppModel, Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// How long a toast stays up before it dismisses itself.
+    pub fn duration(self) -> Duration {
+        match self {
+            Self::Info => Duration::from_secs(4),
+            Self::Warning => Duration::from_secs(10),
+            Self::Error => Duration::from_secs(30),
+        }
+    }
+}
+
+/// Toast text: the severity spelled out, since toasts have no colour.
+pub fn toast_text(severity: Severity, text: &str) -> String {
+    match severity {
+        Severity::Info => text.to_string(),
+        Severity::Warning => format!("Warning: {text}"),
+        Severity::Error => format!("Error: {text}"),
+    }
+}
+
+/// A toast waiting for `flush_toasts`.
+#[derive(Debug, Clone)]
+pub struct QueuedToast {
+    pub severity: Severity,
+    pub text: String,
+    /// Button label and the message it sends.
+    pub action: Option<(String, Message)>,
+}
+
+impl AppModel {
+    /// Queue a toast; it shows once the current message is handled.
+    pub(super) fn notify(&mut self, severity: Severity, text: impl Into<String>) {
+        self.queue_toast(QueuedToast {
+            severity,
+            text: text.into(),
+            action: None,
+        });
+    }
+
+    /// Queue a toast with a button (Retry, Undo, …).
+    pub(super) fn notify_with_action(
+        &mut self,
+        severity: Severity,
+        text: impl Into<String>,
+        label: &str,
+        message: Message,
+    ) {
+        self.queue_toast(QueuedToast {
+            severity,
+            text: text.into(),
+            action: Some((label.to_string(), message)),
+        });
+    }
+
+    fn queue_toast(&mut self, toast: QueuedToast) {
+        let duplicate = self
+            .queued_toasts
+            .iter()
+            .any(|t| t.severity == toast.severity && t.text == toast.text);
+        if !duplicate {
+            self.queued_toasts.push(toast);
+        }
+    }
+
+    /// Put queued toasts on the stack and start their dismiss timers.
+    pub(super) fn flush_toasts(&mut self) -> Task<Message> {
+        let tasks: Vec<Task<Message>> = std::mem::take(&mut self.queued_toasts)
+            .into_iter()
+            .map(|queued| {
+                let text = toast_text(queued.severity, &queued.text);
+                let mut toast =
+                    widget::toaster::Toast::new(text).duration(queued.severity.duration());
+                if let Some((label, message)) = queued.action {
+                    toast = toast.action(label, move |_id| message.clone());
+                }
+                self.toasts.push(toast).map(cosmic::Action::App)
+            })
+            .collect();
+        cosmic::task::batch(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severity_is_spelled_out_and_errors_stay_longest() {
+        assert_eq!(toast_text(Severity::Info, "Settings imported"), "Settings imported");
+        assert_eq!(toast_text(Severity::Error, "Sync failed"), "Error: Sync failed");
+        assert!(Severity::Error.duration() > Severity::Warning.duration());
+        assert!(Severity::Warning.duration() > Severity::Info.duration());
+    }
+}
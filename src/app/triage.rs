@@ -0,0 +1,109 @@
+//! Triage mode: the message list alone, full width, worked from the
+//! keyboard.
+//!
+//! j/k move, e archives, # trashes, s stars, d defers (leaves the message
+//! where it is and stops counting it), Escape or t leaves. The header counts
+//! what is left in the list and what this session has cleared. Archive and
+//! trash go through the normal move queue, so undo and the flag/move
+//! ordering rules still apply.
+
+use std::collections::HashSet;
+
+use cosmic::app::Task;
+
+use super::{AppModel, Message};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriageAction {
+    Archive,
+    Trash,
+    Star,
+    Defer,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TriageSession {
+    /// Email ids skipped for now; they stay in the list but not in "left".
+    pub deferred: HashSet<String>,
+    /// Archived, trashed or deferred since triage started.
+    pub done: usize,
+}
+
+/// Rows not yet dealt with: everything visible minus the deferred ones.
+fn left<'a>(visible: impl IntoIterator<Item = &'a str>, deferred: &HashSet<String>) -> usize {
+    visible
+        .into_iter()
+        .filter(|id| !deferred.contains(*id))
+        .count()
+}
+
+impl AppModel {
+    /// "14 left" for the triage header.
+    pub(super) fn triage_left(&self) -> usize {
+        let Some(session) = &self.triage else {
+            return 0;
+        };
+        let visible = self
+            .visible_indices
+            .iter()
+            .filter_map(|&i| self.messages.get(i))
+            .map(|m| m.email_id.as_str());
+        left(visible, &session.deferred)
+    }
+
+    pub(super) fn handle_triage(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ToggleTriage => {
+                if self.triage.take().is_some() {
+                    return Task::none();
+                }
+                if self.selected_folder.is_none() && self.smart_view.is_none() {
+                    self.status_message = "Open a folder to triage".into();
+                    return Task::none();
+                }
+                self.triage = Some(TriageSession::default());
+                if self.selected_message.is_none() && !self.visible_indices.is_empty() {
+                    return self.dispatch(Message::SelectionDown);
+                }
+            }
+            Message::Triage(action) => {
+                let Some(index) = self.selected_message else {
+                    return Task::none();
+                };
+                let Some(email_id) = self.messages.get(index).map(|m| m.email_id.clone()) else {
+                    return Task::none();
+                };
+                let Some(session) = self.triage.as_mut() else {
+                    return Task::none();
+                };
+                let next = match action {
+                    TriageAction::Star => return self.dispatch(Message::ToggleStar(index)),
+                    TriageAction::Archive => Message::Archive(index),
+                    TriageAction::Trash => Message::Trash(index),
+                    TriageAction::Defer => {
+                        if !session.deferred.insert(email_id) {
+                            return self.dispatch(Message::SelectionDown);
+                        }
+                        Message::SelectionDown
+                    }
+                };
+                session.done += 1;
+                return self.dispatch(next);
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deferred_rows_are_not_left() {
+        let deferred: HashSet<String> = ["M2".to_string()].into();
+        assert_eq!(left(["M1", "M2", "M3"], &deferred), 2);
+        assert_eq!(left([], &deferred), 0);
+    }
+}
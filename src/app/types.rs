@@ -1,9 +1,12 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Instant;
 
 use cosmic::app::Core;
+use cosmic::widget::menu::key_bind::KeyBind;
+use cosmic::widget::toaster::{ToastId, Toasts};
 use cosmic::widget::{image, markdown, pane_grid, text_editor};
 use futures::future::AbortHandle;
 
@@ -13,19 +16,64 @@ use neverlight_mail_core::models::{AttachmentData, Folder, MessageSummary};
 use neverlight_mail_core::setup::SetupModel;
 use neverlight_mail_core::store::CacheHandle;
 
-use crate::dnd_models::DraggedFiles;
+use crate::dnd_models::{ClipboardPaste, DraggedFiles};
 use crate::ui::compose_dialog::ComposeMode;
 
+use super::attachment_scan::{AttachmentScans, ScannedFile};
+use super::attachments::{
+    AttachmentAction, AttachmentBrowser, AttachmentScan, AttachmentScope, BrowserRead,
+    RiskyAttachment,
+};
+use super::autocomplete::RecipientIndex;
+use super::charset::ReceivedBody;
+use super::cleanup::{CleanupAction, CleanupMatch, CleanupWizard};
+use super::compare::{CompareSide, Comparison};
+use super::detected_event::DetectedEvent;
+use super::drafts::{ComposeSnapshot, LocalDraft};
+use super::external_editor::ExternalEdit;
+use super::flag_conflicts::FlagConflict;
+use super::folder_activity::FolderActivity;
+use super::folder_usage::FolderUsage;
+use super::freshness::FolderSyncTimes;
+use super::metrics::HealthMetrics;
+use super::paged_markdown::PagedMarkdown;
+use super::selection::MessageSelection;
+use super::recipients::ReplyChoice;
+use super::settings::{AppSettings, ReplyTarget};
+use super::row_labels::RowLabels;
+use super::layout::LayoutCycle;
+use super::quote_tools::QuoteTool;
+use super::row_menu::{PendingRowCompose, RowAction};
+use super::search::SearchHits;
+use super::thread_trash::TrashConfirm;
+use super::settings::FolderNotify;
+use super::signatures::{SignatureChoices, SignatureEditor};
+use super::shortcuts::MenuAction;
+use super::sync_state::FolderSyncState;
+use super::image_viewer::ImageViewer;
+use super::quick_look::QuickLook;
+use super::thread_collapse::CollapsedThreads;
+use super::thread_overrides::ThreadOverrides;
+use super::saved_passwords::SavedPassword;
+use super::thumbnails::ThumbnailTarget;
+use super::toasts::QueuedToast;
+use super::triage::{TriageAction, TriageSession};
+use super::visual_mode::{VisualAction, VisualRange};
+
 #[derive(Debug, Clone)]
 pub struct ConversationEntry {
     pub email_id: String,
     pub summary: MessageSummary,
-    pub markdown_items: Vec<markdown::Item>,
+    pub markdown_items: PagedMarkdown,
     pub plain_body: String,
     pub attachments: Vec<AttachmentData>,
     pub image_handles: Vec<Option<image::Handle>>,
+    /// Parallel to `attachments`, for scan verdicts; empty without a scanner.
+    pub attachment_hashes: Vec<String>,
     pub is_sent: bool,
     pub loaded: bool,
+    /// Body shown; folded cards show only their header line.
+    pub expanded: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -42,6 +90,7 @@ pub enum ConnectionState {
     Disconnected,
     Connecting,
     Connected,
+    /// Connected, with the folder list (Mailbox/get) sync in flight.
     Syncing,
     Error(String),
 }
@@ -61,6 +110,19 @@ pub enum ComposePhase {
     Closed,
     Open,
     Sending,
+    /// Close was requested with unsaved changes; asking Discard / Save / Keep.
+    ConfirmClose(CloseIntent),
+    /// Send to this many recipients is over the configured limit; asking first.
+    ConfirmSend(usize),
+}
+
+/// What triggered a guarded compose close.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseIntent {
+    /// Cancel button or Escape — only the dialog closes.
+    Cancel,
+    /// Window close — the app exits once compose is resolved.
+    Quit,
 }
 
 impl ComposePhase {
@@ -174,6 +236,17 @@ pub struct MessageIdentity {
     pub email_id: String,
 }
 
+impl MessageIdentity {
+    /// Identity of a listed message, in the folder it's being shown from.
+    pub fn of(msg: &MessageSummary) -> Self {
+        Self {
+            account_id: msg.account_id.clone(),
+            mailbox_id: msg.context_mailbox_id.clone(),
+            email_id: msg.email_id.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PendingFlagIntent {
     pub message: MessageIdentity,
@@ -187,6 +260,17 @@ pub struct PendingMoveIntent {
     pub dest: MailboxIdentity,
 }
 
+/// A completed move that its toast can still reverse.
+#[derive(Debug, Clone)]
+pub struct UndoableMove {
+    /// Identity in the source mailbox (before the move).
+    pub message: MessageIdentity,
+    pub summary: MessageSummary,
+    /// List position to restore to.
+    pub index: usize,
+    pub dest: MailboxIdentity,
+}
+
 // ---------------------------------------------------------------------------
 // Per-account state
 // ---------------------------------------------------------------------------
@@ -209,6 +293,16 @@ pub struct AccountState {
     pub backfill_active: bool,
     /// Pause flag: set during head sync to avoid contention.
     pub backfill_pause: Arc<AtomicBool>,
+    /// Whether the body prefetch subscription is running for this account.
+    pub prefetch_active: bool,
+    /// Prefetch progress: (messages checked, messages to check).
+    pub prefetch_progress: Option<(usize, usize)>,
+    /// EventSource push is running (cleared on push error/end until reconnect).
+    pub push_healthy: bool,
+    /// Sync suspended by the user or the account's pause schedule.
+    pub paused: bool,
+    /// Per-mailbox sync status; idle folders have no entry.
+    pub folder_sync: HashMap<String, FolderSyncState>,
 }
 
 impl AccountState {
@@ -225,6 +319,11 @@ impl AccountState {
             backfill_progress: HashMap::new(),
             backfill_active: false,
             backfill_pause: Arc::new(AtomicBool::new(false)),
+            prefetch_active: false,
+            prefetch_progress: None,
+            push_healthy: false,
+            paused: false,
+            folder_sync: HashMap::new(),
         }
     }
 
@@ -267,29 +366,78 @@ pub struct AppModel {
 
     pub(super) messages: Vec<MessageSummary>,
     pub(super) selected_message: Option<usize>,
+    /// Checked messages (survives pagination and sync reloads).
+    pub(super) message_selection: MessageSelection,
     pub(super) messages_offset: u32,
     pub(super) has_more_messages: bool,
 
     pub(super) preview_body: String,
-    pub(super) preview_markdown: Vec<markdown::Item>,
+    pub(super) preview_markdown: PagedMarkdown,
+    /// Previewed body before any "View with encoding" override.
+    pub(super) preview_received: Option<ReceivedBody>,
+    /// Index into `ENCODING_LABELS`; 0 is as received.
+    pub(super) preview_encoding: usize,
     pub(super) preview_attachments: Vec<AttachmentData>,
     pub(super) preview_image_handles: Vec<Option<image::Handle>>,
+    /// Parallel to `preview_attachments`; empty without a scanner.
+    pub(super) preview_attachment_hashes: Vec<String>,
+    /// Attachments browser shown in place of the preview pane.
+    pub(super) attachment_browser: Option<AttachmentBrowser>,
+    /// Save/open held for the dangerous-attachment prompt.
+    pub(super) risky_attachment: Option<RiskyAttachment>,
+    /// Virus scan verdicts this session, by content hash.
+    pub(super) attachment_scans: AttachmentScans,
+    /// Timings recorded from task futures for the diagnostics panel.
+    pub(super) metrics: Arc<HealthMetrics>,
+    /// Day/time spotted in the previewed message body.
+    pub(super) detected_event: Option<DetectedEvent>,
+    pub(super) attachment_epoch: u64,
+    /// Sender cleanup wizard dialog, when open.
+    pub(super) cleanup: Option<CleanupWizard>,
+    pub(super) cleanup_epoch: u64,
+    /// Full-window image viewer, when open.
+    pub(super) image_viewer: Option<ImageViewer>,
+    pub(super) viewer_epoch: u64,
 
     /// Conversation view: all messages in the selected thread, bodies loading progressively.
     pub(super) conversation: Vec<ConversationEntry>,
     /// The email_id of the "active" message within the conversation (reply/forward target).
     pub(super) active_conversation_id: Option<String>,
+    /// Sender (address key) the conversation is narrowed to.
+    pub(super) conversation_sender_filter: Option<String>,
 
     /// Thread IDs that are currently collapsed (children hidden)
     pub(super) collapsed_threads: HashSet<String>,
+    /// Collapsed threads per folder, restored when a folder is selected.
+    pub(super) saved_collapsed_threads: CollapsedThreads,
+    /// Account on screen last, saved for the next launch's connect order.
+    pub(super) last_account: Option<AccountId>,
+    /// Sidebar account groups folded to their header.
+    pub(super) collapsed_groups: HashSet<String>,
     /// Maps visible row positions → real indices into `messages`
     pub(super) visible_indices: Vec<usize>,
     /// Total messages per thread_id (for collapse indicators)
     pub(super) thread_sizes: HashMap<String, usize>,
+    /// Precomputed sender/date strings per email id for list rows.
+    pub(super) row_labels: HashMap<String, RowLabels>,
+    /// Attachments per email id, known once a body has been loaded.
+    pub(super) attachment_counts: HashMap<String, usize>,
+    /// One-line body previews per email id, for opened or prefetched bodies.
+    pub(super) snippets: HashMap<String, String>,
     /// Snapshot of optimistically removed messages for move rollback.
     pub(super) pending_move_restore: HashMap<MessageIdentity, (MessageSummary, usize)>,
+    /// Recent completed moves offered for undo, oldest first (capped).
+    pub(super) undoable_moves: VecDeque<UndoableMove>,
+    /// Trash waiting on the "others replied to this" warning.
+    pub(super) trash_confirm: Option<TrashConfirm>,
     /// Latest flag operation epoch per envelope (stale completions are dropped).
     pub(super) pending_flag_epochs: HashMap<MessageIdentity, u64>,
+    /// Flags before and after each in-flight flag op, checked against reloads.
+    pub(super) pending_local_flags: HashMap<MessageIdentity, (u8, u8)>,
+    /// Server flags that won a conflict; restored once our op lands.
+    pub(super) server_won_flags: HashMap<MessageIdentity, u8>,
+    /// Recent flag conflicts for the diagnostics panel.
+    pub(super) flag_conflicts: VecDeque<FlagConflict>,
     /// Latest move operation epoch per envelope (stale completions are dropped).
     pub(super) pending_move_epochs: HashMap<MessageIdentity, u64>,
     /// Abort handles for true in-flight cancellation of superseded lane operations.
@@ -304,7 +452,10 @@ pub struct AppModel {
     /// Monotonic epochs by lane.
     pub(super) folder_epoch: u64,
     pub(super) message_epoch: u64,
+    /// Lane epoch for push-driven head-page merges.
+    pub(super) push_epoch: u64,
     pub(super) search_epoch: u64,
+    pub(super) smart_view_epoch: u64,
     pub(super) refresh_epoch: u64,
     pub(super) mutation_epoch: u64,
     pub(super) flag_epoch: u64,
@@ -319,10 +470,15 @@ pub struct AppModel {
     pub(super) refresh_pending_after_sync: bool,
     pub(super) mutation_in_flight_accounts: HashSet<AccountId>,
     pub(super) flag_in_flight_accounts: HashSet<AccountId>,
-    pub(super) pending_move_intents: HashMap<AccountId, PendingMoveIntent>,
-    pub(super) pending_flag_intents: HashMap<AccountId, PendingFlagIntent>,
+    pub(super) pending_move_intents: HashMap<AccountId, VecDeque<PendingMoveIntent>>,
+    pub(super) pending_flag_intents: HashMap<AccountId, VecDeque<PendingFlagIntent>>,
     /// Recently notified messages (dedup push events).
     pub(super) notified_messages: HashSet<MessageIdentity>,
+    /// New mail waiting to be announced, per (account, mailbox).
+    pub(super) pending_notifications:
+        HashMap<(AccountId, String), super::notifications::NewMailBatch>,
+    /// Accounts resumed by hand inside their scheduled pause window.
+    pub(super) pause_overrides: HashSet<AccountId>,
     /// Diagnostics counters.
     pub(super) stale_apply_drop_count: u64,
     pub(super) toc_drift_count: u64,
@@ -332,11 +488,32 @@ pub struct AppModel {
     pub(super) reconnect_count: u64,
     /// Timing for diagnostics.
     pub(super) last_sync_at: Option<Instant>,
+    /// Persisted per-folder last-sync times (freshness indicators).
+    pub(super) folder_sync_times: FolderSyncTimes,
+    /// Sync times recorded since the last write.
+    pub(super) folder_sync_times_dirty: bool,
+    /// Persisted folder open counts, for the launch warm-up.
+    pub(super) folder_usage: FolderUsage,
+    /// Accounts whose most used folders were warmed up this launch.
+    pub(super) warmed_up_accounts: HashSet<AccountId>,
+    /// Persisted manual merges/splits applied over server thread ids.
+    pub(super) thread_overrides: ThreadOverrides,
+    /// account_id → mailbox_id → cache-derived activity for folder tooltips.
+    pub(super) folder_activity: HashMap<AccountId, HashMap<String, FolderActivity>>,
     pub(super) last_refresh_at: Option<Instant>,
 
     // Search state
     pub(super) search_phase: SearchPhase,
     pub(super) search_query: String,
+    /// Terms of the search on screen, AND-ed; shown as removable chips.
+    pub(super) search_terms: Vec<String>,
+
+    /// Cross-folder view shown instead of a folder (selected_folder is None).
+    pub(super) smart_view: Option<super::SmartView>,
+    /// Folders showing unread messages only (this session).
+    pub(super) unread_only_folders: HashSet<MailboxIdentity>,
+    /// Plus-address tag the message list is narrowed to.
+    pub(super) alias_filter: Option<String>,
 
     // Compose dialog state
     pub(super) compose_phase: ComposePhase,
@@ -349,15 +526,68 @@ pub struct AppModel {
     pub(super) compose_in_reply_to: Option<String>,
     pub(super) compose_references: Option<String>,
     pub(super) compose_attachments: Vec<AttachmentData>,
+    /// Message a reply answers; Send & Archive archives it.
+    pub(super) compose_reply_source: Option<MessageIdentity>,
+    /// Reply-To or sender, for a reply to a message that has both.
+    pub(super) compose_reply_choice: Option<ReplyChoice>,
+    /// Set by Send & Archive until the send completes.
+    pub(super) compose_archive_after_send: Option<MessageIdentity>,
+    /// Server draft being edited; moved to Trash once the message is sent.
+    pub(super) compose_server_draft: Option<String>,
     pub(super) compose_error: Option<String>,
     pub(super) compose_drag_hover: bool,
+    /// Body open in the external editor, if any.
+    pub(super) compose_external_edit: Option<ExternalEdit>,
+    /// Reply/forward picked from a row's menu, waiting for its body.
+    pub(super) pending_row_compose: Option<PendingRowCompose>,
+    /// Compose fields as they were when the dialog opened (dirty tracking).
+    pub(super) compose_baseline: ComposeSnapshot,
+    /// Locally saved drafts, newest last.
+    pub(super) local_drafts: Vec<LocalDraft>,
     // Cached for dialog() lifetime (updated when compose_account changes)
     pub(super) compose_account_labels: Vec<String>,
     pub(super) compose_cached_from: Vec<String>,
+    /// To recipients the server refused on the last send attempt.
+    pub(super) compose_rejected: Vec<String>,
+    /// Signatures on offer in compose and the one in the body.
+    pub(super) compose_signatures: SignatureChoices,
+    /// To addresses outside the compose account's internal domains.
+    pub(super) compose_external_recipients: Vec<String>,
+    /// Sent-history matches for the address being typed in To.
+    pub(super) compose_suggestions: Vec<String>,
+    /// Per-account recipients from the Sent folder, for To autocomplete.
+    pub(super) recipient_index: HashMap<AccountId, RecipientIndex>,
+    /// (To field, day) of messages sent this session that the cached Sent
+    /// folder may not have yet; re-counted whenever the index is rebuilt.
+    pub(super) unsynced_sends: HashMap<AccountId, Vec<(String, chrono::NaiveDate)>>,
+
+    /// Transient notifications (undo after move, etc.).
+    pub(super) toasts: Toasts<Message>,
+    /// Toasts raised while handling the current message; `update` shows them.
+    pub(super) queued_toasts: Vec<QueuedToast>,
+
+    /// Shortcut table rendered as hints in the header bar menus.
+    pub(super) key_binds: HashMap<KeyBind, MenuAction>,
+    /// GUI-local preferences (settings.json).
+    pub(super) settings: AppSettings,
 
     // Setup dialog state — core fields live in SetupModel, visibility is local
     pub(super) setup_model: Option<SetupModel>,
     pub(super) setup_password_visible: bool,
+    /// Index into `SYNC_INTERVAL_CHOICES` for the account being set up.
+    pub(super) setup_sync_interval: usize,
+    /// Comma-separated internal domains for the account being set up.
+    pub(super) setup_internal_domains: String,
+    /// Sidebar group for the account being set up; blank for none.
+    pub(super) setup_account_group: String,
+    /// Index into `ACCOUNT_COLOR_LABELS` for the account being set up.
+    pub(super) setup_account_color: usize,
+    /// Signatures per address of the account being set up.
+    pub(super) setup_signatures: SignatureEditor,
+    /// Keyring items offered by "Find saved password".
+    pub(super) setup_saved_passwords: Vec<SavedPassword>,
+    /// Outcome of the last keyring search, under the Token field.
+    pub(super) setup_saved_passwords_note: Option<String>,
     pub(super) confirm_delete_account_id: Option<AccountId>,
     pub(super) oauth_phase: OAuthSetupPhase,
     pub(super) oauth_error: Option<String>,
@@ -375,7 +605,21 @@ pub struct AppModel {
 
     // Pane layout
     pub(super) panes: pane_grid::State<PaneKind>,
+    /// Presets shown by "Next layout" since the last manual resize.
+    pub(super) layout_cycle: Option<LayoutCycle>,
     pub(super) diagnostics_collapsed: bool,
+    /// Keyboard shortcut cheat sheet overlay.
+    pub(super) show_shortcuts: bool,
+    /// Full-width keyboard triage; `None` shows the normal panes.
+    pub(super) triage: Option<TriageSession>,
+    /// Range being marked with Shift+V in the message list.
+    pub(super) visual: Option<VisualRange>,
+    /// Two messages side by side in place of the panes.
+    pub(super) comparison: Option<Comparison>,
+    /// Quick look popup over the selected row.
+    pub(super) quick_look: Option<QuickLook>,
+    /// Main window geometry, saved on quit.
+    pub(super) window_state: super::window_state::WindowState,
 }
 
 #[derive(Debug, Clone)]
@@ -401,7 +645,10 @@ pub enum Message {
     CopyBody,
 
     SaveAttachment(usize),
-    SaveAttachmentComplete(Result<String, String>),
+    SaveAttachmentComplete(Result<PathBuf, String>),
+    /// Every attachment of a message (or the browser) into a picked directory.
+    SaveAllAttachments(ThumbnailTarget),
+    SaveAllAttachmentsComplete(Result<(PathBuf, usize), String>),
 
     ThreadLoaded {
         thread_id: String,
@@ -416,6 +663,18 @@ pub enum Message {
         result: Result<(String, String, Vec<AttachmentData>), String>,
     },
     SetActiveConversation(String),
+    /// Show only this sender's messages in the thread; `None` shows everyone.
+    FilterConversationSender(Option<String>),
+    /// Show the next page of a long body (preview or conversation card).
+    ContinueReading(String),
+    /// Re-read the previewed body in an `ENCODING_LABELS` charset.
+    ViewWithEncoding(usize),
+    /// Thread map click: activate the entry and scroll the conversation to it.
+    JumpToConversationEntry(String),
+    /// Fold or unfold a conversation card.
+    ToggleConversationEntry(String),
+    /// Reply or forward from a conversation card's own buttons.
+    ConversationCompose { email_id: String, action: RowAction },
     SaveConversationAttachment { email_id: String, index: usize },
 
     // Cache-first messages
@@ -441,6 +700,11 @@ pub enum Message {
         epoch: u64,
         result: Result<(), String>,
     },
+    /// Background warm-up synced these folders into the cache.
+    FoldersWarmedUp {
+        account_id: AccountId,
+        mailbox_ids: Vec<String>,
+    },
     LoadMoreMessages,
 
     // Flag/move actions
@@ -450,6 +714,34 @@ pub enum Message {
     Trash(usize),
     Archive(usize),
     RunFlagIntent(PendingFlagIntent),
+    ToggleMessageSelected(usize),
+    ClearMessageSelection,
+    /// Bulk actions over `message_selection`.
+    BulkArchive,
+    BulkTrash,
+    BulkMarkRead,
+    /// Reply warning answers: trash just the message, the whole thread, or
+    /// nothing.
+    TrashConfirmMessage,
+    TrashConfirmThread,
+    TrashConfirmCancel,
+    /// Entry picked from a message row's right-click menu.
+    RowMenu {
+        index: usize,
+        action: RowAction,
+    },
+    /// Set every loaded message in the thread rooted at this row to read/unread.
+    MarkThreadRead {
+        index: usize,
+        read: bool,
+    },
+    /// Mark every unread message in the folder read.
+    MarkFolderRead(MailboxIdentity),
+    MarkFolderReadComplete {
+        folder: MailboxIdentity,
+        /// Messages marked, or what failed.
+        result: Result<usize, String>,
+    },
     RunMoveIntent(PendingMoveIntent),
     FlagOpComplete {
         message: MessageIdentity,
@@ -460,9 +752,94 @@ pub enum Message {
     MoveOpComplete {
         message: MessageIdentity,
         source: MailboxIdentity,
+        dest: MailboxIdentity,
         epoch: u64,
         result: Result<(), String>,
     },
+    /// Toast "Undo" pressed for a completed move (identity in its source mailbox).
+    UndoMove(MessageIdentity),
+    UndoMoveComplete {
+        undo: UndoableMove,
+        result: Result<(), String>,
+    },
+    CloseToast(ToastId),
+    // Attachments browser
+    ShowAttachments(AttachmentScope),
+    CloseAttachments,
+    AttachmentsLoaded {
+        epoch: u64,
+        result: Result<AttachmentScan, String>,
+    },
+    SaveBrowserAttachment(usize),
+    OpenBrowserAttachment(usize),
+    /// Browser entries read back from the cache for a save or open.
+    BrowserAttachmentsRead {
+        read: BrowserRead,
+        result: Result<Vec<AttachmentData>, String>,
+    },
+    OpenAttachmentReady(Result<String, String>),
+    RiskyAttachmentConfirm,
+    RiskyAttachmentCancel,
+    /// Virus scan of an action's files finished, in file order.
+    AttachmentsScanned {
+        action: AttachmentAction,
+        files: Vec<ScannedFile>,
+    },
+    // Sender cleanup wizard
+    CleanupOpen,
+    CleanupPatternChanged(String),
+    CleanupScan,
+    CleanupScanned {
+        epoch: u64,
+        result: Result<Vec<CleanupMatch>, String>,
+    },
+    CleanupRun(CleanupAction),
+    CleanupStepDone {
+        epoch: u64,
+        account_id: AccountId,
+        /// Outcome per email id in the batch.
+        results: Vec<(String, Result<(), String>)>,
+    },
+    CleanupClose,
+    // Image attachments
+    ThumbnailsReady {
+        target: ThumbnailTarget,
+        thumbnails: Vec<(usize, image::Handle)>,
+    },
+    ViewImage {
+        target: ThumbnailTarget,
+        index: usize,
+    },
+    ViewerImageLoaded {
+        epoch: u64,
+        result: Result<image::Handle, String>,
+    },
+    ViewerNext,
+    ViewerPrevious,
+    ViewerRotate,
+    ViewerToggleSlideshow,
+    ViewerSave,
+    CloseImageViewer,
+    RecipientIndexLoaded {
+        account_id: AccountId,
+        index: RecipientIndex,
+    },
+    FolderActivityLoaded {
+        account_id: AccountId,
+        activity: Vec<(String, FolderActivity)>,
+    },
+    // Detected event banner
+    AddDetectedEventToCalendar,
+    CalendarFileReady(Result<String, String>),
+    DismissDetectedEvent,
+    ToggleEventDetection,
+    /// Developer: flip `protocol_log` (takes effect on next launch).
+    ToggleProtocolLog,
+    // Settings export/import
+    ExportSettings,
+    ImportSettings,
+    SettingsExported(Result<PathBuf, String>),
+    SettingsImported(Result<Box<AppSettings>, String>),
     // Keyboard navigation
     SelectionUp,
     SelectionDown,
@@ -472,41 +849,114 @@ pub enum Message {
     // Compose messages
     ComposeNew,
     ComposeReply,
+    ComposeReplyAll,
     ComposeForward,
     ComposeAccountChanged(usize),
     ComposeFromChanged(usize),
+    /// Index into the compose signature choices; 0 is none.
+    ComposeSignatureChanged(usize),
     ComposeToChanged(String),
+    ComposeRecipientPicked(String),
     ComposeSubjectChanged(String),
     ComposeBodyAction(text_editor::Action),
     ComposeAttach,
     ComposeAttachLoaded(Result<Vec<AttachmentData>, String>),
     ComposeRemoveAttachment(usize),
     ComposeFilesDropped(DraggedFiles),
+    /// Ctrl+V outside a text field: attach a pasted image or copied files.
+    ComposePaste,
+    ComposePasted(ClipboardPaste),
     ComposeFileTransfer(String),
     ComposeFileTransferResolved(Result<Vec<String>, String>),
     ComposeDragEnter,
     ComposeDragLeave,
     ComposeSend,
+    /// Send a reply, then archive the message it answers.
+    ComposeSendAndArchive,
+    /// Drop the recipients the server refused and send to the rest.
+    ComposeSendWithoutRejected,
     ComposeCancel,
+    ComposeDiscard,
+    ComposeSaveDraft,
+    ComposeKeepEditing,
+    ComposeResumeDraft,
+    /// Quoting toolbar above the body.
+    ComposeQuoteTool(QuoteTool),
+    /// Send a reply to the Reply-To address or to the sender.
+    ComposeReplyTarget(ReplyTarget),
+    /// Edit the body in the configured editor or `$VISUAL`/`$EDITOR`.
+    ComposeExternalEditor,
+    ComposeExternalEditorDone {
+        path: PathBuf,
+        result: Result<String, String>,
+    },
+    /// Open the selected message from the Drafts folder in compose.
+    ComposeEditServerDraft,
     SendComplete(Result<(), String>),
 
+    /// Main window close requested (routed through the unsaved-compose guard).
+    WindowCloseRequested,
+    WindowResized(cosmic::iced::Size),
+    /// Resize result once we know whether the window is maximized.
+    WindowSizeChecked {
+        size: cosmic::iced::Size,
+        maximized: bool,
+    },
+    WindowMoved(cosmic::iced::Point),
+
+    /// Header bar menu item or bound shortcut.
+    Menu(MenuAction),
+
     /// EventSource push: server state changed, trigger delta sync.
     PushStateChanged(AccountId),
     /// EventSource stream ended or errored — schedule reconnect.
     PushError(AccountId, String),
     PushEnded(AccountId),
+    /// Head page re-synced after a push state change (merged in place).
+    PushDeltaLoaded {
+        account_id: AccountId,
+        mailbox_id: String,
+        epoch: u64,
+        result: Result<Vec<MessageSummary>, String>,
+    },
+    PushFoldersLoaded {
+        account_id: AccountId,
+        result: Result<Vec<Folder>, String>,
+    },
+    /// Coalescing window closed; show the queued new-mail notifications.
+    FlushMailNotifications,
+    /// Set the open folder's new-mail rule.
+    SetFolderNotify(FolderNotify),
 
     // Search
     SearchActivate,
     SearchQueryChanged(String),
     SearchExecute,
+    /// AND the search box onto the current results' terms.
+    SearchRefine,
+    /// Drop one term chip and search again with the rest.
+    SearchRemoveTerm(usize),
     SearchResultsLoaded {
-        query: String,
+        terms: Vec<String>,
         epoch: u64,
-        result: Result<Vec<MessageSummary>, String>,
+        result: Result<SearchHits, String>,
     },
     SearchClear,
 
+    OpenSmartView(super::SmartView),
+    /// Filter the folder on screen to unread messages, or stop filtering.
+    ToggleUnreadOnly,
+    /// Show only mail sent to this plus alias (`None` shows everything).
+    FilterAlias(Option<String>),
+    /// Move the bulk-selected messages into the open message's thread.
+    MergeIntoThread,
+    /// Take this message out of its thread.
+    SplitFromThread(String),
+    SmartViewLoaded {
+        epoch: u64,
+        result: Result<Vec<MessageSummary>, String>,
+    },
+
     // Message-to-folder drag
     DragMessageToFolder {
         message: MessageIdentity,
@@ -517,7 +967,38 @@ pub enum Message {
     FolderDragLeave,
 
     PaneResized(pane_grid::ResizeEvent),
+    /// Show the next layout preset from settings.json.
+    CycleLayout,
     ToggleDiagnostics,
+    ToggleShortcuts,
+    ToggleTriage,
+    /// Triage key on the selected message.
+    Triage(TriageAction),
+    /// Visual-mode key in the message list.
+    Visual(VisualAction),
+
+    // Side-by-side comparison
+    CompareBodyLoaded {
+        email_id: String,
+        result: Result<String, String>,
+    },
+    /// A comparison column scrolled to a relative offset.
+    CompareScrolled {
+        side: CompareSide,
+        offset: f32,
+    },
+    ToggleCompareScroll,
+    CloseComparison,
+
+    // Quick look
+    /// A plain key pressed in the message list, or with quick look open.
+    QuickLookKey(String),
+    QuickLookLoaded {
+        email_id: String,
+        /// Plain body from the cache; `None` when not downloaded.
+        result: Result<Option<String>, String>,
+    },
+    CloseQuickLook,
 
     /// Auto-mark-read: fires 5s after a message is displayed
     AutoMarkRead(String),
@@ -536,8 +1017,22 @@ pub enum Message {
         mailbox_id: String,
     },
 
+    // Background body prefetch for offline reading
+    PrefetchProgress {
+        account_id: AccountId,
+        done: usize,
+        total: usize,
+        /// (email id, list preview) for the message just handled.
+        snippet: Option<(String, String)>,
+    },
+    PrefetchComplete(AccountId),
+
     ForceReconnect(AccountId),
+    /// A staggered startup connect came due.
+    DeferredConnect(AccountId),
     Refresh,
+    /// Per-account background sync timer fired.
+    PeriodicSync(AccountId),
     Noop,
 
     // Account management
@@ -547,6 +1042,17 @@ pub enum Message {
     ConfirmDeleteAccount,
     CancelDeleteAccount,
     ToggleAccountCollapse(usize),
+    /// Collapse or expand a sidebar account group by name.
+    ToggleGroupCollapse(String),
+    ToggleAccountPause(AccountId),
+    /// Minute tick re-checking pause schedules.
+    PauseScheduleTick,
+    /// Minute tick refreshing relative dates in the message list.
+    RowLabelsTick,
+    /// Second tick redrawing a "retrying in 8s" countdown.
+    SyncRetryTick,
+    /// Write folder sync times recorded since the last flush.
+    FlushFolderSyncTimes,
 
     // Setup dialog messages (JMAP-only: 5 fields)
     SetupLabelChanged(String),
@@ -555,6 +1061,17 @@ pub enum Message {
     SetupTokenChanged(String),
     SetupEmailAddressesChanged(String),
     SetupPasswordVisibilityToggled,
+    SetupSyncIntervalChanged(usize),
+    SetupInternalDomainsChanged(String),
+    SetupAccountGroupChanged(String),
+    SetupAccountColorChanged(usize),
+    SetupSignatureAddressChanged(usize),
+    SetupSignatureAction(text_editor::Action),
+    /// Search the Secret Service keyring for this server's password.
+    SetupFindSavedPassword,
+    SetupSavedPasswordsFound(Result<Vec<SavedPassword>, String>),
+    /// Fill the Token field from `setup_saved_passwords[index]`.
+    SetupUseSavedPassword(usize),
     SetupSubmit,
     SetupCancel,
 
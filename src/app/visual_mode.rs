@@ -0,0 +1,178 @@
+//! Visual mode in the message list, as in vim.
+//!
+//! Shift+V marks where a range starts; j/k (or the arrows) stretch it to the
+//! cursor, and the rows in between join the bulk selection. A collapsed
+//! thread in the range brings its hidden messages along. e archives the
+//! range, # trashes it and s stars it (or unstars it when all are starred),
+//! through the same queues as the bulk actions. Shift+V again keeps the
+//! selection for the row menu's other bulk actions (Move to, Mark as spam);
+//! Escape drops the range and leaves the selection as it was.
+
+use cosmic::app::Task;
+
+use super::selection::{page_for_index, MessageSelection};
+use super::{AppModel, Message, MessageIdentity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisualAction {
+    /// Start a range, or leave visual mode keeping the selection.
+    Toggle,
+    Archive,
+    Trash,
+    Star,
+    Cancel,
+}
+
+#[derive(Debug, Clone)]
+pub struct VisualRange {
+    /// Email id of the row the range started on.
+    anchor: String,
+    /// Selection before the range, restored by Escape.
+    base: MessageSelection,
+}
+
+/// Visible rows from `anchor` to `cursor`, either way round; empty when
+/// either isn't visible.
+fn span(visible: &[usize], anchor: usize, cursor: usize) -> &[usize] {
+    let position = |index| visible.iter().position(|&i| i == index);
+    let (Some(a), Some(c)) = (position(anchor), position(cursor)) else {
+        return &[];
+    };
+    &visible[a.min(c)..=a.max(c)]
+}
+
+impl AppModel {
+    pub(super) fn handle_visual(&mut self, action: VisualAction) -> Task<Message> {
+        if self.visual.is_none() {
+            if action == VisualAction::Toggle {
+                self.start_visual();
+            }
+            return Task::none();
+        }
+        match action {
+            VisualAction::Toggle => {
+                self.visual = None;
+                self.status_message = self.message_selection.summary_label();
+                Task::none()
+            }
+            VisualAction::Cancel => {
+                if let Some(visual) = self.visual.take() {
+                    self.message_selection = visual.base;
+                }
+                self.status_message = "Left visual mode".into();
+                Task::none()
+            }
+            VisualAction::Archive => {
+                self.visual = None;
+                self.dispatch(Message::BulkArchive)
+            }
+            VisualAction::Trash => {
+                self.visual = None;
+                self.dispatch(Message::BulkTrash)
+            }
+            VisualAction::Star => {
+                self.visual = None;
+                self.star_selection()
+            }
+        }
+    }
+
+    fn start_visual(&mut self) {
+        let cursor = self
+            .selected_message
+            .filter(|i| self.visible_indices.contains(i))
+            .or_else(|| self.visible_indices.first().copied());
+        let Some(msg) = cursor.and_then(|i| self.messages.get(i)) else {
+            return;
+        };
+        self.visual = Some(VisualRange {
+            anchor: msg.email_id.clone(),
+            base: self.message_selection.clone(),
+        });
+        self.selected_message = cursor;
+        self.extend_visual();
+        self.status_message =
+            "Visual: j/k extend, e archive, # trash, s star, Shift+V keep, Esc cancel".into();
+    }
+
+    /// Select the range between the anchor and the cursor, on top of the
+    /// selection visual mode started from. Ends visual mode when the anchor
+    /// row has gone (moved away, or the folder changed).
+    pub(super) fn extend_visual(&mut self) {
+        let Some(visual) = &self.visual else {
+            return;
+        };
+        let Some(anchor) = self.messages.iter().position(|m| m.email_id == visual.anchor) else {
+            self.visual = None;
+            return;
+        };
+        let Some(cursor) = self.selected_message else {
+            return;
+        };
+        let mut selection = visual.base.clone();
+        for &row in span(&self.visible_indices, anchor, cursor) {
+            for index in self.collapsed_thread_rows(row) {
+                let msg = &self.messages[index];
+                let identity = MessageIdentity::of(msg);
+                if !selection.contains(&identity) {
+                    let page_size = self.page_size_for(&identity.account_id, &identity.mailbox_id);
+                    selection.toggle(identity, page_for_index(index, page_size));
+                }
+            }
+        }
+        self.message_selection = selection;
+    }
+
+    /// `row`, plus the hidden messages of its thread when it's the root of
+    /// a collapsed one.
+    fn collapsed_thread_rows(&self, row: usize) -> Vec<usize> {
+        let Some(msg) = self.messages.get(row) else {
+            return Vec::new();
+        };
+        match msg.thread_id.as_ref() {
+            Some(tid) if msg.thread_depth == 0 && self.collapsed_threads.contains(tid) => self
+                .messages
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.thread_id.as_ref() == Some(tid))
+                .map(|(i, _)| i)
+                .collect(),
+            _ => vec![row],
+        }
+    }
+
+    /// Star every selected message, or unstar them all when they already
+    /// are; then clear the selection like the other bulk actions.
+    fn star_selection(&mut self) -> Task<Message> {
+        let rows: Vec<(usize, bool)> = self
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| self.message_selection.contains(&MessageIdentity::of(m)))
+            .map(|(i, m)| (i, m.is_starred))
+            .collect();
+        let star = rows.iter().any(|&(_, starred)| !starred);
+        self.message_selection.clear();
+        let tasks = rows
+            .into_iter()
+            .filter(|&(_, starred)| starred != star)
+            .map(|(index, _)| self.dispatch(Message::ToggleStar(index)))
+            .collect::<Vec<_>>();
+        cosmic::task::batch(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span_runs_either_way_over_visible_rows() {
+        // Rows 2 and 3 are hidden replies in a collapsed thread.
+        let visible = [0, 1, 4, 5, 6];
+        assert_eq!(span(&visible, 1, 5), [1, 4, 5]);
+        assert_eq!(span(&visible, 5, 1), [1, 4, 5]);
+        assert_eq!(span(&visible, 4, 4), [4]);
+        assert!(span(&visible, 2, 5).is_empty());
+    }
+}
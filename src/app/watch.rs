@@ -1,12 +1,65 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
 use cosmic::app::Task;
 use futures::SinkExt;
 use neverlight_mail_core::client::JmapClient;
+use neverlight_mail_core::models::{Folder, MessageSummary};
 use neverlight_mail_core::push::{self, EventSourceConfig};
 
 use super::{AppModel, ConnectionState, Message};
 
+/// What a freshly synced head page says about the list.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct HeadDelta {
+    /// Head positions not in the list yet. The head page is in display
+    /// order, so inserting each at its own position (in ascending order)
+    /// lands it where a full reload would have put it.
+    new: Vec<usize>,
+    /// (list index, head position) of rows whose flags differ.
+    changed: Vec<(usize, usize)>,
+    /// A row the head page covers is missing from it: deleted or moved.
+    gone: bool,
+}
+
+/// Compare list rows with the head page, both as (key, flags). Rows up to
+/// the last one the head page still holds are covered by it; with
+/// `whole_folder` (a short page) every row is.
+fn head_delta<K: Eq + Hash, F: PartialEq>(
+    list: &[(K, F)],
+    head: &[(K, F)],
+    whole_folder: bool,
+) -> HeadDelta {
+    let in_list: HashSet<&K> = list.iter().map(|(key, _)| key).collect();
+    let new = head
+        .iter()
+        .enumerate()
+        .filter(|(_, (key, _))| !in_list.contains(key))
+        .map(|(i, _)| i)
+        .collect();
+    let mut changed = Vec::new();
+    let mut covered_to = None;
+    for (li, (key, flags)) in list.iter().enumerate() {
+        let Some(hp) = head.iter().position(|(k, _)| k == key) else {
+            continue;
+        };
+        covered_to = Some(li);
+        if head[hp].1 != *flags {
+            changed.push((li, hp));
+        }
+    }
+    let covered = if whole_folder { list.len() } else { covered_to.map_or(0, |li| li + 1) };
+    let in_head: HashSet<&K> = head.iter().map(|(key, _)| key).collect();
+    let gone = list[..covered].iter().any(|(key, _)| !in_head.contains(key));
+    HeadDelta { new, changed, gone }
+}
+
 /// Returns a stream that listens for JMAP EventSource (SSE) push notifications
 /// and maps state changes into app messages.
+///
+/// One stream per account already covers every mailbox on a single
+/// connection (the JMAP counterpart of IMAP NOTIFY), so there is no
+/// per-mailbox IDLE to consolidate and no capability fallback to manage.
 pub(super) fn push_watch_stream(
     client: JmapClient,
     account_id: String,
@@ -41,13 +94,39 @@ impl AppModel {
     pub(super) fn handle_watch(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::PushStateChanged(ref account_id) => {
+                if self.account_paused(account_id) {
+                    return Task::none();
+                }
                 log::debug!("Push state change for account {}", account_id);
-                return self.dispatch(Message::Refresh);
+                return self.push_delta_sync(account_id);
+            }
+
+            Message::PushDeltaLoaded {
+                account_id,
+                mailbox_id,
+                epoch,
+                result: Ok(head),
+            } => {
+                return self.apply_push_delta(&account_id, &mailbox_id, epoch, head);
+            }
+            Message::PushDeltaLoaded { epoch, result: Err(e), .. } => {
+                if epoch == self.push_epoch {
+                    log::warn!("Push delta sync failed, falling back to refresh: {}", e);
+                    return self.dispatch(Message::Refresh);
+                }
+            }
+
+            Message::PushFoldersLoaded { account_id, result: Ok(fresh) } => {
+                return self.apply_push_folder_counts(&account_id, fresh);
+            }
+            Message::PushFoldersLoaded { result: Err(e), .. } => {
+                log::warn!("Push folder count fetch failed: {}", e);
             }
 
             Message::PushError(ref account_id, ref error) => {
                 log::warn!("Push error for account {}: {}", account_id, error);
                 if let Some(idx) = self.account_index(account_id) {
+                    self.accounts[idx].push_healthy = false;
                     self.accounts[idx].last_error = Some(error.clone());
                     let aid = account_id.clone();
                     return cosmic::task::future(async move {
@@ -60,6 +139,7 @@ impl AppModel {
             Message::PushEnded(ref account_id) => {
                 log::info!("Push stream ended for account {}", account_id);
                 if let Some(idx) = self.account_index(account_id) {
+                    self.accounts[idx].push_healthy = false;
                     self.accounts[idx].conn_state =
                         ConnectionState::Error("Push stream ended".into());
                     self.accounts[idx].last_error = Some("Push stream ended".into());
@@ -75,4 +155,206 @@ impl AppModel {
         }
         Task::none()
     }
+
+    /// Incremental update for a push state change: re-sync only the head page
+    /// of the folder on screen (if it belongs to this account) and reconcile
+    /// it in place, plus a cheap Mailbox/get for folder counts. New rows are
+    /// inserted and read/star changes applied; a row deleted or moved away,
+    /// or a folder added or removed, falls back to a full sync of the
+    /// account. Other folders sync when they are opened.
+    fn push_delta_sync(&mut self, account_id: &str) -> Task<Message> {
+        let Some(idx) = self.account_index(account_id) else {
+            return Task::none();
+        };
+        let Some(client) = self.accounts[idx].client.clone() else {
+            return Task::none();
+        };
+        let mut tasks: Vec<Task<Message>> = Vec::new();
+
+        let folders_client = client.clone();
+        let aid = account_id.to_string();
+        tasks.push(cosmic::task::future(async move {
+            let result = neverlight_mail_core::mailbox::fetch_all(&folders_client)
+                .await
+                .map_err(|e| e.to_string());
+            Message::PushFoldersLoaded { account_id: aid, result }
+        }));
+
+        let visible_mailbox = if self.active_account == Some(idx) && !self.search_phase.is_active() {
+            self.selected_folder
+                .and_then(|fi| self.accounts[idx].folders.get(fi))
+                .map(|f| f.mailbox_id.clone())
+        } else {
+            None
+        };
+        if let Some(mailbox_id) = visible_mailbox {
+            self.push_epoch = self.push_epoch.saturating_add(1);
+            let epoch = self.push_epoch;
+            let cache = self.cache.clone();
+            let aid = account_id.to_string();
+            let page_size = self.page_size_for(&aid, &mailbox_id);
+            tasks.push(cosmic::task::future(async move {
+                let result = match cache {
+                    Some(cache) => {
+                        match neverlight_mail_core::sync::sync_emails(
+                            &client, &cache, &aid, &mailbox_id, page_size,
+                        )
+                        .await
+                        {
+                            Ok(()) => {
+                                cache
+                                    .load_messages(aid.clone(), mailbox_id.clone(), page_size, 0)
+                                    .await
+                            }
+                            Err(e) => Err(e.to_string()),
+                        }
+                    }
+                    None => neverlight_mail_core::email::query_and_get(
+                        &client, &mailbox_id, page_size, 0,
+                    )
+                    .await
+                    .map(|(msgs, _)| msgs)
+                    .map_err(|e| e.to_string()),
+                };
+                Message::PushDeltaLoaded {
+                    account_id: aid,
+                    mailbox_id,
+                    epoch,
+                    result,
+                }
+            }));
+        }
+
+        cosmic::task::batch(tasks)
+    }
+
+    fn apply_push_delta(
+        &mut self,
+        account_id: &str,
+        mailbox_id: &str,
+        epoch: u64,
+        head: Vec<MessageSummary>,
+    ) -> Task<Message> {
+        let still_visible = self
+            .active_account
+            .and_then(|i| self.accounts.get(i))
+            .is_some_and(|a| a.config.id == account_id)
+            && self.selected_mailbox_id.as_deref() == Some(mailbox_id)
+            && !self.search_phase.is_active();
+        if epoch != self.push_epoch || !still_visible {
+            self.stale_apply_drop_count = self.stale_apply_drop_count.saturating_add(1);
+            return Task::none();
+        }
+
+        let row = |m: &MessageSummary| {
+            let key = (m.email_id.clone(), m.context_mailbox_id.clone());
+            (key, (m.is_read, m.is_starred))
+        };
+        let list: Vec<_> = self.messages.iter().map(row).collect();
+        let head_rows: Vec<_> = head.iter().map(row).collect();
+        let whole_folder = head.len() < self.page_size_for(account_id, mailbox_id) as usize;
+        let delta = head_delta(&list, &head_rows, whole_folder);
+        if delta.gone {
+            log::debug!("Push removed messages from {}, syncing the account", mailbox_id);
+            return self.handle_refresh_accounts(Some(account_id));
+        }
+        if delta.new.is_empty() && delta.changed.is_empty() {
+            return Task::none();
+        }
+
+        for &(li, hp) in &delta.changed {
+            // A local toggle still on its way to the server wins.
+            let pending = self
+                .pending_flag_epochs
+                .keys()
+                .any(|id| id.email_id == self.messages[li].email_id);
+            if !pending {
+                self.messages[li].is_read = head[hp].is_read;
+                self.messages[li].is_starred = head[hp].is_starred;
+            }
+        }
+        let prev_email_id = self
+            .selected_message
+            .and_then(|i| self.messages.get(i).map(|m| m.email_id.clone()));
+        for &pos in &delta.new {
+            let at = pos.min(self.messages.len());
+            self.messages.insert(at, head[pos].clone());
+        }
+        if let Some(eid) = prev_email_id {
+            self.selected_message = self.messages.iter().position(|m| m.email_id == eid);
+        }
+        self.recompute_visible();
+        self.reconcile_folder_unread_count(account_id, mailbox_id);
+        self.record_folder_synced(account_id, mailbox_id);
+        if delta.new.is_empty() {
+            return Task::none();
+        }
+        self.status_message = format!("{} new message(s)", delta.new.len());
+        self.queue_new_mail(account_id, mailbox_id, delta.new.iter().map(|&pos| &head[pos]))
+    }
+
+    fn apply_push_folder_counts(&mut self, account_id: &str, fresh: Vec<Folder>) -> Task<Message> {
+        let Some(idx) = self.account_index(account_id) else {
+            return Task::none();
+        };
+        let folders = &mut self.accounts[idx].folders;
+        let same_folders = folders.len() == fresh.len()
+            && folders.iter().all(|f| fresh.iter().any(|n| n.mailbox_id == f.mailbox_id));
+        if !same_folders {
+            log::debug!("Push changed the folder list of {}, syncing the account", account_id);
+            return self.handle_refresh_accounts(Some(account_id));
+        }
+        // Update in place so folder indices (selection, drag targets) stay valid.
+        for folder in folders.iter_mut() {
+            if let Some(f) = fresh.iter().find(|f| f.mailbox_id == folder.mailbox_id) {
+                *folder = f.clone();
+            }
+        }
+        Task::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{head_delta, HeadDelta};
+
+    fn rows(keys: &[&'static str]) -> Vec<(&'static str, bool)> {
+        keys.iter().map(|&k| (k, false)).collect()
+    }
+
+    #[test]
+    fn new_messages_at_top_are_found_in_order() {
+        let head = rows(&["m1", "m2", "m3", "m4", "m5"]);
+        let delta = head_delta(&rows(&["m3", "m4", "m5"]), &head, false);
+        assert_eq!(delta, HeadDelta { new: vec![0, 1], ..HeadDelta::default() });
+    }
+
+    #[test]
+    fn interleaved_arrival_keeps_head_position() {
+        let delta = head_delta(&rows(&["m1", "m3"]), &rows(&["m1", "m2", "m3"]), false);
+        assert_eq!(delta.new, vec![1]);
+        assert!(!delta.gone);
+    }
+
+    #[test]
+    fn nothing_new_yields_no_positions() {
+        let delta = head_delta(&rows(&["m1", "m2"]), &rows(&["m1", "m2"]), false);
+        assert_eq!(delta, HeadDelta::default());
+    }
+
+    #[test]
+    fn flag_changes_are_reported() {
+        let delta = head_delta(&rows(&["m1", "m2"]), &[("m1", false), ("m2", true)], false);
+        assert_eq!(delta.changed, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn rows_missing_from_the_covered_head_are_gone() {
+        // m2 sat between rows the head page still holds.
+        assert!(head_delta(&rows(&["m1", "m2", "m3"]), &rows(&["m1", "m3"]), false).gone);
+        // Rows past the head page (older pages) can't be judged...
+        assert!(!head_delta(&rows(&["m1", "m2", "m3"]), &rows(&["m0", "m1"]), false).gone);
+        // ...unless the page is the whole folder.
+        assert!(head_delta(&rows(&["m1", "m2", "m3"]), &rows(&["m0", "m1"]), true).gone);
+    }
 }
@@ -0,0 +1,161 @@
+//! Main window size, maximized state and position across restarts.
+//!
+//! Pane ratios live in core's `LayoutConfig`, which this crate can't extend,
+//! so window geometry is kept GUI-side in `window.json` next to
+//! settings.json. Saved on quit; position only restores where the
+//! compositor lets clients place windows (not on Wayland).
+
+use std::path::PathBuf;
+
+use cosmic::app::Task;
+use cosmic::iced::{Point, Size};
+use serde::{Deserialize, Serialize};
+
+use super::json_store::{load_json, save_json};
+use super::{AppModel, Message};
+
+pub const MIN_WIDTH: f32 = 800.0;
+pub const MIN_HEIGHT: f32 = 400.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub maximized: bool,
+    /// Top-left corner, when the compositor reported one.
+    #[serde(default)]
+    pub position: Option<(f32, f32)>,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            // libcosmic's own first-run size.
+            width: 1024.0,
+            height: 768.0,
+            maximized: false,
+            position: None,
+        }
+    }
+}
+
+impl WindowState {
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    /// Reject unusable sizes and lift anything below the window minimum.
+    fn sanitized(self) -> Option<Self> {
+        if !(self.width.is_finite() && self.height.is_finite()) {
+            return None;
+        }
+        Some(Self {
+            width: self.width.max(MIN_WIDTH),
+            height: self.height.max(MIN_HEIGHT),
+            position: self.position.filter(|(x, y)| x.is_finite() && y.is_finite()),
+            ..self
+        })
+    }
+
+    pub fn load() -> Option<Self> {
+        load_json::<Self>(window_state_path())?.sanitized()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        save_json(window_state_path(), self)
+    }
+}
+
+fn window_state_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("neverlight-mail").join("window.json"))
+}
+
+impl AppModel {
+    pub(super) fn handle_window_state(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::WindowResized(size) => {
+                // (Un)maximizing arrives as a plain resize; ask which it was
+                // so the restore size isn't overwritten by the maximized one.
+                let Some(id) = self.core.main_window_id() else {
+                    return Task::none();
+                };
+                return cosmic::iced::window::is_maximized(id).map(move |maximized| {
+                    cosmic::Action::App(Message::WindowSizeChecked { size, maximized })
+                });
+            }
+            Message::WindowSizeChecked { size, maximized } => {
+                self.window_state.maximized = maximized;
+                if !maximized {
+                    self.window_state.width = size.width;
+                    self.window_state.height = size.height;
+                }
+            }
+            Message::WindowMoved(point) => {
+                if !self.window_state.maximized {
+                    self.window_state.position = Some((point.x, point.y));
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    pub(super) fn save_window_state(&self) {
+        if let Err(e) = self.window_state.save() {
+            log::warn!("Failed to save window state: {}", e);
+        }
+    }
+
+    /// Re-apply maximized state and position from the last session; the size
+    /// itself is set in `main` before the window opens.
+    pub(super) fn restore_window_state(&self) -> Task<Message> {
+        let Some(id) = self.core.main_window_id() else {
+            return Task::none();
+        };
+        let mut tasks = Vec::new();
+        if let Some((x, y)) = self.window_state.position {
+            tasks.push(cosmic::iced::window::move_to(id, Point::new(x, y)));
+        }
+        if self.window_state.maximized {
+            tasks.push(cosmic::iced::window::maximize(id, true));
+        }
+        cosmic::task::batch(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undersized_windows_grow_to_the_minimum() {
+        let state = WindowState {
+            width: 300.0,
+            height: 900.0,
+            maximized: true,
+            position: Some((10.0, 20.0)),
+        };
+        let fixed = state.sanitized().unwrap();
+        assert_eq!(fixed.size(), Size::new(MIN_WIDTH, 900.0));
+        assert!(fixed.maximized);
+        assert_eq!(fixed.position, Some((10.0, 20.0)));
+    }
+
+    #[test]
+    fn non_finite_sizes_are_rejected() {
+        let state = WindowState {
+            width: f32::NAN,
+            ..WindowState::default()
+        };
+        assert_eq!(state.sanitized(), None);
+    }
+
+    #[test]
+    fn maximized_and_position_are_optional() {
+        let state: WindowState = serde_json::from_str(r#"{"width":1200,"height":800}"#).unwrap();
+        assert_eq!(state.size(), Size::new(1200.0, 800.0));
+        assert!(!state.maximized);
+        assert_eq!(state.position, None);
+    }
+}
@@ -21,6 +21,46 @@ impl TryFrom<(Vec<u8>, String)> for DraggedFiles {
     }
 }
 
+/// Clipboard contents compose can attach: a PNG (screenshots) or files
+/// copied in a file manager, as a text/uri-list.
+#[derive(Debug, Clone)]
+pub enum ClipboardPaste {
+    Image(Vec<u8>),
+    Files(String),
+}
+
+const PNG_MIME: &str = "image/png";
+/// GNOME/COSMIC file managers: "copy" or "cut", then one URI per line.
+const COPIED_FILES_MIME: &str = "x-special/gnome-copied-files";
+
+impl AllowedMimeTypes for ClipboardPaste {
+    fn allowed() -> Cow<'static, [String]> {
+        Cow::Owned(vec![
+            PNG_MIME.to_string(),
+            COPIED_FILES_MIME.to_string(),
+            "text/uri-list".to_string(),
+        ])
+    }
+}
+
+impl TryFrom<(Vec<u8>, String)> for ClipboardPaste {
+    type Error = String;
+    fn try_from((bytes, mime): (Vec<u8>, String)) -> Result<Self, Self::Error> {
+        if mime == PNG_MIME {
+            return Ok(ClipboardPaste::Image(bytes));
+        }
+        let text = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+        let uri_list = match mime.as_str() {
+            COPIED_FILES_MIME => text
+                .split_once('\n')
+                .map(|(_, uris)| uris.to_string())
+                .unwrap_or_default(),
+            _ => text,
+        };
+        Ok(ClipboardPaste::Files(uri_list))
+    }
+}
+
 /// Internal message drag data for message-to-folder moves.
 #[derive(Debug, Clone)]
 pub struct DraggedMessage {
@@ -102,6 +142,24 @@ mod tests {
         assert!(DraggedFiles::try_from((data, "text/uri-list".into())).is_err());
     }
 
+    // -- ClipboardPaste --
+
+    #[test]
+    fn clipboard_png_is_an_image() {
+        let paste = ClipboardPaste::try_from((vec![0x89, b'P'], PNG_MIME.into())).unwrap();
+        assert!(matches!(paste, ClipboardPaste::Image(bytes) if bytes == [0x89, b'P']));
+    }
+
+    #[test]
+    fn clipboard_copied_files_drop_the_operation_line() {
+        let data = b"copy\nfile:///home/user/a.pdf\nfile:///home/user/b.pdf".to_vec();
+        let paste = ClipboardPaste::try_from((data, COPIED_FILES_MIME.into())).unwrap();
+        let ClipboardPaste::Files(uris) = paste else {
+            panic!("expected files");
+        };
+        assert_eq!(uris, "file:///home/user/a.pdf\nfile:///home/user/b.pdf");
+    }
+
     // -- DraggedMessage --
 
     #[test]
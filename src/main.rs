@@ -3,14 +3,35 @@ mod dnd_models;
 mod ui;
 
 fn main() -> cosmic::iced::Result {
-    env_logger::init();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", app::USAGE);
+        return Ok(());
+    }
+    let launch = match app::LaunchOptions::parse(args) {
+        Ok(launch) => launch,
+        Err(e) => {
+            eprintln!("neverlight-mail: {e}\n\n{}", app::USAGE);
+            std::process::exit(2);
+        }
+    };
 
-    let settings = cosmic::app::Settings::default()
+    app::init_logging();
+    if launch == app::LaunchOptions::Sync {
+        std::process::exit(app::run_headless_sync());
+    }
+
+    let mut settings = cosmic::app::Settings::default()
         .size_limits(
             cosmic::iced::Limits::NONE
-                .min_width(800.0)
-                .min_height(400.0),
-        );
+                .min_width(app::MIN_WIDTH)
+                .min_height(app::MIN_HEIGHT),
+        )
+        // Window close goes through on_close_requested so unsaved compose can prompt.
+        .exit_on_close(false);
+    if let Some(window) = app::WindowState::load() {
+        settings = settings.size(window.size());
+    }
 
-    cosmic::app::run::<app::AppModel>(settings, ())
+    cosmic::app::run::<app::AppModel>(settings, launch)
 }
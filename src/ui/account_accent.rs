@@ -0,0 +1,13 @@
+use cosmic::iced::Color;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{AccountColor, Message};
+
+/// Colored bar marking the account a header or row belongs to.
+pub fn marker<'a>(color: AccountColor) -> Element<'a, Message> {
+    let (r, g, b) = color.rgb();
+    widget::text::body("\u{258C}")
+        .class(cosmic::theme::Text::Color(Color::from_rgb8(r, g, b)))
+        .into()
+}
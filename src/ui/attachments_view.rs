@@ -0,0 +1,99 @@
+use cosmic::iced::{ContentFit, Length};
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{
+    scan_summary, AttachmentBrowser, AttachmentScans, AttachmentScope, BrowserState, Message,
+    ThumbnailTarget,
+};
+use crate::ui::message_view::human_size;
+
+const THUMBNAIL_SIZE: f32 = 64.0;
+
+/// Attachments browser shown in the preview pane, with virus scan verdicts
+/// from `scans`.
+pub fn view<'a>(browser: &'a AttachmentBrowser, scans: &AttachmentScans) -> Element<'a, Message> {
+    let scope_toggle = match browser.scope {
+        AttachmentScope::Folder => widget::button::text("Whole account")
+            .on_press(Message::ShowAttachments(AttachmentScope::Account)),
+        AttachmentScope::Account => widget::button::text("Current folder")
+            .on_press(Message::ShowAttachments(AttachmentScope::Folder)),
+    };
+    let header = widget::row()
+        .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(widget::text::heading(format!("Attachments — {}", browser.title)).width(Length::Fill))
+        .push(scope_toggle)
+        .push(widget::button::text("Save all").on_press_maybe(
+            (!browser.entries().is_empty()).then(|| {
+                Message::SaveAllAttachments(ThumbnailTarget::Browser {
+                    epoch: browser.epoch,
+                })
+            }),
+        ))
+        .push(widget::button::standard("Close").on_press(Message::CloseAttachments));
+
+    let status = match &browser.state {
+        BrowserState::Loading => "Scanning cached messages...".to_string(),
+        BrowserState::Failed(err) => err.clone(),
+        BrowserState::Loaded(scan) => scan_summary(scan.entries.len(), scan.uncached),
+    };
+
+    let mut list = widget::column().spacing(8);
+    for (i, entry) in browser.entries().iter().enumerate() {
+        let mut row = widget::row()
+            .spacing(12)
+            .align_y(cosmic::iced::Alignment::Center);
+        if let Some(Some(handle)) = browser.thumbnails.get(i) {
+            row = row.push(
+                widget::mouse_area(
+                    widget::Image::new(handle.clone())
+                        .content_fit(ContentFit::Cover)
+                        .width(Length::Fixed(THUMBNAIL_SIZE))
+                        .height(Length::Fixed(THUMBNAIL_SIZE)),
+                )
+                .on_press(Message::ViewImage {
+                    target: ThumbnailTarget::Browser {
+                        epoch: browser.epoch,
+                    },
+                    index: i,
+                }),
+            );
+        }
+        let mut info = widget::column()
+            .spacing(2)
+            .push(widget::text::body(format!(
+                "{} ({})",
+                entry.filename,
+                human_size(entry.size)
+            )))
+            .push(widget::text::caption(format!("{} — {}", entry.from, entry.date)))
+            .push(widget::text::caption(&entry.subject));
+        if let Some(verdict) = entry.hash.as_deref().and_then(|hash| scans.verdict(hash)) {
+            info = info.push(widget::text::caption(verdict.label()));
+        }
+        let info = info.width(Length::Fill);
+        row = row
+            .push(info)
+            .push(widget::button::standard("Open").on_press(Message::OpenBrowserAttachment(i)))
+            .push(widget::button::suggested("Save").on_press(Message::SaveBrowserAttachment(i)));
+        list = list.push(
+            widget::container(row)
+                .padding(8)
+                .width(Length::Fill)
+                .class(cosmic::style::Container::Card),
+        );
+    }
+
+    let col = widget::column()
+        .spacing(8)
+        .padding([8, 16])
+        .push(header)
+        .push(widget::text::caption(status))
+        .push(widget::scrollable(list).height(Length::Fill));
+
+    widget::container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
@@ -0,0 +1,90 @@
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{CleanupAction, CleanupStage, CleanupWizard, Message};
+
+/// Sender cleanup wizard: pattern → scan → review counts → bulk move.
+pub fn view(wizard: &CleanupWizard) -> Element<'_, Message> {
+    let mut controls = widget::column().spacing(12);
+    let mut dialog = widget::dialog().title("Clean up sender");
+
+    match &wizard.stage {
+        CleanupStage::Input | CleanupStage::Scanning => {
+            let scanning = wizard.stage == CleanupStage::Scanning;
+            let mut input = widget::text_input("news@shop.example or shop.example", &wizard.pattern)
+                .label("Sender address or domain");
+            if !scanning {
+                input = input
+                    .on_input(Message::CleanupPatternChanged)
+                    .on_submit(|_| Message::CleanupScan);
+            }
+            controls = controls.push(input);
+            if scanning {
+                controls = controls.push(widget::text::body("Scanning cached folders..."));
+            }
+            let find = widget::button::suggested("Find messages");
+            dialog = dialog.primary_action(if scanning {
+                find
+            } else {
+                find.on_press(Message::CleanupScan)
+            });
+        }
+        CleanupStage::Review => {
+            let total = wizard.matches.len();
+            controls = controls.push(widget::text::body(format!(
+                "{} cached messages from {}",
+                total, wizard.pattern
+            )));
+            for (folder, count) in &wizard.folder_counts {
+                controls = controls.push(widget::text::caption(format!("{folder}: {count}")));
+            }
+            if total > 0 {
+                dialog = dialog
+                    .primary_action(
+                        widget::button::destructive("Move all to Trash")
+                            .on_press(Message::CleanupRun(CleanupAction::Trash)),
+                    )
+                    .tertiary_action(
+                        widget::button::standard("Archive all")
+                            .on_press(Message::CleanupRun(CleanupAction::Archive)),
+                    );
+            }
+        }
+        CleanupStage::Running { action, next, .. } => {
+            let total = wizard.matches.len().max(1);
+            controls = controls
+                .push(widget::text::body(format!(
+                    "{}: {} of {}",
+                    action.verb(),
+                    next,
+                    wizard.matches.len()
+                )))
+                .push(widget::progress_bar(0.0..=total as f32, *next as f32));
+        }
+        CleanupStage::Finished {
+            action,
+            moved,
+            failed,
+        } => {
+            let mut summary = format!("{}: moved {} messages", action.verb(), moved);
+            if *failed > 0 {
+                summary.push_str(&format!(", {failed} failed (see log)"));
+            }
+            controls = controls.push(widget::text::body(summary));
+        }
+    }
+
+    if let Some(ref err) = wizard.error {
+        controls = controls.push(widget::text::body(err.clone()));
+    }
+
+    let close_label = match wizard.stage {
+        CleanupStage::Running { .. } => "Stop",
+        CleanupStage::Finished { .. } => "Done",
+        _ => "Cancel",
+    };
+    dialog
+        .control(controls)
+        .secondary_action(widget::button::standard(close_label).on_press(Message::CleanupClose))
+        .into()
+}
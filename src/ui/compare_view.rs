@@ -0,0 +1,66 @@
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{CompareBody, ComparePane, CompareSide, Comparison, Message, ScrollSync};
+
+/// Two messages side by side, each in its own scrolling column, under a bar
+/// with the scroll link and Close.
+pub fn view(comparison: &Comparison) -> Element<'_, Message> {
+    let linked = comparison.scroll.sync == ScrollSync::Linked;
+    let header = widget::row()
+        .spacing(12)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(widget::text::heading("Comparing two messages"))
+        .push(
+            widget::checkbox("Scroll together", linked)
+                .on_toggle(|_| Message::ToggleCompareScroll),
+        )
+        .push(widget::text::caption("Esc closes").width(Length::Fill))
+        .push(widget::button::standard("Close").on_press(Message::CloseComparison));
+
+    let columns = widget::row()
+        .spacing(8)
+        .push(column(CompareSide::Left, &comparison.left))
+        .push(column(CompareSide::Right, &comparison.right))
+        .height(Length::Fill);
+
+    widget::column()
+        .push(widget::container(header).padding([8, 16]).width(Length::Fill))
+        .push(columns)
+        .height(Length::Fill)
+        .into()
+}
+
+fn column(side: CompareSide, pane: &ComparePane) -> Element<'_, Message> {
+    let ComparePane { summary, body } = pane;
+    let header = widget::column()
+        .spacing(2)
+        .push(widget::text::heading(&summary.subject))
+        .push(widget::text::caption(format!("{} — {}", summary.from, summary.date)));
+    let text: Element<'_, Message> = match body {
+        CompareBody::Loading => widget::text::body("Loading…").into(),
+        CompareBody::Loaded(plain) => widget::text::body(plain).width(Length::Fill).into(),
+        CompareBody::Failed(e) => widget::text::body(format!("Couldn't load the body: {e}")).into(),
+    };
+    let scroller = widget::scrollable(widget::container(text).padding([8, 16]).width(Length::Fill))
+        .id(side.scroll_id())
+        .on_scroll(move |viewport| Message::CompareScrolled {
+            side,
+            offset: viewport.relative_offset().y,
+        })
+        .height(Length::Fill)
+        .width(Length::Fill);
+
+    widget::column()
+        .push(
+            widget::container(header)
+                .padding([4, 16])
+                .width(Length::Fill)
+                .class(cosmic::style::Container::Card),
+        )
+        .push(scroller)
+        .width(Length::FillPortion(1))
+        .height(Length::Fill)
+        .into()
+}
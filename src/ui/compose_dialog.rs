@@ -1,15 +1,18 @@
-use cosmic::iced::Length;
+use cosmic::iced::{Color, Length};
 use cosmic::widget;
 use cosmic::widget::text_editor;
 use cosmic::Element;
 
-use crate::app::Message;
+use crate::app::{Message, QuoteTool, ReplyChoice, ReplyTarget, SendSize, VariableFill};
 use neverlight_mail_core::models::AttachmentData;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ComposeMode {
     New,
+    /// New message filled from `compose --template`.
+    Template,
     Reply,
+    ReplyAll,
     Forward,
 }
 
@@ -19,13 +22,36 @@ pub struct ComposeViewState<'a> {
     pub selected_account: usize,
     pub from_addresses: &'a [String],
     pub from_selected: usize,
+    /// "No signature" and the addresses with one.
+    pub signature_labels: &'a [String],
+    pub signature_selected: usize,
+    /// To addresses outside the account's internal domains.
+    pub external_recipients: &'a [String],
+    pub recipient_count: usize,
+    /// Past the mass-send threshold; Send will ask first.
+    pub over_recipient_limit: bool,
+    /// Sent-history completions for the address being typed.
+    pub suggestions: &'a [String],
+    /// Reply-To or sender, when the replied message has both.
+    pub reply_choice: Option<&'a ReplyChoice>,
+    /// Estimated message size against the account's limit.
+    pub send_size: SendSize,
+    /// Template variables in the subject and body, filled from the first
+    /// To recipient.
+    pub variables: VariableFill,
     pub to: &'a str,
     pub subject: &'a str,
     pub body: &'a text_editor::Content,
     pub attachments: &'a [AttachmentData],
     pub error: Option<&'a str>,
+    /// To recipients the server refused on the last send.
+    pub rejected: &'a [String],
     pub is_sending: bool,
     pub drag_hover: bool,
+    /// The body is open in the external editor.
+    pub editing_externally: bool,
+    /// A reply to a known message: offer Send & Archive.
+    pub can_send_and_archive: bool,
 }
 
 fn format_size(bytes: usize) -> String {
@@ -38,6 +64,110 @@ fn format_size(bytes: usize) -> String {
     }
 }
 
+/// "Reply to" row: the Reply-To address (usually a mailing list) or the
+/// sender.
+fn reply_target_row<'a>(choice: &ReplyChoice) -> Element<'a, Message> {
+    let option = |label: String, target: ReplyTarget| {
+        let class = if choice.target == target {
+            cosmic::theme::Button::Suggested
+        } else {
+            cosmic::theme::Button::Standard
+        };
+        widget::button::text(label)
+            .class(class)
+            .on_press(Message::ComposeReplyTarget(target))
+    };
+    widget::column()
+        .spacing(4)
+        .push(widget::text::body("Reply to"))
+        .push(
+            widget::row()
+                .spacing(4)
+                .push(option(format!("Reply-To: {}", choice.reply_to), ReplyTarget::ReplyTo))
+                .push(option(format!("Sender: {}", choice.sender), ReplyTarget::Sender)),
+        )
+        .into()
+}
+
+/// "About 3.2 MB of 25.0 MB", in the warning color past the limit.
+fn size_footer<'a>(size: SendSize) -> Element<'a, Message> {
+    let label = match size.limit {
+        Some(limit) => format!(
+            "About {} of {}",
+            format_size(size.estimate),
+            format_size(limit)
+        ),
+        None => format!("About {}", format_size(size.estimate)),
+    };
+    if !size.over_limit() {
+        return widget::text::caption(label).into();
+    }
+    let warning = Color::from(cosmic::theme::active().cosmic().warning_color());
+    widget::text::caption(format!("{label}, too large to send"))
+        .class(cosmic::theme::Text::Color(warning))
+        .into()
+}
+
+/// The lines with template variables as they'll be sent, and a warning
+/// for any variable there's nothing to fill with.
+fn variables_preview<'a>(variables: VariableFill) -> Element<'a, Message> {
+    let mut col = widget::column()
+        .spacing(2)
+        .push(widget::text::caption("Filled in when sent:"));
+    for line in variables.preview.iter() {
+        col = col.push(widget::text::body(line.clone()));
+    }
+    let unresolved = variables.unresolved();
+    if !unresolved.is_empty() {
+        let warning = Color::from(cosmic::theme::active().cosmic().warning_color());
+        col = col.push(
+            widget::text::caption(format!("No value for {}", unresolved.join(", ")))
+                .class(cosmic::theme::Text::Color(warning)),
+        );
+    }
+    col.into()
+}
+
+/// Recipients the server refused, with Send to the others when anyone is
+/// left.
+fn rejected_row<'a>(rejected: &[String], recipient_count: usize) -> Element<'a, Message> {
+    let warning = Color::from(cosmic::theme::active().cosmic().warning_color());
+    let mut row = widget::row()
+        .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(
+            widget::text::body(format!(
+                "Refused by the server: {}. Nothing was sent.",
+                rejected.join(", ")
+            ))
+            .class(cosmic::theme::Text::Color(warning))
+            .width(Length::Fill),
+        );
+    if rejected.len() < recipient_count {
+        row = row.push(
+            widget::button::standard("Send to the others")
+                .on_press(Message::ComposeSendWithoutRejected),
+        );
+    }
+    row.into()
+}
+
+/// Quote/unquote act on the selected lines; strip and rewrap on the body.
+fn quote_toolbar<'a>() -> Element<'a, Message> {
+    let tools = [
+        ("Quote", QuoteTool::Quote),
+        ("Unquote", QuoteTool::Unquote),
+        ("Strip quotes", QuoteTool::StripQuotes),
+        ("Rewrap quotes", QuoteTool::Rewrap),
+    ];
+    tools
+        .into_iter()
+        .fold(widget::row().spacing(4), |row, (label, tool)| {
+            row.push(widget::button::text(label).on_press(Message::ComposeQuoteTool(tool)))
+        })
+        .into()
+}
+
 pub fn view<'a>(state: ComposeViewState<'a>) -> Element<'a, Message> {
     let ComposeViewState {
         mode,
@@ -45,18 +175,31 @@ pub fn view<'a>(state: ComposeViewState<'a>) -> Element<'a, Message> {
         selected_account,
         from_addresses,
         from_selected,
+        signature_labels,
+        signature_selected,
+        external_recipients,
+        recipient_count,
+        over_recipient_limit,
+        suggestions,
+        reply_choice,
+        send_size,
+        variables,
         to,
         subject,
         body,
         attachments,
         error,
+        rejected,
         is_sending,
         drag_hover,
+        editing_externally,
+        can_send_and_archive,
     } = state;
 
     let title = match mode {
-        ComposeMode::New => "New Message",
+        ComposeMode::New | ComposeMode::Template => "New Message",
         ComposeMode::Reply => "Reply",
+        ComposeMode::ReplyAll => "Reply all",
         ComposeMode::Forward => "Forward",
     };
 
@@ -96,23 +239,82 @@ pub fn view<'a>(state: ComposeViewState<'a>) -> Element<'a, Message> {
         );
     }
 
+    if signature_labels.len() > 1 {
+        controls = controls.push(
+            widget::column()
+                .spacing(4)
+                .push(widget::text::body("Signature"))
+                .push(widget::dropdown(
+                    signature_labels,
+                    Some(signature_selected),
+                    Message::ComposeSignatureChanged,
+                )),
+        );
+    }
+
+    if let Some(choice) = reply_choice {
+        controls = controls.push(reply_target_row(choice));
+    }
+
     controls = controls
         .push(
             widget::text_input("recipient@example.com", to)
                 .label("To")
                 .on_input(Message::ComposeToChanged),
-        )
+        );
+
+    if !suggestions.is_empty() {
+        let mut list = widget::column().spacing(0);
+        for suggestion in suggestions {
+            list = list.push(
+                widget::button::text(suggestion.as_str())
+                    .on_press(Message::ComposeRecipientPicked(suggestion.clone()))
+                    .width(Length::Fill),
+            );
+        }
+        controls = controls.push(list);
+    }
+
+    if recipient_count > 1 {
+        let label = format!("{recipient_count} recipients");
+        controls = controls.push(if over_recipient_limit {
+            let warning = Color::from(cosmic::theme::active().cosmic().warning_color());
+            widget::text::heading(label).class(cosmic::theme::Text::Color(warning))
+        } else {
+            widget::text::heading(label)
+        });
+    }
+
+    if !external_recipients.is_empty() {
+        let warning = Color::from(cosmic::theme::active().cosmic().warning_color());
+        controls = controls.push(
+            widget::text::body(crate::app::external_warning(external_recipients))
+                .class(cosmic::theme::Text::Color(warning)),
+        );
+    }
+
+    controls = controls
         .push(
             widget::text_input("Subject", subject)
                 .label("Subject")
                 .on_input(Message::ComposeSubjectChanged),
         )
+        .push(quote_toolbar())
         .push(
             widget::text_editor(body)
                 .placeholder("Write your message...")
                 .on_action(Message::ComposeBodyAction)
                 .height(Length::Fixed(300.0)),
-        );
+        )
+        .push(if editing_externally {
+            widget::button::standard("Waiting for the editor...")
+        } else {
+            widget::button::standard("Edit in external editor")
+                .on_press(Message::ComposeExternalEditor)
+        });
+    if !variables.used.is_empty() {
+        controls = controls.push(variables_preview(variables));
+    }
 
     // Attachment section (visual only — actual DnD destination is in the main view
     // because COSMIC dialog overlays don't propagate drag_destinations to the compositor)
@@ -138,7 +340,10 @@ pub fn view<'a>(state: ComposeViewState<'a>) -> Element<'a, Message> {
             attach_col = attach_col.push(row);
         }
     }
-    controls = controls.push(attach_col);
+    controls = controls.push(attach_col).push(size_footer(send_size));
+    if !rejected.is_empty() {
+        controls = controls.push(rejected_row(rejected, recipient_count));
+    }
 
     let send_label = if is_sending { "Sending..." } else { "Send" };
     let send_btn = if is_sending {
@@ -152,6 +357,13 @@ pub fn view<'a>(state: ComposeViewState<'a>) -> Element<'a, Message> {
         .control(controls)
         .primary_action(send_btn)
         .secondary_action(widget::button::standard("Cancel").on_press(Message::ComposeCancel));
+    if can_send_and_archive {
+        let mut send_archive = widget::button::standard("Send & Archive");
+        if !is_sending {
+            send_archive = send_archive.on_press(Message::ComposeSendAndArchive);
+        }
+        dialog = dialog.tertiary_action(send_archive);
+    }
 
     if let Some(err) = error {
         dialog = dialog.body(err);
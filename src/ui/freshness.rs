@@ -0,0 +1,22 @@
+use cosmic::iced::Color;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{Freshness, Message};
+
+/// Colored dot for a folder's sync freshness (neutral when never synced).
+pub fn indicator<'a>(freshness: Freshness) -> Element<'a, Message> {
+    let theme = cosmic::theme::active();
+    let cosmic = theme.cosmic();
+    let color = match freshness {
+        Freshness::Never => None,
+        Freshness::Fresh => Some(Color::from(cosmic.success_color())),
+        Freshness::Aging => Some(Color::from(cosmic.warning_color())),
+        Freshness::Stale => Some(Color::from(cosmic.destructive_color())),
+    };
+    let dot = widget::text::caption("\u{25CF}");
+    match color {
+        Some(c) => dot.class(cosmic::theme::Text::Color(c)).into(),
+        None => dot.into(),
+    }
+}
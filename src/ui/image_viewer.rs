@@ -0,0 +1,60 @@
+use cosmic::iced::{Alignment, Length};
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{ImageViewer, Message};
+
+const MAX_SCALE: f32 = 8.0;
+
+/// Full-window image viewer: wheel to zoom, drag to pan.
+pub fn view(viewer: &ImageViewer) -> Element<'_, Message> {
+    let multiple = viewer.images.len() > 1;
+    let mut toolbar = widget::row()
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .push(widget::text::heading(&viewer.filename).width(Length::Fill));
+    if let Some(counter) = viewer.counter() {
+        toolbar = toolbar.push(widget::text::caption(counter));
+    }
+    let slideshow_label = if viewer.slideshow { "Stop slideshow" } else { "Slideshow" };
+    toolbar = toolbar
+        .push(
+            widget::button::standard("Previous")
+                .on_press_maybe(multiple.then_some(Message::ViewerPrevious)),
+        )
+        .push(
+            widget::button::standard("Next")
+                .on_press_maybe(multiple.then_some(Message::ViewerNext)),
+        )
+        .push(widget::button::standard("Rotate").on_press(Message::ViewerRotate))
+        .push(
+            widget::button::standard(slideshow_label)
+                .on_press_maybe(multiple.then_some(Message::ViewerToggleSlideshow)),
+        )
+        .push(widget::button::suggested("Save").on_press(Message::ViewerSave))
+        .push(widget::button::standard("Close").on_press(Message::CloseImageViewer));
+
+    let body: Element<'_, Message> = match (&viewer.handle, &viewer.error) {
+        (_, Some(err)) => widget::text::body(err.clone()).into(),
+        (Some(handle), None) => cosmic::iced::widget::image::viewer(handle.clone())
+            .max_scale(MAX_SCALE)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into(),
+        (None, None) => widget::text::body("Decoding image...").into(),
+    };
+
+    let col = widget::column()
+        .spacing(8)
+        .padding([8, 16])
+        .push(toolbar)
+        .push(widget::container(body).width(Length::Fill).height(Length::Fill))
+        .push(widget::text::caption(
+            "Scroll to zoom, drag to pan · ←/→ previous/next · R rotate · Space slideshow · Esc close",
+        ));
+
+    widget::container(col)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
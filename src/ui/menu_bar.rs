@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use cosmic::widget::menu::{self, key_bind::KeyBind, ItemHeight, ItemWidth};
+use cosmic::Element;
+
+use crate::app::{AppSettings, FolderNotify, MenuAction, Message};
+
+/// Header bar menus. Shortcut hints come from the shared binding table.
+/// `folder_notify` and `unread_only` are the open folder's state, `None`
+/// outside a folder; `move_targets` are the folder paths of the selected
+/// message's account, empty with nothing selected.
+pub fn view<'a>(
+    key_binds: &'a HashMap<KeyBind, MenuAction>,
+    settings: &AppSettings,
+    folder_notify: Option<FolderNotify>,
+    unread_only: Option<bool>,
+    move_targets: &[&str],
+) -> Element<'a, Message> {
+    let mut view_items = vec![
+        menu::Item::Button("Attachments in folder", None, MenuAction::FolderAttachments),
+        menu::Item::Button("Attachments in account", None, MenuAction::AccountAttachments),
+        menu::Item::Divider,
+        menu::Item::CheckBox(
+            "Detect events in messages",
+            None,
+            settings.detect_events,
+            MenuAction::ToggleEventDetection,
+        ),
+    ];
+    if let Some(on) = unread_only {
+        view_items.push(menu::Item::CheckBox("Unread only", None, on, MenuAction::UnreadOnly));
+    }
+    if let Some(rule) = folder_notify {
+        let choice = |label, option| {
+            menu::Item::CheckBox(label, None, rule == option, MenuAction::FolderNotify(option))
+        };
+        view_items.push(menu::Item::Folder(
+            "Folder notifications",
+            vec![
+                choice("Notify", FolderNotify::Notify),
+                choice("Silent", FolderNotify::Silent),
+                choice("Muted", FolderNotify::Muted),
+            ],
+        ));
+    }
+    view_items.extend([
+        menu::Item::Button("Triage mode", None, MenuAction::Triage),
+        menu::Item::Button("Select a range", None, MenuAction::VisualMode),
+        menu::Item::Button("Next layout", None, MenuAction::CycleLayout),
+        menu::Item::Button("Diagnostics", None, MenuAction::ToggleDiagnostics),
+        menu::Item::Button("Keyboard shortcuts", None, MenuAction::ShowShortcuts),
+    ]);
+    // Only with `"developer_mode": true` in settings.json.
+    if settings.developer_mode {
+        view_items.push(menu::Item::Divider);
+        view_items.push(menu::Item::CheckBox(
+            "Protocol log to file (next launch)",
+            None,
+            settings.protocol_log,
+            MenuAction::ToggleProtocolLog,
+        ));
+    }
+
+    // Folder paths are runtime strings, so this menu's labels are owned.
+    let item = |label: &str, action| menu::Item::Button(label.to_string(), None, action);
+    let mut message_items = vec![
+        item("Reply", MenuAction::Reply),
+        item("Reply all", MenuAction::ReplyAll),
+        item("Forward", MenuAction::Forward),
+        menu::Item::Divider,
+        item("Toggle read", MenuAction::ToggleRead),
+        item("Toggle star", MenuAction::ToggleStar),
+        item("Select / deselect", MenuAction::ToggleSelected),
+        item("Mark folder as read", MenuAction::MarkFolderRead),
+        menu::Item::Divider,
+        item("Merge into thread", MenuAction::MergeIntoThread),
+        item("Split from thread", MenuAction::SplitFromThread),
+        menu::Item::Divider,
+        item("Archive", MenuAction::Archive),
+    ];
+    if !move_targets.is_empty() {
+        let folders = move_targets
+            .iter()
+            .enumerate()
+            .map(|(i, path)| item(path, MenuAction::MoveTo(i)))
+            .collect();
+        message_items.push(menu::Item::Folder("Move to".to_string(), folders));
+    }
+    message_items.extend([
+        item("Move to Trash", MenuAction::Trash),
+        item("Clean up sender…", MenuAction::CleanupSender),
+    ]);
+
+    menu::bar(vec![
+        menu::Tree::with_children(
+            menu::root("File"),
+            menu::items(
+                key_binds,
+                vec![
+                    menu::Item::Button("New message", None, MenuAction::NewMessage),
+                    menu::Item::Button("Resume draft", None, MenuAction::ResumeDraft),
+                    menu::Item::Button("Edit server draft", None, MenuAction::EditDraft),
+                    menu::Item::Divider,
+                    menu::Item::Button("Add account", None, MenuAction::AddAccount),
+                    menu::Item::Button("Refresh", None, MenuAction::Refresh),
+                    menu::Item::Divider,
+                    menu::Item::Button("Export settings", None, MenuAction::ExportSettings),
+                    menu::Item::Button("Import settings", None, MenuAction::ImportSettings),
+                    menu::Item::Divider,
+                    menu::Item::Button("Quit", None, MenuAction::Quit),
+                ],
+            ),
+        ),
+        menu::Tree::with_children(
+            menu::root("Edit"),
+            menu::items(
+                key_binds,
+                vec![
+                    menu::Item::Button("Copy message text", None, MenuAction::CopyBody),
+                    menu::Item::Button("Search", None, MenuAction::Search),
+                ],
+            ),
+        ),
+        menu::Tree::with_children(
+            menu::root("View"),
+            menu::items(key_binds, view_items),
+        ),
+        menu::Tree::with_children(
+            menu::root("Message"),
+            menu::items(key_binds, message_items),
+        ),
+    ])
+    .item_height(ItemHeight::Dynamic(40))
+    .item_width(ItemWidth::Uniform(240))
+    .spacing(4.0)
+    .into()
+}
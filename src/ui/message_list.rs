@@ -1,10 +1,13 @@
 use std::collections::{HashMap, HashSet};
 
 use cosmic::iced::Length;
-use cosmic::widget;
+use cosmic::widget::{self, menu};
 use cosmic::Element;
 
-use crate::app::Message;
+use crate::app::{
+    synced_ago_label, AccountColor, Freshness, Message, MessageIdentity, MessageSelection,
+    RowAction, RowLabels, RowMenuAction,
+};
 use neverlight_mail_core::models::MessageSummary;
 
 use crate::dnd_models::DraggedMessage;
@@ -16,8 +19,30 @@ pub struct MessageListState<'a> {
     pub has_more: bool,
     pub collapsed_threads: &'a HashSet<String>,
     pub thread_sizes: &'a HashMap<String, usize>,
+    /// Sender/date strings by email id, computed at load time.
+    pub row_labels: &'a HashMap<String, RowLabels>,
+    /// Attachment counts by email id, for bodies loaded so far.
+    pub attachment_counts: &'a HashMap<String, usize>,
+    /// One-line body previews by email id, for bodies seen so far.
+    pub snippets: &'a HashMap<String, String>,
     pub search_active: bool,
     pub search_query: &'a str,
+    /// Terms of the search on screen; typing more refines within them.
+    pub search_terms: &'a [String],
+    pub selection: &'a MessageSelection,
+    /// Show the "Updated X ago" header (a folder is selected, not searching).
+    pub show_sync_status: bool,
+    /// Selected folder's last successful sync (Unix seconds).
+    pub last_synced: Option<u64>,
+    /// "Unread only" filter state; `None` hides the toggle (no folder open).
+    pub unread_only: Option<bool>,
+    /// Plus alias the list is narrowed to.
+    pub alias_filter: Option<&'a str>,
+    /// Folder paths per account, in folder order, for the row menu's Move to.
+    pub move_targets: HashMap<&'a str, Vec<&'a str>>,
+    /// Accent per account id, when the rows come from more than one account.
+    pub account_colors: Option<&'a HashMap<String, AccountColor>>,
+    pub now: u64,
 }
 
 pub fn search_input_id() -> widget::Id {
@@ -33,15 +58,59 @@ pub fn view<'a>(state: MessageListState<'a>) -> Element<'a, Message> {
         has_more,
         collapsed_threads,
         thread_sizes,
+        row_labels,
+        attachment_counts,
+        snippets,
         search_active,
         search_query,
+        search_terms,
+        selection,
+        show_sync_status,
+        last_synced,
+        unread_only,
+        alias_filter,
+        move_targets,
+        account_colors,
+        now,
     } = state;
     let mut col = widget::column().spacing(2).padding(8);
 
+    if show_sync_status {
+        let freshness = Freshness::classify(last_synced, now);
+        let mut header = widget::row()
+            .spacing(4)
+            .align_y(cosmic::iced::Alignment::Center)
+            .push(crate::ui::freshness::indicator(freshness))
+            .push(widget::text::caption(synced_ago_label(last_synced, now)).width(Length::Fill));
+        if let Some(on) = unread_only {
+            let label = if on { "Show all" } else { "Unread only" };
+            header = header.push(widget::button::text(label).on_press(Message::ToggleUnreadOnly));
+        }
+        col = col.push(header);
+    }
+
+    if let Some(alias) = alias_filter {
+        col = col.push(
+            widget::row()
+                .spacing(4)
+                .align_y(cosmic::iced::Alignment::Center)
+                .push(
+                    widget::text::caption(format!("Only mail sent to +{alias}"))
+                        .width(Length::Fill),
+                )
+                .push(widget::button::text("Show all").on_press(Message::FilterAlias(None))),
+        );
+    }
+
     if search_active {
-        let input = widget::text_input("Search all mail...", search_query)
+        let (placeholder, submit) = if search_terms.is_empty() {
+            ("Search all mail...", Message::SearchExecute)
+        } else {
+            ("Search within results...", Message::SearchRefine)
+        };
+        let input = widget::text_input(placeholder, search_query)
             .on_input(Message::SearchQueryChanged)
-            .on_submit(|_| Message::SearchExecute)
+            .on_submit(move |_| submit.clone())
             .id(search_input_id());
         let clear_btn = widget::button::text("Clear").on_press(Message::SearchClear);
         col = col.push(
@@ -51,17 +120,62 @@ pub fn view<'a>(state: MessageListState<'a>) -> Element<'a, Message> {
                 .spacing(4)
                 .align_y(cosmic::iced::Alignment::Center),
         );
+        if !search_terms.is_empty() {
+            let chips: Vec<Element<'a, Message>> = search_terms
+                .iter()
+                .enumerate()
+                .map(|(i, term)| {
+                    widget::button::text(format!("{term} ✕"))
+                        .on_press(Message::SearchRemoveTerm(i))
+                        .into()
+                })
+                .collect();
+            col = col.push(widget::flex_row(chips).row_spacing(4).column_spacing(4));
+        }
+    }
+
+    if !selection.is_empty() {
+        col = col.push(
+            widget::row()
+                .spacing(8)
+                .align_y(cosmic::iced::Alignment::Center)
+                .push(
+                    widget::text::body(selection.summary_label()).width(Length::Fill),
+                )
+                .push(widget::button::text("Mark read").on_press(Message::BulkMarkRead))
+                .push(widget::button::text("Archive").on_press(Message::BulkArchive))
+                .push(widget::button::text("Merge into thread").on_press(Message::MergeIntoThread))
+                .push(widget::button::destructive("Trash").on_press(Message::BulkTrash))
+                .push(widget::button::text("Clear").on_press(Message::ClearMessageSelection)),
+        );
     }
 
     if messages.is_empty() {
         col = col.push(widget::text::body("No messages"));
+    } else if visible_indices.is_empty() && unread_only == Some(true) {
+        col = col.push(widget::text::body("No unread messages loaded"));
+    } else if visible_indices.is_empty() && alias_filter.is_some() {
+        col = col.push(widget::text::body("No loaded messages for this alias"));
     } else {
+        let thread_unread = unread_by_thread(messages);
         for &real_index in visible_indices {
             let msg = &messages[real_index];
             let is_selected = selected == Some(real_index);
 
+            // Collapsed thread root: hidden replies fold into its read state.
+            let collapsed_thread = msg
+                .thread_id
+                .as_ref()
+                .filter(|tid| {
+                    msg.thread_depth == 0
+                        && collapsed_threads.contains(*tid)
+                        && thread_sizes.get(*tid).copied().unwrap_or(1) > 1
+                });
+            let thread_has_unread = collapsed_thread
+                .is_some_and(|tid| thread_unread.get(tid.as_str()).copied().unwrap_or(0) > 0);
+
             let star = if msg.is_starred { "★ " } else { "" };
-            let unread = if !msg.is_read { "● " } else { "" };
+            let unread = if !msg.is_read || thread_has_unread { "● " } else { "" };
 
             // Thread collapse/expand indicator for root messages with children
             let thread_indicator = if msg.thread_depth == 0 {
@@ -83,13 +197,29 @@ pub fn view<'a>(state: MessageListState<'a>) -> Element<'a, Message> {
                 String::new()
             };
 
-            let subject_text = format!("{}{}{}{}", unread, star, thread_indicator, msg.subject);
+            let labels = row_labels.get(&msg.email_id);
+            let subject = labels.map_or(msg.subject.as_str(), |l| l.subject.as_str());
+            let subject_text = format!("{}{}{}{}", unread, star, thread_indicator, subject);
             let subject = widget::text::body(subject_text);
-            let meta = widget::text::caption(format!("{} — {}", msg.from, msg.date));
+            let meta = match labels {
+                Some(labels) => widget::text::caption(labels.meta.as_str()),
+                // Not labelled yet (changed since the last recompute).
+                None => widget::text::caption(format!("{} — {}", msg.from, msg.date)),
+            };
+            let mut text_col = widget::column().push(subject).push(meta).spacing(2);
+            if let Some(snippet) = snippets.get(&msg.email_id) {
+                text_col = text_col.push(widget::text::caption(snippet.as_str()));
+            }
 
             let depth = msg.thread_depth.min(4);
             let indent = (depth as u16) * 16;
-            let row_content = widget::column().push(subject).push(meta).spacing(2);
+            let mut row_content = widget::row()
+                .push(text_col.width(Length::Fill))
+                .spacing(8)
+                .align_y(cosmic::iced::Alignment::Center);
+            if let Some(&count) = attachment_counts.get(&msg.email_id).filter(|c| **c > 0) {
+                row_content = row_content.push(widget::text::caption(format!("📎 {count}")));
+            }
             let padded = widget::container(row_content).padding([0, 0, 0, indent]);
 
             let mut btn = widget::button::custom(padded)
@@ -111,7 +241,32 @@ pub fn view<'a>(state: MessageListState<'a>) -> Element<'a, Message> {
                 })
                 .drag_threshold(8.0);
 
-            col = col.push(source);
+            let is_checked = selection.contains(&MessageIdentity::of(msg));
+            let check = widget::checkbox("", is_checked)
+                .on_toggle(move |_| Message::ToggleMessageSelected(real_index));
+
+            let mut row = widget::row().spacing(4).align_y(cosmic::iced::Alignment::Center);
+            if let Some(&color) = account_colors.and_then(|c| c.get(&msg.account_id)) {
+                row = row.push(crate::ui::account_accent::marker(color));
+            }
+            row = row.push(check).push(source);
+            if collapsed_thread.is_some() {
+                let (label, read) = if thread_has_unread {
+                    ("Mark read", true)
+                } else {
+                    ("Mark unread", false)
+                };
+                row = row.push(widget::button::text(label).on_press(Message::MarkThreadRead {
+                    index: real_index,
+                    read,
+                }));
+            }
+            let folders = move_targets
+                .get(msg.account_id.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let menu = row_menu(real_index, is_checked, msg.is_read, folders);
+            col = col.push(widget::context_menu(row, Some(menu)));
         }
 
         if has_more {
@@ -125,3 +280,59 @@ pub fn view<'a>(state: MessageListState<'a>) -> Element<'a, Message> {
 
     widget::scrollable(col).height(Length::Fill).into()
 }
+
+/// Right-click menu for the row at `index`. On a row in the bulk selection
+/// the read, move and delete entries cover the whole selection.
+fn row_menu(
+    index: usize,
+    in_selection: bool,
+    is_read: bool,
+    folders: &[&str],
+) -> Vec<menu::Tree<Message>> {
+    let item = |label: &str, action| {
+        menu::Item::Button(label.to_string(), None, RowMenuAction { index, action })
+    };
+    let read_label = match (in_selection, is_read) {
+        (true, _) => "Mark selected read",
+        (false, true) => "Mark unread",
+        (false, false) => "Mark read",
+    };
+    let (archive_label, spam_label, trash_label) = if in_selection {
+        ("Archive selected", "Mark selected as spam", "Move selected to Trash")
+    } else {
+        ("Archive", "Mark as spam", "Move to Trash")
+    };
+    let move_to = folders
+        .iter()
+        .enumerate()
+        .map(|(i, path)| item(path, RowAction::MoveTo(i)))
+        .collect();
+    menu::items(
+        &HashMap::new(),
+        vec![
+            item("Reply", RowAction::Reply),
+            item("Reply all", RowAction::ReplyAll),
+            item("Forward", RowAction::Forward),
+            item("Compare with open message", RowAction::Compare),
+            menu::Item::Divider,
+            item(read_label, RowAction::ToggleRead),
+            item("Toggle star", RowAction::ToggleStar),
+            menu::Item::Divider,
+            item(archive_label, RowAction::Archive),
+            menu::Item::Folder("Move to".to_string(), move_to),
+            item(spam_label, RowAction::MarkSpam),
+            item(trash_label, RowAction::Trash),
+        ],
+    )
+}
+
+/// Unread messages per thread id among the loaded messages.
+fn unread_by_thread(messages: &[MessageSummary]) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for msg in messages.iter().filter(|m| !m.is_read) {
+        if let Some(ref tid) = msg.thread_id {
+            *counts.entry(tid.as_str()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
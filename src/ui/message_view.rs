@@ -3,23 +3,68 @@ use cosmic::widget;
 use cosmic::widget::{image, markdown};
 use cosmic::Element;
 
-use crate::app::{ConversationEntry, Message};
+use crate::app::{
+    attachment_risk, detect_direction, participants, plus_alias, shows_entry, AttachmentScans,
+    ConversationEntry, DetectedEvent, Message, PagedMarkdown, RowAction, TextDirection,
+    ThumbnailTarget, ENCODING_LABELS, PARTICIPANT_FILTER_MIN_MESSAGES,
+};
 use neverlight_mail_core::models::{AttachmentData, MessageSummary};
 
+/// Threads at least this long get the thread map beside the cards.
+const THREAD_MAP_MIN_MESSAGES: usize = 8;
+const THREAD_MAP_WIDTH: f32 = 220.0;
+
+pub fn conversation_scroll_id() -> widget::Id {
+    widget::Id::new("conversation-scroll")
+}
+
+/// Single-message preview contents.
+pub struct Preview<'a> {
+    pub body: &'a PagedMarkdown,
+    /// Plain-text body, for direction detection and RTL rendering.
+    pub plain: &'a str,
+    pub attachments: &'a [AttachmentData],
+    pub image_handles: &'a [Option<image::Handle>],
+    /// Parallel to `attachments`; verdicts are looked up by these.
+    pub attachment_hashes: &'a [String],
+    pub detected_event: Option<&'a DetectedEvent>,
+    /// Index into `ENCODING_LABELS`.
+    pub encoding: usize,
+    /// Virus scan verdicts, shown under the attachments they cover.
+    pub scans: &'a AttachmentScans,
+}
+
 /// Render the message preview pane with an action toolbar when a message is selected.
 pub fn view<'a>(
-    markdown_items: &'a [markdown::Item],
+    preview: Preview<'a>,
     selected: Option<(usize, &'a MessageSummary)>,
-    attachments: &[AttachmentData],
-    image_handles: &[Option<image::Handle>],
     conversation: &'a [ConversationEntry],
     active_email_id: Option<&'a str>,
+    sender_filter: Option<&'a str>,
+    own_addresses: &'a [String],
 ) -> Element<'a, Message> {
+    let Preview {
+        body,
+        plain,
+        attachments,
+        image_handles,
+        attachment_hashes,
+        detected_event,
+        encoding,
+        scans,
+    } = preview;
     if !conversation.is_empty() {
-        return conversation_view(conversation, active_email_id, selected);
+        return conversation_view(
+            conversation,
+            active_email_id,
+            selected,
+            sender_filter,
+            own_addresses,
+            scans,
+        );
     }
 
-    if markdown_items.is_empty() && attachments.is_empty() {
+    if body.is_empty() && attachments.is_empty() {
         return widget::container(widget::text::body("Select a message to read"))
             .padding(16)
             .width(Length::Fill)
@@ -30,28 +75,47 @@ pub fn view<'a>(
     let mut col = widget::column().spacing(0);
 
     if let Some((index, msg)) = selected {
-        col = col.push(toolbar(index, msg));
+        col = col.push(toolbar(index, msg, Some(encoding)));
         col = col.push(
-            widget::container(message_header(msg))
+            widget::container(message_header(msg, own_addresses, attachments.len()))
                 .padding([4, 16])
                 .width(Length::Fill)
                 .class(cosmic::style::Container::Card),
         );
+        if let Some(event) = detected_event.filter(|e| e.email_id == msg.email_id) {
+            col = col.push(event_banner(event));
+        }
     }
 
-    if !markdown_items.is_empty() {
+    if !body.is_empty() && detect_direction(plain) == TextDirection::Rtl {
+        col = col.push(widget::container(rtl_body(plain)).padding(16).width(Length::Fill));
+    } else if !body.is_empty() {
         let md = markdown::view(
-            markdown_items,
+            body.visible(),
             markdown::Settings::default(),
             markdown::Style::from_palette(cosmic::iced::Theme::Dark.palette()),
         )
         .map(Message::LinkClicked);
 
         col = col.push(widget::container(md).padding(16).width(Length::Fill));
+        if let Some((_, msg)) = selected.filter(|_| body.remaining() > 0) {
+            col = col.push(
+                widget::container(continue_reading(body, &msg.email_id)).padding([0, 16, 16, 16]),
+            );
+        }
     }
 
     if !attachments.is_empty() {
-        col = col.push(attachments_section(attachments, image_handles, None));
+        let target = ThumbnailTarget::Preview {
+            email_id: selected.map(|(_, m)| m.email_id.clone()).unwrap_or_default(),
+        };
+        col = col.push(attachments_section(
+            attachments,
+            image_handles,
+            attachment_hashes,
+            target,
+            scans,
+        ));
     }
 
     widget::scrollable(col)
@@ -60,56 +124,107 @@ pub fn view<'a>(
         .into()
 }
 
+/// A right-to-left body as plain text, every line aligned to the right
+/// whatever script it starts with.
+fn rtl_body<'a>(plain: &'a str) -> Element<'a, Message> {
+    widget::text::body(plain)
+        .width(Length::Fill)
+        .align_x(cosmic::iced::Alignment::End)
+        .into()
+}
+
+/// "Continue reading" under a body that is only partly shown.
+fn continue_reading<'a>(body: &PagedMarkdown, email_id: &str) -> Element<'a, Message> {
+    widget::row()
+        .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(
+            widget::button::standard("Continue reading")
+                .on_press(Message::ContinueReading(email_id.to_string())),
+        )
+        .push(widget::text::caption(format!("{} more sections", body.remaining())))
+        .into()
+}
+
 fn conversation_view<'a>(
     conversation: &'a [ConversationEntry],
     active_email_id: Option<&'a str>,
     selected: Option<(usize, &'a MessageSummary)>,
+    sender_filter: Option<&'a str>,
+    own_addresses: &'a [String],
+    scans: &'a AttachmentScans,
 ) -> Element<'a, Message> {
     let mut col = widget::column().spacing(0);
 
     // Toolbar for the active message
     if let Some((index, msg)) = selected {
-        col = col.push(toolbar(index, msg));
+        col = col.push(toolbar(index, msg, None));
+    }
+    if conversation.len() >= PARTICIPANT_FILTER_MIN_MESSAGES {
+        if let Some(chips) = participant_chips(conversation, sender_filter) {
+            col = col.push(chips);
+        }
     }
 
     // Stacked message cards
-    for entry in conversation {
+    for entry in conversation.iter().filter(|e| shows_entry(sender_filter, e)) {
         let is_active = active_email_id == Some(entry.email_id.as_str());
 
         let mut card_col = widget::column().spacing(4);
 
-        // Header
-        card_col = card_col.push(message_header(&entry.summary));
-
-        // Body
-        if entry.loaded {
-            if !entry.markdown_items.is_empty() {
-                let md = markdown::view(
-                    &entry.markdown_items,
-                    markdown::Settings::default(),
-                    markdown::Style::from_palette(cosmic::iced::Theme::Dark.palette()),
-                )
-                .map(Message::LinkClicked);
+        // Folded cards: one header line; clicking the card unfolds it.
+        if !entry.expanded {
+            card_col = card_col.push(folded_header(&entry.summary));
+        } else {
+            card_col = card_col.push(message_header(
+                &entry.summary,
+                own_addresses,
+                entry.attachments.len(),
+            ));
+            card_col = card_col.push(card_actions(&entry.email_id, entry.loaded, is_active));
+
+            // Body
+            if entry.loaded {
+                if detect_direction(&entry.plain_body) == TextDirection::Rtl {
+                    card_col = card_col.push(
+                        widget::container(rtl_body(&entry.plain_body)).padding([8, 0]),
+                    );
+                } else if !entry.markdown_items.is_empty() {
+                    let md = markdown::view(
+                        entry.markdown_items.visible(),
+                        markdown::Settings::default(),
+                        markdown::Style::from_palette(cosmic::iced::Theme::Dark.palette()),
+                    )
+                    .map(Message::LinkClicked);
+                    card_col = card_col.push(
+                        widget::container(md).padding([8, 0]).width(Length::Fill),
+                    );
+                    if entry.markdown_items.remaining() > 0 {
+                        card_col =
+                            card_col.push(continue_reading(&entry.markdown_items, &entry.email_id));
+                    }
+                }
+
+                if !entry.attachments.is_empty() {
+                    card_col = card_col.push(attachments_section(
+                        &entry.attachments,
+                        &entry.image_handles,
+                        &entry.attachment_hashes,
+                        ThumbnailTarget::Conversation {
+                            email_id: entry.email_id.clone(),
+                        },
+                        scans,
+                    ));
+                }
+            } else {
                 card_col = card_col.push(
-                    widget::container(md).padding([8, 0]).width(Length::Fill),
+                    widget::text::body("Loading...")
+                        .font(cosmic::iced::Font {
+                            style: cosmic::iced::font::Style::Italic,
+                            ..Default::default()
+                        }),
                 );
             }
-
-            if !entry.attachments.is_empty() {
-                card_col = card_col.push(attachments_section(
-                    &entry.attachments,
-                    &entry.image_handles,
-                    Some(&entry.email_id),
-                ));
-            }
-        } else {
-            card_col = card_col.push(
-                widget::text::body("Loading...")
-                    .font(cosmic::iced::Font {
-                        style: cosmic::iced::font::Style::Italic,
-                        ..Default::default()
-                    }),
-            );
         }
 
         let container_class = if entry.is_sent {
@@ -156,13 +271,138 @@ fn conversation_view<'a>(
         );
     }
 
-    widget::scrollable(col)
+    let cards = widget::scrollable(col)
+        .id(conversation_scroll_id())
+        .height(Length::Fill)
+        .width(Length::Fill);
+
+    if conversation.len() < THREAD_MAP_MIN_MESSAGES {
+        return cards.into();
+    }
+    widget::row()
+        .push(cards)
+        .push(thread_map(conversation, active_email_id, sender_filter))
         .height(Length::Fill)
+        .into()
+}
+
+/// One-line header of a folded conversation card.
+fn folded_header<'a>(msg: &MessageSummary) -> Element<'a, Message> {
+    let unread = if msg.is_read { "" } else { "\u{25CF} " };
+    widget::text::body(format!("{unread}{} \u{00B7} {}", sender_name(&msg.from), msg.date))
         .width(Length::Fill)
         .into()
 }
 
-fn toolbar<'a>(index: usize, msg: &MessageSummary) -> Element<'a, Message> {
+/// Reply / Reply all / Forward for this card, a Collapse button, and
+/// "Split from thread" on the active card.
+fn card_actions<'a>(email_id: &str, loaded: bool, is_active: bool) -> Element<'a, Message> {
+    let compose = |label: &'static str, action: RowAction| {
+        widget::button::text(label).on_press_maybe(loaded.then(|| {
+            Message::ConversationCompose {
+                email_id: email_id.to_string(),
+                action,
+            }
+        }))
+    };
+    let mut row = widget::row()
+        .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(compose("Reply", RowAction::Reply))
+        .push(compose("Reply all", RowAction::ReplyAll))
+        .push(compose("Forward", RowAction::Forward))
+        .push(
+            widget::button::text("Collapse")
+                .on_press(Message::ToggleConversationEntry(email_id.to_string())),
+        );
+    if is_active {
+        row = row.push(
+            widget::button::text("Split from thread")
+                .on_press(Message::SplitFromThread(email_id.to_string())),
+        );
+    }
+    row.into()
+}
+
+/// Compact outline of a long thread: one row per message, click to jump.
+fn thread_map<'a>(
+    conversation: &'a [ConversationEntry],
+    active_email_id: Option<&'a str>,
+    sender_filter: Option<&'a str>,
+) -> Element<'a, Message> {
+    let mut col = widget::column().spacing(2).push(widget::text::heading(format!(
+        "Thread ({})",
+        conversation.len()
+    )));
+    for entry in conversation.iter().filter(|e| shows_entry(sender_filter, e)) {
+        let unread = if entry.summary.is_read { " " } else { "\u{25CF}" };
+        let label = format!("{unread} {} \u{00B7} {}", sender_name(&entry.summary.from), entry.summary.date);
+        let row = widget::text::caption(label).width(Length::Fill);
+        let btn = if active_email_id == Some(entry.email_id.as_str()) {
+            widget::button::custom(row).class(cosmic::theme::Button::Suggested)
+        } else {
+            widget::button::custom(row).class(cosmic::theme::Button::Text)
+        };
+        col = col.push(
+            btn.width(Length::Fill)
+                .on_press(Message::JumpToConversationEntry(entry.email_id.clone())),
+        );
+    }
+    widget::container(widget::scrollable(col).height(Length::Fill))
+        .padding([8, 8])
+        .width(Length::Fixed(THREAD_MAP_WIDTH))
+        .height(Length::Fill)
+        .into()
+}
+
+/// "Everyone" plus one chip per sender; `None` when only one person wrote.
+fn participant_chips<'a>(
+    conversation: &[ConversationEntry],
+    sender_filter: Option<&str>,
+) -> Option<Element<'a, Message>> {
+    let people = participants(conversation.iter().map(|e| e.summary.from.as_str()));
+    if people.len() < 2 {
+        return None;
+    }
+    let chip = |label: String, selected: bool, key: Option<String>| {
+        let class = if selected {
+            cosmic::theme::Button::Suggested
+        } else {
+            cosmic::theme::Button::Standard
+        };
+        widget::button::text(label)
+            .class(class)
+            .on_press(Message::FilterConversationSender(key))
+    };
+    let mut chips: Vec<Element<'a, Message>> =
+        vec![chip("Everyone".into(), sender_filter.is_none(), None).into()];
+    for person in people {
+        let selected = sender_filter == Some(person.key.as_str());
+        let label = format!("{} ({})", sender_name(&person.from), person.count);
+        chips.push(chip(label, selected, Some(person.key)).into());
+    }
+    Some(
+        widget::container(widget::flex_row(chips).row_spacing(4).column_spacing(4))
+            .padding([0, 16, 8, 16])
+            .width(Length::Fill)
+            .into(),
+    )
+}
+
+/// Display name from `Name <addr>`, falling back to the whole header.
+fn sender_name(from: &str) -> &str {
+    match from.find('<') {
+        Some(i) if i > 0 => from[..i].trim().trim_matches('"'),
+        _ => from.trim(),
+    }
+}
+
+/// `encoding` adds the "View with encoding" dropdown (single-message preview).
+fn toolbar<'a>(
+    index: usize,
+    msg: &MessageSummary,
+    encoding: Option<usize>,
+) -> Element<'a, Message> {
     let star_label = if msg.is_starred {
         "\u{2605}"
     } else {
@@ -174,15 +414,22 @@ fn toolbar<'a>(index: usize, msg: &MessageSummary) -> Element<'a, Message> {
         "Mark read"
     };
 
-    let toolbar = widget::row()
+    let mut toolbar = widget::row()
         .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
         .push(widget::button::text("Reply").on_press(Message::ComposeReply))
+        .push(widget::button::text("Reply all").on_press(Message::ComposeReplyAll))
         .push(widget::button::text("Forward").on_press(Message::ComposeForward))
         .push(widget::button::text(star_label).on_press(Message::ToggleStar(index)))
         .push(widget::button::text(read_label).on_press(Message::ToggleRead(index)))
         .push(widget::button::text("Archive").on_press(Message::Archive(index)))
         .push(widget::button::text("Copy").on_press(Message::CopyBody))
         .push(widget::button::destructive("Trash").on_press(Message::Delete(index)));
+    if let Some(selected) = encoding {
+        toolbar = toolbar
+            .push(widget::text::caption("Encoding"))
+            .push(widget::dropdown(ENCODING_LABELS, Some(selected), Message::ViewWithEncoding));
+    }
 
     widget::container(toolbar)
         .padding([8, 16])
@@ -190,6 +437,28 @@ fn toolbar<'a>(index: usize, msg: &MessageSummary) -> Element<'a, Message> {
         .into()
 }
 
+/// "Looks like an event" strip with calendar hand-off.
+fn event_banner<'a>(event: &DetectedEvent) -> Element<'a, Message> {
+    let row = widget::row()
+        .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(
+            widget::text::body(format!(
+                "\u{1F4C5} \"{}\" \u{2014} {}",
+                event.phrase,
+                event.when_label()
+            ))
+            .width(Length::Fill),
+        )
+        .push(widget::button::suggested("Add to calendar").on_press(Message::AddDetectedEventToCalendar))
+        .push(widget::button::text("Dismiss").on_press(Message::DismissDetectedEvent));
+
+    widget::container(row)
+        .padding([4, 16])
+        .width(Length::Fill)
+        .into()
+}
+
 fn header_row<'a>(label: &'a str, value: &'a str) -> Element<'a, Message> {
     widget::row()
         .spacing(8)
@@ -205,65 +474,136 @@ fn header_row<'a>(label: &'a str, value: &'a str) -> Element<'a, Message> {
         .into()
 }
 
-fn message_header<'a>(msg: &'a MessageSummary) -> Element<'a, Message> {
+/// "Alias: +shop", clickable to narrow the list to that alias.
+fn alias_row<'a>(alias: String) -> Element<'a, Message> {
+    widget::row()
+        .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(
+            widget::text::body("Alias:")
+                .width(Length::Fixed(80.0))
+                .font(cosmic::iced::Font {
+                    weight: cosmic::iced::font::Weight::Bold,
+                    ..Default::default()
+                }),
+        )
+        .push(
+            widget::button::text(format!("+{alias}"))
+                .on_press(Message::FilterAlias(Some(alias))),
+        )
+        .into()
+}
+
+/// "Attachments: 📎 3"; the files themselves are listed under the body.
+fn attachments_row<'a>(count: usize) -> Element<'a, Message> {
+    widget::row()
+        .spacing(8)
+        .push(
+            widget::text::body("Attachments:")
+                .width(Length::Fixed(80.0))
+                .font(cosmic::iced::Font {
+                    weight: cosmic::iced::font::Weight::Bold,
+                    ..Default::default()
+                }),
+        )
+        .push(widget::text::body(format!("📎 {count}")))
+        .into()
+}
+
+/// Header block; `own_addresses` (the account's) pick out a plus alias in To.
+fn message_header<'a>(
+    msg: &'a MessageSummary,
+    own_addresses: &[String],
+    attachment_count: usize,
+) -> Element<'a, Message> {
     let mut col = widget::column().spacing(4);
     col = col.push(header_row("From:", &msg.from));
     if !msg.to.is_empty() {
         col = col.push(header_row("To:", &msg.to));
     }
+    if let Some(alias) = plus_alias(&msg.to, own_addresses) {
+        col = col.push(alias_row(alias));
+    }
     col = col.push(header_row("Subject:", &msg.subject));
     col = col.push(header_row("Date:", &msg.date));
     if let Some(ref reply_to) = msg.reply_to {
         col = col.push(header_row("Reply-To:", reply_to));
     }
+    if attachment_count > 0 {
+        col = col.push(attachments_row(attachment_count));
+    }
     col.into()
 }
 
-/// Render attachments. If `conversation_email_id` is Some, use SaveConversationAttachment.
+/// Render attachments. Conversation cards save with SaveConversationAttachment;
+/// clicking a thumbnail opens the image viewer. "Save all" shows for two or
+/// more. Scan verdicts are found by `hashes`, parallel to `attachments`.
 fn attachments_section<'a>(
     attachments: &[AttachmentData],
     image_handles: &[Option<image::Handle>],
-    conversation_email_id: Option<&str>,
+    hashes: &[String],
+    target: ThumbnailTarget,
+    scans: &AttachmentScans,
 ) -> Element<'a, Message> {
     let mut att_col = widget::column().spacing(8);
 
-    att_col = att_col.push(widget::text::heading(format!(
-        "Attachments ({})",
-        attachments.len()
-    )));
+    let mut heading = widget::row()
+        .spacing(8)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(
+            widget::text::heading(format!("Attachments ({})", attachments.len()))
+                .width(Length::Fill),
+        );
+    if attachments.len() > 1 {
+        heading = heading.push(
+            widget::button::text("Save all").on_press(Message::SaveAllAttachments(target.clone())),
+        );
+    }
+    att_col = att_col.push(heading);
 
     for (i, att) in attachments.iter().enumerate() {
         let mut card = widget::column().spacing(4);
 
-        // Image preview
+        // Image preview (a bounded thumbnail; click to open the viewer)
         if let Some(Some(handle)) = image_handles.get(i) {
             card = card.push(
-                widget::Image::new(handle.clone())
-                    .content_fit(ContentFit::Contain)
-                    .width(Length::Fill),
+                widget::mouse_area(
+                    widget::Image::new(handle.clone())
+                        .content_fit(ContentFit::ScaleDown)
+                        .width(Length::Fill),
+                )
+                .on_press(Message::ViewImage {
+                    target: target.clone(),
+                    index: i,
+                }),
             );
         }
 
         // Filename, size, save button
         let size_str = human_size(att.data.len());
-        let save_msg = if let Some(eid) = conversation_email_id {
-            Message::SaveConversationAttachment {
-                email_id: eid.to_string(),
+        let save_msg = match &target {
+            ThumbnailTarget::Conversation { email_id } => Message::SaveConversationAttachment {
+                email_id: email_id.clone(),
                 index: i,
-            }
-        } else {
-            Message::SaveAttachment(i)
+            },
+            _ => Message::SaveAttachment(i),
         };
         let info = widget::row()
             .spacing(8)
             .align_y(cosmic::iced::Alignment::Center)
             .push(
-                widget::text::body(format!("{} ({})", att.filename, size_str))
-                    .width(Length::Fill),
+                widget::text::body(match attachment_risk(&att.filename) {
+                    Some(_) => format!("⚠ {} ({})", att.filename, size_str),
+                    None => format!("{} ({})", att.filename, size_str),
+                })
+                .width(Length::Fill),
             )
             .push(widget::button::suggested("Save").on_press(save_msg));
 
         card = card.push(info);
+        if let Some(verdict) = hashes.get(i).and_then(|hash| scans.verdict(hash)) {
+            card = card.push(widget::text::caption(verdict.label()));
+        }
 
         att_col = att_col.push(
             widget::container(card)
@@ -279,7 +619,7 @@ fn attachments_section<'a>(
         .into()
 }
 
-fn human_size(bytes: usize) -> String {
+pub(crate) fn human_size(bytes: usize) -> String {
     if bytes < 1024 {
         format!("{} B", bytes)
     } else if bytes < 1024 * 1024 {
@@ -1,4 +1,14 @@
+pub mod account_accent;
+pub mod attachments_view;
+pub mod cleanup_dialog;
+pub mod compare_view;
 pub mod compose_dialog;
+pub mod freshness;
+pub mod image_viewer;
+pub mod menu_bar;
 pub mod message_list;
 pub mod message_view;
+pub mod quick_look;
+pub mod shortcuts_dialog;
 pub mod sidebar;
+pub mod triage_view;
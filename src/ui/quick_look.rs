@@ -0,0 +1,33 @@
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{Message, QuickLook, QuickLookBody};
+
+/// Tallest the body gets before it scrolls.
+const BODY_HEIGHT: f32 = 420.0;
+
+/// Quick look popup: the row's headers over its plain-text body.
+pub fn view(look: &QuickLook) -> Element<'_, Message> {
+    let summary = &look.summary;
+    let body: Element<'_, Message> = match &look.body {
+        QuickLookBody::Loading => widget::text::body("Loading…").into(),
+        QuickLookBody::Loaded(plain) => widget::text::body(plain).width(Length::Fill).into(),
+        QuickLookBody::NotDownloaded => {
+            widget::text::body("Not downloaded yet. Open the message to fetch it.").into()
+        }
+        QuickLookBody::Failed(e) => {
+            widget::text::body(format!("Couldn't load the body: {e}")).into()
+        }
+    };
+    let controls = widget::column()
+        .spacing(8)
+        .push(widget::text::caption(format!("{} — {}", summary.from, summary.date)))
+        .push(widget::scrollable(body).height(Length::Fixed(BODY_HEIGHT)));
+
+    widget::dialog()
+        .title(summary.subject.clone())
+        .control(controls)
+        .primary_action(widget::button::standard("Close").on_press(Message::CloseQuickLook))
+        .into()
+}
@@ -0,0 +1,33 @@
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{Message, ShortcutContext};
+
+/// Width of the key column, so descriptions line up within a group.
+const KEY_COLUMN: f32 = 140.0;
+
+/// "?" overlay: every active shortcut, grouped by where it applies.
+pub fn view<'a>(groups: Vec<(ShortcutContext, Vec<(String, &'static str)>)>) -> Element<'a, Message> {
+    let mut controls = widget::column().spacing(16);
+    for (context, rows) in groups {
+        let mut group = widget::column()
+            .spacing(4)
+            .push(widget::text::heading(context.label()));
+        for (key, description) in rows {
+            group = group.push(
+                widget::row()
+                    .spacing(12)
+                    .push(widget::text::monotext(key).width(Length::Fixed(KEY_COLUMN)))
+                    .push(widget::text::body(description)),
+            );
+        }
+        controls = controls.push(group);
+    }
+
+    widget::dialog()
+        .title("Keyboard shortcuts")
+        .control(widget::scrollable(controls))
+        .primary_action(widget::button::standard("Close").on_press(Message::ToggleShortcuts))
+        .into()
+}
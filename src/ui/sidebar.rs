@@ -1,12 +1,34 @@
+use std::collections::HashMap;
+
 use cosmic::iced::Length;
 use cosmic::widget;
 use cosmic::Element;
 
 use crate::app::{
-    AccountState, ConnectionState, ErrorSurface, MailboxIdentity, Message, MessageIdentity, Phase,
+    format_bytes, synced_ago_label, AccountColor, AccountSection, AccountState, FolderActivity,
+    AttachmentScope, ConnectionState, ErrorSurface,
+    FolderSyncState, FolderSyncTimes, Freshness, MailboxIdentity, Message, MessageIdentity, MetricKind,
+    MetricStats, Phase, SmartView, SmartViewKind,
 };
 use crate::dnd_models::DraggedMessage;
 
+pub struct FolderFreshness<'a> {
+    pub times: &'a FolderSyncTimes,
+    /// account_id → mailbox_id → cached activity, for the tooltip.
+    pub activity: &'a HashMap<String, HashMap<String, FolderActivity>>,
+    /// Unix seconds, sampled once per view.
+    pub now: u64,
+}
+
+/// What is open in the sidebar.
+pub struct SidebarSelection<'a> {
+    pub active_account: Option<usize>,
+    pub selected_folder: Option<usize>,
+    pub smart_view: Option<&'a SmartView>,
+    /// Global folder index under a message drag.
+    pub drag_target: Option<usize>,
+}
+
 pub struct DiagnosticsState<'a> {
     pub collapsed: bool,
     pub phase: Phase,
@@ -22,16 +44,30 @@ pub struct DiagnosticsState<'a> {
     pub last_sync_at: Option<std::time::Instant>,
     pub last_refresh_at: Option<std::time::Instant>,
     pub refresh_in_flight: bool,
+    /// Rolling timings, only metrics with samples.
+    pub metrics: Vec<(MetricKind, MetricStats)>,
+    pub attachment_bytes: usize,
+    /// Recent read/star conflicts, newest first.
+    pub flag_conflicts: Vec<String>,
 }
 
-/// Render the folder sidebar with multi-account sections.
+/// Render the folder sidebar with multi-account sections. `sections` groups
+/// the accounts under named headers; empty lists them flat.
 pub fn view<'a>(
     accounts: &'a [AccountState],
-    active_account: Option<usize>,
-    selected_folder: Option<usize>,
-    drag_target: Option<usize>,
+    account_colors: &'a HashMap<String, AccountColor>,
+    sections: Vec<AccountSection>,
+    selection: SidebarSelection<'a>,
+    draft_count: usize,
+    freshness: FolderFreshness<'a>,
     diagnostics: DiagnosticsState<'a>,
 ) -> Element<'a, Message> {
+    let SidebarSelection {
+        active_account,
+        selected_folder,
+        smart_view,
+        drag_target,
+    } = selection;
     let mut col = widget::column().spacing(4).padding(8);
 
     col = col.push(
@@ -39,6 +75,40 @@ pub fn view<'a>(
             .on_press(Message::ComposeNew)
             .width(Length::Fill),
     );
+    if draft_count > 0 {
+        col = col.push(
+            widget::button::standard(format!("Resume draft ({draft_count})"))
+                .on_press(Message::ComposeResumeDraft)
+                .width(Length::Fill),
+        );
+    }
+    if selected_folder.is_some() {
+        col = col.push(
+            widget::button::standard("Attachments")
+                .on_press(Message::ShowAttachments(AttachmentScope::Folder))
+                .width(Length::Fill),
+        );
+    }
+    if accounts.len() > 1 {
+        col = col.push(smart_view_button(
+            "★ All starred",
+            SmartView {
+                kind: SmartViewKind::Starred,
+                account_id: None,
+                group: None,
+            },
+            smart_view,
+        ));
+        col = col.push(smart_view_button(
+            "● All unread",
+            SmartView {
+                kind: SmartViewKind::Unread,
+                account_id: None,
+                group: None,
+            },
+            smart_view,
+        ));
+    }
     col = col.push(widget::vertical_space().height(8));
 
     if accounts.is_empty() {
@@ -49,164 +119,287 @@ pub fn view<'a>(
                 .width(Length::Fill),
         );
     } else {
-        // Track a global folder index offset for drag targets
-        let mut global_folder_offset: usize = 0;
-
-        for (acct_idx, acct) in accounts.iter().enumerate() {
-            let is_active_account = active_account == Some(acct_idx);
-
-            // Account header row: collapse toggle + label + status + edit/remove
-            let collapse_icon = if acct.collapsed { "▶" } else { "▼" };
-            let status_icon = match &acct.conn_state {
-                ConnectionState::Connected => "●",
-                ConnectionState::Connecting | ConnectionState::Syncing => "◌",
-                ConnectionState::Error(_) => "✖",
-                ConnectionState::Disconnected => "○",
-            };
+        // Global folder index of each account's first folder, for drag targets
+        let folder_offsets: Vec<usize> = accounts
+            .iter()
+            .scan(0, |offset, acct| {
+                let start = *offset;
+                *offset += acct.folders.len();
+                Some(start)
+            })
+            .collect();
+        let sections = if sections.is_empty() {
+            vec![AccountSection {
+                name: None,
+                accounts: (0..accounts.len()).collect(),
+                unread: 0,
+                collapsed: false,
+            }]
+        } else {
+            sections
+        };
+
+        for (section_pos, section) in sections.iter().enumerate() {
+            if let Some(name) = &section.name {
+                if section_pos > 0 {
+                    col = col.push(widget::vertical_space().height(8));
+                }
+                col = col.push(group_header(section, name));
+                if !section.collapsed {
+                    col = col.push(smart_view_button(
+                        format!("  \u{2709} All {name} inboxes"),
+                        SmartView {
+                            kind: SmartViewKind::Inboxes,
+                            account_id: None,
+                            group: Some(name.clone()),
+                        },
+                        smart_view,
+                    ));
+                }
+            }
+            if section.collapsed {
+                continue;
+            }
 
-            let header_label = format!("{} {} {}", collapse_icon, acct.config.label, status_icon);
+            for (pos, &acct_idx) in section.accounts.iter().enumerate() {
+                let acct = &accounts[acct_idx];
+                let global_folder_offset = folder_offsets[acct_idx];
+                let is_active_account = active_account == Some(acct_idx);
+
+                // Account header row: collapse toggle + label + status + edit/remove
+                let collapse_icon = if acct.collapsed { "▶" } else { "▼" };
+                let status_icon = match &acct.conn_state {
+                    _ if acct.paused => "⏸",
+                    ConnectionState::Connected if acct.is_syncing() => "◌",
+                    ConnectionState::Connected => "●",
+                    ConnectionState::Syncing => "◌",
+                    ConnectionState::Connecting => "◌",
+                    ConnectionState::Error(_) => "✖",
+                    ConnectionState::Disconnected => "○",
+                };
 
-            let aid_edit = acct.config.id.clone();
-            let aid_remove = acct.config.id.clone();
+                let header_label = format!("{} {} {}", collapse_icon, acct.config.label, status_icon);
 
-            let header_row = widget::row()
-                .spacing(2)
-                .align_y(cosmic::iced::Alignment::Center)
-                .push(
-                    widget::button::text(header_label)
-                        .on_press(Message::ToggleAccountCollapse(acct_idx))
-                        .width(Length::Fill),
-                )
-                .push(
-                    widget::button::icon(widget::icon::from_name("document-properties-symbolic"))
-                        .on_press(Message::AccountEdit(aid_edit))
-                        .padding(4)
-                        .class(cosmic::theme::Button::Text),
-                )
-                .push(
-                    widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
-                        .on_press(Message::RequestDeleteAccount(aid_remove))
-                        .padding(4)
-                        .class(cosmic::theme::Button::Text),
-                );
-
-            col = col.push(header_row);
-
-            // Show connection error inline if present
-            if let ConnectionState::Error(ref e) = acct.conn_state {
-                let short_err = if e.len() > 40 {
-                    format!("{}...", &e[..37])
+                let aid_edit = acct.config.id.clone();
+                let aid_remove = acct.config.id.clone();
+                let pause_icon = if acct.paused {
+                    "media-playback-start-symbolic"
                 } else {
-                    e.clone()
+                    "media-playback-pause-symbolic"
                 };
-                let aid = acct.config.id.clone();
-                col = col.push(
-                    widget::button::custom(
-                        widget::container(widget::text::caption(format!("  {short_err} (retry)")))
-                            .padding([2, 8]),
-                    )
-                    .on_press(Message::ForceReconnect(aid))
-                    .class(cosmic::theme::Button::Text)
-                    .width(Length::Fill),
-                );
-            }
 
-            // Folder list (when not collapsed)
-            if !acct.collapsed {
-                if acct.folders.is_empty() {
-                    match &acct.conn_state {
-                        ConnectionState::Connecting | ConnectionState::Syncing => {
-                            col = col.push(widget::text::caption("  Loading..."));
-                        }
-                        _ => {
-                            col = col.push(widget::text::caption("  No folders"));
-                        }
-                    }
-                } else {
-                    for (folder_idx, folder) in acct.folders.iter().enumerate() {
-                        let global_idx = global_folder_offset + folder_idx;
-                        let label = if folder.unread_count > 0 {
-                            format!("  {} ({})", folder.name, folder.unread_count)
-                        } else {
-                            format!("  {}", folder.name)
-                        };
+                let mut header_row = widget::row()
+                    .spacing(2)
+                    .align_y(cosmic::iced::Alignment::Center);
+                if let Some(&color) = account_colors.get(&acct.config.id) {
+                    header_row = header_row.push(crate::ui::account_accent::marker(color));
+                }
+                let header_row = header_row
+                    .push(
+                        widget::button::text(header_label)
+                            .on_press(Message::ToggleAccountCollapse(acct_idx))
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name(pause_icon))
+                            .on_press(Message::ToggleAccountPause(acct.config.id.clone()))
+                            .padding(4)
+                            .class(cosmic::theme::Button::Text),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name("document-properties-symbolic"))
+                            .on_press(Message::AccountEdit(aid_edit))
+                            .padding(4)
+                            .class(cosmic::theme::Button::Text),
+                    )
+                    .push(
+                        widget::button::icon(widget::icon::from_name("edit-delete-symbolic"))
+                            .on_press(Message::RequestDeleteAccount(aid_remove))
+                            .padding(4)
+                            .class(cosmic::theme::Button::Text),
+                    );
 
-                        let is_selected = is_active_account && selected_folder == Some(folder_idx);
-                        let is_drag_target = drag_target == Some(global_idx);
+                col = col.push(header_row);
 
-                        let ai = acct_idx;
-                        let fi = folder_idx;
-                        let mut folder_col = widget::column().spacing(0);
+                if acct.paused {
+                    col = col.push(widget::text::caption("  Paused — showing cached mail"));
+                }
 
-                        let mut btn = widget::button::text(label)
-                            .on_press(Message::SelectFolder(ai, fi))
-                            .width(Length::Fill);
+                // Show connection error inline if present
+                if let ConnectionState::Error(ref e) = acct.conn_state {
+                    let short_err = if e.len() > 40 {
+                        format!("{}...", &e[..37])
+                    } else {
+                        e.clone()
+                    };
+                    let aid = acct.config.id.clone();
+                    col = col.push(
+                        widget::button::custom(
+                            widget::container(widget::text::caption(format!("  {short_err} (retry)")))
+                                .padding([2, 8]),
+                        )
+                        .on_press(Message::ForceReconnect(aid))
+                        .class(cosmic::theme::Button::Text)
+                        .width(Length::Fill),
+                    );
+                }
 
-                        if is_selected || is_drag_target {
-                            btn = btn.class(cosmic::theme::Button::Suggested);
+                // Folder list (when not collapsed)
+                if !acct.collapsed {
+                    if acct.folders.is_empty() {
+                        if acct.conn_state == ConnectionState::Connecting || acct.is_syncing() {
+                            col = col.push(widget::text::caption("  Loading..."));
+                        } else {
+                            col = col.push(widget::text::caption("  No folders"));
                         }
-
-                        let mailbox_id = folder.mailbox_id.clone();
-                        let dest_account_id = acct.config.id.clone();
-                        let dest = widget::dnd_destination::dnd_destination_for_data::<
-                            DraggedMessage,
-                            _,
-                        >(btn, move |data, _action| match data {
-                            Some(msg) => Message::DragMessageToFolder {
-                                message: MessageIdentity {
-                                    account_id: msg.source_account_id.clone(),
-                                    mailbox_id: msg.source_mailbox_id.clone(),
-                                    email_id: msg.email_id.clone(),
-                                },
-                                source: MailboxIdentity {
-                                    account_id: msg.source_account_id,
-                                    mailbox_id: msg.source_mailbox_id,
-                                },
-                                dest: MailboxIdentity {
-                                    account_id: dest_account_id.clone(),
-                                    mailbox_id: mailbox_id.clone(),
-                                },
+                    } else {
+                        col = col.push(smart_view_button(
+                            "  ★ Starred",
+                            SmartView {
+                                kind: SmartViewKind::Starred,
+                                account_id: Some(acct.config.id.clone()),
+                                group: None,
                             },
-                            None => Message::Noop,
-                        })
-                        .on_enter(move |_x, _y, _mimes| Message::FolderDragEnter(global_idx))
-                        .on_leave(|| Message::FolderDragLeave);
-
-                        folder_col = folder_col.push(dest);
-
-                        // Backfill progress indicator or sync trigger
-                        if let Some(&(position, total)) =
-                            acct.backfill_progress.get(&folder.mailbox_id)
-                        {
-                            let progress_label =
-                                format!("    \u{25CC} syncing {}/{}", format_count(position), format_count(total));
-                            folder_col = folder_col.push(widget::text::caption(progress_label));
-                        } else if acct.backfill_active && acct.client.is_some() {
-                            let trigger_aid = acct.config.id.clone();
-                            let trigger_mid = folder.mailbox_id.clone();
-                            folder_col = folder_col.push(
-                                widget::button::custom(
-                                    widget::text::caption("    \u{21BB} Sync full history"),
-                                )
-                                .on_press(Message::BackfillTrigger {
-                                    account_id: trigger_aid,
-                                    mailbox_id: trigger_mid,
-                                })
-                                .class(cosmic::theme::Button::Text)
-                                .width(Length::Fill),
-                            );
+                            smart_view,
+                        ));
+                        col = col.push(smart_view_button(
+                            "  ● Unread",
+                            SmartView {
+                                kind: SmartViewKind::Unread,
+                                account_id: Some(acct.config.id.clone()),
+                                group: None,
+                            },
+                            smart_view,
+                        ));
+                        for (folder_idx, folder) in acct.folders.iter().enumerate() {
+                            let global_idx = global_folder_offset + folder_idx;
+                            let label = if folder.unread_count > 0 {
+                                format!("  {} ({})", folder.name, folder.unread_count)
+                            } else {
+                                format!("  {}", folder.name)
+                            };
+
+                            let is_selected = is_active_account && selected_folder == Some(folder_idx);
+                            let is_drag_target = drag_target == Some(global_idx);
+
+                            let ai = acct_idx;
+                            let fi = folder_idx;
+                            let mut folder_col = widget::column().spacing(0);
+
+                            let mut btn = widget::button::text(label)
+                                .on_press(Message::SelectFolder(ai, fi))
+                                .width(Length::Fill);
+
+                            if is_selected || is_drag_target {
+                                btn = btn.class(cosmic::theme::Button::Suggested);
+                            }
+
+                            let mailbox_id = folder.mailbox_id.clone();
+                            let dest_account_id = acct.config.id.clone();
+                            let dest = widget::dnd_destination::dnd_destination_for_data::<
+                                DraggedMessage,
+                                _,
+                            >(btn, move |data, _action| match data {
+                                Some(msg) => Message::DragMessageToFolder {
+                                    message: MessageIdentity {
+                                        account_id: msg.source_account_id.clone(),
+                                        mailbox_id: msg.source_mailbox_id.clone(),
+                                        email_id: msg.email_id.clone(),
+                                    },
+                                    source: MailboxIdentity {
+                                        account_id: msg.source_account_id,
+                                        mailbox_id: msg.source_mailbox_id,
+                                    },
+                                    dest: MailboxIdentity {
+                                        account_id: dest_account_id.clone(),
+                                        mailbox_id: mailbox_id.clone(),
+                                    },
+                                },
+                                None => Message::Noop,
+                            })
+                            .on_enter(move |_x, _y, _mimes| Message::FolderDragEnter(global_idx))
+                            .on_leave(|| Message::FolderDragLeave);
+
+                            let last_synced =
+                                freshness.times.get(&acct.config.id, &folder.mailbox_id);
+                            let folder_row = widget::row()
+                                .spacing(4)
+                                .align_y(cosmic::iced::Alignment::Center)
+                                .push(dest)
+                                .push(crate::ui::freshness::indicator(Freshness::classify(
+                                    last_synced,
+                                    freshness.now,
+                                )));
+                            let activity = freshness
+                                .activity
+                                .get(&acct.config.id)
+                                .and_then(|m| m.get(&folder.mailbox_id));
+                            folder_col = folder_col.push(widget::tooltip(
+                                folder_row,
+                                folder_tooltip(
+                                    folder.total_count,
+                                    folder.unread_count,
+                                    activity,
+                                    synced_ago_label(last_synced, freshness.now),
+                                ),
+                                widget::tooltip::Position::Right,
+                            ));
+
+                            // Sync status, backfill progress, or the backfill trigger
+                            match acct.folder_sync(&folder.mailbox_id) {
+                                FolderSyncState::SyncingHeaders => {
+                                    folder_col = folder_col
+                                        .push(widget::text::caption("    \u{25CC} checking for new mail"));
+                                }
+                                FolderSyncState::Backfilling { position, total } => {
+                                    let progress_label = format!(
+                                        "    \u{25CC} syncing {}/{}",
+                                        format_count(position),
+                                        format_count(total)
+                                    );
+                                    folder_col = folder_col.push(widget::text::caption(progress_label));
+                                }
+                                FolderSyncState::Error { message, retry_at } => {
+                                    let label = format!(
+                                        "    \u{2716} sync {}",
+                                        FolderSyncState::retry_label(retry_at, std::time::Instant::now())
+                                    );
+                                    folder_col = folder_col.push(widget::tooltip(
+                                        widget::button::custom(widget::text::caption(label))
+                                            .on_press(Message::ForceReconnect(acct.config.id.clone()))
+                                            .class(cosmic::theme::Button::Text)
+                                            .width(Length::Fill),
+                                        widget::text::caption(message),
+                                        widget::tooltip::Position::Right,
+                                    ));
+                                }
+                                FolderSyncState::Idle if acct.backfill_active && acct.client.is_some() => {
+                                    let trigger_aid = acct.config.id.clone();
+                                    let trigger_mid = folder.mailbox_id.clone();
+                                    folder_col = folder_col.push(
+                                        widget::button::custom(
+                                            widget::text::caption("    \u{21BB} Sync full history"),
+                                        )
+                                        .on_press(Message::BackfillTrigger {
+                                            account_id: trigger_aid,
+                                            mailbox_id: trigger_mid,
+                                        })
+                                        .class(cosmic::theme::Button::Text)
+                                        .width(Length::Fill),
+                                    );
+                                }
+                                FolderSyncState::Idle => {}
+                            }
+
+                            col = col.push(folder_col);
                         }
-
-                        col = col.push(folder_col);
                     }
                 }
-            }
-
-            global_folder_offset += acct.folders.len();
 
-            // Separator between accounts
-            if acct_idx < accounts.len() - 1 {
-                col = col.push(widget::vertical_space().height(4));
+                // Separator between accounts
+                if pos < section.accounts.len() - 1 {
+                    col = col.push(widget::vertical_space().height(4));
+                }
             }
         }
 
@@ -232,6 +425,36 @@ pub fn view<'a>(
         .into()
 }
 
+/// Collapsible header of a named account group, with its inbox unread total.
+fn group_header<'a>(section: &AccountSection, name: &str) -> Element<'a, Message> {
+    let icon = if section.collapsed { "▶" } else { "▼" };
+    let label = if section.unread > 0 {
+        format!("{icon} {name} ({})", section.unread)
+    } else {
+        format!("{icon} {name}")
+    };
+    widget::button::text(label)
+        .on_press(Message::ToggleGroupCollapse(name.to_string()))
+        .width(Length::Fill)
+        .into()
+}
+
+/// Sidebar entry for a smart view, highlighted while it's open.
+fn smart_view_button<'a>(
+    label: impl Into<std::borrow::Cow<'a, str>>,
+    view: SmartView,
+    open: Option<&SmartView>,
+) -> Element<'a, Message> {
+    let is_open = open == Some(&view);
+    let mut btn = widget::button::text(label)
+        .on_press(Message::OpenSmartView(view))
+        .width(Length::Fill);
+    if is_open {
+        btn = btn.class(cosmic::theme::Button::Suggested);
+    }
+    btn.into()
+}
+
 fn status_pill_view<'a>(
     accounts: &'a [AccountState],
     active_account: Option<usize>,
@@ -246,9 +469,16 @@ fn status_pill_view<'a>(
     };
 
     let label = match &acct.conn_state {
-        ConnectionState::Connected => format!("● {}", acct.config.label),
+        ConnectionState::Connected | ConnectionState::Syncing => {
+            match acct.sync_summary(std::time::Instant::now()) {
+                Some(detail) if acct.is_syncing() => {
+                    format!("◌ {}  {}", acct.config.label, detail)
+                }
+                Some(detail) => format!("✖ {}  {}", acct.config.label, detail),
+                None => format!("● {}", acct.config.label),
+            }
+        }
         ConnectionState::Connecting => format!("◌ {}  Connecting...", acct.config.label),
-        ConnectionState::Syncing => format!("◌ {}  Syncing...", acct.config.label),
         ConnectionState::Error(msg) => {
             let short = if msg.len() > 30 {
                 format!("{}...", &msg[..27])
@@ -263,7 +493,7 @@ fn status_pill_view<'a>(
     let pill = widget::container(widget::text::caption(label)).padding([6, 8]);
 
     let action = match &acct.conn_state {
-        ConnectionState::Connected => Some(Message::Refresh),
+        ConnectionState::Connected | ConnectionState::Syncing => Some(Message::Refresh),
         ConnectionState::Error(_) | ConnectionState::Disconnected => {
             Some(Message::ForceReconnect(acct.config.id.clone()))
         }
@@ -329,9 +559,10 @@ fn diagnostics_view<'a>(state: DiagnosticsState<'a>) -> Element<'a, Message> {
     // -- Connection state per account --
     for acct in state.accounts {
         let conn_label = match &acct.conn_state {
+            ConnectionState::Connected if acct.is_syncing() => "syncing...".into(),
+            ConnectionState::Syncing => "syncing...".into(),
             ConnectionState::Connected => "connected".into(),
             ConnectionState::Connecting => "connecting...".into(),
-            ConnectionState::Syncing => "syncing...".into(),
             ConnectionState::Disconnected => "disconnected".into(),
             ConnectionState::Error(e) => format!("error: {}", truncate(e, 30)),
         };
@@ -351,6 +582,17 @@ fn diagnostics_view<'a>(state: DiagnosticsState<'a>) -> Element<'a, Message> {
     col = col.push(widget::text::caption(format!("Last refresh: {}", refresh_status)));
     col = col.push(widget::text::caption(format!("Last sync: {}", ago_label(state.last_sync_at))));
 
+    // -- Local health metrics --
+    for (kind, stats) in &state.metrics {
+        col = col.push(widget::text::caption(format!("{}: {}", kind.label(), stats.summary())));
+    }
+    if state.attachment_bytes > 0 {
+        col = col.push(widget::text::caption(format!(
+            "Attachments in memory: {}",
+            format_bytes(state.attachment_bytes)
+        )));
+    }
+
     // -- Phase and counters --
     col = col.push(widget::text::caption(format!("Phase: {}", phase_label)));
 
@@ -383,6 +625,10 @@ fn diagnostics_view<'a>(state: DiagnosticsState<'a>) -> Element<'a, Message> {
         )));
     }
 
+    for conflict in &state.flag_conflicts {
+        col = col.push(widget::text::caption(format!("Conflict: {}", truncate(conflict, 56))));
+    }
+
     if state.selected_folder_evicted {
         col = col.push(widget::text::caption("Folder selection evicted"));
     }
@@ -425,3 +671,33 @@ fn truncate(s: &str, max_len: usize) -> String {
     out.push_str("...");
     out
 }
+
+/// Folder hover card: counts, newest cached date, 30-day sparkline, freshness.
+fn folder_tooltip<'a>(
+    total: u32,
+    unread: u32,
+    activity: Option<&FolderActivity>,
+    synced: String,
+) -> Element<'a, Message> {
+    let mut col = widget::column()
+        .spacing(2)
+        .push(widget::text::caption(format!(
+            "{} messages · {} unread",
+            format_count(total),
+            format_count(unread)
+        )));
+    if let Some(activity) = activity {
+        if let Some(newest) = activity.newest {
+            col = col.push(widget::text::caption(format!("Newest: {}", newest.format("%b %-d, %Y"))));
+        }
+        col = col.push(widget::text::caption(format!(
+            "30 days: {} ({})",
+            activity.sparkline(),
+            activity.recent_total()
+        )));
+    }
+    widget::container(col.push(widget::text::caption(synced)))
+        .padding(6)
+        .class(cosmic::style::Container::Card)
+        .into()
+}
@@ -0,0 +1,25 @@
+use cosmic::iced::Length;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::Message;
+
+/// Triage mode: a progress header over the full-width message list.
+pub fn view<'a>(left: usize, done: usize, list: Element<'a, Message>) -> Element<'a, Message> {
+    let header = widget::row()
+        .spacing(12)
+        .align_y(cosmic::iced::Alignment::Center)
+        .push(widget::text::heading(format!("{left} left")))
+        .push(widget::text::body(format!("{done} done")))
+        .push(
+            widget::text::caption("j/k move · e archive · # trash · s star · d defer · Esc exit")
+                .width(Length::Fill),
+        )
+        .push(widget::button::standard("Exit triage").on_press(Message::ToggleTriage));
+
+    widget::column()
+        .push(widget::container(header).padding([8, 16]).width(Length::Fill))
+        .push(list)
+        .height(Length::Fill)
+        .into()
+}